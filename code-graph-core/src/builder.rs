@@ -0,0 +1,181 @@
+use std::{
+    ffi::OsStr,
+    io,
+    path::{Path, PathBuf},
+};
+
+use tree_sitter::Node;
+
+use crate::{
+    fetch_calls_with, fetch_symbols_with, get_symbol_query, lang::SymbolQuery, read_source,
+    recursion_dir, valid_file_extention, walk_definitions, CodeGraphError, CodeNode, CodeNodeIndex,
+    Graph, Result, Tree, TreeType, VisitContext,
+};
+
+/**
+ * 以编程方式构建代码图
+ *
+ * ```ignore
+ * let graph = GraphBuilder::new()
+ *     .add_file("src/lib.rs")?
+ *     .add_source("main.rs", "fn main() { run(); }")?
+ *     .resolve_calls()
+ *     .build();
+ * ```
+ */
+pub struct GraphBuilder {
+    graph: Graph,
+    // 所有文件中的调用节点
+    calls: Vec<CodeNode>,
+}
+
+impl Default for GraphBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GraphBuilder {
+    pub fn new() -> Self {
+        Self {
+            graph: Graph::new(),
+            calls: vec![],
+        }
+    }
+
+    /**
+     * 读取并解析文件，语言由文件扩展名决定
+     */
+    pub fn add_file(self, path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let ext = path
+            .extension()
+            .unwrap_or(OsStr::new(""))
+            .to_str()
+            .unwrap_or("");
+        if !valid_file_extention(ext) {
+            return Err(CodeGraphError::UnsupportedLanguage(
+                path.display().to_string(),
+            ));
+        }
+        let code = read_source(path)?;
+        self.add_source(path.to_str().unwrap_or(""), &code)
+    }
+
+    /**
+     * 递归读取目录下所有受支持的文件
+     */
+    pub fn add_dir(mut self, path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        if !path.is_dir() {
+            return Err(CodeGraphError::Io(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("目录不存在: {}", path.display()),
+            )));
+        }
+        let mut pathes: Vec<PathBuf> = vec![];
+        let root_tree = Tree::new("", path.to_str().unwrap_or(""), TreeType::Directory);
+        recursion_dir(path, &mut pathes, root_tree)?;
+        for file_path in pathes {
+            let ext = file_path
+                .extension()
+                .unwrap_or(OsStr::new(""))
+                .to_str()
+                .unwrap_or("");
+            if valid_file_extention(ext) {
+                self = self.add_file(&file_path)?;
+            }
+        }
+        Ok(self)
+    }
+
+    /**
+     * 解析内存中的代码，语言由 path 的扩展名决定
+     */
+    pub fn add_source(self, path: &str, code: &str) -> Result<Self> {
+        let ext = Path::new(path)
+            .extension()
+            .unwrap_or(OsStr::new(""))
+            .to_str()
+            .unwrap_or("");
+        self.add_source_with_query(path, code, get_symbol_query(ext)?)
+    }
+
+    /**
+     * 使用指定的 SymbolQuery 解析内存中的代码
+     */
+    pub fn add_source_with_query(
+        mut self,
+        path: &str,
+        code: &str,
+        symbol_query: Box<dyn SymbolQuery>,
+    ) -> Result<Self> {
+        fetch_symbols_with(path, code, symbol_query.as_ref(), &mut self.graph)?;
+        self.calls
+            .extend(fetch_calls_with(path, code, symbol_query.as_ref())?);
+        Ok(self)
+    }
+
+    /**
+     * 解析内存中的代码，并对每个语法节点调用 visitor 以提取自定义信息
+     */
+    pub fn add_source_with_visitor(
+        mut self,
+        path: &str,
+        code: &str,
+        visitor: impl FnMut(&Node, &mut VisitContext),
+    ) -> Result<Self> {
+        let ext = Path::new(path)
+            .extension()
+            .and_then(OsStr::to_str)
+            .unwrap_or("");
+        let symbol_query = get_symbol_query(ext)?;
+        walk_definitions(path, code, symbol_query.as_ref(), &mut self.graph, visitor)?;
+        self.calls
+            .extend(fetch_calls_with(path, code, symbol_query.as_ref())?);
+        Ok(self)
+    }
+
+    /**
+     * 将调用解析到定义上，在调用方和被调用方之间添加 Calls 边，
+     * 同一对节点之间的多处调用合并为一条边并记录每处调用的行号
+     */
+    pub fn resolve_calls(mut self) -> Self {
+        let mut call_edges: Vec<((CodeNodeIndex, CodeNodeIndex), Vec<usize>)> = vec![];
+        for call in &self.calls {
+            let Some(caller) = self
+                .graph
+                .find_innermost_node(&call.file_path, call.file_location)
+            else {
+                continue;
+            };
+            for callee in self.graph.find_by_name(&call.label) {
+                if caller == callee {
+                    continue;
+                }
+                let edge = (caller, callee);
+                match call_edges.iter_mut().find(|(other, _)| *other == edge) {
+                    Some((_, lines)) => lines.push(call.file_location),
+                    None => call_edges.push((edge, vec![call.file_location])),
+                }
+            }
+        }
+        for ((from, to), mut lines) in call_edges {
+            lines.sort_unstable();
+            lines.dedup();
+            self.graph.add_call_edge(from, to, lines);
+        }
+        self
+    }
+
+    /**
+     * 所有文件中的调用节点
+     */
+    pub fn calls(&self) -> &[CodeNode] {
+        &self.calls
+    }
+
+    pub fn build(self) -> Graph {
+        self.graph
+    }
+}
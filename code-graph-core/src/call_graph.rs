@@ -0,0 +1,89 @@
+use std::{collections::HashMap, path::Path};
+
+use crate::{
+    patch::{add_child, group_node},
+    CodeBlockType, CodeNode, CodeNodeIndex, DefinitionIndex, Graph,
+};
+
+/**
+ * 整个项目的调用图：根节点下按文件列出项目中的所有定义，调用方到被调用方连出调用关系，
+ * 调用由 DefinitionIndex 对应到定义，同一对定义之间的多处调用合并为一条边，
+ * 定义保留原来的 ID，可以继续查找调用方、添加书签
+ */
+pub fn build_call_graph(
+    project_name: &str,
+    root_path: &Path,
+    definitions: &DefinitionIndex,
+    calls: &[CodeNode],
+) -> Graph {
+    let mut graph = Graph::new();
+    let root = graph.add_node(group_node(project_name, project_name));
+    let mut file_nodes: HashMap<&str, CodeNodeIndex> = HashMap::new();
+    let mut indexes: HashMap<&str, CodeNodeIndex> = HashMap::new();
+    let mut sorted = definitions
+        .definitions()
+        .iter()
+        .filter(|node| {
+            node.level > 0 && !matches!(node.block_type, CodeBlockType::CALL | CodeBlockType::HOOK)
+        })
+        .collect::<Vec<&CodeNode>>();
+    // 按文件排列，同一文件中保持定义出现的顺序
+    sorted.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+    for node in sorted {
+        let file_index = match file_nodes.get(node.file_path.as_str()) {
+            Some(file_index) => *file_index,
+            None => {
+                let label = Path::new(&node.file_path)
+                    .strip_prefix(root_path)
+                    .unwrap_or(Path::new(&node.file_path))
+                    .display()
+                    .to_string();
+                let mut file_node = group_node(&node.file_path, &label);
+                file_node.file_path = node.file_path.clone();
+                let file_index = add_child(&mut graph, root, file_node, 1);
+                file_nodes.insert(&node.file_path, file_index);
+                file_index
+            }
+        };
+        let mut definition = node.clone();
+        definition.level = 2;
+        definition.visiable = true;
+        let index = graph.add_node(definition);
+        graph.add_edge(file_index, index);
+        indexes.insert(&node.id, index);
+    }
+
+    let mut call_edges: Vec<((CodeNodeIndex, CodeNodeIndex), Vec<usize>)> = vec![];
+    let mut edge_positions: HashMap<(CodeNodeIndex, CodeNodeIndex), usize> = HashMap::new();
+    for call in calls {
+        let (Some(caller), Some(callee)) = (
+            definitions.enclosing(&call.file_path, call.file_location),
+            definitions.resolve(call),
+        ) else {
+            continue;
+        };
+        let (Some(from), Some(to)) = (
+            indexes.get(caller.id.as_str()),
+            indexes.get(callee.id.as_str()),
+        ) else {
+            continue;
+        };
+        if from == to {
+            continue;
+        }
+        let edge = (*from, *to);
+        match edge_positions.get(&edge) {
+            Some(position) => call_edges[*position].1.push(call.file_location),
+            None => {
+                edge_positions.insert(edge, call_edges.len());
+                call_edges.push((edge, vec![call.file_location]));
+            }
+        }
+    }
+    for ((from, to), mut lines) in call_edges {
+        lines.sort_unstable();
+        lines.dedup();
+        graph.add_call_edge(from, to, lines);
+    }
+    graph
+}
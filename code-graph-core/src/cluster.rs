@@ -0,0 +1,191 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{CodeNodeIndex, Graph};
+
+/**
+ * 节点的分组方式
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ClusterBy {
+    #[default]
+    None,
+    // 同一个文件中的节点为一组
+    File,
+    // 同一个目录中的节点为一组
+    Directory,
+}
+
+impl ClusterBy {
+    pub const ALL: [ClusterBy; 3] = [ClusterBy::None, ClusterBy::File, ClusterBy::Directory];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ClusterBy::None => "不分组",
+            ClusterBy::File => "按文件",
+            ClusterBy::Directory => "按目录",
+        }
+    }
+
+    fn key_of(self, file_path: &str) -> Option<String> {
+        match self {
+            ClusterBy::None => None,
+            ClusterBy::File => Some(file_path.to_owned()),
+            ClusterBy::Directory => Path::new(file_path)
+                .parent()
+                .and_then(|dir| dir.to_str())
+                // 相对路径中位于当前目录的文件
+                .map(|dir| if dir.is_empty() { "." } else { dir })
+                .map(str::to_owned),
+        }
+    }
+}
+
+/**
+ * 一组属于同一个文件或目录的节点
+ */
+#[derive(Debug, Clone)]
+pub struct Cluster {
+    // 文件或目录的路径
+    pub key: String,
+    // 去掉所有分组公共前缀后的路径，用于显示
+    pub label: String,
+    // 按节点序号排列
+    pub members: Vec<CodeNodeIndex>,
+}
+
+/**
+ * 折叠的分组：除代表节点外其他节点都被隐藏，连到隐藏节点的边改为连到代表节点
+ */
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct CollapsedCluster {
+    key: String,
+    representative: CodeNodeIndex,
+    // 折叠时隐藏的节点，展开时恢复可见
+    hidden: Vec<CodeNodeIndex>,
+}
+
+/**
+ * 按文件或目录对节点分组，根节点和没有文件路径的节点不参与分组，
+ * 只有一组时返回空，单个文件的图不需要分组
+ */
+pub fn find_clusters(graph: &Graph, by: ClusterBy) -> Vec<Cluster> {
+    let mut clusters: Vec<Cluster> = vec![];
+    for (index, node) in graph.nodes() {
+        if node.level() == 0 || node.file_path.is_empty() {
+            continue;
+        }
+        let Some(key) = by.key_of(&node.file_path) else {
+            continue;
+        };
+        match clusters.iter_mut().find(|cluster| cluster.key == key) {
+            Some(cluster) => cluster.members.push(index),
+            None => clusters.push(Cluster {
+                label: key.clone(),
+                key,
+                members: vec![index],
+            }),
+        }
+    }
+    if clusters.len() < 2 {
+        return vec![];
+    }
+    // 公共的目录前缀不显示
+    let common = clusters
+        .iter()
+        .map(|cluster| Path::new(&cluster.key).to_path_buf())
+        .reduce(|common, path| {
+            common
+                .ancestors()
+                .find(|ancestor| path.starts_with(ancestor))
+                .map(Path::to_path_buf)
+                .unwrap_or_default()
+        })
+        .unwrap_or_default();
+    for cluster in clusters.iter_mut() {
+        if let Ok(relative) = Path::new(&cluster.key).strip_prefix(&common) {
+            if !relative.as_os_str().is_empty() {
+                cluster.label = relative.display().to_string();
+            }
+        }
+    }
+    clusters
+}
+
+impl Graph {
+    /**
+     * 折叠分组，保留第一个可见的节点作为代表，分组中没有可见节点时不折叠
+     */
+    pub fn collapse_cluster(&mut self, cluster: &Cluster) {
+        if self.is_cluster_collapsed(&cluster.key) {
+            return;
+        }
+        let Some(representative) = cluster
+            .members
+            .iter()
+            .copied()
+            .find(|index| self.node(*index).is_some_and(|node| node.visiable()))
+        else {
+            return;
+        };
+        let mut hidden = vec![];
+        for index in &cluster.members {
+            if *index == representative {
+                continue;
+            }
+            if let Some(node) = self.node_mut(*index).filter(|node| node.visiable()) {
+                node.set_visiable(false);
+                hidden.push(*index);
+            }
+        }
+        self.collapsed.push(CollapsedCluster {
+            key: cluster.key.clone(),
+            representative,
+            hidden,
+        });
+    }
+
+    /**
+     * 展开分组，恢复折叠时隐藏的节点
+     */
+    pub fn expand_cluster(&mut self, key: &str) {
+        let Some(position) = self
+            .collapsed
+            .iter()
+            .position(|collapsed| collapsed.key == key)
+        else {
+            return;
+        };
+        let collapsed = self.collapsed.remove(position);
+        for index in collapsed.hidden {
+            if let Some(node) = self.node_mut(index) {
+                node.set_visiable(true);
+            }
+        }
+    }
+
+    /**
+     * 展开所有折叠的分组，切换分组方式前使用
+     */
+    pub fn expand_all_clusters(&mut self) {
+        while let Some(collapsed) = self.collapsed.first() {
+            let key = collapsed.key.clone();
+            self.expand_cluster(&key);
+        }
+    }
+
+    pub fn is_cluster_collapsed(&self, key: &str) -> bool {
+        self.collapsed.iter().any(|collapsed| collapsed.key == key)
+    }
+
+    /**
+     * 被折叠的分组隐藏的节点返回分组的代表节点，其他节点返回自身
+     */
+    pub fn cluster_representative(&self, index: CodeNodeIndex) -> CodeNodeIndex {
+        self.collapsed
+            .iter()
+            .find(|collapsed| collapsed.hidden.contains(&index))
+            .map_or(index, |collapsed| collapsed.representative)
+    }
+}
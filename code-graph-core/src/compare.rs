@@ -0,0 +1,309 @@
+use std::{
+    collections::HashMap,
+    ffi::OsStr,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    fetch_symbols, get_symbol_query,
+    patch::{add_child, group_node},
+    read_source, recursion_dir, valid_file_extention, CodeNode, CodeNodeIndex, Graph, Result, Tree,
+    TreeType,
+};
+
+/**
+ * 两个目录的对比结果，节点的文件路径为各自目录下的完整路径
+ */
+#[derive(Debug, Clone, Default)]
+pub struct DirComparison {
+    // 只存在于左侧目录
+    pub only_left: Vec<CodeNode>,
+    // 只存在于右侧目录
+    pub only_right: Vec<CodeNode>,
+    // 名称或位置变化，但内容一致
+    pub renamed: Vec<(CodeNode, CodeNode)>,
+    // 名称一致，但内容发生变化
+    pub changed: Vec<(CodeNode, CodeNode)>,
+}
+
+/**
+ * 扫描目录中的所有定义，文件路径使用相对路径，使得两侧的 ID 可以对应
+ */
+fn scan_definitions(root: &Path) -> Result<Graph> {
+    let mut pathes: Vec<PathBuf> = vec![];
+    recursion_dir(root, &mut pathes, Tree::new("", "", TreeType::Directory))?;
+    pathes.sort();
+    let mut graph = Graph::new();
+    for path in pathes {
+        let ext = path.extension().and_then(OsStr::to_str).unwrap_or("");
+        if !valid_file_extention(ext) {
+            continue;
+        }
+        // 无法读取的文件直接跳过
+        let Ok(code) = read_source(&path) else {
+            continue;
+        };
+        let relative_path = path.strip_prefix(root).unwrap_or(&path);
+        fetch_symbols(
+            relative_path.to_str().unwrap_or(""),
+            &code,
+            get_symbol_query(ext)?,
+            &mut graph,
+        )?;
+    }
+    Ok(graph)
+}
+
+/**
+ * 忽略空白差异的代码内容
+ */
+fn normalized_block(node: &CodeNode) -> String {
+    node.block
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+/**
+ * 标签中的符号名称，例如 `fn foo` 中的 foo
+ */
+fn symbol_name(node: &CodeNode) -> &str {
+    node.label.split_whitespace().last().unwrap_or("")
+}
+
+/**
+ * 除标签外是否还有实际内容，空的定义无法判断是否为重命名
+ */
+fn has_body(node: &CodeNode) -> bool {
+    let mut body = node.block.clone();
+    for word in node.label.split_whitespace() {
+        body = body.replacen(word, "", 1);
+    }
+    body.chars().any(char::is_alphanumeric)
+}
+
+/**
+ * 将名称替换为新名称后内容一致，则认为是重命名
+ */
+pub(crate) fn is_renamed(left: &CodeNode, right: &CodeNode) -> bool {
+    let (left_name, right_name) = (symbol_name(left), symbol_name(right));
+    left.block_type() == right.block_type()
+        && !left_name.is_empty()
+        && has_body(left)
+        && normalized_block(left).replace(left_name, right_name) == normalized_block(right)
+}
+
+/**
+ * 只保留最外层的节点，避免同时列出 impl 和其中的方法
+ */
+pub(crate) fn outermost(graph: &Graph, indices: &[CodeNodeIndex]) -> Vec<CodeNodeIndex> {
+    indices
+        .iter()
+        .copied()
+        .filter(|index| {
+            !graph
+                .parents_of(*index)
+                .any(|parent| indices.contains(&parent))
+        })
+        .collect()
+}
+
+/**
+ * 只保留最内层的节点，方法修改时不再列出包含它的 impl
+ */
+fn innermost(graph: &Graph, indices: &[CodeNodeIndex]) -> Vec<CodeNodeIndex> {
+    indices
+        .iter()
+        .copied()
+        .filter(|index| {
+            !graph
+                .descendants(*index)
+                .any(|child| indices.contains(&child))
+        })
+        .collect()
+}
+
+/**
+ * 对比两个目录中的定义
+ */
+pub fn compare_dirs(left_root: &Path, right_root: &Path) -> Result<DirComparison> {
+    let left = scan_definitions(left_root)?;
+    let right = scan_definitions(right_root)?;
+    let id_map = |graph: &Graph| {
+        graph
+            .nodes()
+            .filter(|(_, node)| node.level() > 0)
+            .map(|(index, node)| (node.id().to_owned(), index))
+            .collect::<HashMap<String, CodeNodeIndex>>()
+    };
+    let (left_ids, right_ids) = (id_map(&left), id_map(&right));
+    let with_full_path = |root: &Path, graph: &Graph, index: CodeNodeIndex| {
+        let mut node = graph.node(index).cloned().unwrap_or_default();
+        node.file_path = root.join(&node.file_path).to_str().unwrap_or("").to_owned();
+        node
+    };
+
+    let mut only_left = vec![];
+    let mut changed = vec![];
+    for (index, node) in left.nodes().filter(|(_, node)| node.level() > 0) {
+        match right_ids.get(node.id()) {
+            Some(right_index) => {
+                let right_node = right.node(*right_index);
+                if right_node.is_some_and(|right_node| {
+                    normalized_block(node) != normalized_block(right_node)
+                }) {
+                    changed.push((index, *right_index));
+                }
+            }
+            None => only_left.push(index),
+        }
+    }
+    let only_right = right
+        .nodes()
+        .filter(|(_, node)| node.level() > 0 && !left_ids.contains_key(node.id()))
+        .map(|(index, _)| index)
+        .collect::<Vec<CodeNodeIndex>>();
+
+    let mut comparison = DirComparison::default();
+    let changed_left = changed.iter().map(|(index, _)| *index).collect::<Vec<_>>();
+    let changed_left = innermost(&left, &changed_left);
+    for (left_index, right_index) in changed {
+        if changed_left.contains(&left_index) {
+            comparison.changed.push((
+                with_full_path(left_root, &left, left_index),
+                with_full_path(right_root, &right, right_index),
+            ));
+        }
+    }
+
+    let mut only_right = outermost(&right, &only_right);
+    for left_index in outermost(&left, &only_left) {
+        let left_node = with_full_path(left_root, &left, left_index);
+        let renamed_to = only_right.iter().position(|right_index| {
+            right
+                .node(*right_index)
+                .is_some_and(|right_node| is_renamed(&left_node, right_node))
+        });
+        match renamed_to {
+            Some(position) => {
+                let right_index = only_right.remove(position);
+                comparison
+                    .renamed
+                    .push((left_node, with_full_path(right_root, &right, right_index)));
+            }
+            None => comparison.only_left.push(left_node),
+        }
+    }
+    comparison.only_right = only_right
+        .into_iter()
+        .map(|index| with_full_path(right_root, &right, index))
+        .collect();
+    Ok(comparison)
+}
+
+/**
+ * 生成对比结果的图：根节点下按差异类型分组
+ */
+pub fn build_compare_graph(comparison: &DirComparison, left_name: &str, right_name: &str) -> Graph {
+    let mut graph = Graph::new();
+    let title = format!("{} ↔ {}", left_name, right_name);
+    let root = graph.add_node(group_node(&title, &title));
+    let groups = [
+        (
+            "only-left",
+            format!("仅在 {}", left_name),
+            comparison.only_left.to_vec(),
+        ),
+        (
+            "only-right",
+            format!("仅在 {}", right_name),
+            comparison.only_right.to_vec(),
+        ),
+        (
+            "renamed",
+            "重命名".to_owned(),
+            comparison
+                .renamed
+                .iter()
+                .map(|(left, right)| {
+                    let mut node = right.clone();
+                    node.label = format!("{} → {}", left.label.trim(), right.label.trim());
+                    node
+                })
+                .collect(),
+        ),
+        (
+            "changed",
+            "已修改".to_owned(),
+            comparison
+                .changed
+                .iter()
+                .map(|(_, right)| right.clone())
+                .collect(),
+        ),
+    ];
+    for (id, title, nodes) in groups {
+        if nodes.is_empty() {
+            continue;
+        }
+        let group_index = add_child(
+            &mut graph,
+            root,
+            group_node(id, &format!("{} ({})", title, nodes.len())),
+            1,
+        );
+        for node in nodes {
+            add_child(&mut graph, group_index, node, 2);
+        }
+    }
+    graph
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CodeBlockType;
+
+    fn function(label: &str, block: &str) -> CodeNode {
+        CodeNode::new(label, label, block, 1, CodeBlockType::FUNCTION, 1)
+    }
+
+    #[test]
+    fn same_body_with_new_name_is_renamed() {
+        let old = function(
+            "fn total",
+            "fn total(items: &[u32]) -> u32 {\n    items.iter().sum()\n}",
+        );
+        let new = function(
+            "fn sum",
+            "fn sum(items: &[u32]) -> u32 { items.iter().sum() }",
+        );
+        assert!(is_renamed(&old, &new));
+    }
+
+    #[test]
+    fn changed_body_is_not_renamed() {
+        let old = function(
+            "fn total",
+            "fn total(items: &[u32]) -> u32 { items.iter().sum() }",
+        );
+        let new = function(
+            "fn sum",
+            "fn sum(items: &[u32]) -> u32 { items.len() as u32 }",
+        );
+        assert!(!is_renamed(&old, &new));
+    }
+
+    #[test]
+    fn empty_bodies_and_other_kinds_are_not_renamed() {
+        // 空函数都一样，无法判断是哪一个改了名
+        assert!(!is_renamed(
+            &function("fn a", "fn a() {}"),
+            &function("fn b", "fn b() {}")
+        ));
+        let old = function("fn run", "fn run() { start(); }");
+        let mut new = function("struct go", "fn go() { start(); }");
+        new.block_type = CodeBlockType::CLASS;
+        assert!(!is_renamed(&old, &new));
+    }
+}
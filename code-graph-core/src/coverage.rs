@@ -0,0 +1,194 @@
+use std::{collections::HashMap, path::Path};
+
+use crate::{CodeBlockType, Graph};
+
+/**
+ * 测试覆盖率报告，记录每个文件中每一行的执行次数
+ */
+#[derive(Debug, Clone, Default)]
+pub struct Coverage {
+    files: HashMap<String, HashMap<usize, u64>>,
+}
+
+impl Coverage {
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /**
+     * 查找报告中的文件，报告中的路径可能是相对路径，按路径后缀匹配
+     */
+    fn lines_of(&self, file_path: &str) -> Option<&HashMap<usize, u64>> {
+        let path = Path::new(file_path);
+        self.files
+            .iter()
+            .find(|(name, _)| path.ends_with(name) || Path::new(name).ends_with(path))
+            .map(|(_, lines)| lines)
+    }
+}
+
+/**
+ * 解析覆盖率报告，支持 lcov 和 cobertura（xml）格式
+ */
+pub fn parse_coverage(text: &str) -> Coverage {
+    if text.trim_start().starts_with('<') {
+        parse_cobertura(text)
+    } else {
+        parse_lcov(text)
+    }
+}
+
+/**
+ * 解析 lcov 格式，只使用 SF 和 DA 记录
+ */
+pub fn parse_lcov(text: &str) -> Coverage {
+    let mut coverage = Coverage::default();
+    let mut current = None;
+    for line in text.lines() {
+        let line = line.trim();
+        if let Some(file) = line.strip_prefix("SF:") {
+            current = Some(file.to_owned());
+        } else if let Some(record) = line.strip_prefix("DA:") {
+            let mut fields = record.split(',');
+            let (Some(file), Some(Ok(line_number)), Some(Ok(hits))) = (
+                &current,
+                fields.next().map(str::parse::<usize>),
+                fields.next().map(str::parse::<u64>),
+            ) else {
+                continue;
+            };
+            let lines = coverage.files.entry(file.clone()).or_default();
+            *lines.entry(line_number).or_insert(0) += hits;
+        } else if line == "end_of_record" {
+            current = None;
+        }
+    }
+    coverage
+}
+
+/**
+ * 读取 xml 标签中的属性值
+ */
+fn attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let start = tag.find(&format!(" {}=\"", name))? + name.len() + 3;
+    let end = tag[start..].find('"')? + start;
+    Some(&tag[start..end])
+}
+
+/**
+ * 解析 cobertura 格式，文件来自 class 标签的 filename 属性，行来自 line 标签
+ */
+pub fn parse_cobertura(text: &str) -> Coverage {
+    let mut coverage = Coverage::default();
+    let mut current = None;
+    for tag in text.split('<').skip(1) {
+        let tag = tag.split('>').next().unwrap_or("");
+        if tag.starts_with("class ") {
+            current = attribute(tag, "filename").map(str::to_owned);
+        } else if tag.starts_with("line ") {
+            let (Some(file), Some(Ok(line_number)), Some(Ok(hits))) = (
+                &current,
+                attribute(tag, "number").map(str::parse::<usize>),
+                attribute(tag, "hits").map(str::parse::<u64>),
+            ) else {
+                continue;
+            };
+            let lines = coverage.files.entry(file.clone()).or_default();
+            *lines.entry(line_number).or_insert(0) += hits;
+        }
+    }
+    coverage
+}
+
+impl Graph {
+    /**
+     * 计算函数节点的行覆盖率，报告中没有可执行行的节点为 None
+     */
+    pub fn apply_coverage(&mut self, coverage: &Coverage) {
+        let mut lines_cache = HashMap::new();
+        for node in self.nodes.iter_mut() {
+            node.coverage = None;
+            if node.level == 0
+                || !matches!(
+                    node.block_type,
+                    CodeBlockType::FUNCTION | CodeBlockType::COMPONENT
+                )
+            {
+                continue;
+            }
+            let Some(lines) = lines_cache
+                .entry(node.file_path.clone())
+                .or_insert_with(|| coverage.lines_of(&node.file_path))
+            else {
+                continue;
+            };
+            let (start, end) = node.line_range();
+            let (mut covered, mut total) = (0, 0);
+            for (_, hits) in lines
+                .iter()
+                .filter(|(line, _)| (start..=end).contains(*line))
+            {
+                total += 1;
+                if *hits > 0 {
+                    covered += 1;
+                }
+            }
+            if total > 0 {
+                node.coverage = Some(covered as f32 / total as f32);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CodeNode, CodeNodeIndex};
+
+    const LCOV: &str = "TN:\nSF:src/a.rs\nDA:1,1\nDA:2,0\nDA:3,4\nDA:7,0\nend_of_record\n\
+                        SF:src/b.rs\nDA:1,0\nend_of_record\n";
+
+    #[test]
+    fn parse_lcov_records_hits_per_line() {
+        let coverage = parse_coverage(LCOV);
+        let lines = coverage.lines_of("/home/me/project/src/a.rs").unwrap();
+        assert_eq!(lines.get(&3), Some(&4));
+        assert_eq!(lines.get(&2), Some(&0));
+        assert_eq!(lines.len(), 4);
+        assert!(coverage.lines_of("src/c.rs").is_none());
+    }
+
+    #[test]
+    fn parse_cobertura_reads_class_lines() {
+        let coverage = parse_coverage(
+            r#"<?xml version="1.0"?>
+<coverage><packages><package><classes>
+<class name="a" filename="src/a.rs"><lines>
+<line number="1" hits="2"/><line number="2" hits="0"/>
+</lines></class>
+</classes></package></packages></coverage>"#,
+        );
+        let lines = coverage.lines_of("src/a.rs").unwrap();
+        assert_eq!((lines.get(&1), lines.get(&2)), (Some(&2), Some(&0)));
+        assert!(parse_coverage("").is_empty());
+    }
+
+    #[test]
+    fn apply_coverage_uses_lines_inside_each_function() {
+        let mut graph = Graph::new();
+        graph.add_node(CodeNode::new("a", "a", "", 0, CodeBlockType::NORMAL, 0));
+        // 第 1 ~ 3 行的函数有两行被执行，第 5 ~ 6 行的函数报告中没有可执行行
+        let mut add = |location: usize, block: &str| {
+            let mut node = CodeNode::new("f", "fn f", block, location, CodeBlockType::FUNCTION, 1);
+            node.file_path = "/project/src/a.rs".to_owned();
+            graph.add_node(node);
+        };
+        add(1, "fn f() {\n    run();\n}");
+        add(5, "fn g() {\n}");
+        graph.apply_coverage(&parse_coverage(LCOV));
+        let coverage = |index: usize| graph.node(CodeNodeIndex(index)).unwrap().coverage;
+        assert_eq!(coverage(0), None);
+        assert!((coverage(1).unwrap() - 2.0 / 3.0).abs() < f32::EPSILON);
+        assert_eq!(coverage(2), None);
+    }
+}
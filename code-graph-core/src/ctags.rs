@@ -0,0 +1,467 @@
+use std::{
+    collections::HashMap,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    read_source, simplify_generics, CodeBlockType, CodeNode, CodeNodeIndex, GenericsDisplay, Graph,
+    Result,
+};
+
+// 表示所在作用域的字段，例如 class:Foo
+const SCOPE_KINDS: [&str; 12] = [
+    "class",
+    "struct",
+    "interface",
+    "implementation",
+    "namespace",
+    "module",
+    "package",
+    "enum",
+    "union",
+    "trait",
+    "function",
+    "method",
+];
+
+/**
+ * tags 文件中的一个符号
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tag {
+    pub name: String,
+    pub file: String,
+    // 定义所在的行（从 1 开始）
+    pub line: usize,
+    // 定义结束的行，tags 文件中没有 end 字段时为 None
+    pub end: Option<usize>,
+    // 符号类型，例如 function、class
+    pub kind: String,
+    // 所在的作用域（类型，名称），例如 ("class", "Foo")
+    pub scope: Option<(String, String)>,
+    pub language: Option<String>,
+}
+
+/**
+ * 标签中的符号名称，去掉泛型参数、继承和参数列表，
+ * 例如 `pub fn foo <T>` 为 foo，`class Foo extends Bar` 为 Foo，`impl Display for Foo` 为 Foo
+ */
+fn tag_name(label: &str) -> String {
+    let label = simplify_generics(label, GenericsDisplay::Strip);
+    let mut words = vec![];
+    for word in label.split_whitespace() {
+        if matches!(word, "extends" | "implements")
+            || !word.starts_with(|c: char| c.is_alphanumeric() || c == '_' || c == '$')
+        {
+            break;
+        }
+        words.push(word);
+    }
+    words
+        .last()
+        .map(|word| {
+            word.trim_end_matches(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .to_owned()
+        })
+        .unwrap_or_default()
+}
+
+/**
+ * 节点在 tags 文件中的符号类型，使用 universal-ctags 的类型全称
+ */
+fn tag_kind(node: &CodeNode, nested: bool) -> Option<&'static str> {
+    let has_word = |words: &[&str]| {
+        node.label
+            .split_whitespace()
+            .any(|word| words.contains(&word))
+    };
+    let kind = match node.block_type() {
+        CodeBlockType::FUNCTION | CodeBlockType::COMPONENT if nested => "method",
+        CodeBlockType::FUNCTION | CodeBlockType::COMPONENT => "function",
+        CodeBlockType::METHOD => "method",
+        CodeBlockType::STRUCT => "struct",
+        CodeBlockType::IMPL => "implementation",
+        CodeBlockType::CLASS if has_word(&["impl"]) => "implementation",
+        CodeBlockType::CLASS if has_word(&["trait", "interface"]) => "interface",
+        CodeBlockType::CLASS => "class",
+        CodeBlockType::CONST => "constant",
+        CodeBlockType::NORMAL => "variable",
+        // 调用不是定义
+        CodeBlockType::CALL | CodeBlockType::HOOK => return None,
+    };
+    Some(kind)
+}
+
+/**
+ * tags 文件中的符号类型对应的代码类型，同时支持全称和单个字母的简写
+ */
+fn tag_block_type(kind: &str) -> CodeBlockType {
+    match kind {
+        "function" | "f" | "func" | "subroutine" | "procedure" => CodeBlockType::FUNCTION,
+        "method" | "singletonMethod" | "P" => CodeBlockType::METHOD,
+        "class" | "c" | "interface" | "i" | "trait" | "module" | "namespace" | "n" => {
+            CodeBlockType::CLASS
+        }
+        "struct" | "s" | "enum" | "g" | "union" | "u" | "typedef" | "t" | "type" => {
+            CodeBlockType::STRUCT
+        }
+        "implementation" => CodeBlockType::IMPL,
+        "constant" | "const" | "C" | "macro" | "d" => CodeBlockType::CONST,
+        _ => CodeBlockType::NORMAL,
+    }
+}
+
+/**
+ * 图中所有定义对应的符号，嵌套的定义以父节点作为作用域
+ */
+pub fn graph_tags(graph: &Graph) -> Vec<Tag> {
+    let mut tags = vec![];
+    for (index, node) in graph.nodes() {
+        if node.level() == 0 {
+            continue;
+        }
+        let parent = graph
+            .parents_of(index)
+            .filter_map(|parent| graph.node(parent))
+            .find(|parent| parent.level() > 0);
+        let Some(kind) = tag_kind(node, parent.is_some()) else {
+            continue;
+        };
+        let name = tag_name(&node.label);
+        if name.is_empty() {
+            continue;
+        }
+        let scope = parent.and_then(|parent| {
+            let scope_kind = tag_kind(parent, false)?;
+            Some((scope_kind.to_owned(), tag_name(&parent.label)))
+        });
+        tags.push(Tag {
+            name,
+            file: node.file_path.clone(),
+            line: node.file_location,
+            end: Some(node.line_range().1),
+            kind: kind.to_owned(),
+            scope,
+            language: node.language.clone(),
+        });
+    }
+    tags
+}
+
+/**
+ * 以 universal-ctags 的扩展格式写入 tags 文件，符号按名称排序，
+ * 地址使用行号，附带 line、language、作用域和 end 字段
+ */
+pub fn write_tags(tags: &[Tag], writer: &mut dyn Write) -> Result<()> {
+    writeln!(
+        writer,
+        "!_TAG_FILE_FORMAT\t2\t/extended format; --format=1 will not append ;\" to lines/"
+    )?;
+    writeln!(
+        writer,
+        "!_TAG_FILE_SORTED\t1\t/0=unsorted, 1=sorted, 2=foldcase/"
+    )?;
+    writeln!(writer, "!_TAG_PROGRAM_NAME\tcode-graph\t//")?;
+    let mut tags = tags.iter().collect::<Vec<&Tag>>();
+    tags.sort_by(|a, b| (&a.name, &a.file, a.line).cmp(&(&b.name, &b.file, b.line)));
+    for tag in tags {
+        write!(
+            writer,
+            "{}\t{}\t{};\"\t{}\tline:{}",
+            tag.name, tag.file, tag.line, tag.kind, tag.line
+        )?;
+        if let Some(language) = &tag.language {
+            write!(writer, "\tlanguage:{}", language)?;
+        }
+        if let Some((scope_kind, scope_name)) = &tag.scope {
+            write!(writer, "\t{}:{}", scope_kind, scope_name)?;
+        }
+        if let Some(end) = tag.end {
+            write!(writer, "\tend:{}", end)?;
+        }
+        writeln!(writer)?;
+    }
+    Ok(())
+}
+
+/**
+ * 查找模式地址（例如 `/^fn foo() {$/`）对应的行号，找不到时返回 None
+ */
+fn find_pattern_line(pattern: &str, code: &str) -> Option<usize> {
+    let inner = pattern.get(1..pattern.len().saturating_sub(1))?;
+    let (inner, anchored_start) = match inner.strip_prefix('^') {
+        Some(inner) => (inner, true),
+        None => (inner, false),
+    };
+    let (inner, anchored_end) = match inner.strip_suffix('$') {
+        Some(inner) => (inner, true),
+        None => (inner, false),
+    };
+    let text = inner
+        .replace("\\/", "/")
+        .replace("\\?", "?")
+        .replace("\\\\", "\\");
+    code.lines()
+        .position(|line| match (anchored_start, anchored_end) {
+            (true, true) => line == text,
+            (true, false) => line.starts_with(&text),
+            (false, true) => line.ends_with(&text),
+            (false, false) => line.contains(&text),
+        })
+        .map(|index| index + 1)
+}
+
+/**
+ * 解析 tags 文件的内容，相对路径相对 root，
+ * 模式地址需要读取源文件确定行号，找不到对应行的符号会被忽略
+ */
+pub fn parse_tags(text: &str, root: &Path) -> Vec<Tag> {
+    let mut sources: HashMap<PathBuf, Option<String>> = HashMap::new();
+    let mut tags = vec![];
+    for line in text.lines() {
+        if line.starts_with("!_") || line.trim().is_empty() {
+            continue;
+        }
+        let mut columns = line.splitn(3, '\t');
+        let (Some(name), Some(file), Some(rest)) = (columns.next(), columns.next(), columns.next())
+        else {
+            continue;
+        };
+        let (address, fields) = match rest.rfind(";\"") {
+            Some(position) => (&rest[..position], &rest[position + 2..]),
+            None => (rest, ""),
+        };
+        let path = root.join(file);
+        let mut tag = Tag {
+            name: name.to_owned(),
+            file: path.display().to_string(),
+            line: address.trim().parse().unwrap_or(0),
+            end: None,
+            kind: String::new(),
+            scope: None,
+            language: None,
+        };
+        let mut line_field = None;
+        for field in fields.split('\t').filter(|field| !field.is_empty()) {
+            let Some((key, value)) = field.split_once(':') else {
+                // 没有字段名的是符号类型
+                tag.kind = field.to_owned();
+                continue;
+            };
+            match key {
+                "kind" => tag.kind = value.to_owned(),
+                "line" => line_field = value.parse().ok(),
+                "end" => tag.end = value.parse().ok(),
+                "language" => tag.language = Some(value.to_owned()),
+                "scope" => {
+                    tag.scope = value
+                        .split_once(':')
+                        .map(|(kind, name)| (kind.to_owned(), name.to_owned()));
+                }
+                _ if SCOPE_KINDS.contains(&key) => {
+                    tag.scope = Some((key.to_owned(), value.to_owned()));
+                }
+                _ => {}
+            }
+        }
+        if tag.line == 0 {
+            tag.line = match line_field {
+                Some(line) => line,
+                None => {
+                    let code = sources
+                        .entry(path.clone())
+                        .or_insert_with(|| read_source(&path).ok());
+                    let Some(line) = code
+                        .as_deref()
+                        .and_then(|code| find_pattern_line(address.trim(), code))
+                    else {
+                        continue;
+                    };
+                    line
+                }
+            };
+        }
+        tags.push(tag);
+    }
+    tags
+}
+
+/**
+ * 读取 tags 文件，其中的相对路径相对 tags 文件所在的目录
+ */
+pub fn load_tags(path: &Path) -> Result<Vec<Tag>> {
+    let text = read_source(path)?;
+    Ok(parse_tags(&text, path.parent().unwrap_or(Path::new(""))))
+}
+
+/**
+ * 用 tags 中属于 file 的符号生成图，用于还没有语法解析的语言，
+ * 嵌套关系由作用域字段决定，没有 end 字段时定义延续到下一个不属于它的符号之前，
+ * file 中没有符号时返回 None
+ */
+pub fn tags_graph(tags: &[Tag], file: &str) -> Option<Graph> {
+    let mut file_tags = tags
+        .iter()
+        .filter(|tag| Path::new(&tag.file) == Path::new(file))
+        .collect::<Vec<&Tag>>();
+    if file_tags.is_empty() {
+        return None;
+    }
+    file_tags.sort_by_key(|tag| tag.line);
+    let code = read_source(Path::new(file)).unwrap_or_default();
+    let lines = code.lines().collect::<Vec<&str>>();
+
+    // 按作用域名称在外层的符号中查找父符号
+    let mut parents: Vec<Option<usize>> = vec![];
+    let mut stack: Vec<usize> = vec![];
+    for (index, tag) in file_tags.iter().enumerate() {
+        let parent = tag.scope.as_ref().and_then(|(_, scope)| {
+            let scope_name = scope.rsplit([':', '.']).next().unwrap_or(scope);
+            stack
+                .iter()
+                .rposition(|candidate| file_tags[*candidate].name == scope_name)
+        });
+        match parent {
+            Some(position) => stack.truncate(position + 1),
+            None => stack.clear(),
+        }
+        parents.push(stack.last().copied());
+        stack.push(index);
+    }
+    let is_descendant = |mut index: usize, ancestor: usize| {
+        while let Some(parent) = parents[index] {
+            if parent == ancestor {
+                return true;
+            }
+            index = parent;
+        }
+        false
+    };
+
+    let mut graph = Graph::new();
+    let mut root_node = CodeNode::new(file, file, &code, 0, CodeBlockType::NORMAL, 0);
+    root_node.file_path = file.to_owned();
+    let root = graph.add_node(root_node);
+    let mut indices: Vec<CodeNodeIndex> = vec![];
+    let mut levels: Vec<usize> = vec![];
+    let mut ends: Vec<usize> = vec![];
+    for (index, tag) in file_tags.iter().enumerate() {
+        let start = tag.line.max(1);
+        let mut end = tag.end.unwrap_or_else(|| {
+            let mut end = file_tags[index + 1..]
+                .iter()
+                .enumerate()
+                .find(|(offset, _)| !is_descendant(index + 1 + offset, index))
+                .map_or(lines.len(), |(_, next)| next.line.saturating_sub(1));
+            // 去掉与下一个符号之间的空行
+            while end > start
+                && lines
+                    .get(end - 1)
+                    .is_some_and(|line| line.trim().is_empty())
+            {
+                end -= 1;
+            }
+            end
+        });
+        if let Some(parent) = parents[index] {
+            end = end.min(ends[parent]);
+        }
+        ends.push(end);
+        let block = lines
+            .get(start - 1..end.max(start).min(lines.len()))
+            .map(|block| block.join("\n"))
+            .unwrap_or_default();
+        let (parent, level) = match parents[index] {
+            Some(parent) => (indices[parent], levels[parent] + 1),
+            None => (root, 1),
+        };
+        let mut node = CodeNode::new(
+            "",
+            &tag.name,
+            &block,
+            tag.line,
+            tag_block_type(&tag.kind),
+            level,
+        );
+        node.file_path = file.to_owned();
+        node.language = tag.language.clone();
+        let node_index = graph.add_node(node);
+        graph.add_edge(parent, node_index);
+        indices.push(node_index);
+        levels.push(level);
+    }
+    graph.assign_stable_ids(root);
+    Some(graph)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tag(name: &str, line: usize, kind: &str, scope: Option<(&str, &str)>) -> Tag {
+        Tag {
+            name: name.to_owned(),
+            file: "src/a.rs".to_owned(),
+            line,
+            end: Some(line + 2),
+            kind: kind.to_owned(),
+            scope: scope.map(|(kind, name)| (kind.to_owned(), name.to_owned())),
+            language: Some("Rust".to_owned()),
+        }
+    }
+
+    #[test]
+    fn written_tags_parse_back() {
+        let tags = vec![
+            tag("run", 3, "method", Some(("implementation", "A"))),
+            tag("A", 1, "struct", None),
+            Tag {
+                end: None,
+                language: None,
+                ..tag("main", 9, "function", None)
+            },
+        ];
+        let mut output = vec![];
+        write_tags(&tags, &mut output).unwrap();
+        let text = String::from_utf8(output).unwrap();
+        let mut expected = tags.clone();
+        expected.sort_by(|a, b| a.name.cmp(&b.name));
+        assert_eq!(parse_tags(&text, Path::new("")), expected);
+    }
+
+    #[test]
+    fn parses_universal_ctags_fields() {
+        let text = "!_TAG_FILE_FORMAT\t2\t//\n\
+                    foo\tsrc/a.rs\t/^fn foo() {$/;\"\tf\tline:7\tclass:Foo\tend:9\n\
+                    bar\tsrc/a.rs\t12;\"\tkind:function\tscope:module:b\n";
+        let tags = parse_tags(text, Path::new("root"));
+        assert_eq!(tags.len(), 2);
+        assert_eq!(
+            tags[0].file,
+            Path::new("root").join("src/a.rs").display().to_string()
+        );
+        assert_eq!(tags[0].line, 7);
+        assert_eq!(tags[0].end, Some(9));
+        assert_eq!(tags[0].kind, "f");
+        assert_eq!(tags[0].scope, Some(("class".to_owned(), "Foo".to_owned())));
+        assert_eq!(tags[1].line, 12);
+        assert_eq!(tags[1].kind, "function");
+        assert_eq!(tags[1].scope, Some(("module".to_owned(), "b".to_owned())));
+    }
+
+    #[test]
+    fn pattern_addresses_find_lines() {
+        let code = "struct A;\nfn foo() {\n}\n    fn foo() {}\n";
+        assert_eq!(find_pattern_line("/^fn foo() {$/", code), Some(2));
+        assert_eq!(find_pattern_line("/fn foo() {}$/", code), Some(4));
+        assert_eq!(find_pattern_line("/^fn missing/", code), None);
+    }
+
+    #[test]
+    fn tag_names_drop_modifiers() {
+        assert_eq!(tag_name("pub fn foo <T>"), "foo");
+        assert_eq!(tag_name("class Foo extends Bar"), "Foo");
+        assert_eq!(tag_name("impl Display for Foo"), "Foo");
+    }
+}
@@ -0,0 +1,241 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/**
+ * 项目声明的依赖
+ */
+#[derive(Debug, Clone)]
+pub struct Dependency {
+    pub name: String,
+    // 锁文件中的版本，没有锁文件时为声明的版本要求
+    pub version: String,
+    // 依赖源码在本地缓存中的位置，找不到时为 None
+    pub source: Option<PathBuf>,
+}
+
+/**
+ * 读取项目的 Cargo.toml 和 package.json，列出依赖并查找本地源码
+ */
+pub fn find_dependencies(project: &Path) -> Vec<Dependency> {
+    let mut dependencies = vec![];
+    if let Ok(manifest) = fs::read_to_string(project.join("Cargo.toml")) {
+        // 工作区成员的锁文件在上层目录
+        let locked = project
+            .ancestors()
+            .find_map(|dir| fs::read_to_string(dir.join("Cargo.lock")).ok())
+            .map(|lock| parse_cargo_lock(&lock))
+            .unwrap_or_default();
+        let registry_dirs = cargo_registry_dirs();
+        for (name, requirement, path) in parse_cargo_manifest(&manifest) {
+            let mut versions = locked.get(&name).cloned().unwrap_or_default();
+            // 同一个包锁定了多个版本时，优先满足版本要求的
+            let required = version_key(requirement.trim_start_matches(['^', '~', '=', ' ']));
+            versions.sort_by_key(|version| !version_key(version).starts_with(&required));
+            let source = match path {
+                Some(path) => Some(project.join(path)).filter(|dir| dir.is_dir()),
+                None => find_crate_source(&registry_dirs, &name, &versions, &requirement),
+            };
+            let version = versions.into_iter().next().unwrap_or(requirement);
+            dependencies.push(Dependency {
+                name,
+                version,
+                source,
+            });
+        }
+    }
+    if let Ok(package) = fs::read_to_string(project.join("package.json")) {
+        for (name, version) in parse_package_json(&package) {
+            let source = Some(project.join("node_modules").join(&name)).filter(|dir| dir.is_dir());
+            dependencies.push(Dependency {
+                name,
+                version,
+                source,
+            });
+        }
+    }
+    dependencies
+}
+
+/**
+ * 解析 Cargo.toml 中各类依赖表，返回（包名，版本要求，本地路径），
+ * 重命名的依赖使用 package 指定的真实包名
+ */
+fn parse_cargo_manifest(manifest: &str) -> Vec<(String, String, Option<String>)> {
+    let is_dependency_table = |table: &str| {
+        table == "dependencies"
+            || table.ends_with("-dependencies")
+            || table.ends_with(".dependencies")
+    };
+    let mut dependencies: Vec<(String, String, Option<String>)> = vec![];
+    // [dependencies.name] 形式的表
+    let mut current_table = None;
+    let mut in_dependencies = false;
+    for line in manifest.lines() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        if let Some(table) = line
+            .strip_prefix('[')
+            .and_then(|line| line.strip_suffix(']'))
+        {
+            let table = table.trim();
+            in_dependencies = is_dependency_table(table);
+            current_table = table
+                .rsplit_once('.')
+                .filter(|(parent, _)| is_dependency_table(parent))
+                .map(|(_, name)| name.trim_matches('"').to_owned());
+            if let Some(name) = &current_table {
+                dependencies.push((name.clone(), String::new(), None));
+            }
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let (key, value) = (key.trim().trim_matches('"'), value.trim());
+        if current_table.is_some() {
+            if let Some(last) = dependencies.last_mut() {
+                match key {
+                    "version" => last.1 = value.trim_matches('"').to_owned(),
+                    "package" => last.0 = value.trim_matches('"').to_owned(),
+                    "path" => last.2 = Some(value.trim_matches('"').to_owned()),
+                    _ => {}
+                }
+            }
+        } else if in_dependencies {
+            let name = inline_field(value, "package").unwrap_or_else(|| key.to_owned());
+            let version = if value.starts_with('"') {
+                value.trim_matches('"').to_owned()
+            } else {
+                inline_field(value, "version").unwrap_or_default()
+            };
+            dependencies.push((name, version, inline_field(value, "path")));
+        }
+    }
+    dependencies.sort();
+    dependencies.dedup_by(|a, b| a.0 == b.0);
+    dependencies
+}
+
+/**
+ * 读取内联表 { version = "1.0", package = "x" } 中的字段
+ */
+fn inline_field(table: &str, field: &str) -> Option<String> {
+    let table = table.strip_prefix('{')?.strip_suffix('}')?;
+    table.split(',').find_map(|pair| {
+        let (key, value) = pair.split_once('=')?;
+        (key.trim() == field).then(|| value.trim().trim_matches('"').to_owned())
+    })
+}
+
+/**
+ * 解析 Cargo.lock，返回包名到版本的映射
+ */
+fn parse_cargo_lock(lock: &str) -> HashMap<String, Vec<String>> {
+    let mut versions: HashMap<String, Vec<String>> = HashMap::new();
+    let mut name = None;
+    for line in lock.lines() {
+        if let Some(value) = line.strip_prefix("name = ") {
+            name = Some(value.trim_matches('"').to_owned());
+        } else if let Some(value) = line.strip_prefix("version = ") {
+            if let Some(name) = name.take() {
+                versions
+                    .entry(name)
+                    .or_default()
+                    .push(value.trim_matches('"').to_owned());
+            }
+        }
+    }
+    versions
+}
+
+/**
+ * 解析 package.json 中的 dependencies 和 devDependencies
+ */
+fn parse_package_json(package: &str) -> Vec<(String, String)> {
+    let Ok(package) = serde_json::from_str::<serde_json::Value>(package) else {
+        return vec![];
+    };
+    ["dependencies", "devDependencies"]
+        .iter()
+        .filter_map(|field| package.get(field)?.as_object())
+        .flatten()
+        .map(|(name, version)| (name.clone(), version.as_str().unwrap_or("").to_owned()))
+        .collect()
+}
+
+/**
+ * cargo 本地缓存中解压后的源码目录，每个镜像源一个
+ */
+fn cargo_registry_dirs() -> Vec<PathBuf> {
+    let cargo_home = std::env::var_os("CARGO_HOME")
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("HOME")
+                .or_else(|| std::env::var_os("USERPROFILE"))
+                .map(|home| PathBuf::from(home).join(".cargo"))
+        });
+    let Some(Ok(entries)) = cargo_home.map(|home| fs::read_dir(home.join("registry").join("src")))
+    else {
+        return vec![];
+    };
+    entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_dir())
+        .collect()
+}
+
+/**
+ * 版本号中的数字部分，用于比较大小
+ */
+fn version_key(version: &str) -> Vec<u64> {
+    version
+        .split(['.', '-', '+'])
+        .map_while(|part| part.parse().ok())
+        .collect()
+}
+
+/**
+ * 查找 crate 的源码目录，优先使用锁定的版本，
+ * 否则在满足版本要求前缀的版本中选择最大的
+ */
+fn find_crate_source(
+    registry_dirs: &[PathBuf],
+    name: &str,
+    versions: &[String],
+    requirement: &str,
+) -> Option<PathBuf> {
+    for dir in registry_dirs {
+        for version in versions {
+            let source = dir.join(format!("{}-{}", name, version));
+            if source.is_dir() {
+                return Some(source);
+            }
+        }
+    }
+    let prefix = format!("{}-", name);
+    let requirement = version_key(requirement.trim_start_matches(['^', '~', '=', ' ']));
+    let candidates = registry_dirs
+        .iter()
+        .filter_map(|dir| fs::read_dir(dir).ok())
+        .flatten()
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter_map(|path| {
+            let version = path
+                .file_name()
+                .and_then(|file_name| file_name.to_str())
+                .and_then(|file_name| file_name.strip_prefix(&prefix))
+                .filter(|version| version.starts_with(|c: char| c.is_ascii_digit()))
+                .map(version_key)?;
+            Some((version, path))
+        })
+        .collect::<Vec<(Vec<u64>, PathBuf)>>();
+    let matched = candidates
+        .iter()
+        .filter(|(version, _)| version.starts_with(&requirement))
+        .max();
+    matched
+        .or(candidates.iter().max())
+        .map(|(_, path)| path.clone())
+}
@@ -0,0 +1,349 @@
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap, HashSet},
+    hash::{Hash, Hasher},
+    path::Path,
+};
+
+use crate::{
+    patch::{add_child, group_node},
+    CodeBlockType, CodeNode, EdgeKind, Graph,
+};
+
+/**
+ * 少于这么多个词的函数（例如 getter）不参与比较
+ */
+pub const MIN_TOKENS: usize = 40;
+
+/**
+ * 规范化后保留原样的关键字，覆盖目前支持的所有语言，其他标识符都视为相同
+ */
+const KEYWORDS: [&str; 62] = [
+    "if",
+    "else",
+    "elif",
+    "for",
+    "while",
+    "loop",
+    "do",
+    "match",
+    "switch",
+    "case",
+    "default",
+    "return",
+    "break",
+    "continue",
+    "fn",
+    "def",
+    "function",
+    "class",
+    "struct",
+    "impl",
+    "trait",
+    "interface",
+    "enum",
+    "let",
+    "var",
+    "const",
+    "mut",
+    "ref",
+    "new",
+    "try",
+    "catch",
+    "except",
+    "finally",
+    "throw",
+    "raise",
+    "await",
+    "async",
+    "yield",
+    "in",
+    "is",
+    "not",
+    "and",
+    "or",
+    "lambda",
+    "with",
+    "as",
+    "pub",
+    "static",
+    "self",
+    "this",
+    "super",
+    "true",
+    "false",
+    "null",
+    "None",
+    "True",
+    "False",
+    "nil",
+    "typeof",
+    "instanceof",
+    "unsafe",
+    "move",
+];
+
+/**
+ * 一组几乎相同的函数：去掉空白和注释、标识符和字面量统一替换后词序列完全相同
+ */
+#[derive(Debug, Clone)]
+pub struct DuplicateGroup {
+    pub nodes: Vec<CodeNode>,
+    // 每个函数规范化后的词数
+    pub tokens: usize,
+}
+
+/**
+ * 把代码切分为规范化的词：关键字和符号保留原样，其他标识符替换为 $，
+ * 数字、字符串和字符字面量替换为 0，空白和注释丢弃，ext 为文件扩展名
+ */
+pub fn normalized_tokens<'a>(code: &'a str, ext: &str) -> Vec<&'a str> {
+    // python 中 # 开头的是注释，Rust 中单引号还用于生命周期
+    let python = ext == "py";
+    let rust = ext == "rs";
+    let bytes = code.as_bytes();
+    let mut tokens = vec![];
+    let mut i = 0;
+    while i < bytes.len() {
+        let c = bytes[i];
+        let rest = &bytes[i..];
+        if c.is_ascii_whitespace() {
+            i += 1;
+        } else if (python && c == b'#') || (!python && rest.starts_with(b"//")) {
+            while i < bytes.len() && bytes[i] != b'\n' {
+                i += 1;
+            }
+        } else if !python && rest.starts_with(b"/*") {
+            i += 2;
+            while i < bytes.len() && !bytes[i..].starts_with(b"*/") {
+                i += 1;
+            }
+            i += 2;
+        } else if c == b'"' || c == b'`' || (c == b'\'' && !(rust && is_lifetime(rest))) {
+            i += 1;
+            while i < bytes.len() && bytes[i] != c {
+                i += if bytes[i] == b'\\' { 2 } else { 1 };
+            }
+            i += 1;
+            tokens.push("0");
+        } else if c.is_ascii_digit() {
+            while i < bytes.len()
+                && (bytes[i].is_ascii_alphanumeric() || matches!(bytes[i], b'_' | b'.'))
+            {
+                i += 1;
+            }
+            tokens.push("0");
+        } else if c.is_ascii_alphabetic() || c == b'_' || c == b'$' || !c.is_ascii() {
+            let start = i;
+            while i < bytes.len()
+                && (bytes[i].is_ascii_alphanumeric()
+                    || matches!(bytes[i], b'_' | b'$')
+                    || !bytes[i].is_ascii())
+            {
+                i += 1;
+            }
+            let word = &code[start..i];
+            tokens.push(if KEYWORDS.contains(&word) { word } else { "$" });
+        } else {
+            tokens.push(&code[i..i + 1]);
+            i += 1;
+        }
+    }
+    tokens
+}
+
+/**
+ * Rust 中单引号开头、后面不是 'a' 或 '\n' 这样的字符字面量时为生命周期
+ */
+fn is_lifetime(rest: &[u8]) -> bool {
+    rest.get(1) != Some(&b'\\') && rest.get(2) != Some(&b'\'')
+}
+
+/**
+ * 在项目的所有函数中查找几乎相同的函数，按词数从多到少排列，
+ * 同一组中的函数按文件和行号排列
+ */
+pub fn find_duplicates(definitions: &[CodeNode]) -> Vec<DuplicateGroup> {
+    let mut groups: HashMap<u64, (usize, Vec<&CodeNode>)> = HashMap::new();
+    let mut seen = HashSet::new();
+    for node in definitions.iter().filter(|node| {
+        matches!(
+            node.block_type,
+            CodeBlockType::FUNCTION | CodeBlockType::METHOD | CodeBlockType::COMPONENT
+        )
+    }) {
+        if !seen.insert(&node.id) {
+            continue;
+        }
+        let ext = Path::new(&node.file_path)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or("");
+        let tokens = normalized_tokens(&node.block, ext);
+        if tokens.len() < MIN_TOKENS {
+            continue;
+        }
+        let mut hasher = DefaultHasher::new();
+        tokens.hash(&mut hasher);
+        groups
+            .entry(hasher.finish())
+            .or_insert_with(|| (tokens.len(), vec![]))
+            .1
+            .push(node);
+    }
+    let mut groups = groups
+        .into_values()
+        .filter(|(_, nodes)| nodes.len() > 1)
+        .map(|(tokens, mut nodes)| {
+            nodes.sort_by(|a, b| {
+                (&a.file_path, a.file_location).cmp(&(&b.file_path, b.file_location))
+            });
+            DuplicateGroup {
+                nodes: nodes.into_iter().cloned().collect(),
+                tokens,
+            }
+        })
+        .collect::<Vec<DuplicateGroup>>();
+    groups.sort_by(|a, b| {
+        (b.tokens, b.nodes.len(), &a.nodes[0].file_path).cmp(&(
+            a.tokens,
+            a.nodes.len(),
+            &b.nodes[0].file_path,
+        ))
+    });
+    groups
+}
+
+/**
+ * 重复代码图：根节点下每组重复代码一个分组，分组中的第一个函数与其他函数之间连出重复关系，
+ * 函数保留原来的 ID，标签后加上相对于项目目录的位置
+ */
+pub fn build_duplicate_graph(
+    project_name: &str,
+    root_path: &Path,
+    groups: &[DuplicateGroup],
+) -> Graph {
+    let mut graph = Graph::new();
+    let root = graph.add_node(group_node(project_name, project_name));
+    for (number, group) in groups.iter().enumerate() {
+        let group_index = add_child(
+            &mut graph,
+            root,
+            group_node(
+                &format!("duplicate-{}", number),
+                &format!(
+                    "重复 {}（{} 处，{} 个词）",
+                    number + 1,
+                    group.nodes.len(),
+                    group.tokens
+                ),
+            ),
+            1,
+        );
+        let mut first = None;
+        for node in &group.nodes {
+            let mut node = node.clone();
+            let location = Path::new(&node.file_path)
+                .strip_prefix(root_path)
+                .unwrap_or(Path::new(&node.file_path))
+                .display()
+                .to_string();
+            node.label = format!("{}  {}:{}", node.label.trim(), location, node.file_location);
+            node.level = 2;
+            node.visiable = true;
+            let index = graph.add_node(node);
+            graph.add_edge(group_index, index);
+            match first {
+                Some(first) => graph.add_edge_with_kind(first, index, EdgeKind::Duplicate),
+                None => first = Some(index),
+            }
+        }
+    }
+    graph
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SUM: &str = "fn total(items: &[Item]) -> u64 {
+    // 累加所有数量
+    let mut sum = 0;
+    for item in items.iter() {
+        if item.count > 10 && item.enabled {
+            sum += item.count * 2;
+        } else {
+            sum += item.count;
+        }
+    }
+    sum
+}";
+
+    fn function(id: &str, path: &str, location: usize, block: &str) -> CodeNode {
+        let mut node = CodeNode::new(id, id, block, location, CodeBlockType::FUNCTION, 1);
+        node.file_path = path.to_owned();
+        node
+    }
+
+    #[test]
+    fn normalized_tokens_ignore_names_literals_and_comments() {
+        assert_eq!(
+            normalized_tokens("let x = \"a\"; // note\nreturn y + 1;", "rs"),
+            vec!["let", "$", "=", "0", ";", "return", "$", "+", "0", ";"]
+        );
+        assert_eq!(
+            normalized_tokens("fn f<'a>(c: char) { 'x' }", "rs"),
+            vec!["fn", "$", "<", "'", "$", ">", "(", "$", ":", "$", ")", "{", "0", "}"]
+        );
+        assert_eq!(normalized_tokens("x = 1 # 注释", "py"), vec!["$", "=", "0"]);
+    }
+
+    #[test]
+    fn find_duplicates_groups_renamed_copies() {
+        let renamed = SUM
+            .replace("total", "amount")
+            .replace("sum", "acc")
+            .replace("10", "20")
+            .replace("// 累加所有数量\n", "");
+        let changed = SUM.replace("&& item.enabled", "|| item.enabled");
+        let definitions = vec![
+            function("b::amount", "b.rs", 3, &renamed),
+            function("a::total", "a.rs", 1, SUM),
+            function("c::other", "c.rs", 1, &changed),
+            // 同一 ID 只算一次
+            function("a::total", "a.rs", 1, SUM),
+            // 太短的函数不比较
+            function("a::get", "a.rs", 20, "fn get(&self) -> u32 { self.value }"),
+            function("b::get", "b.rs", 20, "fn get(&self) -> u32 { self.other }"),
+        ];
+        let groups = find_duplicates(&definitions);
+        assert_eq!(groups.len(), 1);
+        let ids = groups[0]
+            .nodes
+            .iter()
+            .map(|node| node.id.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(ids, vec!["a::total", "b::amount"]);
+        assert_eq!(groups[0].tokens, normalized_tokens(SUM, "rs").len());
+    }
+
+    #[test]
+    fn duplicate_graph_links_each_copy_to_the_first() {
+        let definitions = vec![
+            function("a::total", "/p/a.rs", 1, SUM),
+            function("b::total", "/p/b.rs", 1, SUM),
+            function("c::total", "/p/c.rs", 1, SUM),
+        ];
+        let graph = build_duplicate_graph("p", Path::new("/p"), &find_duplicates(&definitions));
+        let duplicates = graph
+            .edges()
+            .filter(|edge| edge.kind() == EdgeKind::Duplicate)
+            .map(|edge| (edge.from().0, edge.to().0))
+            .collect::<Vec<(usize, usize)>>();
+        assert_eq!(duplicates, vec![(2, 3), (2, 4)]);
+        assert!(graph
+            .node(crate::CodeNodeIndex(3))
+            .unwrap()
+            .label
+            .ends_with("b.rs:1"));
+    }
+}
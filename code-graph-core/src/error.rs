@@ -0,0 +1,22 @@
+use std::io;
+
+/**
+ * 代码图库中的错误
+ */
+#[derive(Debug, thiserror::Error)]
+pub enum CodeGraphError {
+    #[error("读写文件失败: {0}")]
+    Io(#[from] io::Error),
+    #[error("解析代码失败: {0}")]
+    Parse(String),
+    #[error("不受支持的文件类型: {0}")]
+    UnsupportedLanguage(String),
+    #[error("不受支持的压缩包格式: {0}")]
+    UnsupportedArchive(String),
+    #[error("文件编码不是 UTF-8: {0}")]
+    Encoding(String),
+    #[error("执行命令失败: {0}")]
+    Command(String),
+}
+
+pub type Result<T> = std::result::Result<T, CodeGraphError>;
@@ -0,0 +1,771 @@
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use emath::{Pos2, Rect, Vec2};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    read_source, route_edges, AnnotationNode, CodeBlockType, CodeGraphError, CodeNodeIndex,
+    EdgeKind, EdgeRouting, Graph, GraphBuilder, LayoutEngine, LinearLayout, Result,
+};
+
+/**
+ * 图的导出格式
+ */
+pub trait Exporter {
+    // 格式名称，用于界面显示
+    fn name(&self) -> &str;
+    // 文件扩展名
+    fn extension(&self) -> &str;
+    fn export(&self, graph: &Graph, w: &mut dyn Write) -> Result<()>;
+}
+
+/**
+ * 所有可用的导出格式
+ */
+pub fn exporters() -> Vec<Box<dyn Exporter>> {
+    vec![
+        Box::new(DotExporter),
+        Box::new(JsonExporter),
+        Box::new(MermaidExporter),
+        Box::new(SvgExporter::default()),
+        Box::new(MarkdownExporter::default()),
+    ]
+}
+
+/**
+ * 根据扩展名查找导出格式
+ */
+pub fn exporter_for(extension: &str) -> Option<Box<dyn Exporter>> {
+    exporters()
+        .into_iter()
+        .find(|exporter| exporter.extension() == extension)
+}
+
+/**
+ * 节点是否导出：折叠或被筛选（例如只显示公开接口）隐藏的节点不导出，与画布中看到的一致
+ */
+fn is_exported(graph: &Graph, index: CodeNodeIndex) -> bool {
+    graph.node(index).is_some_and(|node| node.visiable())
+}
+
+/**
+ * 节点标签中的空白合并为单个空格
+ */
+fn single_line(label: &str) -> String {
+    label.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+/**
+ * Graphviz DOT 格式，只包含可见的节点和它们之间的连线
+ */
+pub struct DotExporter;
+
+impl Exporter for DotExporter {
+    fn name(&self) -> &str {
+        "DOT"
+    }
+
+    fn extension(&self) -> &str {
+        "dot"
+    }
+
+    fn export(&self, graph: &Graph, w: &mut dyn Write) -> Result<()> {
+        writeln!(w, "digraph code_graph {{")?;
+        writeln!(w, "    node [shape=box];")?;
+        for (index, node) in graph.nodes().filter(|(_, node)| node.visiable()) {
+            let label = single_line(&node.label)
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"");
+            writeln!(w, "    n{} [label=\"{}\"];", index.0, label)?;
+        }
+        for edge in graph
+            .edges()
+            .filter(|edge| is_exported(graph, edge.from()) && is_exported(graph, edge.to()))
+        {
+            // 多处调用时标注调用次数
+            let style = match edge.kind() {
+                EdgeKind::Contains => String::new(),
+                EdgeKind::Calls if edge.call_lines().len() > 1 => {
+                    format!(" [style=dashed, label=\"{}\"]", edge.call_lines().len())
+                }
+                EdgeKind::Calls => " [style=dashed]".to_owned(),
+                EdgeKind::Duplicate => " [style=dotted, dir=none]".to_owned(),
+            };
+            writeln!(w, "    n{} -> n{}{};", edge.from().0, edge.to().0, style)?;
+        }
+        // 便签使用便签形状，多行文字保持换行
+        for (index, annotation) in graph.annotations().iter().enumerate() {
+            let label = annotation
+                .text
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n");
+            writeln!(
+                w,
+                "    note{} [shape=note, style=filled, fillcolor=\"#fff4a8\", label=\"{}\"];",
+                index, label
+            )?;
+        }
+        writeln!(w, "}}")?;
+        Ok(())
+    }
+}
+
+/**
+ * JSON 格式，可以重新加载
+ */
+pub struct JsonExporter;
+
+impl Exporter for JsonExporter {
+    fn name(&self) -> &str {
+        "JSON"
+    }
+
+    fn extension(&self) -> &str {
+        "json"
+    }
+
+    fn export(&self, graph: &Graph, w: &mut dyn Write) -> Result<()> {
+        serde_json::to_writer_pretty(&mut *w, graph)
+            .map_err(|err| CodeGraphError::Io(err.into()))?;
+        writeln!(w)?;
+        Ok(())
+    }
+}
+
+impl Graph {
+    /**
+     * 保存为 JSON 文件，包含节点位置、可见性、连线、便签和视角
+     */
+    pub fn save_json(&self, path: &Path) -> Result<()> {
+        let mut file = fs::File::create(path)?;
+        JsonExporter.export(self, &mut file)
+    }
+
+    /**
+     * 读取 save_json 保存的文件，节点保持保存时的位置
+     */
+    pub fn load_json(path: &Path) -> Result<Graph> {
+        let text = read_source(path)?;
+        serde_json::from_str(&text)
+            .map_err(|err| CodeGraphError::Parse(format!("图文件格式错误: {}", err)))
+    }
+}
+
+/**
+ * Mermaid 流程图格式，只包含可见的节点和它们之间的连线
+ */
+pub struct MermaidExporter;
+
+impl Exporter for MermaidExporter {
+    fn name(&self) -> &str {
+        "Mermaid"
+    }
+
+    fn extension(&self) -> &str {
+        "mmd"
+    }
+
+    fn export(&self, graph: &Graph, w: &mut dyn Write) -> Result<()> {
+        writeln!(w, "graph TD")?;
+        for (index, node) in graph.nodes().filter(|(_, node)| node.visiable()) {
+            let label = single_line(&node.label).replace('"', "#quot;");
+            writeln!(w, "    n{}[\"{}\"]", index.0, label)?;
+        }
+        for edge in graph
+            .edges()
+            .filter(|edge| is_exported(graph, edge.from()) && is_exported(graph, edge.to()))
+        {
+            let arrow = match edge.kind() {
+                EdgeKind::Contains => "-->".to_owned(),
+                EdgeKind::Calls if edge.call_lines().len() > 1 => {
+                    format!("-.->|{}|", edge.call_lines().len())
+                }
+                EdgeKind::Calls => "-.->".to_owned(),
+                EdgeKind::Duplicate => "-.-".to_owned(),
+            };
+            writeln!(w, "    n{} {} n{}", edge.from().0, arrow, edge.to().0)?;
+        }
+        for (index, annotation) in graph.annotations().iter().enumerate() {
+            let label = annotation
+                .text
+                .lines()
+                .map(|line| line.replace('"', "#quot;"))
+                .collect::<Vec<String>>()
+                .join("<br/>");
+            writeln!(w, "    note{}>\"{}\"]", index, label)?;
+            writeln!(w, "    style note{} fill:#fff4a8", index)?;
+        }
+        Ok(())
+    }
+}
+
+/**
+ * Markdown 大纲中定义位置的链接方式
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum LinkStyle {
+    // 不加链接，只写出 文件:行号
+    #[default]
+    Plain,
+    // 相对路径加 #L 行号，放在仓库中的文档里可以在 GitHub 上跳转
+    GitHub,
+    VSCode,
+    Zed,
+    Idea,
+}
+
+impl LinkStyle {
+    pub const ALL: [LinkStyle; 5] = [
+        LinkStyle::Plain,
+        LinkStyle::GitHub,
+        LinkStyle::VSCode,
+        LinkStyle::Zed,
+        LinkStyle::Idea,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            LinkStyle::Plain => "无链接",
+            LinkStyle::GitHub => "GitHub 相对路径",
+            LinkStyle::VSCode => "VSCode",
+            LinkStyle::Zed => "Zed",
+            LinkStyle::Idea => "Idea",
+        }
+    }
+}
+
+/**
+ * Markdown 大纲，每个文件一节，定义按包含关系写成嵌套列表，可以作为文档的目录
+ */
+#[derive(Debug, Clone, Default)]
+pub struct MarkdownExporter {
+    pub links: LinkStyle,
+    // 项目根目录：GitHub 链接使用相对它的路径，编辑器链接把相对路径补全为绝对路径
+    pub root: PathBuf,
+}
+
+impl MarkdownExporter {
+    /**
+     * 定义位置的链接地址，路径中的空格转义为 %20
+     */
+    fn link(&self, file_path: &str, line: usize) -> Option<String> {
+        let path = Path::new(file_path);
+        // file 地址中的路径以 / 开头，Windows 上为 /C:/...
+        let absolute = || {
+            let absolute = self
+                .root
+                .join(path)
+                .display()
+                .to_string()
+                .replace('\\', "/");
+            if absolute.starts_with('/') {
+                absolute
+            } else {
+                format!("/{}", absolute)
+            }
+        };
+        let link = match self.links {
+            LinkStyle::Plain => return None,
+            LinkStyle::GitHub => {
+                let relative = path.strip_prefix(&self.root).unwrap_or(path);
+                format!("{}#L{}", relative.display(), line)
+            }
+            LinkStyle::VSCode => format!("vscode://file{}:{}", absolute(), line),
+            LinkStyle::Zed => format!("zed://file{}:{}", absolute(), line),
+            LinkStyle::Idea => format!("idea://open?file={}&line={}", absolute(), line),
+        };
+        Some(link.replace(' ', "%20"))
+    }
+
+    /**
+     * 写出 index 的可见子节点，每一层缩进两个空格，调用位置节点不属于大纲
+     */
+    fn write_children(
+        &self,
+        graph: &Graph,
+        index: CodeNodeIndex,
+        depth: usize,
+        w: &mut dyn Write,
+    ) -> Result<()> {
+        for child in graph.children_of(index) {
+            let Some(node) = graph.node(child) else {
+                continue;
+            };
+            if !node.visiable() || node.block_type() == &CodeBlockType::CALL {
+                continue;
+            }
+            let label = format!("`{}`", single_line(&node.label).replace('`', "'"));
+            let indent = "  ".repeat(depth);
+            match self.link(&node.file_path, node.file_location) {
+                Some(link) => writeln!(w, "{}- [{}]({})", indent, label, link)?,
+                None => writeln!(
+                    w,
+                    "{}- {} {}:{}",
+                    indent,
+                    label,
+                    self.relative_path(&node.file_path),
+                    node.file_location
+                )?,
+            }
+            self.write_children(graph, child, depth + 1, w)?;
+        }
+        Ok(())
+    }
+
+    fn relative_path(&self, file_path: &str) -> String {
+        let path = Path::new(file_path);
+        path.strip_prefix(&self.root)
+            .unwrap_or(path)
+            .display()
+            .to_string()
+    }
+}
+
+impl Exporter for MarkdownExporter {
+    fn name(&self) -> &str {
+        "Markdown"
+    }
+
+    fn extension(&self) -> &str {
+        "md"
+    }
+
+    fn export(&self, graph: &Graph, w: &mut dyn Write) -> Result<()> {
+        let files = graph
+            .nodes()
+            .filter(|(_, node)| node.level() == 0)
+            .map(|(index, _)| index)
+            .collect::<Vec<CodeNodeIndex>>();
+        for (position, index) in files.into_iter().enumerate() {
+            let Some(root) = graph.node(index) else {
+                continue;
+            };
+            if position > 0 {
+                writeln!(w)?;
+            }
+            let title = if root.file_path.is_empty() {
+                single_line(&root.label)
+            } else {
+                self.relative_path(&root.file_path)
+            };
+            writeln!(w, "## {}", title)?;
+            writeln!(w)?;
+            self.write_children(graph, index, 0, w)?;
+        }
+        Ok(())
+    }
+}
+
+/**
+ * SVG 图片，不依赖界面。默认按字符数估算节点大小后使用直线布局，
+ * 设置 keep_layout 后保持图中已有的节点位置和大小，连线使用正交走线
+ */
+#[derive(Debug, Clone, Default)]
+pub struct SvgExporter {
+    // 保持图中的布局，连线按布局对应的方式走线
+    pub keep_layout: Option<EdgeRouting>,
+    // 按节点序号排列的填充颜色（#rrggbb），没有时使用默认颜色
+    pub fills: Vec<String>,
+}
+
+impl SvgExporter {
+    const FONT_SIZE: f32 = 13.0;
+    const PADDING: f32 = 16.0;
+    const LINE_HEIGHT: f32 = 16.0;
+
+    /**
+     * 估算标签的宽度，中文等全角字符按两个字符计算
+     */
+    fn text_width(label: &str) -> f32 {
+        label
+            .chars()
+            .map(|c| if c.is_ascii() { 0.6 } else { 1.1 })
+            .sum::<f32>()
+            * Self::FONT_SIZE
+    }
+
+    /**
+     * 便签按行数和最长一行估算大小
+     */
+    fn annotation_rect(annotation: &AnnotationNode) -> Rect {
+        let width = annotation
+            .text
+            .lines()
+            .map(Self::text_width)
+            .fold(0.0, f32::max);
+        let lines = annotation.text.lines().count().max(1) as f32;
+        let size = Vec2::new(width, lines * Self::LINE_HEIGHT) + Vec2::new(16.0, 8.0);
+        Rect::from_min_size(annotation.position, size)
+    }
+
+    fn escape(text: &str) -> String {
+        text.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+    }
+}
+
+impl Exporter for SvgExporter {
+    fn name(&self) -> &str {
+        "SVG"
+    }
+
+    fn extension(&self) -> &str {
+        "svg"
+    }
+
+    fn export(&self, graph: &Graph, w: &mut dyn Write) -> Result<()> {
+        let mut graph = graph.clone();
+        if self.keep_layout.is_none() {
+            for (_, node) in graph.nodes_mut() {
+                let width = Self::text_width(&single_line(&node.label));
+                node.set_size(Vec2::new(width, Self::FONT_SIZE) + Vec2::new(16.0, 8.0));
+            }
+            LinearLayout::default().layout(&mut graph, Vec2::ZERO);
+        }
+        let routes = self
+            .keep_layout
+            .map(|routing| route_edges(&graph, routing))
+            .unwrap_or_default();
+        let rect_of = |index| {
+            graph
+                .node(index)
+                .filter(|node| node.visiable())
+                .map(|node| Rect::from_min_size(node.position(), node.size()))
+        };
+        // 调用边的曲线向节点右侧弯出的距离
+        let bend = |from: Rect, to: Rect| 24.0 + (to.center().y - from.center().y).abs() * 0.1;
+        let mut bounds = graph
+            .nodes()
+            .filter_map(|(index, _)| rect_of(index))
+            .fold(Rect::NOTHING, |bounds, rect| bounds.union(rect));
+        for annotation in graph.annotations() {
+            bounds = bounds.union(Self::annotation_rect(annotation));
+        }
+        if self.keep_layout.is_some() {
+            for point in routes.iter().flat_map(|route| route.points.iter()) {
+                bounds.extend_with(*point);
+            }
+        } else {
+            for edge in graph
+                .edges()
+                .filter(|edge| edge.kind() != EdgeKind::Contains)
+            {
+                if let (Some(from), Some(to)) = (rect_of(edge.from()), rect_of(edge.to())) {
+                    let right = from.right().max(to.right()) + bend(from, to);
+                    bounds.extend_with_x(right);
+                }
+            }
+        }
+        let (offset, size) = if bounds.is_positive() {
+            let offset = Vec2::splat(Self::PADDING) - bounds.min.to_vec2();
+            (offset, bounds.size() + Vec2::splat(Self::PADDING * 2.0))
+        } else {
+            (Vec2::ZERO, Vec2::splat(Self::PADDING * 2.0))
+        };
+        let point = |pos: Pos2| pos + offset;
+
+        writeln!(
+            w,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{:.0}\" height=\"{:.0}\" font-family=\"monospace\" font-size=\"{}\">",
+            size.x,
+            size.y,
+            Self::FONT_SIZE
+        )?;
+        writeln!(w, "  <rect width=\"100%\" height=\"100%\" fill=\"white\"/>")?;
+        // 连线终点的方向标记：调用关系为箭头，包含关系为圆点
+        writeln!(
+            w,
+            "  <defs><marker id=\"call\" viewBox=\"0 0 8 8\" refX=\"8\" refY=\"4\" markerWidth=\"8\" markerHeight=\"8\" orient=\"auto\"><path d=\"M0 0 L8 4 L0 8 z\" fill=\"#d08020\"/></marker><marker id=\"contain\" viewBox=\"0 0 6 6\" refX=\"3\" refY=\"3\" markerWidth=\"5\" markerHeight=\"5\"><circle cx=\"3\" cy=\"3\" r=\"3\" fill=\"#999\"/></marker></defs>"
+        )?;
+        // 保持布局时按走线画折线
+        for route in &routes {
+            let path = route
+                .points
+                .iter()
+                .enumerate()
+                .map(|(i, pos)| {
+                    let pos = point(*pos);
+                    format!(
+                        "{}{:.1} {:.1}",
+                        if i == 0 { "M" } else { "L" },
+                        pos.x,
+                        pos.y
+                    )
+                })
+                .collect::<Vec<String>>()
+                .join(" ");
+            let style = match route.kind {
+                EdgeKind::Contains => "stroke=\"#999\" marker-end=\"url(#contain)\"",
+                EdgeKind::Calls => {
+                    "stroke=\"#d08020\" stroke-dasharray=\"4 3\" marker-end=\"url(#call)\""
+                }
+                EdgeKind::Duplicate => "stroke=\"#9050c0\" stroke-dasharray=\"2 2\"",
+            };
+            writeln!(w, "  <path d=\"{}\" fill=\"none\" {}/>", path, style)?;
+        }
+        for edge in graph.edges().filter(|_| self.keep_layout.is_none()) {
+            let (Some(from), Some(to)) = (rect_of(edge.from()), rect_of(edge.to())) else {
+                continue;
+            };
+            match edge.kind() {
+                // 包含关系：从父节点左侧向下再连到子节点
+                EdgeKind::Contains => {
+                    let start = point(from.left_bottom() + Vec2::new(8.0, 0.0));
+                    let end = point(to.left_center());
+                    writeln!(
+                        w,
+                        "  <path d=\"M{:.1} {:.1} V{:.1} H{:.1}\" fill=\"none\" stroke=\"#999\" marker-end=\"url(#contain)\"/>",
+                        start.x, start.y, end.y, end.x
+                    )?;
+                }
+                // 调用关系和重复关系：在节点右侧用虚线曲线连接，重复关系没有箭头
+                EdgeKind::Calls | EdgeKind::Duplicate => {
+                    let (start, end) = (point(from.right_center()), point(to.right_center()));
+                    let bend = bend(from, to);
+                    let style = if edge.kind() == EdgeKind::Calls {
+                        "stroke=\"#d08020\" stroke-dasharray=\"4 3\" marker-end=\"url(#call)\""
+                    } else {
+                        "stroke=\"#9050c0\" stroke-dasharray=\"2 2\""
+                    };
+                    writeln!(
+                        w,
+                        "  <path d=\"M{:.1} {:.1} C{:.1} {:.1} {:.1} {:.1} {:.1} {:.1}\" fill=\"none\" {}/>",
+                        start.x,
+                        start.y,
+                        start.x + bend,
+                        start.y,
+                        end.x + bend,
+                        end.y,
+                        end.x,
+                        end.y,
+                        style
+                    )?;
+                }
+            }
+        }
+        for (index, node) in graph.nodes() {
+            let Some(rect) = rect_of(index) else {
+                continue;
+            };
+            let min = point(rect.min);
+            let fill = match self.fills.get(index.0) {
+                Some(fill) => fill.as_str(),
+                None if node.level() == 0 => "#dde8f8",
+                None => "#f4f4f4",
+            };
+            writeln!(
+                w,
+                "  <rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" rx=\"4\" fill=\"{}\" stroke=\"#666\"/>",
+                min.x,
+                min.y,
+                rect.width(),
+                rect.height(),
+                fill
+            )?;
+            // 保持布局时节点大小按界面字体测量，估算的文字宽度超出节点时压缩到节点内
+            let label = single_line(&node.label);
+            let available = rect.width() - 16.0;
+            let text_length = if self.keep_layout.is_some() && Self::text_width(&label) > available
+            {
+                format!(
+                    " textLength=\"{:.1}\" lengthAdjust=\"spacingAndGlyphs\"",
+                    available
+                )
+            } else {
+                String::new()
+            };
+            writeln!(
+                w,
+                "  <text x=\"{:.1}\" y=\"{:.1}\" dominant-baseline=\"middle\"{}>{}</text>",
+                min.x + 8.0,
+                min.y + rect.height() / 2.0,
+                text_length,
+                Self::escape(&label)
+            )?;
+        }
+        for annotation in graph.annotations() {
+            let rect = Self::annotation_rect(annotation);
+            let min = point(rect.min);
+            writeln!(
+                w,
+                "  <rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"#fff4a8\" stroke=\"#c8b040\"/>",
+                min.x,
+                min.y,
+                rect.width(),
+                rect.height()
+            )?;
+            for (line_index, line) in annotation.text.lines().enumerate() {
+                writeln!(
+                    w,
+                    "  <text x=\"{:.1}\" y=\"{:.1}\" dominant-baseline=\"middle\">{}</text>",
+                    min.x + 8.0,
+                    min.y + 4.0 + (line_index as f32 + 0.5) * Self::LINE_HEIGHT,
+                    Self::escape(line)
+                )?;
+            }
+        }
+        writeln!(w, "</svg>")?;
+        Ok(())
+    }
+}
+
+/**
+ * CSV 格式，节点和连线分别写入 nodes.csv 和 edges.csv，便于在表格软件或 pandas 中分析，
+ * 连线两端使用节点的 id，与其他格式一样只包含可见的节点和它们之间的连线
+ */
+pub struct CsvExporter;
+
+impl CsvExporter {
+    pub const NODES_FILE: &'static str = "nodes.csv";
+    pub const EDGES_FILE: &'static str = "edges.csv";
+
+    /**
+     * 包含逗号、引号或换行的字段加上引号，字段中的引号写两次
+     */
+    fn field(value: &str) -> String {
+        if value.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", value.replace('"', "\"\""))
+        } else {
+            value.to_owned()
+        }
+    }
+
+    /**
+     * 写入节点表：id,label,type,file,line
+     */
+    pub fn write_nodes(graph: &Graph, w: &mut dyn Write) -> Result<()> {
+        writeln!(w, "id,label,type,file,line")?;
+        for (_, node) in graph
+            .nodes()
+            .filter(|(index, _)| is_exported(graph, *index))
+        {
+            writeln!(
+                w,
+                "{},{},{:?},{},{}",
+                Self::field(node.id()),
+                Self::field(&single_line(&node.label)),
+                node.block_type(),
+                Self::field(&node.file_path),
+                node.file_location
+            )?;
+        }
+        Ok(())
+    }
+
+    /**
+     * 写入连线表：from,to,kind，kind 为 contains 或 calls
+     */
+    pub fn write_edges(graph: &Graph, w: &mut dyn Write) -> Result<()> {
+        writeln!(w, "from,to,kind")?;
+        for edge in graph
+            .edges()
+            .filter(|edge| is_exported(graph, edge.from()) && is_exported(graph, edge.to()))
+        {
+            let (Some(from), Some(to)) = (graph.node(edge.from()), graph.node(edge.to())) else {
+                continue;
+            };
+            let kind = match edge.kind() {
+                EdgeKind::Contains => "contains",
+                EdgeKind::Calls => "calls",
+                EdgeKind::Duplicate => "duplicate",
+            };
+            writeln!(
+                w,
+                "{},{},{}",
+                Self::field(from.id()),
+                Self::field(to.id()),
+                kind
+            )?;
+        }
+        Ok(())
+    }
+
+    /**
+     * 在 dir 中写入 nodes.csv 和 edges.csv
+     */
+    pub fn export_dir(graph: &Graph, dir: &Path) -> Result<()> {
+        let mut nodes = fs::File::create(dir.join(Self::NODES_FILE))?;
+        Self::write_nodes(graph, &mut nodes)?;
+        let mut edges = fs::File::create(dir.join(Self::EDGES_FILE))?;
+        Self::write_edges(graph, &mut edges)
+    }
+}
+
+/**
+ * 为每个文件单独生成一张图并导出到 out_dir 中，保持文件的相对路径，
+ * 例如 src/main.rs 导出为 out_dir/src/main.rs.svg，返回导出的文件数
+ */
+pub fn export_each_file(
+    root: &Path,
+    pathes: &[PathBuf],
+    out_dir: &Path,
+    exporter: &dyn Exporter,
+) -> Result<usize> {
+    let mut count = 0;
+    for path in pathes {
+        // 无法读取或不支持的文件直接跳过
+        let Ok(code) = read_source(path) else {
+            continue;
+        };
+        let Ok(builder) = GraphBuilder::new().add_source(path.to_str().unwrap_or(""), &code) else {
+            continue;
+        };
+        let graph = builder.resolve_calls().build();
+        if graph.is_empty() {
+            continue;
+        }
+        let relative_path = path.strip_prefix(root).unwrap_or(path);
+        let mut out_path = out_dir.join(relative_path).into_os_string();
+        out_path.push(".");
+        out_path.push(exporter.extension());
+        let out_path = PathBuf::from(out_path);
+        if let Some(parent) = out_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = fs::File::create(&out_path)?;
+        exporter.export(&graph, &mut file)?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CodeBlockType, CodeNode};
+
+    #[test]
+    fn csv_skips_hidden_nodes_and_their_edges() {
+        let mut graph = Graph::new();
+        for id in ["a.rs", "run", "hidden"] {
+            graph.add_node(CodeNode::new(id, id, "", 1, CodeBlockType::FUNCTION, 0));
+        }
+        graph.add_edge(CodeNodeIndex(0), CodeNodeIndex(1));
+        graph.add_edge(CodeNodeIndex(0), CodeNodeIndex(2));
+        graph.add_call_edge(CodeNodeIndex(1), CodeNodeIndex(2), vec![1]);
+        graph
+            .node_mut(CodeNodeIndex(2))
+            .unwrap()
+            .set_visiable(false);
+
+        let mut nodes = vec![];
+        CsvExporter::write_nodes(&graph, &mut nodes).unwrap();
+        assert_eq!(
+            String::from_utf8(nodes).unwrap(),
+            "id,label,type,file,line\na.rs,a.rs,FUNCTION,,1\nrun,run,FUNCTION,,1\n"
+        );
+        let mut edges = vec![];
+        CsvExporter::write_edges(&graph, &mut edges).unwrap();
+        assert_eq!(
+            String::from_utf8(edges).unwrap(),
+            "from,to,kind\na.rs,run,contains\n"
+        );
+    }
+}
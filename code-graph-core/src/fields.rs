@@ -0,0 +1,363 @@
+use std::{collections::HashSet, path::Path};
+
+use tree_sitter::Node;
+
+use crate::{
+    get_symbol_query, parse_code,
+    patch::{add_child, group_node},
+    symbols::{declared_type, definition_names},
+    CodeBlockType, CodeGraphError, CodeNode, DefinitionIndex, Graph, Result,
+};
+
+/**
+ * 结构体的一个字段，元组结构体的字段名为序号
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StructField {
+    pub name: String,
+    // 字段类型的代码，例如 Vec<String>
+    pub type_text: String,
+    // 字段所在行，从 1 开始
+    pub line: usize,
+}
+
+/**
+ * 访问字段的方式
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldAccess {
+    Read,
+    // 赋值、复合赋值、&mut 借用和结构体字面量中的初始化
+    Write,
+}
+
+/**
+ * 使用结构体字段的函数
+ */
+#[derive(Debug, Clone)]
+pub struct FieldUser {
+    pub function: CodeNode,
+    // （字段名，访问方式，行号），按出现的顺序排列
+    pub accesses: Vec<(String, FieldAccess, usize)>,
+}
+
+/**
+ * 是否为 Rust 的结构体定义，impl 块的类型也是 STRUCT，需要排除
+ */
+pub fn is_rust_struct(node: &CodeNode) -> bool {
+    node.file_path.ends_with(".rs")
+        && node.block_type == CodeBlockType::STRUCT
+        && node.level > 0
+        && !definition_names(node).is_empty()
+}
+
+/**
+ * Rust 结构体定义中的字段
+ */
+pub fn struct_fields(structure: &CodeNode) -> Result<Vec<StructField>> {
+    if !is_rust_struct(structure) {
+        return Err(CodeGraphError::UnsupportedLanguage(
+            structure.file_path.clone(),
+        ));
+    }
+    let symbol_query = get_symbol_query("rs")?;
+    let tree = parse_code(
+        &structure.file_path,
+        &structure.block,
+        symbol_query.as_ref(),
+    )?;
+    let root = tree.root_node();
+    let Some(item) = root
+        .children(&mut root.walk())
+        .find(|child| child.kind() == "struct_item")
+    else {
+        return Ok(vec![]);
+    };
+    let Some(body) = item.child_by_field_name("body") else {
+        return Ok(vec![]);
+    };
+    let code = structure.block.as_str();
+    let line = |node: &Node| structure.file_location + node.start_position().row;
+    let mut fields = vec![];
+    for child in body.named_children(&mut body.walk()) {
+        match (body.kind(), child.kind()) {
+            ("field_declaration_list", "field_declaration") => {
+                let (Some(name), Some(type_node)) = (
+                    child.child_by_field_name("name"),
+                    child.child_by_field_name("type"),
+                ) else {
+                    continue;
+                };
+                fields.push(StructField {
+                    name: code[name.byte_range()].to_owned(),
+                    type_text: code[type_node.byte_range()].to_owned(),
+                    line: line(&child),
+                });
+            }
+            ("ordered_field_declaration_list", "visibility_modifier" | "attribute_item") => {}
+            ("ordered_field_declaration_list", _) => fields.push(StructField {
+                name: fields.len().to_string(),
+                type_text: code[child.byte_range()].to_owned(),
+                line: line(&child),
+            }),
+            _ => {}
+        }
+    }
+    Ok(fields)
+}
+
+/**
+ * 查找项目中读写结构体字段的函数：结构体的方法中的 self.x 和 Self { x }，
+ * 以及能从声明推断出类型为该结构体的变量的 v.x（推断规则见 symbols::declared_type）和 Foo { x }
+ */
+pub fn field_users(
+    definitions: &DefinitionIndex,
+    structure: &CodeNode,
+    fields: &[StructField],
+) -> Vec<FieldUser> {
+    let Some(name) = definition_names(structure).into_iter().next() else {
+        return vec![];
+    };
+    let Ok(symbol_query) = get_symbol_query("rs") else {
+        return vec![];
+    };
+    let field_names = fields
+        .iter()
+        .map(|field| field.name.as_str())
+        .collect::<HashSet<&str>>();
+    let mut seen = HashSet::new();
+    let mut users = vec![];
+    for function in definitions.definitions().iter().filter(|node| {
+        node.level > 0
+            && node.file_path.ends_with(".rs")
+            && matches!(
+                node.block_type,
+                CodeBlockType::FUNCTION | CodeBlockType::METHOD
+            )
+    }) {
+        // 先按文字筛掉不可能访问字段的函数
+        let block = function.block.as_str();
+        if ![name.as_str(), "self", "Self"]
+            .iter()
+            .any(|word| block.contains(word))
+        {
+            continue;
+        }
+        if !seen.insert(&function.id) {
+            continue;
+        }
+        let Ok(tree) = parse_code(&function.file_path, block, symbol_query.as_ref()) else {
+            continue;
+        };
+        let mut visitor = FieldVisitor {
+            code: block,
+            first_line: function.file_location,
+            structure: &name,
+            is_method: definitions.owner_of(function) == Some(name.as_str()),
+            fields: &field_names,
+            accesses: vec![],
+        };
+        for item in tree
+            .root_node()
+            .named_children(&mut tree.root_node().walk())
+        {
+            visitor.visit(item, true);
+        }
+        if !visitor.accesses.is_empty() {
+            users.push(FieldUser {
+                function: function.clone(),
+                accesses: visitor.accesses,
+            });
+        }
+    }
+    users.sort_by(|a, b| {
+        (&a.function.file_path, a.function.file_location)
+            .cmp(&(&b.function.file_path, b.function.file_location))
+    });
+    users
+}
+
+struct FieldVisitor<'a> {
+    code: &'a str,
+    first_line: usize,
+    structure: &'a str,
+    // 是否为结构体自己的方法，方法中的 self 和 Self 指该结构体
+    is_method: bool,
+    fields: &'a HashSet<&'a str>,
+    accesses: Vec<(String, FieldAccess, usize)>,
+}
+
+impl FieldVisitor<'_> {
+    fn visit(&mut self, node: Node, root: bool) {
+        match node.kind() {
+            // 嵌套的函数是单独的定义
+            "function_item" if !root => return,
+            "field_expression" => self.visit_field(node),
+            "struct_expression" => self.visit_literal(node),
+            _ => {}
+        }
+        for child in node.children(&mut node.walk()) {
+            self.visit(child, false);
+        }
+    }
+
+    fn push(&mut self, field: &str, access: FieldAccess, node: &Node) {
+        if self.fields.contains(field) {
+            self.accesses.push((
+                field.to_owned(),
+                access,
+                self.first_line + node.start_position().row,
+            ));
+        }
+    }
+
+    fn visit_field(&mut self, node: Node) {
+        let (Some(value), Some(field)) = (
+            node.child_by_field_name("value"),
+            node.child_by_field_name("field"),
+        ) else {
+            return;
+        };
+        let is_structure = match value.kind() {
+            "self" => self.is_method,
+            "identifier" => {
+                declared_type(self.code, &self.code[value.byte_range()]) == Some(self.structure)
+            }
+            _ => false,
+        };
+        if is_structure {
+            let access = field_access(node);
+            self.push(&self.code[field.byte_range()], access, &field);
+        }
+    }
+
+    fn visit_literal(&mut self, node: Node) {
+        let Some(name) = node.child_by_field_name("name") else {
+            return;
+        };
+        let name = &self.code[name.byte_range()];
+        let is_structure =
+            name.rsplit("::").next() == Some(self.structure) || (name == "Self" && self.is_method);
+        let Some(body) = node.child_by_field_name("body").filter(|_| is_structure) else {
+            return;
+        };
+        for child in body.named_children(&mut body.walk()) {
+            let field = match child.kind() {
+                "field_initializer" => child.child_by_field_name("field"),
+                "shorthand_field_initializer" => child.named_child(0),
+                _ => None,
+            };
+            if let Some(field) = field {
+                self.push(&self.code[field.byte_range()], FieldAccess::Write, &field);
+            }
+        }
+    }
+}
+
+/**
+ * 字段表达式是读还是写：self.a.b = 1 这样修改字段的一部分也算作写
+ */
+fn field_access(node: Node) -> FieldAccess {
+    let mut current = node;
+    // 向上越过 self.a.b、self.a[0] 中以该字段开头的部分
+    while let Some(parent) = current.parent().filter(|parent| {
+        let value = match parent.kind() {
+            "field_expression" => parent.child_by_field_name("value"),
+            "index_expression" => parent.named_child(0),
+            _ => None,
+        };
+        value == Some(current)
+    }) {
+        current = parent;
+    }
+    let Some(parent) = current.parent() else {
+        return FieldAccess::Read;
+    };
+    let is_left = parent
+        .child_by_field_name("left")
+        .is_some_and(|left| left == current);
+    match parent.kind() {
+        "assignment_expression" | "compound_assignment_expr" if is_left => FieldAccess::Write,
+        "reference_expression"
+            if parent
+                .children(&mut parent.walk())
+                .any(|child| child.kind() == "mutable_specifier") =>
+        {
+            FieldAccess::Write
+        }
+        _ => FieldAccess::Read,
+    }
+}
+
+/**
+ * 字段使用图：根节点下「字段」和「函数」两个分组，从函数连出到它读写的字段，连线上记录访问的行号。
+ * 字段标签后是读写次数，没有连线的字段没有被使用，只被少数函数使用的字段可以考虑拆分出去
+ */
+pub fn build_field_graph(
+    structure: &CodeNode,
+    fields: &[StructField],
+    users: &[FieldUser],
+) -> Graph {
+    let name = definition_names(structure)
+        .into_iter()
+        .next()
+        .unwrap_or_else(|| structure.label.trim().to_owned());
+    let mut graph = Graph::new();
+    let root = graph.add_node(group_node(
+        &format!("{}#fields", structure.id),
+        &format!("{} 的字段使用", name),
+    ));
+    let field_group = add_child(&mut graph, root, group_node("fields", "字段"), 1);
+    let function_group = add_child(&mut graph, root, group_node("functions", "函数"), 1);
+    let mut field_indexes = vec![];
+    for field in fields {
+        let count = |access: FieldAccess| {
+            users
+                .iter()
+                .flat_map(|user| &user.accesses)
+                .filter(|(name, kind, _)| *name == field.name && *kind == access)
+                .count()
+        };
+        let mut node = group_node(
+            &field.name,
+            &format!(
+                "{}: {}（读 {} · 写 {}）",
+                field.name,
+                field.type_text,
+                count(FieldAccess::Read),
+                count(FieldAccess::Write)
+            ),
+        );
+        node.file_path = structure.file_path.clone();
+        node.file_location = field.line;
+        field_indexes.push(add_child(&mut graph, field_group, node, 2));
+    }
+    for user in users {
+        let mut node = user.function.clone();
+        let file_name = Path::new(&node.file_path)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or("");
+        node.label = format!(
+            "{}  {}:{}",
+            node.label.trim(),
+            file_name,
+            node.file_location
+        );
+        let index = add_child(&mut graph, function_group, node, 2);
+        for (field, field_index) in fields.iter().zip(&field_indexes) {
+            let mut lines = user
+                .accesses
+                .iter()
+                .filter(|(name, _, _)| *name == field.name)
+                .map(|(_, _, line)| *line)
+                .collect::<Vec<usize>>();
+            if lines.is_empty() {
+                continue;
+            }
+            lines.dedup();
+            graph.add_call_edge(index, *field_index, lines);
+        }
+    }
+    graph
+}
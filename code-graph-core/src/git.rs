@@ -0,0 +1,276 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+    time::SystemTime,
+};
+
+use serde::{Deserialize, Serialize};
+
+/**
+ * git blame 中的一行
+ */
+#[derive(Debug, Clone)]
+pub struct BlameLine {
+    // 提交 hash
+    pub commit: String,
+    // 作者
+    pub author: String,
+    // 提交时间（unix 时间戳）
+    pub time: i64,
+}
+
+/**
+ * 代码块的变更情况
+ */
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Churn {
+    // 涉及的不同提交数量
+    pub commits: usize,
+    // 最近一次修改时间（unix 时间戳）
+    pub last_modified: i64,
+    // 最近一次修改的作者
+    #[serde(default)]
+    pub last_author: String,
+    // 归一化后的热度 0.0 ~ 1.0
+    pub heat: f32,
+    // 归一化后的修改时间，0.0 为图中最久没有修改的，1.0 为最近修改的
+    #[serde(default)]
+    pub recency: f32,
+    // 归一化后的热点分数（圈复杂度 × 提交数），只有函数有
+    #[serde(default)]
+    pub hotspot: f32,
+}
+
+/**
+ * 执行 git 命令，返回标准输出。blame、历史和版本导出都通过 git 命令完成，
+ * 不依赖 libgit2，使用的是用户安装的 git 及其配置（例如 .mailmap 和 blame.ignoreRevsFile）
+ */
+pub fn run_git(dir: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8(output.stdout).ok()
+}
+
+/**
+ * 对文件执行 git blame，返回每一行的提交信息
+ */
+pub fn blame_file(file_path: &str) -> Option<Vec<BlameLine>> {
+    let path = Path::new(file_path);
+    let dir = path.parent()?;
+    let file_name = path.file_name()?.to_str()?;
+    let output = run_git(dir, &["blame", "--line-porcelain", "--", file_name])?;
+
+    let mut lines = vec![];
+    let mut current = BlameLine {
+        commit: "".to_owned(),
+        author: "".to_owned(),
+        time: 0,
+    };
+    for line in output.lines() {
+        if line.starts_with('\t') {
+            // 代码内容行，表示一行的信息结束
+            lines.push(current.clone());
+        } else if let Some(author) = line.strip_prefix("author ") {
+            current.author = author.to_owned();
+        } else if let Some(time) = line.strip_prefix("author-time ") {
+            current.time = time.parse().unwrap_or(0);
+        } else if let Some(commit) = line.split(' ').next() {
+            if commit.len() == 40 && commit.chars().all(|c| c.is_ascii_hexdigit()) {
+                current.commit = commit.to_owned();
+            }
+        }
+    }
+    Some(lines)
+}
+
+/**
+ * 按文件缓存的 git blame 结果，文件的修改时间没有变化时不再重新执行 blame
+ */
+#[derive(Debug, Clone, Default)]
+pub struct BlameCache {
+    // 文件路径 -> (执行 blame 时文件的修改时间, blame 结果)
+    files: HashMap<String, (Option<SystemTime>, Option<Vec<BlameLine>>)>,
+}
+
+impl BlameCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /**
+     * 对文件执行 git blame，耗时较长，应在后台线程中调用，结果用 merge 合并到已有的缓存
+     */
+    pub fn blame(file_paths: &[String]) -> Self {
+        let files = file_paths
+            .iter()
+            .map(|file_path| {
+                // 先记录修改时间，blame 期间文件被修改时下次会重新 blame
+                let modified = modified_time(file_path);
+                (file_path.clone(), (modified, blame_file(file_path)))
+            })
+            .collect();
+        Self { files }
+    }
+
+    /**
+     * 缓存中没有或修改时间已经变化、需要重新 blame 的文件，去掉重复的路径
+     */
+    pub fn outdated(&self, file_paths: impl IntoIterator<Item = String>) -> Vec<String> {
+        let mut outdated = vec![];
+        for file_path in file_paths {
+            if file_path.is_empty() || outdated.contains(&file_path) {
+                continue;
+            }
+            let fresh = self
+                .files
+                .get(&file_path)
+                .is_some_and(|(modified, _)| *modified == modified_time(&file_path));
+            if !fresh {
+                outdated.push(file_path);
+            }
+        }
+        outdated
+    }
+
+    pub fn merge(&mut self, other: BlameCache) {
+        self.files.extend(other.files);
+    }
+
+    /**
+     * 文件的 blame 结果，没有缓存或不在 git 仓库中时为 None
+     */
+    pub fn get(&self, file_path: &str) -> Option<&[BlameLine]> {
+        self.files.get(file_path)?.1.as_deref()
+    }
+}
+
+fn modified_time(file_path: &str) -> Option<SystemTime> {
+    fs::metadata(file_path)
+        .and_then(|meta| meta.modified())
+        .ok()
+}
+
+/**
+ * 统计指定行范围（从 1 开始，包含两端）的变更情况
+ */
+pub fn churn_of_lines(blame: &[BlameLine], start: usize, end: usize) -> Churn {
+    let start = start.max(1);
+    let mut commits = vec![];
+    let mut last_modified = 0;
+    let mut last_author = "";
+    for line in blame.iter().take(end).skip(start - 1) {
+        if !commits.contains(&&line.commit) {
+            commits.push(&line.commit);
+        }
+        if line.time > last_modified {
+            last_modified = line.time;
+            last_author = &line.author;
+        }
+    }
+    Churn {
+        commits: commits.len(),
+        last_modified,
+        last_author: last_author.to_owned(),
+        heat: 0.0,
+        recency: 0.0,
+        hotspot: 0.0,
+    }
+}
+
+/**
+ * 统计目录下每个文件被提交修改的次数，返回 绝对路径 -> 次数
+ */
+pub fn file_change_counts(root_path: &Path) -> HashMap<PathBuf, usize> {
+    let mut counts = HashMap::new();
+    if let Some(output) = run_git(
+        root_path,
+        &["log", "--format=", "--name-only", "--relative"],
+    ) {
+        for line in output.lines().filter(|line| !line.is_empty()) {
+            *counts.entry(root_path.join(line)).or_insert(0) += 1;
+        }
+    }
+    counts
+}
+
+/**
+ * 将 unix 时间戳格式化为距今的时间
+ */
+pub fn format_elapsed(timestamp: i64) -> String {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+    let days = (now - timestamp).max(0) / 86400;
+    if days == 0 {
+        "今天".to_owned()
+    } else {
+        format!("{} 天前", days)
+    }
+}
+
+/**
+ * 将 unix 时间戳格式化为 UTC 日期，例如 2024-05-01
+ */
+pub fn format_date(timestamp: i64) -> String {
+    // 公历日期换算，以 0000-03-01 为起点，每 400 年为一个周期
+    let days = timestamp.div_euclid(86400) + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use super::*;
+
+    #[test]
+    fn blame_cache_is_outdated_when_modified_time_changes() {
+        let dir = std::env::temp_dir()
+            .join("code-graph-tests")
+            .join(format!("blame-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("a.rs");
+        fs::write(&path, "fn a() {}\n").unwrap();
+        let file_path = path.to_str().unwrap().to_owned();
+
+        let mut cache = BlameCache::new();
+        let files = || vec![file_path.clone(), file_path.clone(), "".to_owned()];
+        // 同一个文件只 blame 一次，空路径跳过
+        assert_eq!(cache.outdated(files()), vec![file_path.clone()]);
+        cache.merge(BlameCache::blame(&cache.outdated(files())));
+        assert!(cache.outdated(files()).is_empty());
+        // 不在 git 仓库中的文件没有 blame 结果
+        assert!(cache.get(&file_path).is_none());
+
+        fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(SystemTime::now() + Duration::from_secs(60))
+            .unwrap();
+        assert_eq!(cache.outdated(files()), vec![file_path]);
+    }
+}
@@ -0,0 +1,1312 @@
+use std::collections::{HashMap, VecDeque};
+
+use emath::{Pos2, Vec2};
+use serde::{Deserialize, Serialize};
+
+use crate::cluster::CollapsedCluster;
+use crate::git::{self, BlameCache, Churn};
+use crate::profile::ProfileSample;
+use crate::symbols::{call_target, definition_names, stable_name, Qualifier};
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CodeBlockType {
+    FUNCTION,
+    METHOD,
+    STRUCT,
+    IMPL,
+    CLASS,
+    CONST,
+    NORMAL,
+    CALL,
+    // React 组件
+    COMPONENT,
+    // React hook 调用，例如 useState
+    HOOK,
+}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CodeNode {
+    pub(crate) id: String,
+    // 标签
+    pub label: String,
+    // 代码内容
+    pub block: String,
+    // 文件定位 LineNumber
+    pub file_location: usize,
+    // 文件路径
+    pub file_path: String,
+    // 等级
+    pub(crate) level: usize,
+    // block
+    pub(crate) block_type: CodeBlockType,
+    // position
+    #[serde(default)]
+    pub(crate) position: Pos2,
+    // 节点大小，由界面根据文字测量后设置
+    #[serde(default)]
+    pub(crate) size: Vec2,
+    #[serde(default = "default_visiable")]
+    pub(crate) visiable: bool,
+    // git 变更情况
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) churn: Option<Churn>,
+    // 圈复杂度
+    #[serde(default)]
+    pub complexity: usize,
+    // 代码的行数和字节数
+    #[serde(default)]
+    pub line_count: usize,
+    #[serde(default)]
+    pub byte_size: usize,
+    // 导入的性能采样数据
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) profile: Option<ProfileSample>,
+    // 测试行覆盖率 0.0 ~ 1.0
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) coverage: Option<f32>,
+    // 定义前的文档注释
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doc: Option<String>,
+    // 项目中引用该定义的次数，扫描项目后才有
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub references: Option<usize>,
+    // 可见性，语言没有对应的概念或无法判断时为 None
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub visibility: Option<Visibility>,
+    // 是否为异步函数
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub is_async: bool,
+    // 是否为测试函数或测试类
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub is_test: bool,
+    // 是否为 unsafe 函数、unsafe impl，或函数体中有 unsafe 块
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub is_unsafe: bool,
+    // 所属语言的名称，例如 Rust
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    // 差异图中节点的变化，其他图中为 None
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diff: Option<DiffStatus>,
+}
+
+/**
+ * 定义的可见性
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Visibility {
+    Public,
+    Private,
+}
+
+/**
+ * 两个图对比时节点或边的变化
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DiffStatus {
+    Added,
+    Removed,
+    // 节点的标签或代码不同，调用边的调用次数不同
+    Changed,
+}
+
+/**
+ * 两个图之间的差异，节点按 ID 对应，边按两端的 ID 和类型对应，没有变化的不记录
+ */
+#[derive(Debug, Clone, Default)]
+pub struct GraphDiff {
+    pub nodes: HashMap<String, DiffStatus>,
+    // （起点 ID，终点 ID，类型）-> 变化
+    pub edges: HashMap<(String, String, EdgeKind), DiffStatus>,
+}
+
+impl GraphDiff {
+    /**
+     * 有某种变化的节点数
+     */
+    pub fn node_count(&self, status: DiffStatus) -> usize {
+        self.nodes
+            .values()
+            .filter(|value| **value == status)
+            .count()
+    }
+
+    /**
+     * 有某种变化的边数
+     */
+    pub fn edge_count(&self, status: DiffStatus) -> usize {
+        self.edges
+            .values()
+            .filter(|value| **value == status)
+            .count()
+    }
+}
+
+fn default_visiable() -> bool {
+    true
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+fn is_default_camera(camera: &Camera) -> bool {
+    *camera == Camera::default()
+}
+
+impl Default for CodeNode {
+    fn default() -> Self {
+        Self {
+            block_type: CodeBlockType::NORMAL,
+            id: "".to_owned(),
+            label: "".to_owned(),
+            block: "".to_owned(),
+            file_location: 0,
+            level: 0,
+            file_path: "".to_owned(),
+            position: Pos2::ZERO,
+            size: Vec2::ZERO,
+            visiable: true,
+            churn: None,
+            complexity: 0,
+            line_count: 0,
+            byte_size: 0,
+            profile: None,
+            coverage: None,
+            doc: None,
+            references: None,
+            visibility: None,
+            is_async: false,
+            is_test: false,
+            is_unsafe: false,
+            language: None,
+            diff: None,
+        }
+    }
+}
+
+impl CodeNode {
+    pub fn new(
+        id: &str,
+        label: &str,
+        block: &str,
+        file_location: usize,
+        block_type: CodeBlockType,
+        level: usize,
+    ) -> Self {
+        Self {
+            id: id.to_owned(),
+            label: label.to_owned(),
+            block: block.to_owned(),
+            file_location: file_location.to_owned(),
+            file_path: "".to_owned(),
+            block_type,
+            position: Pos2::new(0.0, 0.0),
+            size: Vec2::ZERO,
+            level,
+            visiable: true,
+            churn: None,
+            complexity: 0,
+            line_count: block.lines().count(),
+            byte_size: block.len(),
+            profile: None,
+            coverage: None,
+            doc: None,
+            references: None,
+            visibility: None,
+            is_async: false,
+            is_test: false,
+            is_unsafe: false,
+            language: None,
+            diff: None,
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn level(&self) -> usize {
+        self.level
+    }
+
+    pub fn block_type(&self) -> &CodeBlockType {
+        &self.block_type
+    }
+
+    pub fn position(&self) -> Pos2 {
+        self.position
+    }
+
+    pub fn set_position(&mut self, position: Pos2) {
+        self.position = position;
+    }
+
+    pub fn size(&self) -> Vec2 {
+        self.size
+    }
+
+    pub fn set_size(&mut self, size: Vec2) {
+        self.size = size;
+    }
+
+    pub fn visiable(&self) -> bool {
+        self.visiable
+    }
+
+    pub fn set_visiable(&mut self, visiable: bool) {
+        self.visiable = visiable;
+    }
+
+    pub fn churn(&self) -> Option<&Churn> {
+        self.churn.as_ref()
+    }
+
+    /**
+     * 热点分数：圈复杂度 × 涉及的提交数，只有函数并且计算过变更情况时才有
+     */
+    pub fn hotspot_score(&self) -> Option<usize> {
+        if !matches!(
+            self.block_type,
+            CodeBlockType::FUNCTION | CodeBlockType::METHOD | CodeBlockType::COMPONENT
+        ) {
+            return None;
+        }
+        self.churn
+            .as_ref()
+            .map(|churn| self.complexity * churn.commits)
+    }
+
+    pub fn profile(&self) -> Option<&ProfileSample> {
+        self.profile.as_ref()
+    }
+
+    pub fn coverage(&self) -> Option<f32> {
+        self.coverage
+    }
+
+    /**
+     * 判断调用节点是否调用了当前定义，只比较名字，不知道类型的 Foo::new() 这样的路径调用不算
+     */
+    pub fn matches_call(&self, call: &CodeNode) -> bool {
+        let target = call_target(call);
+        !matches!(target.qualifier, Qualifier::Path(_))
+            && definition_names(self).contains(&target.name)
+    }
+
+    /**
+     * 代码块所在的行范围（从 1 开始，包含两端）
+     */
+    pub fn line_range(&self) -> (usize, usize) {
+        let start = self.file_location.max(1);
+        let line_count = self.block.lines().count().max(1);
+        (start, start + line_count - 1)
+    }
+}
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct CodeNodeIndex(pub usize);
+
+/**
+ * 边的类型
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum EdgeKind {
+    // 包含关系，例如 impl 包含方法
+    Contains,
+    // 调用关系
+    Calls,
+    // 重复代码，两端为几乎相同的函数，没有方向
+    Duplicate,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Edge {
+    from: usize,
+    to: usize,
+    #[serde(default = "default_edge_kind")]
+    kind: EdgeKind,
+    // 调用关系在调用方文件中的所有调用行号，同一对节点之间的多处调用合并为一条边
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    call_lines: Vec<usize>,
+    // 差异图中边的变化，其他图中为 None
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    diff: Option<DiffStatus>,
+}
+
+fn default_edge_kind() -> EdgeKind {
+    EdgeKind::Contains
+}
+
+impl Edge {
+    pub fn kind(&self) -> EdgeKind {
+        self.kind
+    }
+
+    pub fn from(&self) -> CodeNodeIndex {
+        CodeNodeIndex(self.from)
+    }
+
+    pub fn to(&self) -> CodeNodeIndex {
+        CodeNodeIndex(self.to)
+    }
+
+    pub fn call_lines(&self) -> &[usize] {
+        &self.call_lines
+    }
+
+    pub fn diff(&self) -> Option<DiffStatus> {
+        self.diff
+    }
+}
+
+/**
+ * 画布上的便签，不属于代码结构，不参与布局和连线
+ */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnnotationNode {
+    pub text: String,
+    // 左上角在画布中的位置，与节点使用相同的坐标
+    pub position: Pos2,
+}
+
+/**
+ * 画布的视角，画布坐标 = (节点坐标 + offset) * zoom，
+ * 平移和缩放只修改视角，不修改节点位置
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Camera {
+    pub offset: Vec2,
+    pub zoom: f32,
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self {
+            offset: Vec2::ZERO,
+            zoom: 1.0,
+        }
+    }
+}
+
+impl Camera {
+    /**
+     * 节点坐标转换为画布坐标（相对画布左上角）
+     */
+    pub fn to_canvas(&self, position: Pos2) -> Pos2 {
+        ((position.to_vec2() + self.offset) * self.zoom).to_pos2()
+    }
+
+    /**
+     * 画布坐标转换为节点坐标
+     */
+    pub fn to_graph(&self, canvas: Pos2) -> Pos2 {
+        (canvas.to_vec2() / self.zoom - self.offset).to_pos2()
+    }
+
+    /**
+     * 以画布坐标 anchor 为中心缩放，保持 anchor 下的内容不动
+     */
+    pub fn zoom_around(&mut self, anchor: Pos2, zoom: f32) {
+        let position = self.to_graph(anchor);
+        self.zoom = zoom;
+        self.look_at(position, anchor);
+    }
+
+    /**
+     * 平移视角，使节点坐标 position 显示在画布坐标 anchor 处
+     */
+    pub fn look_at(&mut self, position: Pos2, anchor: Pos2) {
+        self.offset = anchor.to_vec2() / self.zoom - position.to_vec2();
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Graph {
+    pub(crate) nodes: Vec<CodeNode>,
+    edges: Vec<Edge>,
+    // 画布上的便签
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    annotations: Vec<AnnotationNode>,
+    #[serde(skip)]
+    focus_node: Option<CodeNodeIndex>,
+    // 保存视角，重新打开时保持原来的平移和缩放
+    #[serde(default, skip_serializing_if = "is_default_camera")]
+    camera: Camera,
+    // 折叠的分组
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) collapsed: Vec<CollapsedCluster>,
+}
+
+impl Default for Graph {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Graph {
+    pub fn new() -> Self {
+        Self {
+            nodes: vec![],
+            edges: vec![],
+            annotations: vec![],
+            focus_node: None,
+            camera: Camera::default(),
+            collapsed: vec![],
+        }
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn node(&self, index: CodeNodeIndex) -> Option<&CodeNode> {
+        self.nodes.get(index.0)
+    }
+
+    pub fn node_mut(&mut self, index: CodeNodeIndex) -> Option<&mut CodeNode> {
+        self.nodes.get_mut(index.0)
+    }
+
+    /**
+     * 遍历所有节点
+     */
+    pub fn nodes(&self) -> impl Iterator<Item = (CodeNodeIndex, &CodeNode)> + '_ {
+        self.nodes
+            .iter()
+            .enumerate()
+            .map(|(index, node)| (CodeNodeIndex(index), node))
+    }
+
+    pub fn nodes_mut(&mut self) -> impl Iterator<Item = (CodeNodeIndex, &mut CodeNode)> + '_ {
+        self.nodes
+            .iter_mut()
+            .enumerate()
+            .map(|(index, node)| (CodeNodeIndex(index), node))
+    }
+
+    /**
+     * 遍历所有边
+     */
+    pub fn edges(&self) -> impl Iterator<Item = &Edge> + '_ {
+        self.edges.iter()
+    }
+
+    /**
+     * 直接子节点（沿 Contains 边）
+     */
+    pub fn children_of(&self, index: CodeNodeIndex) -> impl Iterator<Item = CodeNodeIndex> + '_ {
+        self.edges
+            .iter()
+            .filter(move |edge| edge.kind == EdgeKind::Contains && edge.from == index.0)
+            .map(|edge| CodeNodeIndex(edge.to))
+    }
+
+    /**
+     * 直接父节点（沿 Contains 边）
+     */
+    pub fn parents_of(&self, index: CodeNodeIndex) -> impl Iterator<Item = CodeNodeIndex> + '_ {
+        self.edges
+            .iter()
+            .filter(move |edge| edge.kind == EdgeKind::Contains && edge.to == index.0)
+            .map(|edge| CodeNodeIndex(edge.from))
+    }
+
+    /**
+     * 从根节点沿包含关系到该节点的路径（包含根节点和自身），有多个父节点时取第一个
+     */
+    pub fn ancestor_path(&self, index: CodeNodeIndex) -> Vec<CodeNodeIndex> {
+        let mut path = vec![index];
+        while let Some(parent) = path.last().and_then(|last| self.parents_of(*last).next()) {
+            // 避免包含关系成环时死循环
+            if path.contains(&parent) {
+                break;
+            }
+            path.push(parent);
+        }
+        path.reverse();
+        path
+    }
+
+    /**
+     * 所有后代节点，按广度优先顺序（不包含自身）
+     */
+    pub fn descendants(&self, index: CodeNodeIndex) -> impl Iterator<Item = CodeNodeIndex> + '_ {
+        let mut queue: VecDeque<CodeNodeIndex> = self.children_of(index).collect();
+        let mut visited = vec![false; self.nodes.len()];
+        std::iter::from_fn(move || {
+            while let Some(current) = queue.pop_front() {
+                if visited[current.0] {
+                    continue;
+                }
+                visited[current.0] = true;
+                queue.extend(self.children_of(current));
+                return Some(current);
+            }
+            None
+        })
+    }
+
+    /**
+     * 没有父节点的根节点
+     */
+    pub fn roots(&self) -> impl Iterator<Item = CodeNodeIndex> + '_ {
+        (0..self.nodes.len())
+            .map(CodeNodeIndex)
+            .filter(|index| self.parents_of(*index).next().is_none())
+    }
+
+    /**
+     * 根据 git blame 的结果计算每个节点的变更热度和最近修改的作者、时间，
+     * 热度由修改频率（涉及的提交数）和最近修改时间共同决定，
+     * 函数的热点分数为圈复杂度 × 提交数，缓存中没有 blame 结果的文件不着色
+     */
+    pub fn apply_churn(&mut self, blames: &BlameCache) {
+        for node in self.nodes.iter_mut() {
+            node.churn = blames.get(&node.file_path).map(|blame| {
+                let (start, end) = node.line_range();
+                git::churn_of_lines(blame, start, end)
+            });
+        }
+        // 归一化
+        let churn_list = self.nodes.iter().filter_map(|node| node.churn.as_ref());
+        let max_commits = churn_list.clone().map(|c| c.commits).max().unwrap_or(0);
+        let min_time = churn_list
+            .clone()
+            .map(|c| c.last_modified)
+            .min()
+            .unwrap_or(0);
+        let max_time = churn_list.map(|c| c.last_modified).max().unwrap_or(0);
+        for churn in self.nodes.iter_mut().filter_map(|node| node.churn.as_mut()) {
+            let frequency = if max_commits > 0 {
+                churn.commits as f32 / max_commits as f32
+            } else {
+                0.0
+            };
+            let recency = if max_time > min_time {
+                (churn.last_modified - min_time) as f32 / (max_time - min_time) as f32
+            } else {
+                0.0
+            };
+            churn.heat = (frequency + recency) / 2.0;
+            churn.recency = recency;
+        }
+        let max_score = self
+            .nodes
+            .iter()
+            .filter_map(|node| node.hotspot_score())
+            .max()
+            .unwrap_or(0);
+        for node in self.nodes.iter_mut() {
+            let score = node.hotspot_score().unwrap_or(0);
+            if let Some(churn) = node.churn.as_mut() {
+                churn.hotspot = if max_score > 0 {
+                    score as f32 / max_score as f32
+                } else {
+                    0.0
+                };
+            }
+        }
+    }
+
+    /**
+     * 根据 count_references 的结果设置定义节点的引用次数
+     */
+    pub fn apply_references(&mut self, counts: &HashMap<String, usize>) {
+        for node in self.nodes.iter_mut() {
+            node.references = (node.level > 0 && node.block_type != CodeBlockType::CALL)
+                .then(|| counts.get(&node.id).copied().unwrap_or(0));
+        }
+    }
+
+    pub fn get_focus_idx(&mut self) -> Option<CodeNodeIndex> {
+        self.focus_node
+    }
+
+    pub fn set_focus_idx(&mut self, index: Option<CodeNodeIndex>) {
+        self.focus_node = index;
+    }
+
+    pub fn annotations(&self) -> &[AnnotationNode] {
+        &self.annotations
+    }
+
+    pub fn annotation_mut(&mut self, index: usize) -> Option<&mut AnnotationNode> {
+        self.annotations.get_mut(index)
+    }
+
+    /**
+     * 添加便签，返回便签的序号
+     */
+    pub fn add_annotation(&mut self, position: Pos2, text: impl Into<String>) -> usize {
+        self.annotations.push(AnnotationNode {
+            text: text.into(),
+            position,
+        });
+        self.annotations.len() - 1
+    }
+
+    pub fn remove_annotation(&mut self, index: usize) {
+        if index < self.annotations.len() {
+            self.annotations.remove(index);
+        }
+    }
+
+    /**
+     * 替换所有便签，用于恢复之前保存的便签
+     */
+    pub fn set_annotations(&mut self, annotations: Vec<AnnotationNode>) {
+        self.annotations = annotations;
+    }
+
+    pub fn camera(&self) -> Camera {
+        self.camera
+    }
+
+    pub fn camera_mut(&mut self) -> &mut Camera {
+        &mut self.camera
+    }
+
+    /**
+     * 根据行号查找节点
+     */
+    pub fn find_node_by_location(&self, file_location: usize) -> Option<CodeNodeIndex> {
+        self.nodes
+            .iter()
+            .position(|node| {
+                node.file_location == file_location && node.block_type != CodeBlockType::CALL
+            })
+            .map(CodeNodeIndex)
+    }
+
+    pub fn add_node(&mut self, node: CodeNode) -> CodeNodeIndex {
+        let index = self.nodes.len();
+        self.nodes.push(node);
+        CodeNodeIndex(index)
+    }
+
+    /**
+     * 为 root 及其后代生成稳定的 ID：文件路径::限定名#类型，限定名由所属节点和定义的名字组成，
+     * 修改可见性、async 或泛型后不变；同名同类型的节点按出现顺序追加 @序号，重新解析后 ID 保持不变
+     */
+    pub fn assign_stable_ids(&mut self, root: CodeNodeIndex) {
+        let Some(root_node) = self.node(root) else {
+            return;
+        };
+        let file_path = root_node.file_path.clone();
+        let mut ids = vec![(root, file_path.clone())];
+        let mut queue = VecDeque::from([(root, String::new())]);
+        let mut id_counts: HashMap<String, usize> = HashMap::new();
+        while let Some((parent, parent_name)) = queue.pop_front() {
+            for child in self.children_of(parent).collect::<Vec<CodeNodeIndex>>() {
+                let node = &self.nodes[child.0];
+                let name = stable_name(node);
+                let qualified_name = if parent_name.is_empty() {
+                    name
+                } else {
+                    format!("{}::{}", parent_name, name)
+                };
+                let mut id = format!("{}::{}#{:?}", file_path, qualified_name, node.block_type);
+                let count = id_counts.entry(id.clone()).or_insert(0);
+                if *count > 0 {
+                    id = format!("{}@{}", id, count);
+                }
+                *count += 1;
+                ids.push((child, id));
+                queue.push_back((child, qualified_name));
+            }
+        }
+        for (index, id) in ids {
+            self.nodes[index.0].id = id;
+        }
+    }
+
+    /**
+     * 删除不满足条件的节点及其相连的边，其余节点重新编号
+     */
+    pub fn retain_nodes(&mut self, mut keep: impl FnMut(&CodeNode) -> bool) {
+        let mut index_map = vec![None; self.nodes.len()];
+        let mut nodes = vec![];
+        for (index, node) in self.nodes.drain(..).enumerate() {
+            if keep(&node) {
+                index_map[index] = Some(nodes.len());
+                nodes.push(node);
+            }
+        }
+        self.nodes = nodes;
+        self.edges
+            .retain_mut(|edge| match (index_map[edge.from], index_map[edge.to]) {
+                (Some(from), Some(to)) => {
+                    edge.from = from;
+                    edge.to = to;
+                    true
+                }
+                _ => false,
+            });
+        self.focus_node = self
+            .focus_node
+            .and_then(|index| index_map[index.0])
+            .map(CodeNodeIndex);
+    }
+
+    pub fn add_edge(&mut self, from: CodeNodeIndex, to: CodeNodeIndex) {
+        self.add_edge_with_kind(from, to, EdgeKind::Contains);
+    }
+
+    pub fn add_edge_with_kind(&mut self, from: CodeNodeIndex, to: CodeNodeIndex, kind: EdgeKind) {
+        self.edges.push(Edge {
+            from: from.0,
+            to: to.0,
+            kind,
+            call_lines: vec![],
+            diff: None,
+        })
+    }
+
+    /**
+     * 添加调用关系，call_lines 为调用方中每处调用所在的行号
+     */
+    pub fn add_call_edge(
+        &mut self,
+        from: CodeNodeIndex,
+        to: CodeNodeIndex,
+        call_lines: Vec<usize>,
+    ) {
+        self.edges.push(Edge {
+            from: from.0,
+            to: to.0,
+            kind: EdgeKind::Calls,
+            call_lines,
+            diff: None,
+        })
+    }
+
+    /**
+     * 两个节点之间已经有调用关系时合并调用行号，否则添加调用关系
+     */
+    pub fn merge_call_edge(
+        &mut self,
+        from: CodeNodeIndex,
+        to: CodeNodeIndex,
+        call_lines: Vec<usize>,
+    ) {
+        match self
+            .edges
+            .iter_mut()
+            .find(|edge| edge.kind == EdgeKind::Calls && edge.from == from.0 && edge.to == to.0)
+        {
+            Some(edge) => {
+                for line in call_lines {
+                    if !edge.call_lines.contains(&line) {
+                        edge.call_lines.push(line);
+                    }
+                }
+                edge.call_lines.sort_unstable();
+            }
+            None => self.add_call_edge(from, to, call_lines),
+        }
+    }
+
+    /**
+     * 以当前图为旧版本、other 为新版本对比节点和边：只在一边的为新增或删除，
+     * 两边都有时定义的标签或代码（忽略首尾空白）不同的节点、调用次数不同的调用边为修改
+     */
+    pub fn diff(&self, other: &Graph) -> GraphDiff {
+        let old_nodes = self
+            .nodes
+            .iter()
+            .map(|node| (node.id.as_str(), node))
+            .collect::<HashMap<&str, &CodeNode>>();
+        let new_nodes = other
+            .nodes
+            .iter()
+            .map(|node| (node.id.as_str(), node))
+            .collect::<HashMap<&str, &CodeNode>>();
+        let mut diff = GraphDiff::default();
+        for (id, old_node) in &old_nodes {
+            match new_nodes.get(id) {
+                None => {
+                    diff.nodes.insert(id.to_string(), DiffStatus::Removed);
+                }
+                Some(new_node)
+                    if old_node.label.trim() != new_node.label.trim()
+                        || old_node.block.trim() != new_node.block.trim() =>
+                {
+                    diff.nodes.insert(id.to_string(), DiffStatus::Changed);
+                }
+                Some(_) => {}
+            }
+        }
+        for id in new_nodes.keys().filter(|id| !old_nodes.contains_key(*id)) {
+            diff.nodes.insert(id.to_string(), DiffStatus::Added);
+        }
+
+        let edges_by_key = |graph: &Graph| {
+            graph
+                .edges
+                .iter()
+                .map(|edge| {
+                    let key = (
+                        graph.nodes[edge.from].id.clone(),
+                        graph.nodes[edge.to].id.clone(),
+                        edge.kind,
+                    );
+                    (key, edge.call_lines.len())
+                })
+                .collect::<HashMap<(String, String, EdgeKind), usize>>()
+        };
+        let (old_edges, new_edges) = (edges_by_key(self), edges_by_key(other));
+        for (key, old_calls) in &old_edges {
+            match new_edges.get(key) {
+                None => {
+                    diff.edges.insert(key.clone(), DiffStatus::Removed);
+                }
+                Some(new_calls) if new_calls != old_calls => {
+                    diff.edges.insert(key.clone(), DiffStatus::Changed);
+                }
+                Some(_) => {}
+            }
+        }
+        for key in new_edges.keys().filter(|key| !old_edges.contains_key(*key)) {
+            diff.edges.insert(key.clone(), DiffStatus::Added);
+        }
+        diff
+    }
+
+    /**
+     * 差异图：在新版本 other 中加回被删除的节点和边，每个节点和边记录自己的变化，
+     * 被删除的节点挂在旧版本中最近的仍然存在的父节点下
+     */
+    pub fn diff_view(&self, other: &Graph, diff: &GraphDiff) -> Graph {
+        let mut graph = other.clone();
+        graph.focus_node = None;
+        for node in graph.nodes.iter_mut() {
+            node.diff = diff.nodes.get(&node.id).copied();
+        }
+        let mut indexes = graph
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(index, node)| (node.id.clone(), index))
+            .collect::<HashMap<String, usize>>();
+        // 旧图中父节点在子节点之前加入，按顺序加回时父节点已经在图中
+        for (old_index, old_node) in self.nodes.iter().enumerate() {
+            if diff.nodes.get(&old_node.id) != Some(&DiffStatus::Removed) {
+                continue;
+            }
+            let mut node = old_node.clone();
+            node.diff = Some(DiffStatus::Removed);
+            node.visiable = true;
+            let index = graph.add_node(node);
+            indexes.insert(old_node.id.clone(), index.0);
+            if let Some(parent) = self
+                .parents_of(CodeNodeIndex(old_index))
+                .find_map(|parent| indexes.get(&self.nodes[parent.0].id))
+            {
+                graph.add_edge(CodeNodeIndex(*parent), index);
+            }
+        }
+        for edge in graph.edges.iter_mut() {
+            let key = (
+                graph.nodes[edge.from].id.clone(),
+                graph.nodes[edge.to].id.clone(),
+                edge.kind,
+            );
+            edge.diff = diff.edges.get(&key).copied();
+        }
+        for edge in self
+            .edges
+            .iter()
+            .filter(|edge| edge.kind != EdgeKind::Contains)
+        {
+            let (from_id, to_id) = (&self.nodes[edge.from].id, &self.nodes[edge.to].id);
+            let key = (from_id.clone(), to_id.clone(), edge.kind);
+            if diff.edges.get(&key) != Some(&DiffStatus::Removed) {
+                continue;
+            }
+            if let (Some(from), Some(to)) = (indexes.get(from_id), indexes.get(to_id)) {
+                graph.edges.push(Edge {
+                    from: *from,
+                    to: *to,
+                    kind: edge.kind,
+                    call_lines: edge.call_lines.clone(),
+                    diff: Some(DiffStatus::Removed),
+                });
+            }
+        }
+        graph
+    }
+
+    /**
+     * 按 ID 查找节点
+     */
+    pub fn find_node_by_id(&self, id: &str) -> Option<CodeNodeIndex> {
+        self.nodes
+            .iter()
+            .position(|node| node.id == id)
+            .map(CodeNodeIndex)
+    }
+
+    /**
+     * 把项目中的定义加入图中并放在指定层级，图中已有同一 ID 的节点时直接返回该节点
+     */
+    pub fn insert_definition(&mut self, definition: &CodeNode, level: usize) -> CodeNodeIndex {
+        if let Some(index) = self.find_node_by_id(&definition.id) {
+            return index;
+        }
+        let mut node = definition.clone();
+        node.level = level;
+        node.visiable = true;
+        self.add_node(node)
+    }
+
+    /**
+     * 查找文件中包含指定行的最内层节点
+     */
+    pub fn find_innermost_node(&self, file_path: &str, line: usize) -> Option<CodeNodeIndex> {
+        self.nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, node)| {
+                node.file_path == file_path && node.block_type != CodeBlockType::CALL
+            })
+            .filter(|(_, node)| {
+                let (start, end) = node.line_range();
+                start <= line && line <= end
+            })
+            .min_by_key(|(_, node)| {
+                let (start, end) = node.line_range();
+                end - start
+            })
+            .map(|(index, _)| CodeNodeIndex(index))
+    }
+
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.edges.clear();
+        self.annotations.clear();
+        self.focus_node = None;
+    }
+    /**
+     * 最深的层级
+     */
+    pub fn max_level(&self) -> usize {
+        self.nodes.iter().map(|node| node.level).max().unwrap_or(0)
+    }
+
+    /**
+     * 只显示不超过指定层级的节点，None 表示显示全部
+     */
+    pub fn limit_depth(&mut self, depth: Option<usize>) {
+        for node in self.nodes.iter_mut() {
+            node.visiable = depth.is_none_or(|depth| node.level <= depth);
+        }
+    }
+
+    /**
+     * 孤立节点：自身和后代都没有调用关系的定义
+     */
+    pub fn orphans(&self) -> Vec<CodeNodeIndex> {
+        self.orphans_by(|index| {
+            self.edges.iter().any(|edge| {
+                edge.kind == EdgeKind::Calls && (edge.from == index.0 || edge.to == index.0)
+            })
+        })
+    }
+
+    /**
+     * 根据自定义的关联判断查找孤立节点
+     */
+    pub fn orphans_by(&self, connected: impl Fn(CodeNodeIndex) -> bool) -> Vec<CodeNodeIndex> {
+        self.nodes()
+            .filter(|(_, node)| node.level > 0)
+            .map(|(index, _)| index)
+            .filter(|index| !connected(*index) && !self.descendants(*index).any(&connected))
+            .collect()
+    }
+
+    /**
+     * 隐藏不满足条件的定义，满足条件的节点的祖先节点保留可见，便于看出所在位置，
+     * 只会隐藏节点，已经隐藏的节点不会重新显示
+     */
+    pub fn hide_unmatched(&mut self, matches: impl Fn(&CodeNode) -> bool) {
+        let matched = self.nodes.iter().map(matches).collect::<Vec<bool>>();
+        self.hide_unmatched_indexes(&matched);
+    }
+
+    /**
+     * 只显示公开接口：隐藏非公开的定义，公开定义所在的上层节点保留。
+     * 没有写可见性的定义中，Rust trait 和接口中的方法以及 JavaScript、TypeScript 类的成员跟随所在的类型，
+     * Rust trait 实现中的方法视为公开，其他（例如 Java 的包内可见、没有导出的 JavaScript 函数）视为非公开
+     */
+    pub fn hide_non_public(&mut self) {
+        let matched = (0..self.nodes.len())
+            .map(|index| self.is_public(CodeNodeIndex(index)))
+            .collect::<Vec<bool>>();
+        self.hide_unmatched_indexes(&matched);
+    }
+
+    fn is_public(&self, index: CodeNodeIndex) -> bool {
+        let node = &self.nodes[index.0];
+        let parent = self
+            .parents_of(index)
+            .find(|parent| self.nodes[parent.0].level > 0);
+        let words = parent
+            .map(|parent| {
+                self.nodes[parent.0]
+                    .label
+                    .split_whitespace()
+                    .collect::<Vec<&str>>()
+            })
+            .unwrap_or_default();
+        let is_impl = words.contains(&"impl");
+        match (node.visibility, parent) {
+            (Some(Visibility::Private), _) => false,
+            // 非公开类型中的公开成员在外部也无法使用
+            (Some(Visibility::Public), Some(parent)) if !is_impl => self.is_public(parent),
+            (Some(Visibility::Public), _) => true,
+            (None, None) => false,
+            (None, Some(parent)) => {
+                let script = matches!(node.language.as_deref(), Some("JavaScript" | "TypeScript"));
+                if script || words.contains(&"trait") || words.contains(&"interface") {
+                    self.is_public(parent)
+                } else {
+                    // impl Trait for Type
+                    is_impl && words.contains(&"for")
+                }
+            }
+        }
+    }
+
+    fn hide_unmatched_indexes(&mut self, matched: &[bool]) {
+        let mut keep = vec![false; self.nodes.len()];
+        let mut stack = self
+            .nodes()
+            .filter(|(index, node)| node.level == 0 || matched[index.0])
+            .map(|(index, _)| index)
+            .collect::<Vec<CodeNodeIndex>>();
+        while let Some(index) = stack.pop() {
+            if keep[index.0] {
+                continue;
+            }
+            keep[index.0] = true;
+            stack.extend(self.parents_of(index));
+        }
+        for (node, keep) in self.nodes.iter_mut().zip(keep) {
+            if !keep {
+                node.visiable = false;
+            }
+        }
+    }
+
+    /**
+     * 平移所有节点
+     */
+    pub fn translate(&mut self, offset: Vec2) {
+        for node in self.nodes.iter_mut() {
+            node.position += offset;
+        }
+    }
+
+    pub fn get_node(&mut self, index: CodeNodeIndex) -> CodeNode {
+        let default_node = CodeNode::default();
+        let node = self.nodes.get(index.0).unwrap_or(&default_node);
+        node.clone()
+    }
+
+    pub fn node_index(&mut self, node_id: &str) -> CodeNodeIndex {
+        for (index, node) in self.nodes.iter().enumerate() {
+            if node.id == node_id {
+                return CodeNodeIndex(index);
+            }
+        }
+        CodeNodeIndex(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /**
+     * 按 (ID, 代码, 父节点) 建图，节点按顺序加入，父节点在子节点之前
+     */
+    fn tree_graph(nodes: &[(&str, &str, Option<usize>)], calls: &[(usize, usize, usize)]) -> Graph {
+        let mut graph = Graph::new();
+        for (id, block, parent) in nodes {
+            let index = graph.add_node(CodeNode::new(id, id, block, 1, CodeBlockType::FUNCTION, 0));
+            if let Some(parent) = parent {
+                graph.add_edge(CodeNodeIndex(*parent), index);
+            }
+        }
+        for (from, to, count) in calls {
+            graph.add_call_edge(
+                CodeNodeIndex(*from),
+                CodeNodeIndex(*to),
+                (1..=*count).collect(),
+            );
+        }
+        graph
+    }
+
+    fn call_key(from: &str, to: &str) -> (String, String, EdgeKind) {
+        (from.to_owned(), to.to_owned(), EdgeKind::Calls)
+    }
+
+    /**
+     * 旧图：a.rs 下有 f、c、g，g 下有 h，f 调用 c 一次、调用 g
+     * 新图：f 的代码变了，g 和 h 被删除，新增 k 调用 f，f 调用 c 两次
+     */
+    fn sample() -> (Graph, Graph) {
+        let old = tree_graph(
+            &[
+                ("a.rs", "", None),
+                ("f", "fn f() {}", Some(0)),
+                ("c", "fn c() {}", Some(0)),
+                ("g", "fn g() {}", Some(0)),
+                ("h", "fn h() {}", Some(3)),
+            ],
+            &[(1, 2, 1), (1, 3, 1)],
+        );
+        let new = tree_graph(
+            &[
+                ("a.rs", "", None),
+                ("f", "fn f() { c(); }", Some(0)),
+                ("c", "fn c() {}", Some(0)),
+                ("k", "fn k() {}", Some(0)),
+            ],
+            &[(1, 2, 2), (3, 1, 1)],
+        );
+        (old, new)
+    }
+
+    #[test]
+    fn diff_records_node_changes() {
+        let (old, new) = sample();
+        let diff = old.diff(&new);
+        assert_eq!(diff.nodes.get("f"), Some(&DiffStatus::Changed));
+        assert_eq!(diff.nodes.get("g"), Some(&DiffStatus::Removed));
+        assert_eq!(diff.nodes.get("h"), Some(&DiffStatus::Removed));
+        assert_eq!(diff.nodes.get("k"), Some(&DiffStatus::Added));
+        // 没有变化的节点不记录
+        assert!(!diff.nodes.contains_key("a.rs"));
+        assert!(!diff.nodes.contains_key("c"));
+        assert_eq!(diff.node_count(DiffStatus::Removed), 2);
+    }
+
+    #[test]
+    fn diff_records_edge_changes() {
+        let (old, new) = sample();
+        let diff = old.diff(&new);
+        assert_eq!(
+            diff.edges.get(&call_key("f", "c")),
+            Some(&DiffStatus::Changed)
+        );
+        assert_eq!(
+            diff.edges.get(&call_key("f", "g")),
+            Some(&DiffStatus::Removed)
+        );
+        assert_eq!(
+            diff.edges.get(&call_key("k", "f")),
+            Some(&DiffStatus::Added)
+        );
+        let contains = |from: &str, to: &str| (from.to_owned(), to.to_owned(), EdgeKind::Contains);
+        assert_eq!(
+            diff.edges.get(&contains("a.rs", "g")),
+            Some(&DiffStatus::Removed)
+        );
+        assert_eq!(
+            diff.edges.get(&contains("a.rs", "k")),
+            Some(&DiffStatus::Added)
+        );
+        assert!(!diff.edges.contains_key(&contains("a.rs", "f")));
+        assert!(old.diff(&old).nodes.is_empty() && old.diff(&old).edges.is_empty());
+    }
+
+    #[test]
+    fn diff_view_adds_back_removed_nodes_under_their_parents() {
+        let (old, new) = sample();
+        let view = old.diff_view(&new, &old.diff(&new));
+        assert_eq!(view.node_count(), 6);
+        let index = |id: &str| view.find_node_by_id(id).unwrap();
+        for (id, status) in [
+            ("a.rs", None),
+            ("f", Some(DiffStatus::Changed)),
+            ("g", Some(DiffStatus::Removed)),
+            ("h", Some(DiffStatus::Removed)),
+            ("k", Some(DiffStatus::Added)),
+        ] {
+            assert_eq!(view.node(index(id)).unwrap().diff, status, "{}", id);
+        }
+        // 被删除的 g 挂在 a.rs 下，h 挂在同样被删除的 g 下
+        assert_eq!(
+            view.parents_of(index("g")).collect::<Vec<_>>(),
+            vec![index("a.rs")]
+        );
+        assert_eq!(
+            view.parents_of(index("h")).collect::<Vec<_>>(),
+            vec![index("g")]
+        );
+
+        let edge = |from: &str, to: &str| {
+            view.edges()
+                .find(|edge| {
+                    edge.kind() == EdgeKind::Calls
+                        && edge.from() == index(from)
+                        && edge.to() == index(to)
+                })
+                .map(|edge| edge.diff())
+        };
+        assert_eq!(edge("f", "g"), Some(Some(DiffStatus::Removed)));
+        assert_eq!(edge("f", "c"), Some(Some(DiffStatus::Changed)));
+        assert_eq!(edge("k", "f"), Some(Some(DiffStatus::Added)));
+    }
+
+    #[cfg(feature = "lang-rust")]
+    fn stable_ids(code: &str) -> Vec<String> {
+        let mut graph = Graph::new();
+        crate::fetch_symbols(
+            "a.rs",
+            code,
+            crate::get_symbol_query("rs").unwrap(),
+            &mut graph,
+        )
+        .unwrap();
+        graph.nodes().map(|(_, node)| node.id.clone()).collect()
+    }
+
+    #[cfg(feature = "lang-rust")]
+    #[test]
+    fn stable_ids_use_owner_name_and_kind() {
+        let ids = stable_ids(
+            "struct Foo;\nimpl Foo {\n    fn new() -> Self { Foo }\n}\n\
+             impl std::fmt::Display for Foo {\n    fn fmt(&self) {}\n}\n\
+             fn twice() {}\nfn twice() {}\n",
+        );
+        assert_eq!(
+            ids,
+            vec![
+                "a.rs",
+                "a.rs::Foo#STRUCT",
+                "a.rs::Foo#CLASS",
+                "a.rs::Foo::new#FUNCTION",
+                "a.rs::Display for Foo#CLASS",
+                "a.rs::Display for Foo::fmt#FUNCTION",
+                "a.rs::twice#FUNCTION",
+                // 同名同类型的定义按顺序追加序号
+                "a.rs::twice#FUNCTION@1",
+            ]
+        );
+    }
+
+    #[cfg(feature = "lang-rust")]
+    #[test]
+    fn stable_ids_ignore_visibility_async_and_generics() {
+        assert_eq!(
+            stable_ids("impl Foo {\n    fn run(&self) {}\n}\n"),
+            stable_ids("impl<T: Clone> Foo<T> {\n    pub async fn run<U>(&self, u: U) {}\n}\n")
+        );
+    }
+}
@@ -0,0 +1,195 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::{
+    label::{simplify_generics, GenericsDisplay},
+    patch::{add_child, group_node},
+    CodeBlockType, CodeNode, CodeNodeIndex, DefinitionIndex, Graph,
+};
+
+/**
+ * 从定义的标签中取出的类型声明
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeDeclaration {
+    pub name: String,
+    // 继承的父类、实现的接口或 trait，以及 trait 的父 trait
+    pub supertypes: Vec<String>,
+    // 是否为类型本身的定义，impl Trait for Type 只是实现，不是 Type 的定义
+    pub defines: bool,
+}
+
+/**
+ * 解析 Java 的 class / interface 和 Rust 的 struct / trait / impl Trait for Type，
+ * 类型名去掉泛型参数和路径，只保留最后一段
+ */
+pub fn type_declaration(node: &CodeNode) -> Option<TypeDeclaration> {
+    if !matches!(
+        node.block_type,
+        CodeBlockType::CLASS | CodeBlockType::STRUCT
+    ) {
+        return None;
+    }
+    let label = simplify_generics(&node.label, GenericsDisplay::Strip);
+    if node.file_path.ends_with(".rs") {
+        rust_declaration(&label)
+    } else if node.file_path.ends_with(".java") {
+        java_declaration(&label)
+    } else {
+        None
+    }
+}
+
+/**
+ * 类型表达式中的类型名：去掉引用、dyn 和路径，例如 &'a mut fmt::Display 为 Display
+ */
+fn type_name(text: &str) -> Option<String> {
+    let text = text
+        .split_whitespace()
+        .filter(|word| !word.starts_with('\'') && !matches!(*word, "mut" | "dyn" | "&"))
+        .collect::<Vec<&str>>()
+        .join(" ");
+    let text = text.trim_start_matches('&');
+    let last = text.rsplit("::").next()?.rsplit('.').next()?;
+    let name = last
+        .chars()
+        .take_while(|c| c.is_alphanumeric() || *c == '_')
+        .collect::<String>();
+    (!name.is_empty()).then_some(name)
+}
+
+fn rust_declaration(label: &str) -> Option<TypeDeclaration> {
+    let words = label.split_whitespace().collect::<Vec<&str>>();
+    let keyword = words
+        .iter()
+        .position(|word| matches!(*word, "impl" | "trait" | "struct"))?;
+    let rest = words[keyword + 1..].join(" ");
+    match words[keyword] {
+        "impl" => {
+            // 没有 for 的 impl 是固有实现，impl !Send for T 是否定实现
+            let (trait_name, type_text) = rest.split_once(" for ")?;
+            if trait_name.trim_start().starts_with('!') {
+                return None;
+            }
+            Some(TypeDeclaration {
+                name: type_name(type_text)?,
+                supertypes: vec![type_name(trait_name)?],
+                defines: false,
+            })
+        }
+        "trait" => {
+            let (name, bounds) = rest.split_once(':').unwrap_or((&rest, ""));
+            Some(TypeDeclaration {
+                name: type_name(name)?,
+                // ?Sized 和生命周期不是父 trait
+                supertypes: bounds
+                    .split('+')
+                    .map(str::trim)
+                    .filter(|bound| !bound.starts_with(['?', '\'']))
+                    .filter_map(type_name)
+                    .collect(),
+                defines: true,
+            })
+        }
+        _ => Some(TypeDeclaration {
+            name: type_name(&rest)?,
+            supertypes: vec![],
+            defines: true,
+        }),
+    }
+}
+
+fn java_declaration(label: &str) -> Option<TypeDeclaration> {
+    let words = label
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<&str>>();
+    let keyword = words
+        .iter()
+        .position(|word| matches!(*word, "class" | "interface"))?;
+    let name = type_name(words.get(keyword + 1)?)?;
+    let mut supertypes = vec![];
+    let mut in_supertypes = false;
+    for word in &words[keyword + 2..] {
+        match *word {
+            "extends" | "implements" => in_supertypes = true,
+            // Java 17 的 sealed 类型
+            "permits" => in_supertypes = false,
+            _ if in_supertypes => supertypes.extend(type_name(word)),
+            _ => {}
+        }
+    }
+    Some(TypeDeclaration {
+        name,
+        supertypes,
+        defines: true,
+    })
+}
+
+/**
+ * 类型层级图：父类型在上，子类型在下，根节点下是没有父类型的类型，
+ * 继承多个父类型的类型会出现在每个父类型下面，项目中没有定义的父类型（例如 Display）只显示名字。
+ * 只包含参与继承或实现关系的类型
+ */
+pub fn build_hierarchy_graph(project_name: &str, definitions: &DefinitionIndex) -> Graph {
+    let mut subtypes: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    let mut has_supertype: BTreeSet<String> = BTreeSet::new();
+    let mut nodes: HashMap<String, &CodeNode> = HashMap::new();
+    for node in definitions.definitions() {
+        let Some(declaration) = type_declaration(node) else {
+            continue;
+        };
+        if declaration.defines {
+            nodes.entry(declaration.name.clone()).or_insert(node);
+        }
+        for supertype in declaration.supertypes {
+            if supertype == declaration.name {
+                continue;
+            }
+            has_supertype.insert(declaration.name.clone());
+            subtypes
+                .entry(supertype)
+                .or_default()
+                .insert(declaration.name.clone());
+        }
+    }
+
+    let mut graph = Graph::new();
+    let root = graph.add_node(group_node(project_name, project_name));
+    let mut path = vec![];
+    for name in subtypes
+        .keys()
+        .filter(|name| !has_supertype.contains(*name))
+    {
+        add_type(&mut graph, root, name, 1, &subtypes, &nodes, &mut path);
+    }
+    graph
+}
+
+fn add_type(
+    graph: &mut Graph,
+    parent: CodeNodeIndex,
+    name: &str,
+    level: usize,
+    subtypes: &BTreeMap<String, BTreeSet<String>>,
+    nodes: &HashMap<String, &CodeNode>,
+    path: &mut Vec<String>,
+) {
+    let node = match nodes.get(name) {
+        Some(node) => {
+            let mut node = (*node).clone();
+            node.id = name.to_owned();
+            node
+        }
+        None => group_node(name, name),
+    };
+    let index = add_child(graph, parent, node, level);
+    // 互相继承的错误代码中避免无限展开
+    if path.iter().any(|ancestor| ancestor == name) {
+        return;
+    }
+    path.push(name.to_owned());
+    for subtype in subtypes.get(name).into_iter().flatten() {
+        add_type(graph, index, subtype, level + 1, subtypes, nodes, path);
+    }
+    path.pop();
+}
@@ -0,0 +1,193 @@
+use std::{
+    collections::{HashMap, HashSet},
+    io::Write,
+    path::Path,
+    process::{Command, Stdio},
+    thread,
+};
+
+use crate::{
+    git::run_git, snapshot::sources_graph, valid_file_extention, CodeGraphError, CodeNode, Graph,
+    Result,
+};
+
+/**
+ * 历史中的一个提交
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Revision {
+    pub hash: String,
+    pub short_hash: String,
+    pub author: String,
+    // 提交时间（unix 时间戳）
+    pub time: i64,
+    // 提交说明的第一行
+    pub subject: String,
+}
+
+/**
+ * 修改过项目目录的最近 limit 个提交，从旧到新排列
+ */
+pub fn recent_revisions(project: &Path, limit: usize) -> Result<Vec<Revision>> {
+    let output = run_git(
+        project,
+        &[
+            "log",
+            "--format=%H%x1f%h%x1f%an%x1f%at%x1f%s",
+            &format!("-n{}", limit),
+            "--",
+            ".",
+        ],
+    )
+    .ok_or_else(|| CodeGraphError::Command(format!("{} 不在 git 仓库中", project.display())))?;
+    let mut revisions = output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\x1f');
+            Some(Revision {
+                hash: fields.next()?.to_owned(),
+                short_hash: fields.next()?.to_owned(),
+                author: fields.next()?.to_owned(),
+                time: fields.next()?.parse().unwrap_or(0),
+                subject: fields.next().unwrap_or("").to_owned(),
+            })
+        })
+        .collect::<Vec<Revision>>();
+    revisions.reverse();
+    Ok(revisions)
+}
+
+/**
+ * 从 git 对象中读取项目目录在 revision 时的所有源代码文件，返回（相对项目目录的路径，代码），
+ * 不读取工作区，非 UTF-8 的文件跳过
+ */
+pub fn revision_sources(project: &Path, revision: &str) -> Result<Vec<(String, String)>> {
+    let not_found = || CodeGraphError::Command(format!("找不到版本 {}", revision));
+    // 项目是仓库中的子目录时，ls-tree 列出的路径相对于子目录，cat-file 需要相对仓库根目录的路径
+    let prefix = run_git(project, &["rev-parse", "--show-prefix"]).ok_or_else(not_found)?;
+    let prefix = prefix.trim_end_matches('\n');
+    let listing = run_git(
+        project,
+        &["ls-tree", "-r", "-z", "--name-only", revision, "--"],
+    )
+    .ok_or_else(not_found)?;
+    let mut paths = listing
+        .split('\0')
+        .filter(|path| {
+            Path::new(path)
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(valid_file_extention)
+        })
+        .map(str::to_owned)
+        .collect::<Vec<String>>();
+    paths.sort();
+    if paths.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let mut child = Command::new("git")
+        .arg("-C")
+        .arg(project)
+        .args(["cat-file", "--batch"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()?;
+    let mut stdin = child.stdin.take().ok_or_else(not_found)?;
+    let requests = paths
+        .iter()
+        .map(|path| format!("{}:{}{}\n", revision, prefix, path))
+        .collect::<String>();
+    // 输出较多时 git 会等待读取，在另一个线程中写入请求
+    let writer = thread::spawn(move || stdin.write_all(requests.as_bytes()));
+    let output = child.wait_with_output()?;
+    let _ = writer.join();
+    if !output.status.success() {
+        return Err(not_found());
+    }
+
+    // 每个对象的输出为 <oid> <type> <size>\n<内容>\n，找不到时为 <名字> missing\n
+    let bytes = output.stdout.as_slice();
+    let mut sources = vec![];
+    let mut position = 0;
+    for path in paths {
+        let Some(header_end) = bytes[position..]
+            .iter()
+            .position(|byte| *byte == b'\n')
+            .map(|end| position + end)
+        else {
+            break;
+        };
+        let header = String::from_utf8_lossy(&bytes[position..header_end]);
+        position = header_end + 1;
+        let mut fields = header.split(' ');
+        let (Some(kind), Some(size)) = (fields.nth(1), fields.next()) else {
+            continue;
+        };
+        let Ok(size) = size.parse::<usize>() else {
+            continue;
+        };
+        let end = (position + size).min(bytes.len());
+        let content = &bytes[position..end];
+        position = end + 1;
+        if kind != "blob" {
+            continue;
+        }
+        if let Ok(code) = std::str::from_utf8(content) {
+            sources.push((path, code.to_owned()));
+        }
+    }
+    Ok(sources)
+}
+
+/**
+ * 项目目录在 revision 时的符号图，节点 ID 使用相对路径，不同版本中的同一个定义 ID 相同，
+ * 文件路径为 project 下的完整路径
+ */
+pub fn revision_graph(project: &Path, revision: &str) -> Result<Graph> {
+    let mut graph = sources_graph(&revision_sources(project, revision)?)?;
+    for (_, node) in graph.nodes_mut() {
+        node.file_path = project
+            .join(&node.file_path)
+            .to_str()
+            .unwrap_or("")
+            .to_owned();
+    }
+    Ok(graph)
+}
+
+/**
+ * 两个版本之间节点的变化：新版本中出现的节点 ID 和旧版本中消失的节点
+ */
+#[derive(Debug, Clone, Default)]
+pub struct RevisionChange {
+    pub appeared: HashSet<String>,
+    pub vanished: Vec<CodeNode>,
+}
+
+/**
+ * 对比前后两个版本的图中可见的节点，消失的节点保留在旧图中的位置和大小
+ */
+pub fn revision_change(old: &Graph, new: &Graph) -> RevisionChange {
+    let visible_nodes = |graph: &Graph| {
+        graph
+            .nodes()
+            .filter(|(_, node)| node.visiable())
+            .map(|(_, node)| (node.id().to_owned(), node.clone()))
+            .collect::<HashMap<String, CodeNode>>()
+    };
+    let (old_nodes, new_nodes) = (visible_nodes(old), visible_nodes(new));
+    RevisionChange {
+        appeared: new_nodes
+            .keys()
+            .filter(|id| !old_nodes.contains_key(*id))
+            .cloned()
+            .collect(),
+        vanished: old_nodes
+            .into_iter()
+            .filter(|(id, _)| !new_nodes.contains_key(id))
+            .map(|(_, node)| node)
+            .collect(),
+    }
+}
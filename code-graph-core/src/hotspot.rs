@@ -0,0 +1,75 @@
+use std::{
+    ffi::OsStr,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    fetch_definitions, get_symbol_query, git, valid_file_extention, CodeBlockType, CodeNode,
+};
+
+/**
+ * 热点：复杂度高且经常被修改的函数
+ */
+#[derive(Debug, Clone)]
+pub struct Hotspot {
+    pub node: CodeNode,
+    // 圈复杂度
+    pub complexity: usize,
+    // 函数所在行涉及的提交数
+    pub commits: usize,
+    // 所在文件的修改次数
+    pub changes: usize,
+    // 热点分数 = 复杂度 × 函数涉及的提交数
+    pub score: usize,
+}
+
+/**
+ * 对项目中的所有函数进行热点分析，按分数从高到低排序，
+ * 只分析有提交记录的文件，函数的提交数由 git blame 统计
+ */
+pub fn analyse_hotspots(root_path: &Path, pathes: &[PathBuf]) -> Vec<Hotspot> {
+    let change_counts = git::file_change_counts(root_path);
+    let mut hotspots = vec![];
+    for path in pathes {
+        let ext = path
+            .extension()
+            .unwrap_or(OsStr::new(""))
+            .to_str()
+            .unwrap_or("");
+        let changes = change_counts.get(path).copied().unwrap_or(0);
+        if !valid_file_extention(ext) || changes == 0 {
+            continue;
+        }
+        let Ok(code) = fs::read_to_string(path) else {
+            continue;
+        };
+        let Some(blame) = git::blame_file(path.to_str().unwrap_or("")) else {
+            continue;
+        };
+        let definitions = get_symbol_query(ext)
+            .and_then(|symbol_query| {
+                fetch_definitions(path.to_str().unwrap_or(""), &code, symbol_query)
+            })
+            .unwrap_or_default();
+        for node in definitions {
+            if matches!(
+                node.block_type,
+                CodeBlockType::FUNCTION | CodeBlockType::METHOD | CodeBlockType::COMPONENT
+            ) {
+                let (start, end) = node.line_range();
+                let commits = git::churn_of_lines(&blame, start, end).commits;
+                hotspots.push(Hotspot {
+                    complexity: node.complexity,
+                    commits,
+                    changes,
+                    score: node.complexity * commits,
+                    node,
+                });
+            }
+        }
+    }
+    // 分数相同时文件修改越多越靠前
+    hotspots.sort_by_key(|hotspot| std::cmp::Reverse((hotspot.score, hotspot.changes)));
+    hotspots
+}
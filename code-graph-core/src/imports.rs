@@ -0,0 +1,696 @@
+use std::{
+    collections::{HashMap, HashSet},
+    ffi::OsStr,
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+use crate::{
+    patch::{add_child, group_node},
+    valid_file_extention, CodeNodeIndex, EdgeKind, Graph,
+};
+
+// JavaScript 和 TypeScript 导入时可以省略的扩展名
+const SCRIPT_EXTENSIONS: [&str; 6] = ["ts", "tsx", "js", "jsx", "mjs", "cjs"];
+
+/**
+ * 源文件中的一条导入语句
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Import {
+    // 导入的模块，Rust 为展开后的完整路径，
+    // Python 和 Java 为点分隔的模块名，JavaScript 和 C 为引号中的路径
+    pub module: String,
+    // 导入语句所在的行号
+    pub line: usize,
+}
+
+/**
+ * 按扩展名解析源代码中的 use、import、require 和 #include 语句，
+ * Rust 的 mod 声明是模块的包含关系而不是依赖，不算作导入
+ */
+pub fn parse_imports(extension: &str, code: &str) -> Vec<Import> {
+    match extension {
+        "rs" => parse_rust_imports(code),
+        "py" => parse_python_imports(code),
+        "java" => parse_java_imports(code),
+        "c" | "h" => parse_c_includes(code),
+        "js" | "jsx" | "ts" | "tsx" => parse_script_imports(code),
+        _ => vec![],
+    }
+}
+
+fn parse_rust_imports(code: &str) -> Vec<Import> {
+    let mut imports = vec![];
+    let mut lines = code.lines().enumerate();
+    while let Some((index, line)) = lines.next() {
+        let Some(rest) = strip_visibility(line.trim()).strip_prefix("use ") else {
+            continue;
+        };
+        // use 语句可能跨越多行，读到分号为止
+        let mut tree = rest.to_owned();
+        while !tree.contains(';') {
+            let Some((_, next)) = lines.next() else {
+                break;
+            };
+            tree.push_str(next.trim());
+        }
+        let tree = tree.split(';').next().unwrap_or("");
+        let mut pathes = vec![];
+        expand_use_tree(tree, "", &mut pathes);
+        imports.extend(pathes.into_iter().map(|module| Import {
+            module,
+            line: index + 1,
+        }));
+    }
+    imports
+}
+
+/**
+ * 去掉 pub、pub(crate) 等可见性修饰
+ */
+fn strip_visibility(statement: &str) -> &str {
+    let Some(rest) = statement.strip_prefix("pub") else {
+        return statement;
+    };
+    let rest = match rest.strip_prefix('(') {
+        Some(scope) => scope.split_once(')').map_or("", |(_, rest)| rest),
+        None => rest,
+    };
+    if rest.starts_with(' ') {
+        rest.trim_start()
+    } else {
+        statement
+    }
+}
+
+/**
+ * 把 a::{b::{c, d}, e as f} 展开为 a::b::c、a::b::d、a::e
+ */
+fn expand_use_tree(tree: &str, prefix: &str, pathes: &mut Vec<String>) {
+    for item in split_top_level(tree) {
+        let item = item.split(" as ").next().unwrap_or("").trim();
+        if item.is_empty() {
+            continue;
+        }
+        let join = |path: &str| match (prefix.is_empty(), path) {
+            (true, _) => path.to_owned(),
+            (false, "self") => prefix.to_owned(),
+            (false, _) => format!("{}::{}", prefix, path),
+        };
+        match item.split_once('{') {
+            Some((head, inner)) => {
+                let inner = inner.strip_suffix('}').unwrap_or(inner);
+                expand_use_tree(inner, &join(head.trim().trim_end_matches("::")), pathes);
+            }
+            None => pathes.push(join(item)),
+        }
+    }
+}
+
+/**
+ * 按不在花括号中的逗号分割
+ */
+fn split_top_level(tree: &str) -> Vec<&str> {
+    let mut items = vec![];
+    let mut depth = 0;
+    let mut start = 0;
+    for (index, c) in tree.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            ',' if depth == 0 => {
+                items.push(&tree[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    items.push(&tree[start..]);
+    items
+}
+
+fn parse_python_imports(code: &str) -> Vec<Import> {
+    let mut imports = vec![];
+    for (index, line) in code.lines().enumerate() {
+        let line = line.trim();
+        if let Some(rest) = line.strip_prefix("import ") {
+            for module in rest.split(',') {
+                let module = module.split(" as ").next().unwrap_or("").trim();
+                if !module.is_empty() {
+                    imports.push(Import {
+                        module: module.to_owned(),
+                        line: index + 1,
+                    });
+                }
+            }
+        } else if let Some((module, names)) = line
+            .strip_prefix("from ")
+            .and_then(|rest| rest.split_once(" import "))
+        {
+            let module = module.trim();
+            // from . import a 中的 a 是同一个包中的模块
+            let names = names
+                .trim_matches(|c: char| c == '(' || c == ')' || c.is_whitespace())
+                .split(',')
+                .map(|name| name.split(" as ").next().unwrap_or("").trim())
+                .filter(|name| !name.is_empty() && *name != "*")
+                .collect::<Vec<&str>>();
+            imports.extend(names.iter().map(|name| Import {
+                module: if module.ends_with('.') {
+                    format!("{}{}", module, name)
+                } else {
+                    format!("{}.{}", module, name)
+                },
+                line: index + 1,
+            }));
+            imports.push(Import {
+                module: module.to_owned(),
+                line: index + 1,
+            });
+        }
+    }
+    imports
+}
+
+fn parse_java_imports(code: &str) -> Vec<Import> {
+    code.lines()
+        .enumerate()
+        .filter_map(|(index, line)| {
+            let rest = line.trim().strip_prefix("import ")?;
+            let rest = rest.strip_prefix("static ").unwrap_or(rest);
+            Some(Import {
+                module: rest.split(';').next()?.trim().to_owned(),
+                line: index + 1,
+            })
+        })
+        .collect()
+}
+
+fn parse_c_includes(code: &str) -> Vec<Import> {
+    code.lines()
+        .enumerate()
+        .filter_map(|(index, line)| {
+            let rest = line.trim().strip_prefix('#')?.trim_start();
+            let rest = rest.strip_prefix("include")?.trim();
+            let close = match rest.chars().next()? {
+                '"' => '"',
+                '<' => '>',
+                _ => return None,
+            };
+            let (module, _) = rest[1..].split_once(close)?;
+            Some(Import {
+                module: module.to_owned(),
+                line: index + 1,
+            })
+        })
+        .collect()
+}
+
+fn parse_script_imports(code: &str) -> Vec<Import> {
+    let mut imports = vec![];
+    for (index, line) in code.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("//") || trimmed.starts_with('*') {
+            continue;
+        }
+        // import x from './a'、export * from './a'、import './a'、require('./a')、import('./a')
+        for keyword in ["from", "import", "require(", "import("] {
+            let mut rest = line;
+            while let Some(position) = rest.find(keyword) {
+                let before = rest[..position].chars().next_back();
+                rest = &rest[position + keyword.len()..];
+                if before.is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '.') {
+                    continue;
+                }
+                let quoted = rest.trim_start();
+                let Some(quote) = quoted
+                    .chars()
+                    .next()
+                    .filter(|c| matches!(c, '\'' | '"' | '`'))
+                else {
+                    continue;
+                };
+                if let Some((module, _)) = quoted[1..].split_once(quote) {
+                    let import = Import {
+                        module: module.to_owned(),
+                        line: index + 1,
+                    };
+                    if !imports.contains(&import) {
+                        imports.push(import);
+                    }
+                }
+            }
+        }
+    }
+    imports
+}
+
+/**
+ * 去掉路径中的 . 和 ..，不访问文件系统
+ */
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/**
+ * 项目中的源文件，用于把导入对应到文件
+ */
+struct ProjectFiles<'a> {
+    root_path: &'a Path,
+    files: HashSet<PathBuf>,
+}
+
+impl ProjectFiles<'_> {
+    fn find(&self, path: &Path) -> Option<PathBuf> {
+        let path = normalize(path);
+        self.files.contains(&path).then_some(path)
+    }
+
+    /**
+     * 以 suffix 结尾的文件，C 的头文件和 Java 的类没有固定的搜索目录
+     */
+    fn find_suffix(&self, suffix: &Path) -> Vec<PathBuf> {
+        let mut found = self
+            .files
+            .iter()
+            .filter(|file| file.ends_with(suffix))
+            .cloned()
+            .collect::<Vec<PathBuf>>();
+        found.sort();
+        found
+    }
+
+    /**
+     * 文件导入的项目中的其他文件和每个文件的导入行号，行号已排序去重
+     */
+    fn imported_files(&self, path: &Path) -> Vec<(PathBuf, Vec<usize>)> {
+        let extension = path.extension().and_then(OsStr::to_str).unwrap_or("");
+        let Ok(code) = fs::read_to_string(path) else {
+            return vec![];
+        };
+        let mut imported: Vec<(PathBuf, Vec<usize>)> = vec![];
+        for import in parse_imports(extension, &code) {
+            for target in self.resolve(path, extension, &import.module) {
+                if target == path {
+                    continue;
+                }
+                match imported.iter_mut().find(|(file, _)| *file == target) {
+                    Some((_, lines)) => lines.push(import.line),
+                    None => imported.push((target, vec![import.line])),
+                }
+            }
+        }
+        for (_, lines) in &mut imported {
+            lines.sort_unstable();
+            lines.dedup();
+        }
+        imported
+    }
+
+    fn resolve(&self, file: &Path, extension: &str, module: &str) -> Vec<PathBuf> {
+        let dir = file.parent().unwrap_or(Path::new(""));
+        match extension {
+            "rs" => self.resolve_rust(file, module).into_iter().collect(),
+            "py" => self.resolve_python(dir, module).into_iter().collect(),
+            "java" => self.resolve_java(module),
+            "c" | "h" => match self.find(&dir.join(module)) {
+                Some(found) => vec![found],
+                None => self
+                    .find_suffix(Path::new(module))
+                    .into_iter()
+                    .take(1)
+                    .collect(),
+            },
+            "js" | "jsx" | "ts" | "tsx" => {
+                if !module.starts_with('.') {
+                    return vec![];
+                }
+                let base = dir.join(module);
+                let mut candidates = vec![base.clone()];
+                for extension in SCRIPT_EXTENSIONS {
+                    candidates.push(base.with_extension(extension));
+                    let mut with_extension = base.clone().into_os_string();
+                    with_extension.push(format!(".{}", extension));
+                    candidates.push(PathBuf::from(with_extension));
+                }
+                for extension in SCRIPT_EXTENSIONS {
+                    candidates.push(base.join(format!("index.{}", extension)));
+                }
+                candidates
+                    .iter()
+                    .find_map(|candidate| self.find(candidate))
+                    .into_iter()
+                    .collect()
+            }
+            _ => vec![],
+        }
+    }
+
+    /**
+     * 模块目录中名为 name 的子模块文件：name.rs 或 name/mod.rs
+     */
+    fn rust_child(&self, module_dir: &Path, name: &str) -> Option<PathBuf> {
+        self.find(&module_dir.join(format!("{}.rs", name)))
+            .or_else(|| self.find(&module_dir.join(name).join("mod.rs")))
+    }
+
+    /**
+     * 按 crate、self、super 或子模块名开头的路径逐段查找模块文件，取能找到的最深的一层，
+     * 其他 crate 中的路径不在项目中，忽略
+     */
+    fn resolve_rust(&self, file: &Path, module: &str) -> Option<PathBuf> {
+        let mut segments = module.split("::").map(str::trim).peekable();
+        let (mut module_file, mut module_dir) = match *segments.peek()? {
+            "crate" => {
+                segments.next();
+                let root_dir = file
+                    .ancestors()
+                    .skip(1)
+                    .take_while(|dir| dir.starts_with(self.root_path))
+                    .find(|dir| self.crate_root(dir).is_some())?;
+                (self.crate_root(root_dir)?, root_dir.to_path_buf())
+            }
+            "self" => {
+                segments.next();
+                (file.to_path_buf(), rust_module_dir(file))
+            }
+            "super" => {
+                segments.next();
+                let mut dir = rust_module_dir(file);
+                dir.pop();
+                let mut module_file = self.rust_module_file(&dir)?;
+                while segments.next_if_eq(&"super").is_some() {
+                    dir.pop();
+                    module_file = self.rust_module_file(&dir)?;
+                }
+                (module_file, dir)
+            }
+            // 2018 版本中可以直接使用子模块的名字
+            name if self.rust_child(&rust_module_dir(file), name).is_some() => {
+                (file.to_path_buf(), rust_module_dir(file))
+            }
+            _ => return None,
+        };
+        for segment in segments {
+            let Some(child) = self.rust_child(&module_dir, segment) else {
+                break;
+            };
+            module_dir = rust_module_dir(&child);
+            module_file = child;
+        }
+        Some(module_file).filter(|module_file| module_file != file)
+    }
+
+    fn crate_root(&self, dir: &Path) -> Option<PathBuf> {
+        self.find(&dir.join("lib.rs"))
+            .or_else(|| self.find(&dir.join("main.rs")))
+    }
+
+    /**
+     * 模块目录对应的模块文件
+     */
+    fn rust_module_file(&self, module_dir: &Path) -> Option<PathBuf> {
+        self.find(&module_dir.with_extension("rs"))
+            .or_else(|| self.find(&module_dir.join("mod.rs")))
+            .or_else(|| self.crate_root(module_dir))
+    }
+
+    /**
+     * 相对导入从所在的包开始查找，其他导入依次在所在目录和上层目录（直到项目根目录）以及 src 中查找，
+     * from a import b 中的 b 可能是子模块
+     */
+    fn resolve_python(&self, dir: &Path, module: &str) -> Option<PathBuf> {
+        let dots = module.chars().take_while(|c| *c == '.').count();
+        let name = module[dots..].replace('.', "/");
+        let bases = if dots > 0 {
+            let mut base = dir.to_path_buf();
+            for _ in 1..dots {
+                base.pop();
+            }
+            vec![base]
+        } else {
+            let mut bases = dir
+                .ancestors()
+                .take_while(|base| base.starts_with(self.root_path))
+                .map(Path::to_path_buf)
+                .collect::<Vec<PathBuf>>();
+            bases.push(self.root_path.join("src"));
+            bases
+        };
+        bases.iter().find_map(|base| {
+            let path = base.join(&name);
+            self.find(&path.with_extension("py"))
+                .filter(|_| !name.is_empty())
+                .or_else(|| self.find(&path.join("__init__.py")))
+        })
+    }
+
+    /**
+     * a.b.C 对应以 a/b/C.java 结尾的文件，a.b.* 对应 a/b 目录中的所有文件，
+     * 静态导入的成员名找不到时去掉最后一段再找
+     */
+    fn resolve_java(&self, module: &str) -> Vec<PathBuf> {
+        let mut segments = module.split('.').collect::<Vec<&str>>();
+        if segments.last() == Some(&"*") {
+            segments.pop();
+            let package = segments.join("/");
+            let mut found = self
+                .files
+                .iter()
+                .filter(|file| {
+                    file.parent().is_some_and(|dir| dir.ends_with(&package))
+                        && file.extension() == Some(OsStr::new("java"))
+                })
+                .cloned()
+                .collect::<Vec<PathBuf>>();
+            found.sort();
+            return found;
+        }
+        while segments.len() > 1 {
+            let found = self.find_suffix(Path::new(&format!("{}.java", segments.join("/"))));
+            if !found.is_empty() {
+                return found.into_iter().take(1).collect();
+            }
+            segments.pop();
+        }
+        vec![]
+    }
+}
+
+/**
+ * 文件中声明的子模块所在的目录：mod.rs、lib.rs、main.rs 为所在目录，其他文件为同名目录
+ */
+fn rust_module_dir(file: &Path) -> PathBuf {
+    let dir = file.parent().unwrap_or(Path::new("")).to_path_buf();
+    match file.file_name().and_then(|name| name.to_str()) {
+        Some("mod.rs" | "lib.rs" | "main.rs") => dir,
+        _ => file.with_extension(""),
+    }
+}
+
+/**
+ * 文件级的依赖图：根节点下每个源文件一个节点（标签为相对项目根目录的路径），
+ * 从导入方连出到被导入文件的依赖关系，同一对文件之间的多条导入合并为一条，可以在连线上看到导入的行号
+ */
+pub fn build_import_graph(project_name: &str, root_path: &Path, pathes: &[PathBuf]) -> Graph {
+    let root_path = normalize(root_path);
+    let mut sources = pathes
+        .iter()
+        .map(|path| normalize(path))
+        .filter(|path| valid_file_extention(path.extension().and_then(OsStr::to_str).unwrap_or("")))
+        .collect::<Vec<PathBuf>>();
+    sources.sort();
+    sources.dedup();
+    let project_files = ProjectFiles {
+        root_path: &root_path,
+        files: sources.iter().cloned().collect(),
+    };
+
+    let mut graph = Graph::new();
+    let root = graph.add_node(group_node(project_name, project_name));
+    let mut indexes: HashMap<&Path, CodeNodeIndex> = HashMap::new();
+    for path in &sources {
+        let file_path = path.to_str().unwrap_or("");
+        let label = path
+            .strip_prefix(&root_path)
+            .unwrap_or(path)
+            .display()
+            .to_string();
+        let mut node = group_node(file_path, &label);
+        node.file_path = file_path.to_owned();
+        node.file_location = 1;
+        indexes.insert(path, add_child(&mut graph, root, node, 1));
+    }
+    for path in &sources {
+        let from = indexes[path.as_path()];
+        for (target, lines) in project_files.imported_files(path) {
+            if let Some(to) = indexes.get(target.as_path()).copied() {
+                graph.add_call_edge(from, to, lines);
+            }
+        }
+    }
+    graph
+}
+
+/**
+ * 每个源文件导入的项目中的文件，键和值都使用 pathes 中的原始路径，
+ * 只包含能对应到项目中文件的导入，用于 DefinitionIndex::with_imports
+ */
+pub fn resolve_imports(root_path: &Path, pathes: &[PathBuf]) -> HashMap<String, HashSet<String>> {
+    let root_path = normalize(root_path);
+    let originals = pathes
+        .iter()
+        .filter(|path| valid_file_extention(path.extension().and_then(OsStr::to_str).unwrap_or("")))
+        .map(|path| (normalize(path), path.to_str().unwrap_or("").to_owned()))
+        .collect::<HashMap<PathBuf, String>>();
+    let project_files = ProjectFiles {
+        root_path: &root_path,
+        files: originals.keys().cloned().collect(),
+    };
+    originals
+        .iter()
+        .map(|(path, original)| {
+            let imported = project_files
+                .imported_files(path)
+                .into_iter()
+                .filter_map(|(target, _)| originals.get(&target).cloned())
+                .collect::<HashSet<String>>();
+            (original.clone(), imported)
+        })
+        .filter(|(_, imported)| !imported.is_empty())
+        .collect()
+}
+
+/**
+ * 依赖关系中的循环：互相可达的节点组成一组（强连通分量），只返回包含两个以上节点的组
+ */
+pub fn dependency_cycles(graph: &Graph) -> Vec<Vec<CodeNodeIndex>> {
+    let mut successors = vec![vec![]; graph.node_count()];
+    for edge in graph.edges().filter(|edge| edge.kind() == EdgeKind::Calls) {
+        successors[edge.from().0].push(edge.to().0);
+    }
+    let mut tarjan = Tarjan {
+        successors: &successors,
+        index: vec![None; successors.len()],
+        low_link: vec![0; successors.len()],
+        on_stack: vec![false; successors.len()],
+        stack: vec![],
+        next_index: 0,
+        components: vec![],
+    };
+    for node in 0..successors.len() {
+        if tarjan.index[node].is_none() {
+            tarjan.visit(node);
+        }
+    }
+    tarjan.components
+}
+
+struct Tarjan<'a> {
+    successors: &'a [Vec<usize>],
+    index: Vec<Option<usize>>,
+    low_link: Vec<usize>,
+    on_stack: Vec<bool>,
+    stack: Vec<usize>,
+    next_index: usize,
+    components: Vec<Vec<CodeNodeIndex>>,
+}
+
+impl Tarjan<'_> {
+    fn visit(&mut self, node: usize) {
+        self.index[node] = Some(self.next_index);
+        self.low_link[node] = self.next_index;
+        self.next_index += 1;
+        self.stack.push(node);
+        self.on_stack[node] = true;
+        let successors = self.successors;
+        for &next in &successors[node] {
+            match self.index[next] {
+                None => {
+                    self.visit(next);
+                    self.low_link[node] = self.low_link[node].min(self.low_link[next]);
+                }
+                Some(index) if self.on_stack[next] => {
+                    self.low_link[node] = self.low_link[node].min(index);
+                }
+                Some(_) => {}
+            }
+        }
+        if Some(self.low_link[node]) == self.index[node] {
+            let mut component = vec![];
+            while let Some(member) = self.stack.pop() {
+                self.on_stack[member] = false;
+                component.push(CodeNodeIndex(member));
+                if member == node {
+                    break;
+                }
+            }
+            if component.len() > 1 {
+                component.reverse();
+                self.components.push(component);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CodeBlockType, CodeNode};
+
+    fn file_graph(count: usize, calls: &[(usize, usize)]) -> Graph {
+        let mut graph = Graph::new();
+        for index in 0..count {
+            let name = format!("{}.rs", index);
+            graph.add_node(CodeNode::new(&name, &name, "", 0, CodeBlockType::NORMAL, 0));
+        }
+        for (from, to) in calls {
+            graph.add_call_edge(CodeNodeIndex(*from), CodeNodeIndex(*to), vec![]);
+        }
+        graph
+    }
+
+    fn cycles(graph: &Graph) -> Vec<Vec<usize>> {
+        let mut cycles = dependency_cycles(graph)
+            .into_iter()
+            .map(|cycle| {
+                let mut cycle = cycle.iter().map(|index| index.0).collect::<Vec<usize>>();
+                cycle.sort();
+                cycle
+            })
+            .collect::<Vec<Vec<usize>>>();
+        cycles.sort();
+        cycles
+    }
+
+    #[test]
+    fn no_cycles_in_acyclic_graph() {
+        let graph = file_graph(4, &[(0, 1), (1, 2), (0, 2), (2, 3)]);
+        assert!(cycles(&graph).is_empty());
+    }
+
+    #[test]
+    fn finds_each_strongly_connected_component() {
+        // 0 -> 1 -> 2 -> 0 和 3 <-> 4 为两个循环，2 -> 3 不属于循环，5 只依赖自己
+        let graph = file_graph(6, &[(0, 1), (1, 2), (2, 0), (2, 3), (3, 4), (4, 3), (5, 5)]);
+        assert_eq!(cycles(&graph), vec![vec![0, 1, 2], vec![3, 4]]);
+    }
+
+    #[test]
+    fn contains_edges_do_not_form_cycles() {
+        let mut graph = file_graph(2, &[(0, 1)]);
+        graph.add_edge(CodeNodeIndex(1), CodeNodeIndex(0));
+        assert!(cycles(&graph).is_empty());
+    }
+}
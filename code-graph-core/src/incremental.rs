@@ -0,0 +1,339 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+};
+
+use tree_sitter::{InputEdit, Point, Tree};
+
+use crate::{
+    get_symbol_query, lang::SymbolQuery, parse_code, parse_code_with, recursion_call,
+    visit::walk_tree, CodeNode, CodeNodeIndex, Graph, Result,
+};
+
+/**
+ * 打开的文件和它的语法树，文件修改后只重新解析修改的部分
+ */
+pub struct ParsedFile {
+    path: String,
+    code: String,
+    tree: Tree,
+    symbol_query: Box<dyn SymbolQuery>,
+}
+
+impl ParsedFile {
+    pub fn parse(path: &str, code: String) -> Result<Self> {
+        let extension = Path::new(path)
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .unwrap_or("");
+        let symbol_query = get_symbol_query(extension)?;
+        let tree = parse_code(path, &code, symbol_query.as_ref())?;
+        Ok(Self {
+            path: path.to_owned(),
+            code,
+            tree,
+            symbol_query,
+        })
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    /**
+     * 在图中生成文件的大纲，与 fetch_symbols 的结果相同，返回文件根节点
+     */
+    pub fn outline(&self, graph: &mut Graph) -> CodeNodeIndex {
+        walk_tree(
+            &self.tree,
+            &self.path,
+            &self.code,
+            self.symbol_query.as_ref(),
+            graph,
+            |_, _| {},
+        )
+    }
+
+    /**
+     * 文件中所有的调用，与 fetch_calls 的结果相同
+     */
+    pub fn calls(&self) -> Vec<CodeNode> {
+        recursion_call(
+            self.tree.root_node(),
+            &self.path,
+            &self.code,
+            self.symbol_query.as_ref(),
+        )
+    }
+
+    /**
+     * 文件内容变为 code 后，把旧语法树按修改的位置调整后增量解析，内容没有变化时返回 false
+     */
+    pub fn update(&mut self, code: String) -> Result<bool> {
+        let Some(edit) = input_edit(&self.code, &code) else {
+            return Ok(false);
+        };
+        // 解析失败时保持原来的语法树
+        let mut old_tree = self.tree.clone();
+        old_tree.edit(&edit);
+        self.tree = parse_code_with(
+            &self.path,
+            &code,
+            self.symbol_query.as_ref(),
+            Some(&old_tree),
+        )?;
+        self.code = code;
+        Ok(true)
+    }
+}
+
+/**
+ * 比较修改前后的代码，把相同的开头和结尾之间的部分作为一次修改，代码相同时返回 None
+ */
+pub fn input_edit(old: &str, new: &str) -> Option<InputEdit> {
+    if old == new {
+        return None;
+    }
+    let mut start = old
+        .bytes()
+        .zip(new.bytes())
+        .take_while(|(a, b)| a == b)
+        .count();
+    while !old.is_char_boundary(start) || !new.is_char_boundary(start) {
+        start -= 1;
+    }
+    // 相同的结尾不能和相同的开头重叠，例如在 aa 后面再输入一个 a
+    let mut suffix = old
+        .bytes()
+        .rev()
+        .zip(new.bytes().rev())
+        .take(old.len().min(new.len()) - start)
+        .take_while(|(a, b)| a == b)
+        .count();
+    while !old.is_char_boundary(old.len() - suffix) || !new.is_char_boundary(new.len() - suffix) {
+        suffix -= 1;
+    }
+    let (old_end, new_end) = (old.len() - suffix, new.len() - suffix);
+    Some(InputEdit {
+        start_byte: start,
+        old_end_byte: old_end,
+        new_end_byte: new_end,
+        start_position: point(old, start),
+        old_end_position: point(old, old_end),
+        new_end_position: point(new, new_end),
+    })
+}
+
+/**
+ * 字节位置所在的行和列，列也按字节计算
+ */
+fn point(code: &str, byte: usize) -> Point {
+    let before = &code[..byte];
+    let column = byte - before.rfind('\n').map_or(0, |newline| newline + 1);
+    Point::new(before.matches('\n').count(), column)
+}
+
+/**
+ * 用新的大纲更新图后节点的变化，记录节点 ID
+ */
+#[derive(Debug, Clone, Default)]
+pub struct OutlineChange {
+    // 代码、标签或行号等内容变化的节点
+    pub changed: Vec<String>,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+impl OutlineChange {
+    pub fn is_empty(&self) -> bool {
+        self.changed.is_empty() && self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+/**
+ * 用重新解析得到的大纲更新图中同一文件的节点，按 ID 对应：ID 相同的节点只更新代码等内容，
+ * 保留位置、大小、可见性和附加的数据；新的节点加在父节点下，父节点展开时可见；
+ * 图中有而大纲中没有的节点删除，其他文件的节点不变
+ */
+pub fn update_outline(graph: &mut Graph, outline: &Graph) -> OutlineChange {
+    let mut change = OutlineChange::default();
+    let Some(path) = outline
+        .node(CodeNodeIndex(0))
+        .map(|root| root.file_path.clone())
+    else {
+        return change;
+    };
+    let ids = outline
+        .nodes()
+        .map(|(_, node)| node.id.as_str())
+        .collect::<HashSet<&str>>();
+    graph.retain_nodes(|node| {
+        let keep = node.file_path != path || ids.contains(node.id.as_str());
+        if !keep {
+            change.removed.push(node.id.clone());
+        }
+        keep
+    });
+
+    let mut indexes: HashMap<String, CodeNodeIndex> = HashMap::new();
+    for (index, node) in graph.nodes() {
+        indexes.entry(node.id.clone()).or_insert(index);
+    }
+    // 大纲中父节点在子节点之前，加入新节点时父节点已经在图中
+    for (outline_index, node) in outline.nodes() {
+        if let Some(index) = indexes.get(&node.id).copied() {
+            if graph
+                .node_mut(index)
+                .is_some_and(|target| update_content(target, node))
+            {
+                change.changed.push(node.id.clone());
+            }
+            continue;
+        }
+        let parent = outline
+            .parents_of(outline_index)
+            .next()
+            .and_then(|parent| outline.node(parent))
+            .and_then(|parent| indexes.get(&parent.id))
+            .copied();
+        let mut new_node = node.clone();
+        if let Some(parent) = parent {
+            let expanded = graph.node(parent).is_some_and(|parent_node| {
+                let mut children = graph.children_of(parent).peekable();
+                parent_node.visiable
+                    && (children.peek().is_none()
+                        || children.any(|child| graph.node(child).is_some_and(|c| c.visiable)))
+            });
+            new_node.visiable = expanded;
+            new_node.position = graph.node(parent).map_or(new_node.position, |p| p.position);
+        }
+        let index = graph.add_node(new_node);
+        if let Some(parent) = parent {
+            graph.add_edge(parent, index);
+        }
+        indexes.insert(node.id.clone(), index);
+        change.added.push(node.id.clone());
+    }
+    change
+}
+
+/**
+ * 把解析得到的内容复制到图中的节点，返回内容是否变化
+ */
+fn update_content(target: &mut CodeNode, source: &CodeNode) -> bool {
+    let changed = target.label != source.label
+        || target.block != source.block
+        || target.file_location != source.file_location
+        || target.complexity != source.complexity
+        || target.doc != source.doc
+        || target.visibility != source.visibility
+        || target.is_async != source.is_async
+        || target.is_test != source.is_test
+        || target.is_unsafe != source.is_unsafe;
+    if changed {
+        target.label = source.label.clone();
+        target.block = source.block.clone();
+        target.file_location = source.file_location;
+        target.complexity = source.complexity;
+        target.line_count = source.line_count;
+        target.byte_size = source.byte_size;
+        target.doc = source.doc.clone();
+        target.visibility = source.visibility;
+        target.is_async = source.is_async;
+        target.is_test = source.is_test;
+        target.is_unsafe = source.is_unsafe;
+    }
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn input_edit_unchanged_code() {
+        assert!(input_edit("fn a() {}", "fn a() {}").is_none());
+    }
+
+    #[test]
+    fn input_edit_covers_only_the_changed_part() {
+        let edit = input_edit("fn a() {}\nfn b() {}\n", "fn a() {}\nfn bc() {}\n").unwrap();
+        assert_eq!(edit.start_byte, 14);
+        assert_eq!(edit.old_end_byte, 14);
+        assert_eq!(edit.new_end_byte, 15);
+        assert_eq!(edit.start_position, Point::new(1, 4));
+        assert_eq!(edit.old_end_position, Point::new(1, 4));
+        assert_eq!(edit.new_end_position, Point::new(1, 5));
+    }
+
+    #[test]
+    fn input_edit_suffix_does_not_overlap_prefix() {
+        let edit = input_edit("aa", "aaa").unwrap();
+        assert_eq!(
+            (edit.start_byte, edit.old_end_byte, edit.new_end_byte),
+            (2, 2, 3)
+        );
+        let edit = input_edit("aaa", "aa").unwrap();
+        assert_eq!(
+            (edit.start_byte, edit.old_end_byte, edit.new_end_byte),
+            (2, 3, 2)
+        );
+    }
+
+    #[test]
+    fn input_edit_stays_on_char_boundaries() {
+        // é 和 è 的第一个字节相同，修改需要从字符开头算起
+        let edit = input_edit("aé", "aè").unwrap();
+        assert_eq!(
+            (edit.start_byte, edit.old_end_byte, edit.new_end_byte),
+            (1, 3, 3)
+        );
+        let edit = input_edit("中文", "中国文").unwrap();
+        assert_eq!(
+            (edit.start_byte, edit.old_end_byte, edit.new_end_byte),
+            (3, 3, 6)
+        );
+    }
+
+    #[cfg(feature = "lang-rust")]
+    #[test]
+    fn update_outline_matches_fresh_parse() {
+        let old = "struct A;\nimpl A {\n    fn run(&self) {}\n}\nfn removed() {}\n";
+        let new =
+            "struct A;\nimpl A {\n    pub fn run(&self) { let x = 1; }\n    fn added() {}\n}\n";
+        let mut parsed = ParsedFile::parse("a.rs", old.to_owned()).unwrap();
+        let mut graph = Graph::new();
+        parsed.outline(&mut graph);
+
+        assert!(parsed.update(new.to_owned()).unwrap());
+        assert!(!parsed.update(new.to_owned()).unwrap());
+        let mut outline = Graph::new();
+        parsed.outline(&mut outline);
+        let change = update_outline(&mut graph, &outline);
+        // 包含修改位置的文件和 impl 的代码也变了
+        assert_eq!(
+            change.changed,
+            vec!["a.rs", "a.rs::A#CLASS", "a.rs::A::run#FUNCTION"]
+        );
+        assert_eq!(change.added, vec!["a.rs::A::added#FUNCTION"]);
+        assert_eq!(change.removed, vec!["a.rs::removed#FUNCTION"]);
+
+        let fresh = ParsedFile::parse("a.rs", new.to_owned()).unwrap();
+        let mut expected = Graph::new();
+        fresh.outline(&mut expected);
+        let nodes = |graph: &Graph| {
+            let mut nodes = graph
+                .nodes()
+                .map(|(_, node)| (node.id.clone(), node.block.clone()))
+                .collect::<Vec<(String, String)>>();
+            nodes.sort();
+            nodes
+        };
+        assert_eq!(nodes(&graph), nodes(&expected));
+        assert!(update_outline(&mut graph, &outline).is_empty());
+    }
+}
@@ -0,0 +1,87 @@
+use serde::{Deserialize, Serialize};
+
+/**
+ * 标签中泛型参数、生命周期和 where 子句的显示方式
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum GenericsDisplay {
+    // 完整显示
+    #[default]
+    Full,
+    // 缩写为 <…>
+    Abbreviate,
+    // 完全去掉
+    Strip,
+}
+
+/**
+ * 按显示方式处理标签中的泛型参数，并去掉 where 子句
+ */
+pub fn simplify_generics(label: &str, display: GenericsDisplay) -> String {
+    if display == GenericsDisplay::Full {
+        return label.to_owned();
+    }
+    // where 子句总是位于标签末尾
+    let label = match label.split_whitespace().position(|word| word == "where") {
+        Some(position) => label
+            .split_whitespace()
+            .take(position)
+            .collect::<Vec<&str>>()
+            .join(" "),
+        None => label.to_owned(),
+    };
+    let mut output = String::new();
+    let mut depth = 0;
+    let mut previous = ' ';
+    for ch in label.chars() {
+        match ch {
+            '<' => {
+                if depth == 0 && display == GenericsDisplay::Abbreviate {
+                    output.push_str("<…>");
+                }
+                depth += 1;
+            }
+            // -> 中的 > 不是泛型的结束
+            '>' if depth > 0 && previous != '-' => depth -= 1,
+            _ if depth == 0 => output.push(ch),
+            _ => {}
+        }
+        previous = ch;
+    }
+    output.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LABEL: &str = "impl<'a, T: Into<String>> From<&'a T> for Name where T: Clone";
+
+    #[test]
+    fn full_display_keeps_the_label() {
+        assert_eq!(simplify_generics(LABEL, GenericsDisplay::Full), LABEL);
+    }
+
+    #[test]
+    fn abbreviate_and_strip_nested_generics_and_where_clauses() {
+        assert_eq!(
+            simplify_generics(LABEL, GenericsDisplay::Abbreviate),
+            "impl<…> From<…> for Name"
+        );
+        assert_eq!(
+            simplify_generics(LABEL, GenericsDisplay::Strip),
+            "impl From for Name"
+        );
+    }
+
+    #[test]
+    fn arrows_are_not_closing_brackets() {
+        assert_eq!(
+            simplify_generics(
+                "fn map<F: Fn(u8) -> u8>(f: F) -> Vec<u8>",
+                GenericsDisplay::Strip
+            ),
+            "fn map(f: F) -> Vec"
+        );
+    }
+}
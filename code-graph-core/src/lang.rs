@@ -0,0 +1,1184 @@
+use tree_sitter::{Language, Node};
+
+#[cfg(any(
+    feature = "lang-rust",
+    feature = "lang-java",
+    feature = "lang-c",
+    feature = "lang-javascript",
+    feature = "lang-python",
+    feature = "lang-typescript"
+))]
+use crate::CodeBlockType;
+use crate::{CodeNode, Visibility};
+
+/**
+ * 定义的修饰信息，显示为节点上的角标
+ */
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Modifiers {
+    pub visibility: Option<Visibility>,
+    pub is_async: bool,
+    pub is_test: bool,
+    pub is_unsafe: bool,
+}
+
+pub trait SymbolQuery {
+    fn get_call(&self, code: &str, node: &Node) -> Option<CodeNode>;
+    fn get_lang(&self) -> Language;
+    fn get_definition(&self, code: &str, node: &Node) -> Option<CodeNode>;
+    /**
+     * 语言名称，多语言项目中显示在节点上
+     */
+    fn get_lang_name(&self) -> &'static str;
+    /**
+     * get_definition 识别出的定义节点的可见性、是否异步、是否为测试
+     */
+    fn get_modifiers(&self, _code: &str, _node: &Node) -> Modifiers {
+        Modifiers::default()
+    }
+}
+#[cfg(feature = "lang-rust")]
+pub struct RustQuery;
+#[cfg(feature = "lang-c")]
+pub struct CQuery;
+#[cfg(feature = "lang-java")]
+pub struct JavaQuery;
+#[cfg(feature = "lang-javascript")]
+pub struct JsQuery;
+#[cfg(feature = "lang-python")]
+pub struct PythonQuery;
+#[cfg(feature = "lang-typescript")]
+pub struct TsQuery;
+#[cfg(feature = "lang-javascript")]
+pub struct JsxQuery;
+#[cfg(feature = "lang-typescript")]
+pub struct TsxQuery;
+
+#[cfg(feature = "lang-javascript")]
+impl SymbolQuery for JsQuery {
+    fn get_call(&self, code: &str, node: &Node) -> Option<CodeNode> {
+        let node_type = node.kind();
+
+        if node_type == "call_expression" {
+            let block_text = &code[node.byte_range()];
+            let fe = node.child_by_field_name("function");
+            if let Some(fe) = fe {
+                let fi = fe.child_by_field_name("property");
+                if let Some(fi) = fi {
+                    let label = &code[fi.byte_range()];
+                    return Some(CodeNode::new(
+                        "",
+                        label,
+                        block_text,
+                        fi.start_position().row + 1,
+                        CodeBlockType::CALL,
+                        0,
+                    ));
+                } else {
+                    let label = &code[fe.byte_range()];
+                    return Some(CodeNode::new(
+                        "",
+                        label,
+                        block_text,
+                        fe.start_position().row + 1,
+                        CodeBlockType::CALL,
+                        0,
+                    ));
+                }
+            }
+        }
+        None
+    }
+
+    fn get_lang(&self) -> Language {
+        tree_sitter_javascript::language()
+    }
+
+    fn get_lang_name(&self) -> &'static str {
+        "JavaScript"
+    }
+
+    fn get_modifiers(&self, code: &str, node: &Node) -> Modifiers {
+        js_modifiers(code, node)
+    }
+
+    fn get_definition(&self, code: &str, node: &Node) -> Option<CodeNode> {
+        let node_type = node.kind();
+        let definition_list = [
+            ("function_declaration", "formal_parameters"),
+            ("class_declaration", "class_body"),
+            ("method_definition", "formal_parameters"),
+        ];
+        for (root_type, end_type) in definition_list {
+            if node_type == root_type {
+                let mut output = String::new();
+                for child in node.children(&mut node.walk()) {
+                    if child.kind() == end_type {
+                        break;
+                    } else {
+                        let node_text = &code[child.byte_range()];
+                        output.push_str(node_text);
+                        output.push(' ');
+                    }
+                }
+                let block_type = match root_type {
+                    "function_declaration" => CodeBlockType::FUNCTION,
+                    "method_definition" => CodeBlockType::FUNCTION,
+                    "class_declaration" => CodeBlockType::CLASS,
+                    _ => CodeBlockType::NORMAL,
+                };
+                let block_text = &code[node.byte_range()];
+                return Some(CodeNode::new(
+                    "",
+                    output.as_str(),
+                    block_text,
+                    node.start_position().row + 1,
+                    block_type,
+                    0,
+                ));
+            }
+        }
+        if node_type == "lexical_declaration"
+            && node
+                .parent()
+                .is_some_and(|parent| parent.grammar_name() == "program")
+        {
+            let mut output = String::new();
+            let kind_node = node.child_by_field_name("kind");
+            if let Some(kind_node) = kind_node {
+                output.push_str(&code[kind_node.byte_range()]);
+            }
+            for child in node.children(&mut node.walk()) {
+                if "variable_declarator" == child.kind() {
+                    let name = child.child_by_field_name("name");
+                    if let Some(name) = name {
+                        output.push(' ');
+                        output.push_str(&code[name.byte_range()]);
+                    }
+                }
+            }
+            let block_type = CodeBlockType::CONST;
+            let block_text = &code[node.byte_range()];
+            return Some(CodeNode::new(
+                "",
+                output.as_str(),
+                block_text,
+                node.start_position().row + 1,
+                block_type,
+                0,
+            ));
+        }
+        None
+    }
+}
+
+#[cfg(feature = "lang-c")]
+impl SymbolQuery for CQuery {
+    fn get_call(&self, code: &str, node: &Node) -> Option<CodeNode> {
+        let node_type = node.kind();
+
+        if node_type == "call_expression" {
+            let block_text = &code[node.byte_range()];
+            let fe = node.child_by_field_name("function");
+            if let Some(fe) = fe {
+                let fi = fe.child_by_field_name("field");
+                if let Some(fi) = fi {
+                    let label = &code[fi.byte_range()];
+                    return Some(CodeNode::new(
+                        "",
+                        label,
+                        block_text,
+                        fi.start_position().row + 1,
+                        CodeBlockType::CALL,
+                        0,
+                    ));
+                } else {
+                    let label = &code[fe.byte_range()];
+                    return Some(CodeNode::new(
+                        "",
+                        label,
+                        block_text,
+                        fe.start_position().row + 1,
+                        CodeBlockType::CALL,
+                        0,
+                    ));
+                }
+            }
+        }
+        None
+    }
+
+    fn get_lang(&self) -> Language {
+        tree_sitter_c::language()
+    }
+
+    fn get_lang_name(&self) -> &'static str {
+        "C"
+    }
+
+    // static 函数只在当前文件中可见
+    fn get_modifiers(&self, code: &str, node: &Node) -> Modifiers {
+        let is_static = node.children(&mut node.walk()).any(|child| {
+            child.kind() == "storage_class_specifier" && &code[child.byte_range()] == "static"
+        });
+        Modifiers {
+            visibility: Some(if is_static {
+                Visibility::Private
+            } else {
+                Visibility::Public
+            }),
+            ..Modifiers::default()
+        }
+    }
+
+    fn get_definition(&self, code: &str, node: &Node) -> Option<CodeNode> {
+        let node_type = node.kind();
+        let definition_list = [("function_definition", "compound_statement")];
+        for (root_type, end_type) in definition_list {
+            if node_type == root_type {
+                let mut output = String::new();
+                for child in node.children(&mut node.walk()) {
+                    if child.kind() == end_type {
+                        break;
+                    } else {
+                        let node_text = &code[child.byte_range()];
+                        output.push_str(node_text);
+                        output.push(' ');
+                    }
+                }
+                let block_type = match root_type {
+                    "function_definition" => CodeBlockType::FUNCTION,
+                    "struct_item" => CodeBlockType::STRUCT,
+                    _ => CodeBlockType::NORMAL,
+                };
+                let block_text = &code[node.byte_range()];
+                return Some(CodeNode::new(
+                    "",
+                    output.as_str().split("(").next().unwrap_or("bad symbol"),
+                    block_text,
+                    node.start_position().row + 1,
+                    block_type,
+                    0,
+                ));
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(feature = "lang-java")]
+impl SymbolQuery for JavaQuery {
+    fn get_call(&self, code: &str, node: &Node) -> Option<CodeNode> {
+        let node_type = node.kind();
+
+        if node_type == "method_invocation" {
+            let block_text = &code[node.byte_range()];
+            let fe = node.child_by_field_name("name");
+            if let Some(fe) = fe {
+                let label = &code[fe.byte_range()];
+                return Some(CodeNode::new(
+                    "",
+                    label,
+                    block_text,
+                    fe.start_position().row + 1,
+                    CodeBlockType::CALL,
+                    0,
+                ));
+            }
+        }
+        None
+    }
+
+    fn get_lang(&self) -> Language {
+        tree_sitter_java::language()
+    }
+
+    fn get_lang_name(&self) -> &'static str {
+        "Java"
+    }
+
+    // 测试方法带有 @Test 等注解，测试类以 Test 结尾
+    fn get_modifiers(&self, code: &str, node: &Node) -> Modifiers {
+        let mut modifiers = Modifiers::default();
+        for child in node.children(&mut node.walk()) {
+            if child.kind() != "modifiers" {
+                continue;
+            }
+            for modifier in child.children(&mut child.walk()) {
+                match modifier.kind() {
+                    "public" => modifiers.visibility = Some(Visibility::Public),
+                    "private" => modifiers.visibility = Some(Visibility::Private),
+                    "marker_annotation" | "annotation" => {
+                        modifiers.is_test |= modifier
+                            .child_by_field_name("name")
+                            .is_some_and(|name| code[name.byte_range()].ends_with("Test"));
+                    }
+                    _ => {}
+                }
+            }
+        }
+        if node.kind() == "class_declaration" {
+            modifiers.is_test |= node.child_by_field_name("name").is_some_and(|name| {
+                let name = &code[name.byte_range()];
+                name.ends_with("Test") || name.ends_with("Tests")
+            });
+        }
+        modifiers
+    }
+
+    fn get_definition(&self, code: &str, node: &Node) -> Option<CodeNode> {
+        let node_type = node.kind();
+        let definition_list = [
+            ("class_declaration", "class_body"),
+            ("method_declaration", "formal_parameters"),
+            ("interface_declaration", "interface_body"),
+        ];
+        for (root_type, end_type) in definition_list {
+            if node_type == root_type {
+                let mut output = String::new();
+                for child in node.children(&mut node.walk()) {
+                    if child.kind() == end_type {
+                        break;
+                    } else {
+                        let node_text = &code[child.byte_range()];
+
+                        output.push_str(node_text);
+
+                        output.push(' ');
+                    }
+                }
+                let block_type = match root_type {
+                    "method_declaration" => CodeBlockType::FUNCTION,
+                    "class_declaration" => CodeBlockType::CLASS,
+                    "interface_declaration" => CodeBlockType::CLASS,
+                    _ => CodeBlockType::NORMAL,
+                };
+                let block_text = &code[node.byte_range()];
+                return Some(CodeNode::new(
+                    "",
+                    output.as_str(),
+                    block_text,
+                    node.start_position().row + 1,
+                    block_type,
+                    0,
+                ));
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(feature = "lang-rust")]
+impl SymbolQuery for RustQuery {
+    fn get_lang(&self) -> Language {
+        tree_sitter_rust::language()
+    }
+
+    fn get_lang_name(&self) -> &'static str {
+        "Rust"
+    }
+
+    fn get_modifiers(&self, code: &str, node: &Node) -> Modifiers {
+        let children = node.children(&mut node.walk()).collect::<Vec<Node>>();
+        // trait 中的方法和 trait 实现中的方法跟随 trait 的可见性
+        let in_trait = node
+            .parent()
+            .and_then(|list| list.parent())
+            .is_some_and(|owner| {
+                owner.kind() == "trait_item"
+                    || (owner.kind() == "impl_item" && owner.child_by_field_name("trait").is_some())
+            });
+        let visibility = if matches!(
+            node.kind(),
+            "impl_item" | "attribute_item" | "macro_invocation"
+        ) || in_trait
+        {
+            None
+        } else if node
+            .parent()
+            .is_some_and(|parent| parent.kind() == "token_tree")
+        {
+            // 宏中的函数：fn 前面的记号中有 pub
+            let mut sibling = node.prev_sibling();
+            let mut public = false;
+            while let Some(current) = sibling.filter(|current| !current.is_named()) {
+                public |= current.kind() == "pub";
+                sibling = current.prev_sibling();
+            }
+            Some(if public {
+                Visibility::Public
+            } else {
+                Visibility::Private
+            })
+        } else if node.kind() == "macro_definition" {
+            // 带 #[macro_export] 的宏可以在其他 crate 中使用
+            let mut sibling = node.prev_sibling();
+            let mut exported = false;
+            while let Some(current) = sibling.filter(|current| current.kind() == "attribute_item") {
+                exported |= code[current.byte_range()].contains("macro_export");
+                sibling = current.prev_sibling();
+            }
+            Some(if exported {
+                Visibility::Public
+            } else {
+                Visibility::Private
+            })
+        } else if children.iter().any(|child| {
+            // pub(crate)、pub(super) 和 pub(in path) 只在 crate 内部可见，不算公开
+            child.kind() == "visibility_modifier" && code[child.byte_range()].trim() == "pub"
+        }) {
+            Some(Visibility::Public)
+        } else {
+            Some(Visibility::Private)
+        };
+        let has_modifier = |modifier: &str| {
+            children.iter().any(|child| {
+                child.kind() == "function_modifiers"
+                    && code[child.byte_range()]
+                        .split_whitespace()
+                        .any(|word| word == modifier)
+            })
+        };
+        let is_async = has_modifier("async");
+        // unsafe fn、unsafe impl、unsafe trait，以及函数体中有 unsafe 块的函数
+        let is_unsafe = has_modifier("unsafe")
+            || children.iter().any(|child| child.kind() == "unsafe")
+            || (node.kind() == "function_item" && contains_unsafe_block(node));
+        // 定义前的属性中有 #[test]、#[tokio::test] 等
+        let mut is_test = false;
+        let mut sibling = node.prev_sibling();
+        while let Some(current) = sibling {
+            match current.kind() {
+                "attribute_item" => {
+                    let attribute = code[current.byte_range()]
+                        .trim_start_matches("#[")
+                        .trim_end_matches(']');
+                    let path = attribute.split('(').next().unwrap_or("").trim();
+                    is_test |= path.rsplit("::").next() == Some("test");
+                }
+                "line_comment" | "block_comment" => {}
+                _ => break,
+            }
+            sibling = current.prev_sibling();
+        }
+        Modifiers {
+            visibility,
+            is_async,
+            is_test,
+            is_unsafe,
+        }
+    }
+
+    // call_expression 下 identifier 和 field_identifier
+    fn get_call(&self, code: &str, node: &Node) -> Option<CodeNode> {
+        let node_type = node.kind();
+
+        if node_type == "call_expression" {
+            let block_text = &code[node.byte_range()];
+            let fe = node.child_by_field_name("function");
+            if let Some(fe) = fe {
+                let fi = fe.child_by_field_name("field");
+                if let Some(fi) = fi {
+                    let label = &code[fi.byte_range()];
+                    return Some(CodeNode::new(
+                        "",
+                        label,
+                        block_text,
+                        fi.start_position().row + 1,
+                        CodeBlockType::CALL,
+                        0,
+                    ));
+                } else {
+                    let label = &code[fe.byte_range()];
+                    return Some(CodeNode::new(
+                        "",
+                        label,
+                        block_text,
+                        fe.start_position().row + 1,
+                        CodeBlockType::CALL,
+                        0,
+                    ));
+                }
+            }
+        }
+        None
+    }
+
+    fn get_definition(&self, code: &str, node: &Node) -> Option<CodeNode> {
+        let node_type = node.kind();
+        if let Some(definition) = rust_macro_definition(code, node) {
+            return Some(definition);
+        }
+        let definition_list = [
+            ("function_item", "parameters"),
+            ("impl_item", "declaration_list"),
+            ("struct_item", "field_declaration_list"),
+            ("trait_item", "declaration_list"),
+            ("function_signature_item", "parameters"),
+        ];
+        for (root_type, end_type) in definition_list {
+            if node_type == root_type {
+                let mut output = String::new();
+                for child in node.children(&mut node.walk()) {
+                    if child.kind() == end_type {
+                        break;
+                    } else {
+                        let node_text = &code[child.byte_range()];
+
+                        output.push_str(node_text);
+
+                        output.push(' ');
+                    }
+                }
+                let block_type = match root_type {
+                    "function_item" => CodeBlockType::FUNCTION,
+                    "struct_item" => CodeBlockType::STRUCT,
+                    "function_signature_item" => CodeBlockType::FUNCTION,
+                    "trait_item" => CodeBlockType::CLASS,
+                    "impl_item" => CodeBlockType::CLASS,
+                    _ => CodeBlockType::NORMAL,
+                };
+                let block_text = &code[node.byte_range()];
+                return Some(CodeNode::new(
+                    "",
+                    output.as_str(),
+                    block_text,
+                    node.start_position().row + 1,
+                    block_type,
+                    0,
+                ));
+            }
+        }
+
+        None
+    }
+}
+
+/**
+ * 代码中是否有 unsafe 块，嵌套定义的函数单独计算
+ */
+#[cfg(feature = "lang-rust")]
+fn contains_unsafe_block(node: &Node) -> bool {
+    node.children(&mut node.walk())
+        .any(|child| match child.kind() {
+            "unsafe_block" => true,
+            "function_item" => false,
+            _ => contains_unsafe_block(&child),
+        })
+}
+
+/**
+ * 与宏有关、语法树中看不到普通定义的 Rust 代码：
+ * macro_rules! 定义、模块和 impl 中直接展开的宏、宏参数中 fn 开头的函数，
+ * 以及 #[derive] 和 #[cfg_attr(..., derive(...))] 生成的实现
+ */
+#[cfg(feature = "lang-rust")]
+fn rust_macro_definition(code: &str, node: &Node) -> Option<CodeNode> {
+    let single_line = |text: &str| text.split_whitespace().collect::<Vec<&str>>().join(" ");
+    let (label, block_range, block_type) = match node.kind() {
+        "macro_definition" => {
+            let name = node.child_by_field_name("name")?;
+            (
+                format!("macro_rules! {}", &code[name.byte_range()]),
+                node.byte_range(),
+                CodeBlockType::FUNCTION,
+            )
+        }
+        // 只有模块或 impl 中直接展开的宏可能产生定义，函数体中的 println! 等不算
+        "macro_invocation"
+            if node
+                .parent()
+                .map(|parent| match parent.kind() {
+                    "expression_statement" => parent.parent(),
+                    _ => Some(parent),
+                })
+                .is_some_and(|owner| {
+                    owner.is_some_and(|owner| {
+                        matches!(owner.kind(), "source_file" | "declaration_list")
+                    })
+                }) =>
+        {
+            let text = single_line(&code[node.byte_range()]);
+            let label = if text.chars().count() <= 40 {
+                text
+            } else {
+                let name = node.child_by_field_name("macro")?;
+                format!("{}! {{ … }}", &code[name.byte_range()])
+            };
+            (label, node.byte_range(), CodeBlockType::NORMAL)
+        }
+        // 宏的记号中 fn 后面的名字（可能是 $name 这样的宏变量）
+        "identifier" | "metavariable"
+            if node
+                .parent()
+                .is_some_and(|parent| parent.kind() == "token_tree")
+                && node.prev_sibling().is_some_and(|prev| prev.kind() == "fn") =>
+        {
+            // 向前包含 pub、async、unsafe 等修饰，向后到函数体或分号为止
+            let mut start = node.prev_sibling()?;
+            while let Some(prev) = start
+                .prev_sibling()
+                .filter(|prev| matches!(prev.kind(), "pub" | "async" | "unsafe" | "const"))
+            {
+                start = prev;
+            }
+            let mut end = *node;
+            let mut sibling = node.next_sibling();
+            while let Some(current) = sibling {
+                end = current;
+                let text = &code[current.byte_range()];
+                if current.kind() == ";"
+                    || (current.kind() == "token_tree" && text.starts_with('{'))
+                {
+                    break;
+                }
+                sibling = current.next_sibling();
+            }
+            (
+                format!(
+                    "{} ",
+                    single_line(&code[start.start_byte()..node.end_byte()])
+                ),
+                start.start_byte()..end.end_byte(),
+                CodeBlockType::FUNCTION,
+            )
+        }
+        "attribute_item" => {
+            let text = single_line(&code[node.byte_range()]);
+            if !text.contains("derive(") {
+                return None;
+            }
+            (text, node.byte_range(), CodeBlockType::IMPL)
+        }
+        _ => return None,
+    };
+    let start_row = code[..block_range.start].matches('\n').count();
+    Some(CodeNode::new(
+        "",
+        &label,
+        &code[block_range],
+        start_row + 1,
+        block_type,
+        0,
+    ))
+}
+
+#[cfg(feature = "lang-python")]
+impl SymbolQuery for PythonQuery {
+    fn get_call(&self, code: &str, node: &Node) -> Option<CodeNode> {
+        let node_type = node.kind();
+
+        if node_type == "call" {
+            let block_text = &code[node.byte_range()];
+            let fe = node.child_by_field_name("function");
+            if let Some(fe) = fe {
+                // obj.method() 只取方法名
+                let fi = fe.child_by_field_name("attribute").unwrap_or(fe);
+                let label = &code[fi.byte_range()];
+                return Some(CodeNode::new(
+                    "",
+                    label,
+                    block_text,
+                    fi.start_position().row + 1,
+                    CodeBlockType::CALL,
+                    0,
+                ));
+            }
+        }
+        None
+    }
+
+    fn get_lang(&self) -> Language {
+        tree_sitter_python::language()
+    }
+
+    fn get_lang_name(&self) -> &'static str {
+        "Python"
+    }
+
+    // 下划线开头的名称为私有，pytest 的测试函数以 test 开头，测试类以 Test 开头
+    fn get_modifiers(&self, code: &str, node: &Node) -> Modifiers {
+        let name = node
+            .child_by_field_name("name")
+            .map_or("", |name| &code[name.byte_range()]);
+        let is_dunder = name.starts_with("__") && name.ends_with("__");
+        let visibility = if name.starts_with('_') && !is_dunder {
+            Visibility::Private
+        } else {
+            Visibility::Public
+        };
+        let is_test = match node.kind() {
+            "function_definition" => name.starts_with("test"),
+            "class_definition" => name.starts_with("Test"),
+            _ => false,
+        };
+        Modifiers {
+            visibility: Some(visibility),
+            is_async: has_child(node, "async"),
+            is_test,
+            ..Modifiers::default()
+        }
+    }
+
+    fn get_definition(&self, code: &str, node: &Node) -> Option<CodeNode> {
+        let node_type = node.kind();
+        let definition_list = [
+            ("function_definition", "parameters"),
+            ("class_definition", ":"),
+        ];
+        for (root_type, end_type) in definition_list {
+            if node_type == root_type {
+                let mut output = String::new();
+                for child in node.children(&mut node.walk()) {
+                    if child.kind() == end_type {
+                        break;
+                    } else {
+                        let node_text = &code[child.byte_range()];
+                        output.push_str(node_text);
+                        output.push(' ');
+                    }
+                }
+                let block_type = match root_type {
+                    "function_definition" => CodeBlockType::FUNCTION,
+                    "class_definition" => CodeBlockType::CLASS,
+                    _ => CodeBlockType::NORMAL,
+                };
+                let block_text = &code[node.byte_range()];
+                return Some(CodeNode::new(
+                    "",
+                    output.as_str(),
+                    block_text,
+                    node.start_position().row + 1,
+                    block_type,
+                    0,
+                ));
+            }
+        }
+        None
+    }
+}
+
+#[cfg(feature = "lang-typescript")]
+impl SymbolQuery for TsQuery {
+    fn get_call(&self, code: &str, node: &Node) -> Option<CodeNode> {
+        let node_type = node.kind();
+
+        if node_type == "call_expression" {
+            let block_text = &code[node.byte_range()];
+            let fe = node.child_by_field_name("function");
+            if let Some(fe) = fe {
+                // obj.method() 只取方法名
+                let fi = fe.child_by_field_name("property").unwrap_or(fe);
+                let label = &code[fi.byte_range()];
+                return Some(CodeNode::new(
+                    "",
+                    label,
+                    block_text,
+                    fi.start_position().row + 1,
+                    CodeBlockType::CALL,
+                    0,
+                ));
+            }
+        }
+        None
+    }
+
+    fn get_lang(&self) -> Language {
+        tree_sitter_typescript::language_typescript()
+    }
+
+    fn get_lang_name(&self) -> &'static str {
+        "TypeScript"
+    }
+
+    fn get_modifiers(&self, code: &str, node: &Node) -> Modifiers {
+        js_modifiers(code, node)
+    }
+
+    fn get_definition(&self, code: &str, node: &Node) -> Option<CodeNode> {
+        let node_type = node.kind();
+        let definition_list = [
+            ("function_declaration", "formal_parameters"),
+            ("class_declaration", "class_body"),
+            ("abstract_class_declaration", "class_body"),
+            ("method_definition", "formal_parameters"),
+            ("interface_declaration", "interface_body"),
+            ("type_alias_declaration", "="),
+            ("enum_declaration", "enum_body"),
+        ];
+        for (root_type, end_type) in definition_list {
+            if node_type == root_type {
+                let mut output = String::new();
+                for child in node.children(&mut node.walk()) {
+                    if child.kind() == end_type {
+                        break;
+                    } else if child.kind() == "decorator" {
+                        // 装饰器不放进标签中
+                        continue;
+                    } else {
+                        let node_text = &code[child.byte_range()];
+                        output.push_str(node_text);
+                        output.push(' ');
+                    }
+                }
+                let block_type = match root_type {
+                    "function_declaration" => CodeBlockType::FUNCTION,
+                    "method_definition" => CodeBlockType::FUNCTION,
+                    "class_declaration" => CodeBlockType::CLASS,
+                    "abstract_class_declaration" => CodeBlockType::CLASS,
+                    "interface_declaration" => CodeBlockType::CLASS,
+                    "type_alias_declaration" => CodeBlockType::STRUCT,
+                    "enum_declaration" => CodeBlockType::STRUCT,
+                    _ => CodeBlockType::NORMAL,
+                };
+                let block_text = &code[node.byte_range()];
+                return Some(CodeNode::new(
+                    "",
+                    output.as_str(),
+                    block_text,
+                    node.start_position().row + 1,
+                    block_type,
+                    0,
+                ));
+            }
+        }
+        // 值为箭头函数的类字段，例如 React 组件中的 handleClick = () => {}
+        if node_type == "public_field_definition"
+            && node
+                .child_by_field_name("value")
+                .is_some_and(|value| value.kind() == "arrow_function")
+        {
+            let mut output = String::new();
+            for child in node.children(&mut node.walk()) {
+                if matches!(child.kind(), "=" | "type_annotation") {
+                    break;
+                } else if child.kind() != "decorator" {
+                    output.push_str(&code[child.byte_range()]);
+                    output.push(' ');
+                }
+            }
+            return Some(CodeNode::new(
+                "",
+                output.as_str(),
+                &code[node.byte_range()],
+                node.start_position().row + 1,
+                CodeBlockType::FUNCTION,
+                0,
+            ));
+        }
+        // 顶层（包括 export 的）const/let，值为箭头函数时作为函数
+        let top_level = node.parent().is_some_and(|parent| {
+            parent.kind() == "program"
+                || (parent.kind() == "export_statement"
+                    && parent
+                        .parent()
+                        .is_some_and(|parent| parent.kind() == "program"))
+        });
+        if node_type == "lexical_declaration" && top_level {
+            let mut output = String::new();
+            let kind_node = node.child_by_field_name("kind");
+            if let Some(kind_node) = kind_node {
+                output.push_str(&code[kind_node.byte_range()]);
+            }
+            let mut block_type = CodeBlockType::CONST;
+            for child in node.children(&mut node.walk()) {
+                if "variable_declarator" == child.kind() {
+                    let name = child.child_by_field_name("name");
+                    if let Some(name) = name {
+                        output.push(' ');
+                        output.push_str(&code[name.byte_range()]);
+                    }
+                    let value = child.child_by_field_name("value");
+                    if value.is_some_and(|value| value.kind() == "arrow_function") {
+                        block_type = CodeBlockType::FUNCTION;
+                    }
+                }
+            }
+            let block_text = &code[node.byte_range()];
+            return Some(CodeNode::new(
+                "",
+                output.as_str(),
+                block_text,
+                node.start_position().row + 1,
+                block_type,
+                0,
+            ));
+        }
+        None
+    }
+}
+
+/**
+ * 是否有指定类型的直接子节点，包括 async 等关键字
+ */
+#[cfg(any(
+    feature = "lang-javascript",
+    feature = "lang-typescript",
+    feature = "lang-python"
+))]
+fn has_child(node: &Node, kind: &str) -> bool {
+    node.children(&mut node.walk())
+        .any(|child| child.kind() == kind)
+}
+
+/**
+ * js/ts 定义的修饰信息：export 的定义为公开，方法使用 ts 的 private/public 修饰符
+ * 或 # 开头的私有名称，箭头函数（包括类字段中的）的 async 在赋值的函数上
+ */
+#[cfg(any(feature = "lang-javascript", feature = "lang-typescript"))]
+fn js_modifiers(code: &str, node: &Node) -> Modifiers {
+    let exported = node
+        .parent()
+        .is_some_and(|parent| parent.kind() == "export_statement");
+    let accessibility = node
+        .children(&mut node.walk())
+        .find(|child| child.kind() == "accessibility_modifier")
+        .map(|modifier| &code[modifier.byte_range()]);
+    let private_name = node
+        .child_by_field_name("name")
+        .is_some_and(|name| name.kind() == "private_property_identifier");
+    let visibility = match accessibility {
+        Some("private") => Some(Visibility::Private),
+        Some("public") => Some(Visibility::Public),
+        _ if private_name => Some(Visibility::Private),
+        _ if exported => Some(Visibility::Public),
+        _ => None,
+    };
+    let is_async = has_child(node, "async")
+        || node
+            .children(&mut node.walk())
+            .filter(|child| child.kind() == "variable_declarator")
+            .filter_map(|child| child.child_by_field_name("value"))
+            .any(|value| has_child(&value, "async"))
+        || node
+            .child_by_field_name("value")
+            .is_some_and(|value| has_child(&value, "async"));
+    Modifiers {
+        visibility,
+        is_async,
+        ..Modifiers::default()
+    }
+}
+
+/**
+ * 是否为 React 组件或 hook 的名称：组件以大写字母开头，hook 以 use 加大写字母开头
+ */
+#[cfg(any(feature = "lang-javascript", feature = "lang-typescript"))]
+fn is_component_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_uppercase())
+}
+
+#[cfg(any(feature = "lang-javascript", feature = "lang-typescript"))]
+fn is_hook_name(name: &str) -> bool {
+    name.strip_prefix("use")
+        .is_some_and(|rest| rest.starts_with(|c: char| c.is_ascii_uppercase()))
+}
+
+#[cfg(any(feature = "lang-javascript", feature = "lang-typescript"))]
+fn contains_jsx(node: &Node) -> bool {
+    let mut stack = vec![*node];
+    while let Some(current) = stack.pop() {
+        if matches!(current.kind(), "jsx_element" | "jsx_self_closing_element") {
+            return true;
+        }
+        stack.extend(current.children(&mut current.walk()));
+    }
+    false
+}
+
+/**
+ * 在 js/ts 定义的基础上识别 React 组件：返回 JSX 的大写函数、
+ * 值中包含 JSX 的大写常量（例如 memo 包装的箭头函数）和继承 Component 的类，
+ * 另外把 useState 等 hook 调用作为单独的节点
+ */
+#[cfg(any(feature = "lang-javascript", feature = "lang-typescript"))]
+fn react_definition(code: &str, node: &Node, definition: Option<CodeNode>) -> Option<CodeNode> {
+    let Some(mut definition) = definition else {
+        if node.kind() != "call_expression" {
+            return None;
+        }
+        let fe = node.child_by_field_name("function")?;
+        // React.useState 只取 hook 名称
+        let fi = fe.child_by_field_name("property").unwrap_or(fe);
+        let name = &code[fi.byte_range()];
+        if !is_hook_name(name) {
+            return None;
+        }
+        return Some(CodeNode::new(
+            "",
+            name,
+            &code[node.byte_range()],
+            node.start_position().row + 1,
+            CodeBlockType::HOOK,
+            0,
+        ));
+    };
+    let name_of = |node: &Node| {
+        node.child_by_field_name("name")
+            .map(|name| code[name.byte_range()].to_owned())
+            .unwrap_or_default()
+    };
+    let is_component = match node.kind() {
+        "function_declaration" => is_component_name(&name_of(node)) && contains_jsx(node),
+        "lexical_declaration" => node.children(&mut node.walk()).any(|child| {
+            child.kind() == "variable_declarator"
+                && is_component_name(&name_of(&child))
+                && contains_jsx(&child)
+        }),
+        "class_declaration" => node.children(&mut node.walk()).any(|child| {
+            child.kind() == "class_heritage"
+                && code[child.byte_range()]
+                    .split(|c: char| !c.is_alphanumeric())
+                    .any(|word| word == "Component" || word == "PureComponent")
+        }),
+        _ => false,
+    };
+    if is_component {
+        definition.block_type = CodeBlockType::COMPONENT;
+    }
+    Some(definition)
+}
+
+/**
+ * JSX 中使用的组件，例如 <Child /> 和 <Layout.Header>，作为对组件的调用
+ */
+#[cfg(any(feature = "lang-javascript", feature = "lang-typescript"))]
+fn react_call(code: &str, node: &Node) -> Option<CodeNode> {
+    if !matches!(
+        node.kind(),
+        "jsx_opening_element" | "jsx_self_closing_element"
+    ) {
+        return None;
+    }
+    let fe = node.child_by_field_name("name")?;
+    let fi = fe.child_by_field_name("property").unwrap_or(fe);
+    let label = &code[fi.byte_range()];
+    if !is_component_name(label) {
+        return None;
+    }
+    Some(CodeNode::new(
+        "",
+        label,
+        &code[node.byte_range()],
+        fi.start_position().row + 1,
+        CodeBlockType::CALL,
+        0,
+    ))
+}
+
+#[cfg(feature = "lang-javascript")]
+impl SymbolQuery for JsxQuery {
+    fn get_call(&self, code: &str, node: &Node) -> Option<CodeNode> {
+        JsQuery
+            .get_call(code, node)
+            .or_else(|| react_call(code, node))
+    }
+
+    fn get_lang(&self) -> Language {
+        tree_sitter_javascript::language()
+    }
+
+    fn get_definition(&self, code: &str, node: &Node) -> Option<CodeNode> {
+        react_definition(code, node, JsQuery.get_definition(code, node))
+    }
+
+    fn get_lang_name(&self) -> &'static str {
+        JsQuery.get_lang_name()
+    }
+
+    fn get_modifiers(&self, code: &str, node: &Node) -> Modifiers {
+        JsQuery.get_modifiers(code, node)
+    }
+}
+
+#[cfg(feature = "lang-typescript")]
+impl SymbolQuery for TsxQuery {
+    fn get_call(&self, code: &str, node: &Node) -> Option<CodeNode> {
+        TsQuery
+            .get_call(code, node)
+            .or_else(|| react_call(code, node))
+    }
+
+    fn get_lang(&self) -> Language {
+        tree_sitter_typescript::language_tsx()
+    }
+
+    fn get_definition(&self, code: &str, node: &Node) -> Option<CodeNode> {
+        react_definition(code, node, TsQuery.get_definition(code, node))
+    }
+
+    fn get_lang_name(&self) -> &'static str {
+        TsQuery.get_lang_name()
+    }
+
+    fn get_modifiers(&self, code: &str, node: &Node) -> Modifiers {
+        TsQuery.get_modifiers(code, node)
+    }
+}
+
+#[cfg(all(test, any(feature = "lang-rust", feature = "lang-typescript")))]
+mod tests {
+    use crate::{fetch_definitions, get_symbol_query, CodeNode};
+
+    use super::*;
+
+    fn definitions(path: &str, code: &str) -> Vec<CodeNode> {
+        let extension = path.rsplit('.').next().unwrap_or("");
+        fetch_definitions(path, code, get_symbol_query(extension).unwrap()).unwrap()
+    }
+
+    #[cfg(feature = "lang-rust")]
+    #[test]
+    fn rust_restricted_visibility_is_not_public() {
+        let definitions = definitions(
+            "a.rs",
+            "pub fn open() {}\npub(crate) fn in_crate() {}\npub(super) fn in_super() {}\n\
+             pub(in crate::a) fn in_path() {}\nfn private() {}\n",
+        );
+        let visibility_of = |name: &str| {
+            definitions
+                .iter()
+                .find(|node| node.label.contains(name))
+                .unwrap_or_else(|| panic!("没有找到 {}", name))
+                .visibility
+        };
+        assert_eq!(visibility_of("open"), Some(Visibility::Public));
+        for name in ["in_crate", "in_super", "in_path", "private"] {
+            assert_eq!(visibility_of(name), Some(Visibility::Private), "{}", name);
+        }
+    }
+
+    #[cfg(feature = "lang-typescript")]
+    #[test]
+    fn typescript_class_field_arrows_are_functions() {
+        let definitions = definitions(
+            "a.ts",
+            "class Button {\n    count = 0;\n    @bound private handleClick = async (e: Event) => {\n        this.count += 1;\n    };\n    render(): void {}\n}\n",
+        );
+        let outline = definitions
+            .iter()
+            .map(|node| {
+                (
+                    node.label.trim(),
+                    node.block_type.clone(),
+                    node.file_location,
+                )
+            })
+            .collect::<Vec<_>>();
+        // 值不是箭头函数的字段不是定义
+        assert_eq!(
+            outline,
+            vec![
+                ("class Button", CodeBlockType::CLASS, 1),
+                ("private handleClick", CodeBlockType::FUNCTION, 3),
+                ("render", CodeBlockType::FUNCTION, 6),
+            ]
+        );
+        let handler = &definitions[1];
+        assert_eq!(handler.visibility, Some(Visibility::Private));
+        assert!(handler.is_async);
+        assert!(handler.block.contains("this.count += 1"));
+    }
+}
@@ -0,0 +1,900 @@
+use emath::{Pos2, Vec2};
+use serde::{Deserialize, Serialize};
+
+use crate::{CodeNodeIndex, EdgeKind, Graph};
+
+/**
+ * 包含关系的边的画法，由布局决定
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EdgeRouting {
+    // 从父节点左侧分出，连到缩进的子节点左侧
+    Branch,
+    // 从父节点底部连到下一层子节点的顶部
+    TopDown,
+    // 从父节点右侧连到右边一列子节点的左侧
+    LeftRight,
+    // 两个节点中心之间的直线
+    Straight,
+}
+
+/**
+ * 布局引擎，根据节点大小计算节点位置，节点大小需要提前设置
+ */
+pub trait LayoutEngine {
+    /**
+     * viewport 为画布的大小
+     */
+    fn layout(&self, graph: &mut Graph, viewport: Vec2);
+
+    fn edge_routing(&self) -> EdgeRouting {
+        EdgeRouting::Branch
+    }
+}
+
+/**
+ * 内置的布局，用于在界面中选择和保存
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum LayoutKind {
+    #[default]
+    Linear,
+    Layered,
+    Tree,
+    Force,
+}
+
+impl LayoutKind {
+    pub const ALL: [LayoutKind; 4] = [
+        LayoutKind::Linear,
+        LayoutKind::Layered,
+        LayoutKind::Tree,
+        LayoutKind::Force,
+    ];
+
+    pub fn engine(self) -> Box<dyn LayoutEngine> {
+        match self {
+            LayoutKind::Linear => Box::new(LinearLayout::default()),
+            LayoutKind::Layered => Box::new(LayeredLayout::default()),
+            LayoutKind::Tree => Box::new(TreeLayout::default()),
+            LayoutKind::Force => Box::new(ForceLayout::default()),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            LayoutKind::Linear => "直线布局",
+            LayoutKind::Layered => "分层布局",
+            LayoutKind::Tree => "树形布局",
+            LayoutKind::Force => "力导向布局",
+        }
+    }
+}
+
+/**
+ * 直线布局：节点按深度优先顺序自上而下排列，按层级缩进
+ */
+#[derive(Debug, Clone, Copy)]
+pub struct LinearLayout {
+    // 每一层的缩进
+    pub indent: f32,
+    // 节点之间的垂直间距
+    pub spacing: f32,
+}
+
+impl Default for LinearLayout {
+    fn default() -> Self {
+        Self {
+            indent: 20.0,
+            spacing: 8.0,
+        }
+    }
+}
+
+impl LayoutEngine for LinearLayout {
+    fn layout(&self, graph: &mut Graph, viewport: Vec2) {
+        let start_point = Vec2::new(viewport.x / 2.0, 32.0);
+        let mut y = start_point.y;
+        let mut stack = graph.roots().collect::<Vec<CodeNodeIndex>>();
+        stack.reverse();
+        let mut visited = vec![false; graph.node_count()];
+        while let Some(index) = stack.pop() {
+            if visited[index.0] {
+                continue;
+            }
+            visited[index.0] = true;
+            let mut children = graph.children_of(index).collect::<Vec<CodeNodeIndex>>();
+            children.reverse();
+            stack.extend(children);
+            let Some(node) = graph.node_mut(index) else {
+                continue;
+            };
+            if node.visiable() {
+                node.set_position(Pos2::new(
+                    start_point.x + node.level() as f32 * self.indent,
+                    y,
+                ));
+                y += node.size().y + self.spacing;
+            }
+        }
+    }
+}
+
+/**
+ * 分层布局（Sugiyama）：去掉环后按最长路径分层，自上而下排列，
+ * 用重心法多轮调整每层内的顺序以减少边的交叉，
+ * 最后让节点在不重叠的前提下尽量与相邻层中相连的节点对齐
+ */
+#[derive(Debug, Clone, Copy)]
+pub struct LayeredLayout {
+    // 相邻两层之间的垂直间距
+    pub layer_spacing: f32,
+    // 同一层中节点之间的水平间距
+    pub node_spacing: f32,
+    // 减少交叉和对齐坐标的轮数
+    pub iterations: usize,
+    // 是否将调用关系也用于分层，默认只使用包含关系
+    pub follow_calls: bool,
+}
+
+impl Default for LayeredLayout {
+    fn default() -> Self {
+        Self {
+            layer_spacing: 40.0,
+            node_spacing: 16.0,
+            iterations: 8,
+            follow_calls: false,
+        }
+    }
+}
+
+impl LayoutEngine for LayeredLayout {
+    fn layout(&self, graph: &mut Graph, viewport: Vec2) {
+        let node_count = graph.node_count();
+        let visible = graph
+            .nodes()
+            .map(|(_, node)| node.visiable())
+            .collect::<Vec<bool>>();
+        let mut edges = graph
+            .edges()
+            .filter(|edge| {
+                edge.kind() == EdgeKind::Contains
+                    || (self.follow_calls && edge.kind() == EdgeKind::Calls)
+            })
+            .map(|edge| (edge.from().0, edge.to().0))
+            .filter(|(from, to)| from != to && visible[*from] && visible[*to])
+            .collect::<Vec<(usize, usize)>>();
+        edges.sort();
+        edges.dedup();
+        let edges = remove_cycles(node_count, &visible, edges);
+        let ranks = assign_ranks(node_count, &edges);
+
+        // 跨越多层的边拆成经过虚拟节点的短边，虚拟节点编号从 node_count 开始
+        let mut slot_rank = ranks.clone();
+        let mut slot_width = graph
+            .nodes()
+            .map(|(_, node)| node.size().x)
+            .collect::<Vec<f32>>();
+        let mut short_edges = vec![];
+        for (from, to) in edges {
+            let mut previous = from;
+            for rank in ranks[from] + 1..ranks[to] {
+                let dummy = slot_rank.len();
+                slot_rank.push(rank);
+                slot_width.push(0.0);
+                short_edges.push((previous, dummy));
+                previous = dummy;
+            }
+            short_edges.push((previous, to));
+        }
+        let slot_count = slot_rank.len();
+        let mut ups = vec![vec![]; slot_count];
+        let mut downs = vec![vec![]; slot_count];
+        for (from, to) in &short_edges {
+            downs[*from].push(*to);
+            ups[*to].push(*from);
+        }
+
+        // 初始顺序：从根节点深度优先遍历，子节点保持原有顺序
+        let layer_count = slot_rank.iter().max().map_or(0, |max| max + 1);
+        let mut layers: Vec<Vec<usize>> = vec![vec![]; layer_count];
+        let mut placed = vec![false; slot_count];
+        let in_graph = |slot: usize| slot >= node_count || visible[slot];
+        let starts = (0..slot_count)
+            .filter(|slot| in_graph(*slot) && ups[*slot].is_empty())
+            .chain(0..slot_count)
+            .collect::<Vec<usize>>();
+        for start in starts {
+            let mut stack = vec![start];
+            while let Some(slot) = stack.pop() {
+                if placed[slot] || !in_graph(slot) {
+                    continue;
+                }
+                placed[slot] = true;
+                layers[slot_rank[slot]].push(slot);
+                stack.extend(downs[slot].iter().rev().copied());
+            }
+        }
+
+        // 重心法减少交叉，保留交叉最少的顺序
+        let mut best = layers.clone();
+        let mut best_crossings = count_crossings(&layers, &downs, slot_count);
+        for iteration in 0..self.iterations {
+            let mut position = positions_of(&layers, slot_count);
+            // 向下时按上一层排序，向上时按下一层排序，每排好一层就更新位置
+            let (sweep, neighbors): (Vec<&mut Vec<usize>>, _) = if iteration % 2 == 0 {
+                (layers.iter_mut().skip(1).collect(), &ups)
+            } else {
+                (layers.iter_mut().rev().skip(1).collect(), &downs)
+            };
+            for layer in sweep {
+                reorder_by_barycenter(layer, neighbors, &position);
+                for (order, slot) in layer.iter().enumerate() {
+                    position[*slot] = order as f32;
+                }
+            }
+            let crossings = count_crossings(&layers, &downs, slot_count);
+            if crossings < best_crossings {
+                best_crossings = crossings;
+                best = layers.clone();
+            }
+        }
+        let layers = best;
+
+        // 坐标：先依次排开，再交替向相邻层中相连节点的中心对齐，最后一轮让父节点居中于子节点之上
+        let mut center_x = vec![0.0; slot_count];
+        for layer in &layers {
+            let mut x = 0.0;
+            for slot in layer {
+                center_x[*slot] = x + slot_width[*slot] / 2.0;
+                x += slot_width[*slot] + self.node_spacing;
+            }
+        }
+        for round in 0..self.iterations * 2 {
+            let downward = round % 2 == 0;
+            let sweep = if downward {
+                (0..layer_count).collect::<Vec<usize>>()
+            } else {
+                (0..layer_count).rev().collect()
+            };
+            for rank in sweep {
+                let neighbors = if downward { &ups } else { &downs };
+                let layer = &layers[rank];
+                let desired = layer
+                    .iter()
+                    .map(|slot| {
+                        let linked = &neighbors[*slot];
+                        if linked.is_empty() {
+                            center_x[*slot]
+                        } else {
+                            linked.iter().map(|other| center_x[*other]).sum::<f32>()
+                                / linked.len() as f32
+                        }
+                    })
+                    .collect::<Vec<f32>>();
+                let widths = layer
+                    .iter()
+                    .map(|slot| slot_width[*slot])
+                    .collect::<Vec<f32>>();
+                let placed = place_in_order(&desired, &widths, self.node_spacing);
+                for (slot, x) in layer.iter().zip(placed) {
+                    center_x[*slot] = x;
+                }
+            }
+        }
+
+        // 每层的高度取层中最高的节点
+        let mut layer_top = vec![32.0; layer_count];
+        for rank in 1..layer_count {
+            let height = layers[rank - 1]
+                .iter()
+                .filter(|slot| **slot < node_count)
+                .filter_map(|slot| graph.node(CodeNodeIndex(*slot)))
+                .map(|node| node.size().y)
+                .fold(0.0, f32::max);
+            layer_top[rank] = layer_top[rank - 1] + height + self.layer_spacing;
+        }
+        let (min_x, max_x) = (0..node_count)
+            .filter(|index| visible[*index])
+            .map(|index| {
+                let half = slot_width[index] / 2.0;
+                (center_x[index] - half, center_x[index] + half)
+            })
+            .fold((f32::MAX, f32::MIN), |(min, max), (left, right)| {
+                (min.min(left), max.max(right))
+            });
+        let shift = if min_x <= max_x {
+            viewport.x / 2.0 - (min_x + max_x) / 2.0
+        } else {
+            0.0
+        };
+        for index in 0..node_count {
+            if !visible[index] {
+                continue;
+            }
+            if let Some(node) = graph.node_mut(CodeNodeIndex(index)) {
+                let x = center_x[index] - slot_width[index] / 2.0 + shift;
+                node.set_position(Pos2::new(x, layer_top[ranks[index]]));
+            }
+        }
+    }
+
+    fn edge_routing(&self) -> EdgeRouting {
+        EdgeRouting::TopDown
+    }
+}
+
+/**
+ * 深度优先遍历，把指向遍历栈中节点的边反向，得到无环图
+ */
+fn remove_cycles(
+    node_count: usize,
+    visible: &[bool],
+    edges: Vec<(usize, usize)>,
+) -> Vec<(usize, usize)> {
+    let mut outs = vec![vec![]; node_count];
+    for (from, to) in &edges {
+        outs[*from].push(*to);
+    }
+    // 0 未访问，1 在栈中，2 已完成
+    let mut state = vec![0u8; node_count];
+    let mut back_edges = vec![];
+    for start in 0..node_count {
+        if state[start] != 0 || !visible[start] {
+            continue;
+        }
+        let mut stack = vec![(start, 0)];
+        state[start] = 1;
+        while let Some((node, next)) = stack.last_mut() {
+            let node = *node;
+            if let Some(to) = outs[node].get(*next).copied() {
+                *next += 1;
+                match state[to] {
+                    0 => {
+                        state[to] = 1;
+                        stack.push((to, 0));
+                    }
+                    1 => back_edges.push((node, to)),
+                    _ => {}
+                }
+            } else {
+                state[node] = 2;
+                stack.pop();
+            }
+        }
+    }
+    let mut edges = edges
+        .into_iter()
+        .map(|edge| {
+            if back_edges.contains(&edge) {
+                (edge.1, edge.0)
+            } else {
+                edge
+            }
+        })
+        .filter(|(from, to)| from != to)
+        .collect::<Vec<(usize, usize)>>();
+    edges.sort();
+    edges.dedup();
+    edges
+}
+
+/**
+ * 最长路径分层：没有入边的节点在第 0 层，其余节点在所有前驱的下一层
+ */
+fn assign_ranks(node_count: usize, edges: &[(usize, usize)]) -> Vec<usize> {
+    let mut in_degree = vec![0; node_count];
+    let mut outs = vec![vec![]; node_count];
+    for (from, to) in edges {
+        in_degree[*to] += 1;
+        outs[*from].push(*to);
+    }
+    let mut ranks = vec![0; node_count];
+    let mut queue = (0..node_count)
+        .filter(|index| in_degree[*index] == 0)
+        .collect::<Vec<usize>>();
+    while let Some(node) = queue.pop() {
+        for to in &outs[node] {
+            ranks[*to] = ranks[*to].max(ranks[node] + 1);
+            in_degree[*to] -= 1;
+            if in_degree[*to] == 0 {
+                queue.push(*to);
+            }
+        }
+    }
+    ranks
+}
+
+fn positions_of(layers: &[Vec<usize>], slot_count: usize) -> Vec<f32> {
+    let mut position = vec![0.0; slot_count];
+    for layer in layers {
+        for (order, slot) in layer.iter().enumerate() {
+            position[*slot] = order as f32;
+        }
+    }
+    position
+}
+
+/**
+ * 按相邻层中相连节点位置的平均值排序，没有相连节点的保持原位置
+ */
+fn reorder_by_barycenter(layer: &mut [usize], neighbors: &[Vec<usize>], position: &[f32]) {
+    let barycenter = |slot: usize| {
+        let linked = &neighbors[slot];
+        if linked.is_empty() {
+            position[slot]
+        } else {
+            linked.iter().map(|other| position[*other]).sum::<f32>() / linked.len() as f32
+        }
+    };
+    let mut keyed = layer
+        .iter()
+        .map(|slot| (barycenter(*slot), *slot))
+        .collect::<Vec<(f32, usize)>>();
+    keyed.sort_by(|left, right| left.0.total_cmp(&right.0));
+    for (target, (_, slot)) in layer.iter_mut().zip(keyed) {
+        *target = slot;
+    }
+}
+
+/**
+ * 相邻层之间边的交叉数
+ */
+fn count_crossings(layers: &[Vec<usize>], downs: &[Vec<usize>], slot_count: usize) -> usize {
+    let position = positions_of(layers, slot_count);
+    let mut crossings = 0;
+    for layer in layers {
+        let edges = layer
+            .iter()
+            .flat_map(|from| {
+                downs[*from]
+                    .iter()
+                    .map(|to| (position[*from], position[*to]))
+            })
+            .collect::<Vec<(f32, f32)>>();
+        for (index, (from, to)) in edges.iter().enumerate() {
+            crossings += edges[index + 1..]
+                .iter()
+                .filter(|(other_from, other_to)| (from - other_from) * (to - other_to) < 0.0)
+                .count();
+        }
+    }
+    crossings
+}
+
+/**
+ * 在保持顺序且互不重叠的前提下，让节点中心尽量接近期望位置（最小二乘），
+ * 使用保序回归中的相邻违例合并算法
+ */
+fn place_in_order(desired: &[f32], widths: &[f32], spacing: f32) -> Vec<f32> {
+    // offset[i] 为第 i 个节点中心到第一个节点中心的最小距离
+    let mut offsets = vec![0.0; desired.len()];
+    for index in 1..desired.len() {
+        offsets[index] = offsets[index - 1] + (widths[index - 1] + widths[index]) / 2.0 + spacing;
+    }
+    // 每一块为（起始下标，元素个数，平均值）
+    let mut blocks: Vec<(usize, usize, f32)> = vec![];
+    for index in 0..desired.len() {
+        blocks.push((index, 1, desired[index] - offsets[index]));
+        while blocks.len() > 1 {
+            let (_, count, mean) = blocks[blocks.len() - 1];
+            let (start, previous_count, previous_mean) = blocks[blocks.len() - 2];
+            if previous_mean <= mean {
+                break;
+            }
+            let total = previous_count + count;
+            let merged =
+                (previous_mean * previous_count as f32 + mean * count as f32) / total as f32;
+            blocks.pop();
+            let last = blocks.len() - 1;
+            blocks[last] = (start, total, merged);
+        }
+    }
+    let mut placed = vec![0.0; desired.len()];
+    for (start, count, mean) in blocks {
+        for index in start..start + count {
+            placed[index] = mean + offsets[index];
+        }
+    }
+    placed
+}
+
+/**
+ * 树形布局：按包含关系从左向右展开，每一层占一列，
+ * 叶子节点依次向下排列，父节点垂直居中于子节点
+ */
+#[derive(Debug, Clone, Copy)]
+pub struct TreeLayout {
+    // 相邻两列之间的水平间距
+    pub column_spacing: f32,
+    // 节点之间的垂直间距
+    pub spacing: f32,
+}
+
+impl Default for TreeLayout {
+    fn default() -> Self {
+        Self {
+            column_spacing: 40.0,
+            spacing: 8.0,
+        }
+    }
+}
+
+impl LayoutEngine for TreeLayout {
+    fn layout(&self, graph: &mut Graph, viewport: Vec2) {
+        let node_count = graph.node_count();
+        let visible = graph
+            .nodes()
+            .map(|(_, node)| node.visiable())
+            .collect::<Vec<bool>>();
+        // 每个节点只挂在第一次遍历到它的父节点下
+        let mut children = vec![vec![]; node_count];
+        let mut depth = vec![0; node_count];
+        let mut visited = vec![false; node_count];
+        let mut order = vec![];
+        let mut stack = graph.roots().collect::<Vec<CodeNodeIndex>>();
+        stack.reverse();
+        while let Some(index) = stack.pop() {
+            if visited[index.0] || !visible[index.0] {
+                continue;
+            }
+            visited[index.0] = true;
+            order.push(index.0);
+            let mut next = graph
+                .children_of(index)
+                .filter(|child| !visited[child.0] && visible[child.0])
+                .collect::<Vec<CodeNodeIndex>>();
+            for child in &next {
+                if depth[child.0] == 0 {
+                    depth[child.0] = depth[index.0] + 1;
+                    children[index.0].push(child.0);
+                }
+            }
+            next.reverse();
+            stack.extend(next);
+        }
+        let size_of = |index: usize| {
+            graph
+                .node(CodeNodeIndex(index))
+                .map_or(Vec2::ZERO, |node| node.size())
+        };
+        // 每一列的宽度取列中最宽的节点
+        let max_depth = order.iter().map(|index| depth[*index]).max().unwrap_or(0);
+        let mut column_x = vec![0.0; max_depth + 1];
+        for column in 1..=max_depth {
+            let width = order
+                .iter()
+                .filter(|index| depth[**index] == column - 1)
+                .map(|index| size_of(*index).x)
+                .fold(0.0, f32::max);
+            column_x[column] = column_x[column - 1] + width + self.column_spacing;
+        }
+        // 后序遍历：叶子依次向下，父节点居中
+        let mut center_y = vec![0.0; node_count];
+        let mut next_y = 32.0;
+        let mut stack = order
+            .iter()
+            .copied()
+            .filter(|index| depth[*index] == 0)
+            .map(|index| (index, false))
+            .collect::<Vec<(usize, bool)>>();
+        stack.reverse();
+        while let Some((index, expanded)) = stack.pop() {
+            if children[index].is_empty() {
+                let height = size_of(index).y;
+                center_y[index] = next_y + height / 2.0;
+                next_y += height + self.spacing;
+            } else if expanded {
+                let first = children[index][0];
+                let last = children[index][children[index].len() - 1];
+                center_y[index] = (center_y[first] + center_y[last]) / 2.0;
+            } else {
+                stack.push((index, true));
+                stack.extend(children[index].iter().rev().map(|child| (*child, false)));
+            }
+        }
+        let start_x = viewport.x / 2.0;
+        for index in order {
+            if let Some(node) = graph.node_mut(CodeNodeIndex(index)) {
+                let y = center_y[index] - node.size().y / 2.0;
+                node.set_position(Pos2::new(start_x + column_x[depth[index]], y));
+            }
+        }
+    }
+
+    fn edge_routing(&self) -> EdgeRouting {
+        EdgeRouting::LeftRight
+    }
+}
+
+/**
+ * 力导向布局：连线的节点相互吸引，所有节点相互排斥，
+ * 包含关系和调用关系都参与计算，适合查看调用较多的文件
+ */
+#[derive(Debug, Clone, Copy)]
+pub struct ForceLayout {
+    // 理想的连线长度
+    pub edge_length: f32,
+    // 最多迭代的次数，节点很多时会自动减少
+    pub iterations: usize,
+}
+
+impl Default for ForceLayout {
+    fn default() -> Self {
+        Self {
+            edge_length: 120.0,
+            iterations: 300,
+        }
+    }
+}
+
+impl LayoutEngine for ForceLayout {
+    fn layout(&self, graph: &mut Graph, viewport: Vec2) {
+        let indices = graph
+            .nodes()
+            .filter(|(_, node)| node.visiable())
+            .map(|(index, _)| index.0)
+            .collect::<Vec<usize>>();
+        let count = indices.len();
+        if count == 0 {
+            return;
+        }
+        let mut slot_of = vec![usize::MAX; graph.node_count()];
+        for (slot, index) in indices.iter().enumerate() {
+            slot_of[*index] = slot;
+        }
+        let sizes = indices
+            .iter()
+            .map(|index| {
+                graph
+                    .node(CodeNodeIndex(*index))
+                    .map_or(Vec2::ZERO, |node| node.size())
+            })
+            .collect::<Vec<Vec2>>();
+        let edges = graph
+            .edges()
+            .map(|edge| (slot_of[edge.from().0], slot_of[edge.to().0]))
+            .filter(|(from, to)| *from != usize::MAX && *to != usize::MAX && from != to)
+            .collect::<Vec<(usize, usize)>>();
+        // 初始位置放在螺旋线上，保证每次布局结果一致
+        let mut centers = (0..count)
+            .map(|slot| {
+                let angle = slot as f32 * 2.4;
+                let radius = self.edge_length * (slot as f32).sqrt();
+                Vec2::new(angle.cos(), angle.sin()) * radius
+            })
+            .collect::<Vec<Vec2>>();
+        // 斥力计算是 O(n²)，节点很多时减少迭代次数
+        let budget = 5_000_000 / (count * count).max(1);
+        let iterations = self.iterations.min(budget).max(10);
+        let mut temperature = self.edge_length;
+        for _ in 0..iterations {
+            let mut moves = vec![Vec2::ZERO; count];
+            for a in 0..count {
+                for b in a + 1..count {
+                    let delta = centers[a] - centers[b];
+                    let distance = delta.length().max(1.0);
+                    // 节点越大，需要的间距越大
+                    let spread = self.edge_length + (sizes[a].x + sizes[b].x) / 4.0;
+                    let force = delta / distance * (spread * spread / distance);
+                    moves[a] += force;
+                    moves[b] -= force;
+                }
+            }
+            for (from, to) in &edges {
+                let delta = centers[*from] - centers[*to];
+                let distance = delta.length().max(1.0);
+                let force = delta / distance * (distance * distance / self.edge_length);
+                moves[*from] -= force;
+                moves[*to] += force;
+            }
+            for (center, movement) in centers.iter_mut().zip(moves) {
+                let length = movement.length();
+                if length > 0.0 {
+                    *center += movement / length * length.min(temperature);
+                }
+            }
+            temperature = (temperature * 0.97).max(1.0);
+        }
+        // 整体放在画布顶部中间
+        let min = centers
+            .iter()
+            .zip(&sizes)
+            .fold(Vec2::splat(f32::MAX), |min, (center, size)| {
+                min.min(*center - *size / 2.0)
+            });
+        let max = centers
+            .iter()
+            .zip(&sizes)
+            .fold(Vec2::splat(f32::MIN), |max, (center, size)| {
+                max.max(*center + *size / 2.0)
+            });
+        let offset = Vec2::new(viewport.x / 2.0 - (min.x + max.x) / 2.0, 32.0 - min.y);
+        for (slot, index) in indices.iter().enumerate() {
+            if let Some(node) = graph.node_mut(CodeNodeIndex(*index)) {
+                let position = centers[slot] - sizes[slot] / 2.0 + offset;
+                node.set_position(position.to_pos2());
+            }
+        }
+        // 斥力只按中心距离计算，宽度差别很大的节点仍可能重叠
+        separate_overlaps(graph, None, 12.0);
+    }
+
+    fn edge_routing(&self) -> EdgeRouting {
+        EdgeRouting::Straight
+    }
+}
+
+/**
+ * 推开互相重叠的节点，节点之间至少保留 spacing 的间距，
+ * pinned 为正在拖动的节点，保持不动，只移动与它重叠的节点
+ */
+pub fn separate_overlaps(graph: &mut Graph, pinned: Option<CodeNodeIndex>, spacing: f32) {
+    let indices = graph
+        .nodes()
+        .filter(|(_, node)| node.visiable())
+        .map(|(index, _)| index)
+        .collect::<Vec<CodeNodeIndex>>();
+    let sizes = indices
+        .iter()
+        .map(|index| graph.node(*index).map_or(Vec2::ZERO, |node| node.size()))
+        .collect::<Vec<Vec2>>();
+    let mut positions = indices
+        .iter()
+        .map(|index| {
+            graph
+                .node(*index)
+                .map_or(Pos2::ZERO, |node| node.position())
+        })
+        .collect::<Vec<Pos2>>();
+    let pinned_slot = pinned.and_then(|pinned| indices.iter().position(|index| *index == pinned));
+    let mut order = (0..indices.len()).collect::<Vec<usize>>();
+    let mut moved = false;
+    // 每轮按左边界排序后扫描，只比较水平方向上可能重叠的节点
+    for _ in 0..50 {
+        order.sort_by(|a, b| positions[*a].x.total_cmp(&positions[*b].x));
+        let mut separated = false;
+        for (i, a) in order.iter().enumerate() {
+            for b in &order[i + 1..] {
+                let (a, b) = (*a, *b);
+                if positions[b].x >= positions[a].x + sizes[a].x + spacing {
+                    break;
+                }
+                let overlap_x = (positions[a].x + sizes[a].x).min(positions[b].x + sizes[b].x)
+                    - positions[a].x.max(positions[b].x)
+                    + spacing;
+                let overlap_y = (positions[a].y + sizes[a].y).min(positions[b].y + sizes[b].y)
+                    - positions[a].y.max(positions[b].y)
+                    + spacing;
+                if overlap_x <= 0.0 || overlap_y <= 0.0 {
+                    continue;
+                }
+                // 沿重叠较少的方向推开，中心重合时按顺序决定方向
+                let delta = (positions[b] + sizes[b] / 2.0) - (positions[a] + sizes[a] / 2.0);
+                let push = if overlap_x < overlap_y {
+                    Vec2::new(if delta.x < 0.0 { -overlap_x } else { overlap_x }, 0.0)
+                } else {
+                    Vec2::new(0.0, if delta.y < 0.0 { -overlap_y } else { overlap_y })
+                };
+                if pinned_slot == Some(a) {
+                    positions[b] += push;
+                } else if pinned_slot == Some(b) {
+                    positions[a] -= push;
+                } else {
+                    positions[a] -= push / 2.0;
+                    positions[b] += push / 2.0;
+                }
+                separated = true;
+            }
+        }
+        if !separated {
+            break;
+        }
+        moved = true;
+    }
+    if !moved {
+        return;
+    }
+    for (index, position) in indices.into_iter().zip(positions) {
+        if let Some(node) = graph.node_mut(index) {
+            node.set_position(position);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use emath::Rect;
+
+    use super::*;
+    use crate::{CodeBlockType, CodeNode};
+
+    /**
+     * 文件下有两个函数，第一个函数中有一个嵌套函数，第二个函数调用第一个
+     */
+    fn sample_graph() -> Graph {
+        let mut graph = Graph::new();
+        let add = |graph: &mut Graph, id: &str, level: usize, width: f32| {
+            let mut node = CodeNode::new(id, id, "", 1, CodeBlockType::FUNCTION, level);
+            node.set_size(Vec2::new(width, 20.0));
+            graph.add_node(node)
+        };
+        let file = add(&mut graph, "a.rs", 0, 60.0);
+        let first = add(&mut graph, "first", 1, 80.0);
+        let second = add(&mut graph, "second", 1, 120.0);
+        let nested = add(&mut graph, "nested", 2, 40.0);
+        graph.add_edge(file, first);
+        graph.add_edge(file, second);
+        graph.add_edge(first, nested);
+        graph.add_call_edge(second, first, vec![1]);
+        graph
+    }
+
+    fn rect(graph: &Graph, index: usize) -> Rect {
+        let node = graph.node(CodeNodeIndex(index)).unwrap();
+        Rect::from_min_size(node.position(), node.size())
+    }
+
+    fn assert_no_overlaps(graph: &Graph) {
+        for a in 0..graph.node_count() {
+            for b in a + 1..graph.node_count() {
+                assert!(
+                    !rect(graph, a).intersects(rect(graph, b)),
+                    "{} 与 {} 重叠",
+                    a,
+                    b
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn layered_layout_puts_children_below_parents() {
+        let mut graph = sample_graph();
+        LayeredLayout::default().layout(&mut graph, Vec2::new(800.0, 600.0));
+        for (parent, child) in [(0, 1), (0, 2), (1, 3)] {
+            assert!(rect(&graph, parent).max.y < rect(&graph, child).min.y);
+        }
+        // 同一层的节点在同一高度
+        assert_eq!(rect(&graph, 1).min.y, rect(&graph, 2).min.y);
+        assert_no_overlaps(&graph);
+    }
+
+    #[test]
+    fn layered_layout_can_rank_by_calls() {
+        let mut graph = sample_graph();
+        let layout = LayeredLayout {
+            follow_calls: true,
+            ..Default::default()
+        };
+        layout.layout(&mut graph, Vec2::new(800.0, 600.0));
+        // second 调用 first，first 排在 second 的下一层
+        assert!(rect(&graph, 2).max.y < rect(&graph, 1).min.y);
+        assert_no_overlaps(&graph);
+    }
+
+    #[test]
+    fn force_layout_is_deterministic_without_overlaps() {
+        let mut graph = sample_graph();
+        ForceLayout::default().layout(&mut graph, Vec2::new(800.0, 600.0));
+        let mut again = sample_graph();
+        ForceLayout::default().layout(&mut again, Vec2::new(800.0, 600.0));
+        for index in 0..graph.node_count() {
+            let position = rect(&graph, index).min;
+            assert!(position.x.is_finite() && position.y.is_finite());
+            assert_eq!(position, rect(&again, index).min);
+        }
+        assert_no_overlaps(&graph);
+    }
+
+    #[test]
+    fn force_layout_skips_hidden_nodes() {
+        let mut graph = sample_graph();
+        graph
+            .node_mut(CodeNodeIndex(3))
+            .unwrap()
+            .set_visiable(false);
+        ForceLayout::default().layout(&mut graph, Vec2::new(800.0, 600.0));
+        assert_eq!(rect(&graph, 3).min, Pos2::ZERO);
+    }
+}
@@ -0,0 +1,946 @@
+#[cfg(feature = "lang-c")]
+use lang::CQuery;
+#[cfg(feature = "lang-java")]
+use lang::JavaQuery;
+#[cfg(feature = "lang-javascript")]
+use lang::JsQuery;
+#[cfg(feature = "lang-javascript")]
+use lang::JsxQuery;
+#[cfg(feature = "lang-python")]
+use lang::PythonQuery;
+#[cfg(feature = "lang-rust")]
+use lang::RustQuery;
+use lang::SymbolQuery;
+#[cfg(feature = "lang-typescript")]
+use lang::TsQuery;
+#[cfg(feature = "lang-typescript")]
+use lang::TsxQuery;
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet, VecDeque},
+    fs, io,
+    path::Path,
+};
+use symbols::{call_target, declared_type, definition_names, owner_type, Qualifier};
+use visit::walk_node;
+
+use tree_sitter::Node;
+use tree_sitter::Parser;
+
+pub mod builder;
+pub mod call_graph;
+pub mod cluster;
+pub mod compare;
+pub mod coverage;
+pub mod ctags;
+pub mod deps;
+pub mod duplicate;
+pub mod error;
+pub mod export;
+pub mod fields;
+pub mod git;
+pub mod graph;
+pub mod hierarchy;
+pub mod history;
+pub mod hotspot;
+pub mod imports;
+pub mod incremental;
+pub mod label;
+pub mod lang;
+pub mod layout;
+pub mod matrix;
+pub mod patch;
+pub mod profile;
+pub mod query;
+pub mod route;
+pub mod search;
+pub mod snapshot;
+pub mod source;
+pub mod stats;
+pub mod symbol_cache;
+pub mod symbols;
+pub mod todo;
+pub mod tree;
+pub mod visit;
+
+pub use builder::GraphBuilder;
+pub use cluster::{find_clusters, Cluster, ClusterBy};
+pub use error::{CodeGraphError, Result};
+pub use export::{export_each_file, exporter_for, exporters, Exporter, LinkStyle};
+pub use graph::{
+    AnnotationNode, Camera, CodeBlockType, CodeNode, CodeNodeIndex, DiffStatus, Edge, EdgeKind,
+    Graph, GraphDiff, Visibility,
+};
+pub use label::{simplify_generics, GenericsDisplay};
+pub use layout::{
+    separate_overlaps, EdgeRouting, LayeredLayout, LayoutEngine, LayoutKind, LinearLayout,
+};
+pub use query::SubGraph;
+pub use route::{route_edges, EdgeRoute};
+pub use tree::{recursion_dir, recursion_dir_with_depth, Tree, TreeType};
+pub use tree_sitter;
+pub use visit::{walk_definitions, VisitContext};
+
+/**
+ * 根据扩展名查找语言，未启用对应 feature 的语言返回 None
+ */
+fn find_symbol_query(extention: &str) -> Option<Box<dyn SymbolQuery>> {
+    match extention {
+        #[cfg(feature = "lang-rust")]
+        "rs" => Some(Box::new(RustQuery)),
+        #[cfg(feature = "lang-java")]
+        "java" => Some(Box::new(JavaQuery)),
+        #[cfg(feature = "lang-c")]
+        "c" | "h" => Some(Box::new(CQuery)),
+        #[cfg(feature = "lang-javascript")]
+        "js" => Some(Box::new(JsQuery)),
+        #[cfg(feature = "lang-javascript")]
+        "jsx" => Some(Box::new(JsxQuery)),
+        #[cfg(feature = "lang-python")]
+        "py" => Some(Box::new(PythonQuery)),
+        #[cfg(feature = "lang-typescript")]
+        "ts" => Some(Box::new(TsQuery)),
+        #[cfg(feature = "lang-typescript")]
+        "tsx" => Some(Box::new(TsxQuery)),
+        _ => None,
+    }
+}
+
+pub fn valid_file_extention(extension: &str) -> bool {
+    find_symbol_query(extension).is_some()
+}
+
+pub fn get_symbol_query(extention: &str) -> Result<Box<dyn SymbolQuery>> {
+    find_symbol_query(extention)
+        .ok_or_else(|| CodeGraphError::UnsupportedLanguage(extention.to_owned()))
+}
+
+/**
+ * 读取源代码文件，非 UTF-8 编码时返回 Encoding 错误
+ */
+pub fn read_source(path: &Path) -> Result<String> {
+    fs::read_to_string(path).map_err(|err| match err.kind() {
+        io::ErrorKind::InvalidData => CodeGraphError::Encoding(path.display().to_string()),
+        _ => CodeGraphError::Io(err),
+    })
+}
+
+thread_local! {
+    // 每个线程复用一个解析器，并行扫描项目时每个工作线程各有一个
+    static PARSER: RefCell<Parser> = RefCell::new(Parser::new());
+}
+
+pub(crate) fn parse_code(
+    path: &str,
+    code: &str,
+    symbol_query: &dyn SymbolQuery,
+) -> Result<tree_sitter::Tree> {
+    parse_code_with(path, code, symbol_query, None)
+}
+
+/**
+ * 解析代码，old_tree 为已经按修改位置调用过 Tree::edit 的旧语法树时只重新解析修改的部分
+ */
+pub(crate) fn parse_code_with(
+    path: &str,
+    code: &str,
+    symbol_query: &dyn SymbolQuery,
+    old_tree: Option<&tree_sitter::Tree>,
+) -> Result<tree_sitter::Tree> {
+    PARSER.with_borrow_mut(|parser| {
+        parser
+            .set_language(&symbol_query.get_lang())
+            .map_err(|err| CodeGraphError::Parse(format!("{}: {}", path, err)))?;
+        parser
+            .parse(code, old_tree)
+            .ok_or_else(|| CodeGraphError::Parse(path.to_owned()))
+    })
+}
+
+pub fn fetch_calls(
+    path: &str,
+    code: &str,
+    symbol_query: Box<dyn SymbolQuery>,
+) -> Result<Vec<CodeNode>> {
+    fetch_calls_with(path, code, symbol_query.as_ref())
+}
+
+pub fn fetch_calls_with(
+    path: &str,
+    code: &str,
+    symbol_query: &dyn SymbolQuery,
+) -> Result<Vec<CodeNode>> {
+    let tree = parse_code(path, code, symbol_query)?;
+    let root_node = tree.root_node();
+    Ok(recursion_call(root_node, path, code, symbol_query))
+}
+
+pub fn recursion_call(
+    node: Node,
+    path: &str,
+    code: &str,
+    symbol_query: &dyn SymbolQuery,
+) -> Vec<CodeNode> {
+    let mut nodes = vec![];
+    let code_node = symbol_query.get_call(code, &node);
+    if let Some(mut call) = code_node {
+        // 同一行可能有多个调用，ID 中加上列号
+        call.id = format!(
+            "{}:{}:{}#CALL",
+            path,
+            call.file_location,
+            node.start_position().column + 1
+        );
+        call.file_path = path.to_string();
+        nodes.push(call);
+    }
+
+    for child in node.children(&mut node.walk()) {
+        let sub_nodes = recursion_call(child, path, code, symbol_query);
+        nodes.extend(sub_nodes);
+    }
+    nodes
+}
+/**
+* 打印大纲
+*/
+pub fn fetch_symbols(
+    path: &str,
+    code: &str,
+    symbol_query: Box<dyn SymbolQuery>,
+    graph: &mut Graph,
+) -> Result<()> {
+    fetch_symbols_with(path, code, symbol_query.as_ref(), graph)
+}
+
+pub fn fetch_symbols_with(
+    path: &str,
+    code: &str,
+    symbol_query: &dyn SymbolQuery,
+    graph: &mut Graph,
+) -> Result<()> {
+    walk_definitions(path, code, symbol_query, graph, |_, _| {})?;
+    Ok(())
+}
+
+/**
+ * 获取文件中的所有定义（不包含文件根节点）
+ */
+pub fn fetch_definitions(
+    path: &str,
+    code: &str,
+    symbol_query: Box<dyn SymbolQuery>,
+) -> Result<Vec<CodeNode>> {
+    let mut graph = Graph::new();
+    fetch_symbols(path, code, symbol_query, &mut graph)?;
+    Ok(graph.nodes.into_iter().skip(1).collect())
+}
+
+/**
+ * 按名字查找调用对应的定义（规则见 CodeNode::matches_call），同名定义有多个时优先选择同一文件中的，
+ * 扫描项目后应使用 DefinitionIndex::resolve
+ */
+pub fn resolve_definition<'a>(
+    definitions: &'a [CodeNode],
+    call: &CodeNode,
+) -> Option<&'a CodeNode> {
+    let mut candidates = definitions.iter().filter(|node| {
+        node.level > 0 && node.block_type != CodeBlockType::CALL && node.matches_call(call)
+    });
+    let first = candidates.next()?;
+    if first.file_path == call.file_path {
+        return Some(first);
+    }
+    candidates
+        .find(|node| node.file_path == call.file_path)
+        .or(Some(first))
+}
+
+/**
+ * 项目中所有定义的索引：按（语言，限定名）登记的符号表和按文件的索引，
+ * 方法同时以方法名和 类型::方法 登记，调用通过接收者的类型和导入关系对应到定义
+ */
+#[derive(Debug, Clone, Default)]
+pub struct DefinitionIndex {
+    definitions: Vec<CodeNode>,
+    // （语言，名字或 类型::名字）-> 定义的序号
+    symbols: HashMap<(String, String), Vec<usize>>,
+    // 定义所属的类型，与 definitions 一一对应
+    owners: Vec<Option<String>>,
+    // 文件路径 -> 定义的序号
+    by_file: HashMap<String, Vec<usize>>,
+    // 文件路径 -> 它导入的项目中的文件
+    imports: HashMap<String, HashSet<String>>,
+}
+
+impl DefinitionIndex {
+    pub fn new(definitions: Vec<CodeNode>) -> Self {
+        let mut by_file: HashMap<String, Vec<usize>> = HashMap::new();
+        for (index, node) in definitions.iter().enumerate() {
+            if node.level == 0 || node.block_type == CodeBlockType::CALL {
+                continue;
+            }
+            by_file
+                .entry(node.file_path.clone())
+                .or_default()
+                .push(index);
+        }
+        // 所属类型为包含它的最内层定义（impl、类等）的类型，嵌套在函数中的函数没有所属类型
+        let owners = definitions
+            .iter()
+            .map(|node| {
+                let (start, _) = node.line_range();
+                by_file
+                    .get(&node.file_path)?
+                    .iter()
+                    .map(|index| &definitions[*index])
+                    .filter(|parent| {
+                        let (parent_start, parent_end) = parent.line_range();
+                        parent.level < node.level && parent_start <= start && start <= parent_end
+                    })
+                    .max_by_key(|parent| parent.level)
+                    .and_then(owner_type)
+            })
+            .collect::<Vec<Option<String>>>();
+        let mut symbols: HashMap<(String, String), Vec<usize>> = HashMap::new();
+        for index in by_file.values().flatten().copied() {
+            let node = &definitions[index];
+            let language = symbols::language_of(&node.file_path, node.language.as_deref());
+            for name in definition_names(node) {
+                if let Some(owner) = &owners[index] {
+                    symbols
+                        .entry((language.clone(), format!("{}::{}", owner, name)))
+                        .or_default()
+                        .push(index);
+                }
+                symbols
+                    .entry((language.clone(), name))
+                    .or_default()
+                    .push(index);
+            }
+        }
+        // 按定义的顺序排列，同样优先的候选选择先出现的
+        for candidates in symbols.values_mut() {
+            candidates.sort_unstable();
+            candidates.dedup();
+        }
+        Self {
+            definitions,
+            symbols,
+            owners,
+            by_file,
+            imports: HashMap::new(),
+        }
+    }
+
+    /**
+     * 设置每个文件导入的项目中的文件，例如 imports::resolve_imports 的结果，
+     * 同名定义有多个时优先选择被导入的文件中的
+     */
+    pub fn with_imports(mut self, imports: HashMap<String, HashSet<String>>) -> Self {
+        self.imports = imports;
+        self
+    }
+
+    pub fn definitions(&self) -> &[CodeNode] {
+        &self.definitions
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.definitions.is_empty()
+    }
+
+    /**
+     * 查找调用对应的定义，只在调用所在文件的语言中查找：
+     * self.foo()、Foo::new() 和能从声明推断出接收者类型的 x.foo() 对应该类型的方法，
+     * b::other() 和 b.other() 对应模块 b 中的定义，其他方法调用只对应方法，
+     * 项目外的类型（例如 Vec::new()）不对应任何定义；
+     * 同样符合的定义有多个时依次优先选择同一文件、被导入的文件中的
+     */
+    pub fn resolve(&self, call: &CodeNode) -> Option<&CodeNode> {
+        let language = symbols::language_of(&call.file_path, None);
+        let target = call_target(call);
+        let candidates = self.symbols.get(&(language.clone(), target.name.clone()))?;
+        let qualified = |owner: &str| {
+            self.symbols
+                .get(&(language.clone(), format!("{}::{}", owner, target.name)))
+        };
+        let owner = match &target.qualifier {
+            Qualifier::SelfType => self.owner_at(&call.file_path, call.file_location),
+            Qualifier::Path(name) | Qualifier::Receiver(name) if qualified(name).is_some() => {
+                Some(name.clone())
+            }
+            Qualifier::Receiver(name) => self.receiver_type(call, name, &language),
+            // Java 中直接调用的方法可能是所在类的方法
+            Qualifier::None if language == "Java" => {
+                self.owner_at(&call.file_path, call.file_location)
+            }
+            _ => None,
+        };
+        if let Some(found) = owner.as_deref().and_then(qualified) {
+            return self.prefer(found, call);
+        }
+        let methods = || {
+            candidates
+                .iter()
+                .copied()
+                .filter(|index| self.owners[*index].is_some())
+                .collect::<Vec<usize>>()
+        };
+        match &target.qualifier {
+            Qualifier::None => self.prefer(candidates, call),
+            // 所在类型中没有，可能是 trait 或父类中的方法
+            Qualifier::SelfType => self.prefer(&methods(), call),
+            Qualifier::Path(module) | Qualifier::Receiver(module) => {
+                let in_module = candidates
+                    .iter()
+                    .copied()
+                    .filter(|index| {
+                        symbols::is_module_file(&self.definitions[*index].file_path, module)
+                    })
+                    .collect::<Vec<usize>>();
+                if !in_module.is_empty() {
+                    self.prefer(&in_module, call)
+                } else if matches!(target.qualifier, Qualifier::Receiver(_)) {
+                    self.prefer(&methods(), call)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    /**
+     * 候选定义中优先选择与调用同一文件的，其次是调用所在文件导入的文件中的
+     */
+    fn prefer(&self, candidates: &[usize], call: &CodeNode) -> Option<&CodeNode> {
+        let imported = self.imports.get(&call.file_path);
+        candidates
+            .iter()
+            .map(|index| &self.definitions[*index])
+            .min_by_key(|node| {
+                if node.file_path == call.file_path {
+                    0
+                } else if imported.is_some_and(|files| files.contains(&node.file_path)) {
+                    1
+                } else {
+                    2
+                }
+            })
+    }
+
+    /**
+     * 定义所属的类型，例如方法所在 impl 的类型名
+     */
+    pub fn owner_of(&self, definition: &CodeNode) -> Option<&str> {
+        self.by_file
+            .get(&definition.file_path)?
+            .iter()
+            .find(|index| self.definitions[**index].id == definition.id)
+            .and_then(|index| self.owners[*index].as_deref())
+    }
+
+    /**
+     * 文件中该行所在的类型，例如方法所属的 impl 或类的类型名
+     */
+    fn owner_at(&self, file_path: &str, line: usize) -> Option<String> {
+        let index = self.enclosing_index(file_path, line)?;
+        self.owners[index]
+            .clone()
+            .or_else(|| owner_type(&self.definitions[index]))
+    }
+
+    /**
+     * 从调用所在的定义和所属类型的定义（例如结构体字段）中的声明推断接收者的类型
+     */
+    fn receiver_type(&self, call: &CodeNode, receiver: &str, language: &str) -> Option<String> {
+        let caller = self
+            .enclosing_index(&call.file_path, call.file_location)
+            .map(|index| &self.definitions[index]);
+        let owner = self.owner_at(&call.file_path, call.file_location);
+        let types = owner
+            .and_then(|owner| self.symbols.get(&(language.to_owned(), owner)))
+            .into_iter()
+            .flatten()
+            .map(|index| &self.definitions[*index])
+            .filter(|node| {
+                matches!(
+                    node.block_type,
+                    CodeBlockType::CLASS | CodeBlockType::STRUCT
+                )
+            });
+        caller
+            .into_iter()
+            .chain(types)
+            .find_map(|node| declared_type(&node.block, receiver))
+            .map(str::to_owned)
+    }
+
+    /**
+     * 文件中包含该行的最内层定义，例如调用所在的函数
+     */
+    pub fn enclosing(&self, file_path: &str, line: usize) -> Option<&CodeNode> {
+        self.enclosing_index(file_path, line)
+            .map(|index| &self.definitions[index])
+    }
+
+    fn enclosing_index(&self, file_path: &str, line: usize) -> Option<usize> {
+        self.by_file
+            .get(file_path)?
+            .iter()
+            .copied()
+            .filter(|index| {
+                let node = &self.definitions[*index];
+                let (start, end) = node.line_range();
+                node.block_type != CodeBlockType::HOOK && start <= line && line <= end
+            })
+            .max_by_key(|index| self.definitions[*index].level)
+    }
+
+    /**
+     * 统计每个定义在项目中被调用的次数，键为定义的 ID，对应规则与 resolve 一致
+     */
+    pub fn reference_counts(&self, calls: &[CodeNode]) -> HashMap<String, usize> {
+        let mut counts = HashMap::new();
+        for call in calls {
+            if let Some(definition) = self.resolve(call) {
+                *counts.entry(definition.id.clone()).or_insert(0) += 1;
+            }
+        }
+        counts
+    }
+
+    /**
+     * 调用了 definition 的定义和其中每处调用的行号，按第一次出现的顺序排列，
+     * 不在任何定义中的调用和递归调用不计入
+     */
+    pub fn callers(
+        &self,
+        calls: &[CodeNode],
+        definition: &CodeNode,
+    ) -> Vec<(&CodeNode, Vec<usize>)> {
+        let mut callers: Vec<(&CodeNode, Vec<usize>)> = vec![];
+        for call in calls {
+            if self
+                .resolve(call)
+                .is_none_or(|resolved| resolved.id != definition.id)
+            {
+                continue;
+            }
+            let Some(caller) = self.enclosing(&call.file_path, call.file_location) else {
+                continue;
+            };
+            if caller.id == definition.id {
+                continue;
+            }
+            match callers.iter_mut().find(|(node, _)| node.id == caller.id) {
+                Some((_, lines)) => lines.push(call.file_location),
+                None => callers.push((caller, vec![call.file_location])),
+            }
+        }
+        callers
+    }
+
+    /**
+     * definition 中调用的定义和每处调用的行号，按第一次出现的顺序排列，
+     * 找不到定义的调用（例如标准库函数）和递归调用不计入
+     */
+    pub fn callees(
+        &self,
+        calls: &[CodeNode],
+        definition: &CodeNode,
+    ) -> Vec<(&CodeNode, Vec<usize>)> {
+        let (start, end) = definition.line_range();
+        let mut callees: Vec<(&CodeNode, Vec<usize>)> = vec![];
+        for call in calls.iter().filter(|call| {
+            call.file_path == definition.file_path
+                && start <= call.file_location
+                && call.file_location <= end
+        }) {
+            let Some(callee) = self.resolve(call) else {
+                continue;
+            };
+            if callee.id == definition.id {
+                continue;
+            }
+            match callees.iter_mut().find(|(node, _)| node.id == callee.id) {
+                Some((_, lines)) => lines.push(call.file_location),
+                None => callees.push((callee, vec![call.file_location])),
+            }
+        }
+        callees
+    }
+
+    /**
+     * 定义在调用关系中的扇入、扇出和深度，对应规则与 callers / callees 一致
+     */
+    pub fn call_stats(&self, calls: &[CodeNode], definition: &CodeNode) -> CallStats {
+        // 调用方 ID -> 被调用方 ID，以及反向的关系
+        let mut callees: HashMap<&str, HashSet<&str>> = HashMap::new();
+        let mut callers: HashMap<&str, HashSet<&str>> = HashMap::new();
+        for (caller, callee, _) in self.resolved_calls(calls) {
+            callees.entry(&caller.id).or_default().insert(&callee.id);
+            callers.entry(&callee.id).or_default().insert(&caller.id);
+        }
+        let fan_in = callers.get(definition.id.as_str()).map_or(0, HashSet::len);
+        let fan_out = callees.get(definition.id.as_str()).map_or(0, HashSet::len);
+        let (upstream, caller_depth) = reachable(&callers, &definition.id);
+        let (downstream, callee_depth) = reachable(&callees, &definition.id);
+        CallStats {
+            fan_in,
+            fan_out,
+            upstream,
+            caller_depth,
+            downstream,
+            callee_depth,
+        }
+    }
+
+    /**
+     * 影响分析：直接或间接调用 definition 的定义，最多向上查找 max_depth 层，
+     * 对应规则与 callers 一致
+     */
+    pub fn impact<'a>(
+        &'a self,
+        calls: &'a [CodeNode],
+        definition: &'a CodeNode,
+        max_depth: usize,
+    ) -> Impact<'a> {
+        // 被调用方 ID -> 调用方和每处调用的行号
+        let mut callers: HashMap<&str, Vec<(&CodeNode, Vec<usize>)>> = HashMap::new();
+        for (caller, callee, line) in self.resolved_calls(calls) {
+            let entry = callers.entry(&callee.id).or_default();
+            match entry.iter_mut().find(|(node, _)| node.id == caller.id) {
+                Some((_, lines)) => lines.push(line),
+                None => entry.push((caller, vec![line])),
+            }
+        }
+        let mut impact = Impact::default();
+        let mut visited: HashSet<&str> = HashSet::from([definition.id.as_str()]);
+        let mut queue = VecDeque::from([(definition.id.as_str(), 0)]);
+        while let Some((id, depth)) = queue.pop_front() {
+            if depth >= max_depth {
+                continue;
+            }
+            for (caller, lines) in callers.get(id).into_iter().flatten() {
+                impact.calls.push((caller, id, lines.clone()));
+                if visited.insert(&caller.id) {
+                    impact.dependents.push((caller, depth + 1));
+                    queue.push_back((&caller.id, depth + 1));
+                }
+            }
+        }
+        impact
+    }
+
+    /**
+     * 能对应到调用方和被调用方的调用（调用方，被调用方，行号），不包括递归调用
+     */
+    fn resolved_calls<'a>(
+        &'a self,
+        calls: &'a [CodeNode],
+    ) -> impl Iterator<Item = (&'a CodeNode, &'a CodeNode, usize)> + 'a {
+        calls.iter().filter_map(|call| {
+            let caller = self.enclosing(&call.file_path, call.file_location)?;
+            let callee = self.resolve(call)?;
+            (caller.id != callee.id).then_some((caller, callee, call.file_location))
+        })
+    }
+}
+
+/**
+ * 影响分析的结果
+ */
+#[derive(Debug, Clone, Default)]
+pub struct Impact<'a> {
+    // 受影响的定义和它到被分析定义的层数，直接调用方为 1，按层数排列
+    pub dependents: Vec<(&'a CodeNode, usize)>,
+    // 受影响的定义之间的调用关系（调用方，被调用方 ID，每处调用的行号）
+    pub calls: Vec<(&'a CodeNode, &'a str, Vec<usize>)>,
+}
+
+/**
+ * 定义的调用统计
+ */
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CallStats {
+    // 直接调用它的不同定义数量
+    pub fan_in: usize,
+    // 它直接调用的不同定义数量
+    pub fan_out: usize,
+    // 直接或间接调用它的定义数量
+    pub upstream: usize,
+    // 调用方的层数，按最短的调用链计算，只有直接调用方时为 1
+    pub caller_depth: usize,
+    // 直接或间接被它调用的定义数量
+    pub downstream: usize,
+    // 被调用方的层数，按最短的调用链计算，只调用叶子函数时为 1
+    pub callee_depth: usize,
+}
+
+/**
+ * 从 start 出发广度优先遍历，返回能到达的节点数量和最远的层数，环上的节点只计一次
+ */
+fn reachable(edges: &HashMap<&str, HashSet<&str>>, start: &str) -> (usize, usize) {
+    let mut visited: HashSet<&str> = HashSet::from([start]);
+    let mut queue = VecDeque::from([(start, 0)]);
+    let mut depth = 0;
+    while let Some((id, distance)) = queue.pop_front() {
+        depth = depth.max(distance);
+        for next in edges.get(id).into_iter().flatten() {
+            if visited.insert(next) {
+                queue.push_back((next, distance + 1));
+            }
+        }
+    }
+    (visited.len() - 1, depth)
+}
+
+/**
+ * 统计每个定义在项目中被调用的次数，键为定义的 ID，
+ * 调用与定义的对应规则与 DefinitionIndex::resolve 一致
+ */
+pub fn count_references(definitions: &[CodeNode], calls: &[CodeNode]) -> HashMap<String, usize> {
+    DefinitionIndex::new(definitions.to_vec()).reference_counts(calls)
+}
+
+/**
+ * 会产生分支的语法节点，覆盖目前支持的所有语言
+ */
+const BRANCH_KINDS: [&str; 21] = [
+    "if_expression",
+    "if_statement",
+    "match_arm",
+    "while_expression",
+    "while_statement",
+    "loop_expression",
+    "for_expression",
+    "for_statement",
+    "for_in_statement",
+    "enhanced_for_statement",
+    "do_statement",
+    "case_statement",
+    "switch_case",
+    "switch_label",
+    "catch_clause",
+    "try_expression",
+    "conditional_expression",
+    "ternary_expression",
+    "&&",
+    "||",
+    "elif_clause",
+];
+
+/**
+ * 估算圈复杂度：1 + 分支节点数量
+ */
+pub fn cyclomatic_complexity(node: &Node) -> usize {
+    let mut complexity = 1;
+    let mut cursor = node.walk();
+    let mut stack = vec![*node];
+    while let Some(current) = stack.pop() {
+        if BRANCH_KINDS.contains(&current.kind()) {
+            complexity += 1;
+        }
+        for child in current.children(&mut cursor) {
+            stack.push(child);
+        }
+    }
+    complexity
+}
+
+/**
+ * 提取定义前的文档注释（`///`、`/** */`）或 python 的 docstring，去掉注释符号，没有时返回 None
+ */
+pub fn doc_comment(code: &str, node: &Node) -> Option<String> {
+    // python 的 docstring 是函数体或类体中的第一个字符串
+    if let Some(string) = node
+        .child_by_field_name("body")
+        .filter(|body| body.kind() == "block")
+        .and_then(|body| body.named_child(0))
+        .filter(|first| first.kind() == "expression_statement")
+        .and_then(|first| first.named_child(0))
+        .filter(|string| string.kind() == "string")
+    {
+        let text = code[string.byte_range()].trim_start_matches(['r', 'R', 'u', 'U']);
+        let quote = if text.starts_with("\"\"\"") || text.starts_with("'''") {
+            3
+        } else {
+            1
+        };
+        let inner = text
+            .get(quote..text.len().saturating_sub(quote))
+            .unwrap_or("");
+        let doc = inner
+            .lines()
+            .map(str::trim)
+            .collect::<Vec<&str>>()
+            .join("\n");
+        return Some(doc.trim().to_owned()).filter(|doc| !doc.is_empty());
+    }
+    // js 中 export 的定义，注释位于 export 语句之前
+    let target = match node.parent() {
+        Some(parent) if parent.kind() == "export_statement" => parent,
+        _ => *node,
+    };
+    let mut comments = vec![];
+    let mut sibling = target.prev_sibling();
+    while let Some(current) = sibling {
+        let text = code[current.byte_range()].trim();
+        if current.kind() == "attribute_item" || current.kind() == "decorator" {
+            // 跳过 rust 的属性和 ts 的装饰器，例如 #[derive]、@Input()
+        } else if current.kind().contains("comment") && text.starts_with("///") {
+            let line = &text[3..];
+            comments.push(line.strip_prefix(' ').unwrap_or(line));
+        } else if current.kind().contains("comment") && text.starts_with("/**") {
+            // 块注释只取紧挨着定义的一个
+            if comments.is_empty() {
+                comments.push(text);
+            }
+            break;
+        } else {
+            break;
+        }
+        sibling = current.prev_sibling();
+    }
+    if comments.is_empty() {
+        return None;
+    }
+    comments.reverse();
+    let doc = match comments.first() {
+        Some(block) if block.starts_with("/**") => block
+            .trim_start_matches("/**")
+            .trim_end_matches("*/")
+            .lines()
+            .map(|line| {
+                let line = line.trim_start();
+                let line = line.strip_prefix('*').unwrap_or(line);
+                line.strip_prefix(' ').unwrap_or(line).trim_end()
+            })
+            .collect::<Vec<&str>>()
+            .join("\n"),
+        _ => comments.join("\n"),
+    };
+    let doc = doc.trim();
+    (!doc.is_empty()).then(|| doc.to_owned())
+}
+
+pub fn recursion_outline(
+    node: Node,
+    parent_id: CodeNodeIndex,
+    path: &str,
+    code: &str,
+    level: usize,
+    symbol_query: &dyn SymbolQuery,
+    graph: &mut Graph,
+) {
+    let mut context = VisitContext::new(path, code, parent_id, level, graph);
+    walk_node(node, symbol_query, &mut context, &mut |_, _| {});
+}
+
+#[cfg(all(
+    test,
+    any(
+        feature = "lang-rust",
+        feature = "lang-python",
+        feature = "lang-typescript"
+    )
+))]
+mod tests {
+    use super::*;
+
+    /**
+     * 文件中标签包含 name 的定义的文档注释
+     */
+    fn doc_of(path: &str, code: &str, name: &str) -> Option<String> {
+        let extension = path.rsplit('.').next().unwrap_or("");
+        fetch_definitions(path, code, get_symbol_query(extension).unwrap())
+            .unwrap()
+            .into_iter()
+            .find(|node| node.label.contains(name))
+            .unwrap_or_else(|| panic!("没有找到 {}", name))
+            .doc
+    }
+
+    #[cfg(feature = "lang-rust")]
+    #[test]
+    fn rust_line_and_block_doc_comments() {
+        let code = "// 普通注释\n/// 第一行\n///第二行\n#[inline]\nfn line() {}\n\n\
+                    /**\n * 块注释\n *   缩进保留\n */\nfn block() {}\n\n\
+                    // 普通注释\nfn none() {}\n";
+        assert_eq!(
+            doc_of("a.rs", code, "line").as_deref(),
+            Some("第一行\n第二行")
+        );
+        assert_eq!(
+            doc_of("a.rs", code, "block").as_deref(),
+            Some("块注释\n  缩进保留")
+        );
+        assert_eq!(doc_of("a.rs", code, "none"), None);
+    }
+
+    #[cfg(feature = "lang-python")]
+    #[test]
+    fn python_docstrings() {
+        let code = "def run():\n    \"\"\"\n    运行任务\n    \"\"\"\n    pass\n\n\
+                    def quiet():\n    x = 'not a doc'\n";
+        assert_eq!(doc_of("a.py", code, "run").as_deref(), Some("运行任务"));
+        assert_eq!(doc_of("a.py", code, "quiet"), None);
+    }
+
+    #[cfg(feature = "lang-typescript")]
+    #[test]
+    fn typescript_doc_before_export() {
+        let code = "/** 导出的函数 */\nexport function open() {}\n";
+        assert_eq!(doc_of("a.ts", code, "open").as_deref(), Some("导出的函数"));
+    }
+
+    #[cfg(feature = "lang-rust")]
+    #[test]
+    fn definition_index_resolves_calls_by_receiver_and_module() {
+        let a = "struct Foo;
+impl Foo {
+    fn new() -> Self { Foo }
+    fn run(&self) { self.step(); }
+    fn step(&self) {}
+}
+struct Bar;
+impl Bar {
+    fn new() -> Self { Bar }
+    fn run(&self) {}
+}
+fn helper() {}
+fn main() {
+    let foo: Foo = Foo::new();
+    foo.run();
+    helper();
+    b::helper();
+    let v: Vec<u8> = Vec::new();
+}
+";
+        let b = "pub fn helper() {}\n";
+        let query = || get_symbol_query("rs").unwrap();
+        let mut definitions = fetch_definitions("a.rs", a, query()).unwrap();
+        definitions.extend(fetch_definitions("b.rs", b, query()).unwrap());
+        let index = DefinitionIndex::new(definitions);
+        let calls = fetch_calls("a.rs", a, query()).unwrap();
+        let resolve = |line: usize, text: &str| {
+            let call = calls
+                .iter()
+                .find(|call| call.file_location == line && call.label.contains(text))
+                .unwrap_or_else(|| panic!("第 {} 行没有调用 {}", line, text));
+            index
+                .resolve(call)
+                .map(|found| (found.file_path.as_str(), found.file_location))
+        };
+        assert_eq!(resolve(4, "step"), Some(("a.rs", 5)));
+        assert_eq!(resolve(14, "Foo::new"), Some(("a.rs", 3)));
+        // 从声明推断出 foo 的类型是 Foo
+        assert_eq!(resolve(15, "run"), Some(("a.rs", 4)));
+        // 同名定义优先选择同一文件中的，指定模块时选择模块中的
+        assert_eq!(resolve(16, "helper"), Some(("a.rs", 12)));
+        assert_eq!(resolve(17, "b::helper"), Some(("b.rs", 1)));
+        // 项目外的类型不对应任何定义
+        assert_eq!(resolve(18, "Vec::new"), None);
+    }
+}
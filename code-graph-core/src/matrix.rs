@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{CodeNodeIndex, EdgeKind, Graph};
+
+/**
+ * 调用矩阵中行和列的排列方式
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum MatrixOrder {
+    // 调用次数多的在前，行按调用别人的次数，列按被调用的次数
+    #[default]
+    Calls,
+    // 按名称
+    Name,
+    // 按所在文件和行号
+    Location,
+}
+
+impl MatrixOrder {
+    pub const ALL: [MatrixOrder; 3] =
+        [MatrixOrder::Calls, MatrixOrder::Name, MatrixOrder::Location];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            MatrixOrder::Calls => "按调用次数",
+            MatrixOrder::Name => "按名称",
+            MatrixOrder::Location => "按位置",
+        }
+    }
+}
+
+/**
+ * 调用矩阵：行为调用方，列为被调用方，单元格为调用次数（调用行数）
+ */
+#[derive(Debug, Clone, Default)]
+pub struct CallMatrix {
+    pub callers: Vec<CodeNodeIndex>,
+    pub callees: Vec<CodeNodeIndex>,
+    counts: HashMap<(CodeNodeIndex, CodeNodeIndex), usize>,
+    max_count: usize,
+}
+
+impl CallMatrix {
+    /**
+     * 由图中两端都可见的调用边生成矩阵
+     */
+    pub fn new(graph: &Graph, order: MatrixOrder) -> Self {
+        let mut counts: HashMap<(CodeNodeIndex, CodeNodeIndex), usize> = HashMap::new();
+        let visible = |index: CodeNodeIndex| graph.node(index).is_some_and(|node| node.visiable);
+        for edge in graph.edges().filter(|edge| {
+            edge.kind() == EdgeKind::Calls && visible(edge.from()) && visible(edge.to())
+        }) {
+            *counts.entry((edge.from(), edge.to())).or_default() += edge.call_lines().len().max(1);
+        }
+        let mut outgoing: HashMap<CodeNodeIndex, usize> = HashMap::new();
+        let mut incoming: HashMap<CodeNodeIndex, usize> = HashMap::new();
+        for ((from, to), count) in &counts {
+            *outgoing.entry(*from).or_default() += count;
+            *incoming.entry(*to).or_default() += count;
+        }
+        let sorted = |totals: HashMap<CodeNodeIndex, usize>| {
+            let key = |index: &CodeNodeIndex| {
+                graph.node(*index).map(|node| {
+                    (
+                        node.label.trim(),
+                        node.file_path.as_str(),
+                        node.file_location,
+                    )
+                })
+            };
+            let mut indexes = totals.keys().copied().collect::<Vec<CodeNodeIndex>>();
+            indexes.sort_by(|a, b| match order {
+                MatrixOrder::Calls => totals[b].cmp(&totals[a]).then(key(a).cmp(&key(b))),
+                MatrixOrder::Name => key(a).cmp(&key(b)),
+                MatrixOrder::Location => key(a)
+                    .map(|(_, file_path, line)| (file_path, line))
+                    .cmp(&key(b).map(|(_, file_path, line)| (file_path, line))),
+            });
+            indexes
+        };
+        Self {
+            callers: sorted(outgoing),
+            callees: sorted(incoming),
+            max_count: counts.values().copied().max().unwrap_or(0),
+            counts,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /**
+     * caller 调用 callee 的次数，没有调用时为 0
+     */
+    pub fn count(&self, caller: CodeNodeIndex, callee: CodeNodeIndex) -> usize {
+        self.counts.get(&(caller, callee)).copied().unwrap_or(0)
+    }
+
+    /**
+     * 单元格中最大的调用次数，用于按比例着色
+     */
+    pub fn max_count(&self) -> usize {
+        self.max_count
+    }
+}
@@ -0,0 +1,297 @@
+use std::{
+    ffi::OsStr,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    fetch_definitions, get_symbol_query, git, imports::resolve_imports, valid_file_extention,
+    CodeBlockType, CodeNode, CodeNodeIndex, DefinitionIndex, Graph,
+};
+
+/**
+ * 补丁中单个文件的修改
+ */
+#[derive(Debug, Clone)]
+pub struct FilePatch {
+    // 修改后的文件路径（相对于仓库根目录）
+    pub path: String,
+    // 修改的行范围（新文件中的行号，从 1 开始，包含两端）
+    pub ranges: Vec<(usize, usize)>,
+}
+
+/**
+ * 解析 unified diff 格式的补丁
+ */
+pub fn parse_patch(patch: &str) -> Vec<FilePatch> {
+    let mut file_patches: Vec<FilePatch> = vec![];
+    for line in patch.lines() {
+        if let Some(path) = line.strip_prefix("+++ ") {
+            let path = path.split('\t').next().unwrap_or("").trim();
+            if path == "/dev/null" {
+                continue;
+            }
+            let path = path.strip_prefix("b/").unwrap_or(path);
+            file_patches.push(FilePatch {
+                path: path.to_owned(),
+                ranges: vec![],
+            });
+        } else if let Some(hunk) = line.strip_prefix("@@ ") {
+            // @@ -a,b +c,d @@
+            let Some(new_range) = hunk.split(' ').find(|part| part.starts_with('+')) else {
+                continue;
+            };
+            let mut parts = new_range[1..].split(',');
+            let start = parts.next().and_then(|s| s.parse().ok()).unwrap_or(0);
+            let count: usize = parts.next().and_then(|s| s.parse().ok()).unwrap_or(1);
+            if let Some(file_patch) = file_patches.last_mut() {
+                let start = usize::max(start, 1);
+                file_patch
+                    .ranges
+                    .push((start, start + count.saturating_sub(1)));
+            }
+        }
+    }
+    file_patches
+}
+
+fn overlaps(range: (usize, usize), other: (usize, usize)) -> bool {
+    range.0 <= other.1 && other.0 <= range.1
+}
+
+fn contains(outer: &CodeNode, inner: &CodeNode) -> bool {
+    let (outer_start, outer_end) = outer.line_range();
+    let (inner_start, inner_end) = inner.line_range();
+    outer.file_path == inner.file_path
+        && outer_start <= inner_start
+        && inner_end <= outer_end
+        && (outer_start, outer_end) != (inner_start, inner_end)
+}
+
+/**
+ * 获取包含指定行的最内层定义
+ */
+fn innermost_definition<'a>(
+    definitions: &'a [CodeNode],
+    file_path: &str,
+    line: usize,
+) -> Option<&'a CodeNode> {
+    definitions
+        .iter()
+        .filter(|node| node.file_path == file_path)
+        .filter(|node| {
+            let (start, end) = node.line_range();
+            start <= line && line <= end
+        })
+        .min_by_key(|node| {
+            let (start, end) = node.line_range();
+            end - start
+        })
+}
+
+pub(crate) fn add_child(
+    graph: &mut Graph,
+    parent: CodeNodeIndex,
+    mut node: CodeNode,
+    level: usize,
+) -> CodeNodeIndex {
+    // 同一定义可能出现在多处，ID 需要加上父节点的路径
+    node.id = format!("{}/{}", graph.get_node(parent).id, node.id);
+    node.level = level;
+    node.visiable = true;
+    let index = graph.add_node(node);
+    graph.add_edge(parent, index);
+    index
+}
+
+pub(crate) fn group_node(id: &str, label: &str) -> CodeNode {
+    CodeNode::new(id, label, "", 0, CodeBlockType::NORMAL, 0)
+}
+
+/**
+ * 根据补丁生成图：只包含被修改的定义，以及它们的直接调用方和被调用方，
+ * 调用与定义的对应规则与 DefinitionIndex::resolve 一致
+ */
+pub fn build_patch_graph(
+    root_path: &Path,
+    patch_name: &str,
+    patch: &str,
+    pathes: &[PathBuf],
+    call_nodes: &[CodeNode],
+) -> Graph {
+    // 项目中所有的定义
+    let definitions = pathes
+        .iter()
+        .flat_map(|path| {
+            let ext = path
+                .extension()
+                .unwrap_or(OsStr::new(""))
+                .to_str()
+                .unwrap_or("");
+            if !valid_file_extention(ext) {
+                return vec![];
+            }
+            let code = fs::read_to_string(path).unwrap_or_default();
+            get_symbol_query(ext)
+                .and_then(|symbol_query| {
+                    fetch_definitions(path.to_str().unwrap_or(""), &code, symbol_query)
+                })
+                .unwrap_or_default()
+        })
+        .collect::<Vec<CodeNode>>();
+    let index = DefinitionIndex::new(definitions).with_imports(resolve_imports(root_path, pathes));
+    let definitions = index.definitions();
+    // 补丁中的路径相对于仓库根目录
+    let git_root = git::run_git(root_path, &["rev-parse", "--show-toplevel"])
+        .map(|output| PathBuf::from(output.trim()));
+
+    let mut graph = Graph::new();
+    let root = graph.add_node(CodeNode::new(
+        patch_name,
+        patch_name,
+        patch,
+        0,
+        CodeBlockType::NORMAL,
+        0,
+    ));
+    for file_patch in parse_patch(patch) {
+        let mut full_path = root_path.join(&file_patch.path);
+        if !full_path.exists() {
+            if let Some(git_root) = &git_root {
+                full_path = git_root.join(&file_patch.path);
+            }
+        }
+        let full_path = full_path.to_str().unwrap_or("").to_owned();
+        let touched = definitions
+            .iter()
+            .filter(|node| node.file_path == full_path)
+            .filter(|node| {
+                file_patch
+                    .ranges
+                    .iter()
+                    .any(|range| overlaps(node.line_range(), *range))
+            })
+            .collect::<Vec<&CodeNode>>();
+        // 只保留最内层的定义
+        let touched = touched
+            .iter()
+            .filter(|node| !touched.iter().any(|inner| contains(node, inner)))
+            .copied()
+            .collect::<Vec<&CodeNode>>();
+        if touched.is_empty() {
+            continue;
+        }
+
+        let mut file_node = group_node(&file_patch.path, &file_patch.path);
+        file_node.file_path = full_path.clone();
+        let file_index = add_child(&mut graph, root, file_node, 1);
+        for node in touched {
+            let node_index = add_child(&mut graph, file_index, node.clone(), 2);
+
+            // 调用方：调用了该定义的代码所在的定义
+            let mut callers: Vec<&CodeNode> = vec![];
+            for call in call_nodes.iter().filter(|call| {
+                index
+                    .resolve(call)
+                    .is_some_and(|definition| definition.id == node.id)
+            }) {
+                if let Some(caller) =
+                    innermost_definition(definitions, &call.file_path, call.file_location)
+                {
+                    if !callers.iter().any(|c| c.id == caller.id) {
+                        callers.push(caller);
+                    }
+                }
+            }
+            // 被调用方：该定义中调用的、能在项目中找到的定义
+            let (start, end) = node.line_range();
+            let mut callees: Vec<&CodeNode> = vec![];
+            for call in call_nodes.iter().filter(|call| {
+                call.file_path == node.file_path
+                    && start <= call.file_location
+                    && call.file_location <= end
+            }) {
+                if let Some(callee) = index.resolve(call) {
+                    if !callees.iter().any(|c| c.id == callee.id) {
+                        callees.push(callee);
+                    }
+                }
+            }
+
+            for (id, title, related) in [
+                ("callers", "调用方", callers),
+                ("callees", "被调用", callees),
+            ] {
+                if related.is_empty() {
+                    continue;
+                }
+                let group_index = add_child(
+                    &mut graph,
+                    node_index,
+                    group_node(id, &format!("{} ({})", title, related.len())),
+                    3,
+                );
+                for related_node in related {
+                    add_child(&mut graph, group_index, related_node.clone(), 4);
+                }
+            }
+        }
+    }
+    graph
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_hunks_of_each_file() {
+        let patch = "\
+diff --git a/src/a.rs b/src/a.rs
+--- a/src/a.rs
++++ b/src/a.rs
+@@ -3,4 +3,5 @@ fn a() {
+ context
++added
+@@ -20 +21 @@
+-old
++new
+diff --git a/b.rs b/b.rs
+--- a/b.rs\t2024-01-01 00:00:00
++++ b/b.rs\t2024-01-02 00:00:00
+@@ -0,0 +1,3 @@
++one
+";
+        let file_patches = parse_patch(patch);
+        assert_eq!(file_patches.len(), 2);
+        assert_eq!(file_patches[0].path, "src/a.rs");
+        assert_eq!(file_patches[0].ranges, vec![(3, 7), (21, 21)]);
+        assert_eq!(file_patches[1].path, "b.rs");
+        assert_eq!(file_patches[1].ranges, vec![(1, 3)]);
+    }
+
+    #[test]
+    fn skips_deleted_files() {
+        let patch = "\
+--- a/gone.rs
++++ /dev/null
+@@ -1,2 +0,0 @@
+-fn gone() {}
+-
+";
+        assert!(parse_patch(patch).is_empty());
+    }
+
+    #[test]
+    fn pure_deletion_touches_the_following_line() {
+        let patch = "--- a/a.rs\n+++ b/a.rs\n@@ -5,2 +4,0 @@\n-x\n-y\n";
+        assert_eq!(parse_patch(patch)[0].ranges, vec![(4, 4)]);
+    }
+
+    #[test]
+    fn overlapping_ranges() {
+        assert!(overlaps((1, 5), (5, 8)));
+        assert!(overlaps((3, 4), (1, 10)));
+        assert!(!overlaps((1, 4), (5, 8)));
+    }
+}
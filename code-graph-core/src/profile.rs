@@ -0,0 +1,211 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{simplify_generics, CodeBlockType, GenericsDisplay, Graph};
+
+/**
+ * 性能采样数据，按函数名汇总
+ */
+#[derive(Debug, Clone, Default)]
+pub struct Profile {
+    // 函数自身的采样数
+    self_samples: HashMap<String, u64>,
+    // 包含调用的函数在内的采样数
+    total_samples: HashMap<String, u64>,
+    // 全部采样数
+    total: u64,
+}
+
+/**
+ * 节点对应函数的耗时
+ */
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProfileSample {
+    pub self_samples: u64,
+    pub total_samples: u64,
+    // 占全部采样的比例 0.0 ~ 1.0
+    pub ratio: f32,
+    // 相对最耗时的节点归一化后的热度 0.0 ~ 1.0
+    pub heat: f32,
+}
+
+impl Profile {
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.total == 0
+    }
+}
+
+/**
+ * 从栈帧中取出函数名，去掉模块路径、泛型参数、符号 hash 和附加信息，
+ * 例如 `code_graph::graph::Graph::layout::h0123456789abcdef` 得到 `layout`
+ */
+pub fn frame_function_name(frame: &str) -> String {
+    // 泛型参数和 <T as Trait> 中可能包含空格，先去掉
+    let frame = simplify_generics(frame, GenericsDisplay::Strip);
+    let frame = frame.split_whitespace().next().unwrap_or("");
+    let frame = frame.split('(').next().unwrap_or(frame);
+    let mut segments = frame
+        .split("::")
+        .flat_map(|segment| segment.rsplit(['.', '/']).next())
+        .filter(|segment| !segment.is_empty() && !segment.starts_with("{{"))
+        .collect::<Vec<&str>>();
+    // rust 符号末尾的 hash
+    if segments.last().is_some_and(|last| {
+        last.len() == 17
+            && last.starts_with('h')
+            && last[1..].chars().all(|c| c.is_ascii_hexdigit())
+    }) {
+        segments.pop();
+    }
+    segments.last().copied().unwrap_or("").to_owned()
+}
+
+/**
+ * 解析 collapsed stacks 格式（flamegraph 的 stackcollapse-perf.pl、inferno 等工具的输出），
+ * 每行为 `栈帧;栈帧;栈帧 采样数`，根在前叶子在后
+ */
+pub fn parse_collapsed(text: &str) -> Profile {
+    let mut profile = Profile::default();
+    for line in text.lines() {
+        let Some((stack, count)) = line.trim().rsplit_once(' ') else {
+            continue;
+        };
+        let Ok(count) = count.parse::<u64>() else {
+            continue;
+        };
+        profile.total += count;
+        let names = stack
+            .split(';')
+            .map(frame_function_name)
+            .filter(|name| !name.is_empty())
+            .collect::<Vec<String>>();
+        if let Some(leaf) = names.last() {
+            *profile.self_samples.entry(leaf.clone()).or_insert(0) += count;
+        }
+        // 递归调用时同一个函数只计算一次
+        for name in names.into_iter().collect::<HashSet<String>>() {
+            *profile.total_samples.entry(name).or_insert(0) += count;
+        }
+    }
+    profile
+}
+
+impl Graph {
+    /**
+     * 根据标签中的函数名把采样数据对应到函数节点上
+     */
+    pub fn apply_profile(&mut self, profile: &Profile) {
+        for node in self.nodes.iter_mut() {
+            node.profile = None;
+            if node.level == 0
+                || !matches!(
+                    node.block_type,
+                    CodeBlockType::FUNCTION | CodeBlockType::COMPONENT
+                )
+            {
+                continue;
+            }
+            let total_samples = node.label.split(' ').find_map(|word| {
+                profile
+                    .total_samples
+                    .get(word)
+                    .map(|samples| (word, *samples))
+            });
+            if let Some((name, total_samples)) = total_samples {
+                node.profile = Some(ProfileSample {
+                    self_samples: profile.self_samples.get(name).copied().unwrap_or(0),
+                    total_samples,
+                    ratio: total_samples as f32 / profile.total.max(1) as f32,
+                    heat: 0.0,
+                });
+            }
+        }
+        // 归一化
+        let max_samples = self
+            .nodes
+            .iter()
+            .filter_map(|node| node.profile.as_ref())
+            .map(|sample| sample.total_samples)
+            .max()
+            .unwrap_or(0);
+        for sample in self
+            .nodes
+            .iter_mut()
+            .filter_map(|node| node.profile.as_mut())
+        {
+            sample.heat = sample.total_samples as f32 / max_samples.max(1) as f32;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CodeNode, CodeNodeIndex};
+
+    #[test]
+    fn frame_function_name_strips_paths_generics_and_hashes() {
+        assert_eq!(
+            frame_function_name("code_graph::graph::Graph::layout::h0123456789abcdef"),
+            "layout"
+        );
+        assert_eq!(
+            frame_function_name("<alloc::vec::Vec<T> as core::ops::Drop>::drop"),
+            "drop"
+        );
+        assert_eq!(frame_function_name("main.parse (/src/main.go:10)"), "parse");
+    }
+
+    #[test]
+    fn parse_collapsed_counts_self_and_total_samples() {
+        let profile = parse_collapsed(
+            "main;run;parse 30\nmain;run 10\nmain;run;run;parse 5\ninvalid line\nmain;draw x\n",
+        );
+        assert_eq!(profile.total(), 45);
+        assert_eq!(profile.self_samples.get("parse"), Some(&35));
+        assert_eq!(profile.self_samples.get("run"), Some(&10));
+        assert_eq!(profile.self_samples.get("main"), None);
+        // 递归的 run 只计算一次
+        assert_eq!(profile.total_samples.get("run"), Some(&45));
+        assert_eq!(profile.total_samples.get("main"), Some(&45));
+        assert!(parse_collapsed("").is_empty());
+    }
+
+    #[test]
+    fn apply_profile_puts_samples_on_function_nodes() {
+        let mut graph = Graph::new();
+        graph.add_node(CodeNode::new(
+            "a.rs",
+            "a.rs",
+            "",
+            0,
+            CodeBlockType::NORMAL,
+            0,
+        ));
+        for name in ["run", "parse", "idle"] {
+            let label = format!("fn {}", name);
+            graph.add_node(CodeNode::new(
+                name,
+                &label,
+                "",
+                1,
+                CodeBlockType::FUNCTION,
+                1,
+            ));
+        }
+        graph.apply_profile(&parse_collapsed("main;run;parse 30\nmain;run 10\n"));
+        let sample = |index: usize| graph.node(CodeNodeIndex(index)).unwrap().profile.clone();
+        assert!(sample(0).is_none() && sample(3).is_none());
+        let run = sample(1).unwrap();
+        assert_eq!((run.self_samples, run.total_samples), (10, 40));
+        assert_eq!((run.ratio, run.heat), (1.0, 1.0));
+        let parse = sample(2).unwrap();
+        assert_eq!((parse.self_samples, parse.total_samples), (30, 30));
+        assert_eq!(parse.heat, 0.75);
+    }
+}
@@ -0,0 +1,148 @@
+use crate::{CodeBlockType, CodeNode, CodeNodeIndex, Edge, Graph};
+
+/**
+ * 图中满足条件的节点集合，边只保留两端都在集合内的
+ */
+pub struct SubGraph<'a> {
+    graph: &'a Graph,
+    indices: Vec<CodeNodeIndex>,
+}
+
+impl<'a> SubGraph<'a> {
+    pub fn graph(&self) -> &'a Graph {
+        self.graph
+    }
+
+    pub fn indices(&self) -> &[CodeNodeIndex] {
+        &self.indices
+    }
+
+    pub fn len(&self) -> usize {
+        self.indices.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    pub fn contains(&self, index: CodeNodeIndex) -> bool {
+        self.indices.contains(&index)
+    }
+
+    pub fn nodes(&self) -> impl Iterator<Item = (CodeNodeIndex, &'a CodeNode)> + '_ {
+        let graph = self.graph;
+        self.indices
+            .iter()
+            .filter_map(move |index| graph.node(*index).map(|node| (*index, node)))
+    }
+
+    pub fn edges(&self) -> impl Iterator<Item = &'a Edge> + '_ {
+        self.graph
+            .edges()
+            .filter(|edge| self.contains(edge.from()) && self.contains(edge.to()))
+    }
+
+    /**
+     * 复制为独立的图，节点按原顺序重新编号
+     */
+    pub fn to_graph(&self) -> Graph {
+        let mut graph = Graph::new();
+        let mut index_map = vec![None; self.graph.node_count()];
+        for (index, node) in self.nodes() {
+            index_map[index.0] = Some(graph.add_node(node.clone()));
+        }
+        for edge in self.edges() {
+            if let (Some(from), Some(to)) = (index_map[edge.from().0], index_map[edge.to().0]) {
+                graph.add_edge_with_kind(from, to, edge.kind());
+            }
+        }
+        graph
+    }
+}
+
+impl Graph {
+    /**
+     * 筛选满足条件的节点
+     */
+    pub fn filter(&self, predicate: impl Fn(&CodeNode) -> bool) -> SubGraph<'_> {
+        SubGraph {
+            graph: self,
+            indices: self
+                .nodes()
+                .filter(|(_, node)| predicate(node))
+                .map(|(index, _)| index)
+                .collect(),
+        }
+    }
+
+    /**
+     * 节点及其满足条件的后代，后代沿包含关系查找
+     */
+    pub fn subtree(
+        &self,
+        root: CodeNodeIndex,
+        predicate: impl Fn(&CodeNode) -> bool,
+    ) -> SubGraph<'_> {
+        let mut indices = vec![root];
+        indices.extend(
+            self.descendants(root)
+                .filter(|index| self.node(*index).is_some_and(&predicate)),
+        );
+        SubGraph {
+            graph: self,
+            indices,
+        }
+    }
+
+    /**
+     * 从节点出发不超过 hops 步能到达的节点，包含和调用关系都沿两个方向查找
+     */
+    pub fn neighborhood(&self, center: CodeNodeIndex, hops: usize) -> SubGraph<'_> {
+        // 每个节点离中心的步数，没有到达的为 None
+        let mut distance = vec![None; self.node_count()];
+        let mut indices = vec![];
+        if center.0 < distance.len() {
+            distance[center.0] = Some(0);
+            indices.push(center);
+        }
+        for hop in 0..hops {
+            let reached = indices.len();
+            for edge in self.edges() {
+                for (from, to) in [(edge.from(), edge.to()), (edge.to(), edge.from())] {
+                    if distance[from.0] == Some(hop) && distance[to.0].is_none() {
+                        distance[to.0] = Some(hop + 1);
+                        indices.push(to);
+                    }
+                }
+            }
+            if indices.len() == reached {
+                break;
+            }
+        }
+        SubGraph {
+            graph: self,
+            indices,
+        }
+    }
+
+    /**
+     * 查找指定类型的节点
+     */
+    pub fn find_by_type(&self, block_type: &CodeBlockType) -> Vec<CodeNodeIndex> {
+        self.filter(|node| node.block_type() == block_type).indices
+    }
+
+    /**
+     * 按名称查找定义节点，名称需与标签中的某个单词完全一致
+     */
+    pub fn find_by_name(&self, name: &str) -> Vec<CodeNodeIndex> {
+        self.filter(|node| {
+            node.level() > 0
+                && node.block_type() != &CodeBlockType::CALL
+                // hook 节点是调用位置而不是定义
+                && node.block_type() != &CodeBlockType::HOOK
+                && node.label.split(' ').any(|word| word == name)
+        })
+        .indices
+    }
+}
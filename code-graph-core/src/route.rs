@@ -0,0 +1,285 @@
+use std::collections::HashMap;
+
+use emath::{Pos2, Rect};
+
+use crate::{CodeNodeIndex, EdgeKind, EdgeRouting, Graph};
+
+// 相邻平行线段之间的距离
+const TRACK_SPACING: f32 = 6.0;
+// 连线与节点之间的最小距离
+const MARGIN: f32 = 12.0;
+
+/**
+ * 一条边的走线，points 为依次经过的点（图坐标）
+ */
+#[derive(Debug, Clone)]
+pub struct EdgeRoute {
+    pub from: CodeNodeIndex,
+    pub to: CodeNodeIndex,
+    pub kind: EdgeKind,
+    pub points: Vec<Pos2>,
+}
+
+/**
+ * 为所有可见的边计算正交走线：
+ * 包含关系在层与层之间的空隙中走线，不同父节点的横线分到不同的轨道上；
+ * 调用关系绕到跨越范围内所有节点的外侧，重叠的调用边使用不同的轨道，避免穿过节点
+ */
+pub fn route_edges(graph: &Graph, routing: EdgeRouting) -> Vec<EdgeRoute> {
+    let rect_of = |index: CodeNodeIndex| {
+        graph
+            .node(index)
+            .filter(|node| node.visiable())
+            .map(|node| Rect::from_min_size(node.position(), node.size()))
+    };
+    let rects = graph
+        .nodes()
+        .filter_map(|(index, _)| rect_of(index))
+        .collect::<Vec<Rect>>();
+    let mut routes = vec![];
+    // 每个父节点下所有可见的子节点
+    let mut children: Vec<(CodeNodeIndex, Vec<CodeNodeIndex>)> = vec![];
+    let mut calls = vec![];
+    for edge in graph.edges() {
+        // 折叠的分组中隐藏的节点由分组的代表节点代替，合并后重复的边只保留一条
+        let from = graph.cluster_representative(edge.from());
+        let to = graph.cluster_representative(edge.to());
+        if from == to || rect_of(from).is_none() || rect_of(to).is_none() {
+            continue;
+        }
+        match edge.kind() {
+            EdgeKind::Contains => match children.iter_mut().find(|(parent, _)| *parent == from) {
+                Some((_, targets)) => {
+                    if !targets.contains(&to) {
+                        targets.push(to);
+                    }
+                }
+                None => children.push((from, vec![to])),
+            },
+            kind => {
+                if !calls.contains(&(from, to, kind)) {
+                    calls.push((from, to, kind));
+                }
+            }
+        }
+    }
+
+    // 包含关系
+    let buses = children
+        .iter()
+        .filter_map(|(from, targets)| {
+            let from_rect = rect_of(*from)?;
+            let target_rects = targets.iter().filter_map(|to| rect_of(*to)).collect();
+            Some((*from, from_rect, targets, target_rects))
+        })
+        .collect::<Vec<(CodeNodeIndex, Rect, &Vec<CodeNodeIndex>, Vec<Rect>)>>();
+    match routing {
+        EdgeRouting::Branch => {
+            for (from, from_rect, targets, target_rects) in &buses {
+                let start = from_rect.left_center();
+                let branch_x = start.x - 10.0;
+                for (to, to_rect) in targets.iter().zip(target_rects) {
+                    let end = to_rect.left_center();
+                    routes.push(EdgeRoute {
+                        from: *from,
+                        to: *to,
+                        kind: EdgeKind::Contains,
+                        points: vec![
+                            start,
+                            Pos2::new(branch_x, start.y),
+                            Pos2::new(branch_x, end.y),
+                            end,
+                        ],
+                    });
+                }
+            }
+        }
+        EdgeRouting::TopDown | EdgeRouting::LeftRight => {
+            let vertical = routing == EdgeRouting::TopDown;
+            // 沿主方向和横向的坐标，竖直方向布局时主方向为 y
+            let main = |pos: Pos2| if vertical { pos.y } else { pos.x };
+            let cross = |pos: Pos2| if vertical { pos.x } else { pos.y };
+            let point = |main: f32, cross: f32| {
+                if vertical {
+                    Pos2::new(cross, main)
+                } else {
+                    Pos2::new(main, cross)
+                }
+            };
+            let exit = |rect: &Rect| {
+                if vertical {
+                    rect.center_bottom()
+                } else {
+                    rect.right_center()
+                }
+            };
+            let entry = |rect: &Rect| {
+                if vertical {
+                    rect.center_top()
+                } else {
+                    rect.left_center()
+                }
+            };
+            // 出口位置相同的父节点共用一个空隙，在其中分配轨道
+            let mut gaps: HashMap<i64, Vec<usize>> = HashMap::new();
+            for (bus, (_, from_rect, _, _)) in buses.iter().enumerate() {
+                gaps.entry(main(exit(from_rect)).round() as i64)
+                    .or_default()
+                    .push(bus);
+            }
+            for members in gaps.values() {
+                let spans = members
+                    .iter()
+                    .map(|bus| {
+                        let (_, from_rect, _, target_rects) = &buses[*bus];
+                        target_rects.iter().fold(
+                            (cross(exit(from_rect)), cross(exit(from_rect))),
+                            |(min, max), rect| {
+                                (min.min(cross(entry(rect))), max.max(cross(entry(rect))))
+                            },
+                        )
+                    })
+                    .collect::<Vec<(f32, f32)>>();
+                let (tracks, track_count) = assign_tracks(&spans);
+                let gap_start = members
+                    .iter()
+                    .map(|bus| main(exit(&buses[*bus].1)))
+                    .fold(f32::MIN, f32::max);
+                let gap_end = members
+                    .iter()
+                    .flat_map(|bus| buses[*bus].3.iter().map(|rect| main(entry(rect))))
+                    .fold(f32::MAX, f32::min);
+                let step = (gap_end - gap_start) / (track_count + 1) as f32;
+                for (member, bus) in members.iter().enumerate() {
+                    let (from, from_rect, targets, target_rects) = &buses[*bus];
+                    let start = exit(from_rect);
+                    let channel = gap_start + step * (tracks[member] + 1) as f32;
+                    for (to, to_rect) in targets.iter().zip(target_rects) {
+                        let end = entry(to_rect);
+                        routes.push(EdgeRoute {
+                            from: *from,
+                            to: *to,
+                            kind: EdgeKind::Contains,
+                            points: vec![
+                                start,
+                                point(channel, cross(start)),
+                                point(channel, cross(end)),
+                                end,
+                            ],
+                        });
+                    }
+                }
+            }
+        }
+        EdgeRouting::Straight => {
+            for (from, from_rect, targets, target_rects) in &buses {
+                for (to, to_rect) in targets.iter().zip(target_rects) {
+                    routes.push(EdgeRoute {
+                        from: *from,
+                        to: *to,
+                        kind: EdgeKind::Contains,
+                        points: vec![from_rect.center(), to_rect.center()],
+                    });
+                }
+            }
+        }
+    }
+
+    // 调用关系，重复关系的走线与调用关系相同
+    let call_rects = calls
+        .iter()
+        .filter_map(|(from, to, kind)| Some((*from, *to, *kind, rect_of(*from)?, rect_of(*to)?)))
+        .collect::<Vec<(CodeNodeIndex, CodeNodeIndex, EdgeKind, Rect, Rect)>>();
+    if routing == EdgeRouting::Straight {
+        for (from, to, kind, from_rect, to_rect) in call_rects {
+            routes.push(EdgeRoute {
+                from,
+                to,
+                kind,
+                points: vec![from_rect.center(), to_rect.center()],
+            });
+        }
+        return routes;
+    }
+    // 竖直方向的布局中，调用边从节点下方的空隙出入，避免横穿同一层的节点
+    let below = routing == EdgeRouting::TopDown;
+    let ends = call_rects
+        .iter()
+        .map(|(_, _, _, from_rect, to_rect)| {
+            if below {
+                (
+                    from_rect.bottom() + MARGIN / 2.0,
+                    to_rect.bottom() + MARGIN / 2.0,
+                )
+            } else {
+                (from_rect.center().y, to_rect.center().y)
+            }
+        })
+        .collect::<Vec<(f32, f32)>>();
+    let spans = ends
+        .iter()
+        .map(|(from_y, to_y)| (from_y.min(*to_y), from_y.max(*to_y)))
+        .collect::<Vec<(f32, f32)>>();
+    let (tracks, _) = assign_tracks(&spans);
+    for (call, (from, to, kind, from_rect, to_rect)) in call_rects.into_iter().enumerate() {
+        let (min_y, max_y) = spans[call];
+        // 绕到跨越范围内所有节点的右侧
+        let outside = rects
+            .iter()
+            .filter(|rect| rect.bottom() >= min_y && rect.top() <= max_y)
+            .map(|rect| rect.right())
+            .fold(from_rect.right().max(to_rect.right()), f32::max);
+        let channel = outside + MARGIN + tracks[call] as f32 * TRACK_SPACING;
+        let (from_y, to_y) = ends[call];
+        let points = if below {
+            let (start, end) = (from_rect.center_bottom(), to_rect.center_bottom());
+            vec![
+                start,
+                Pos2::new(start.x, from_y),
+                Pos2::new(channel, from_y),
+                Pos2::new(channel, to_y),
+                Pos2::new(end.x, to_y),
+                end,
+            ]
+        } else {
+            let (start, end) = (from_rect.right_center(), to_rect.right_center());
+            vec![
+                start,
+                Pos2::new(channel, from_y),
+                Pos2::new(channel, to_y),
+                end,
+            ]
+        };
+        routes.push(EdgeRoute {
+            from,
+            to,
+            kind,
+            points,
+        });
+    }
+    routes
+}
+
+/**
+ * 区间着色：重叠的区间分到不同的轨道，返回每个区间的轨道和轨道总数
+ */
+fn assign_tracks(spans: &[(f32, f32)]) -> (Vec<usize>, usize) {
+    let mut order = (0..spans.len()).collect::<Vec<usize>>();
+    order.sort_by(|left, right| spans[*left].0.total_cmp(&spans[*right].0));
+    // 每条轨道上最后一个区间的结束位置
+    let mut track_ends: Vec<f32> = vec![];
+    let mut tracks = vec![0; spans.len()];
+    for index in order {
+        let (start, end) = spans[index];
+        let track = match track_ends.iter().position(|track_end| *track_end < start) {
+            Some(track) => track,
+            None => {
+                track_ends.push(f32::MIN);
+                track_ends.len() - 1
+            }
+        };
+        track_ends[track] = end;
+        tracks[index] = track;
+    }
+    (tracks, track_ends.len())
+}
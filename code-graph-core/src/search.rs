@@ -0,0 +1,179 @@
+use crate::{CodeBlockType, CodeNode};
+
+/**
+ * 模糊匹配的结果
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FuzzyMatch {
+    // 分数越高越靠前
+    pub score: i32,
+    // 匹配到的字符在文本中的位置（按字符计数）
+    pub positions: Vec<usize>,
+}
+
+/**
+ * 单词的开头：文本开头、分隔符之后或小写字母之后的大写字母
+ */
+fn is_word_start(previous: Option<char>, current: char) -> bool {
+    match previous {
+        None => true,
+        Some(previous) => {
+            !previous.is_alphanumeric() || (previous.is_lowercase() && current.is_uppercase())
+        }
+    }
+}
+
+/**
+ * 按顺序在文本中查找查询的每个字符（忽略大小写），全部找到时返回匹配结果，
+ * 连续匹配和匹配在单词开头时得分更高，跳过的字符越多得分越低，
+ * 第一个字符尝试所有可能的位置，取得分最高的匹配，例如 fuzzy 在 fn fuzzy_match 中匹配后一个单词
+ */
+pub fn fuzzy_match(query: &str, text: &str) -> Option<FuzzyMatch> {
+    let query = query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect::<Vec<char>>();
+    let first_char = *query.first()?;
+    let chars = text.chars().collect::<Vec<char>>();
+    let lower = chars
+        .iter()
+        .map(|c| c.to_lowercase().next().unwrap_or(*c))
+        .collect::<Vec<char>>();
+    (0..lower.len())
+        .filter(|index| lower[*index] == first_char)
+        .filter_map(|first| {
+            let positions = match_from(&query, &chars, &lower, first)?;
+            let score = score_positions(&chars, &positions);
+            Some(FuzzyMatch { score, positions })
+        })
+        .max_by(|a, b| a.score.cmp(&b.score).then(b.positions.cmp(&a.positions)))
+}
+
+/**
+ * 第一个字符匹配在 first 处，之后的字符依次向后匹配：能与上一个字符连续时连续匹配，
+ * 否则尽量匹配单词开头，但要保证剩下的字符仍然能匹配
+ */
+fn match_from(query: &[char], chars: &[char], lower: &[char], first: usize) -> Option<Vec<usize>> {
+    let mut positions = vec![first];
+    for (query_index, query_char) in query.iter().enumerate().skip(1) {
+        let start = positions.last().map_or(0, |last| last + 1);
+        let next = (start..lower.len()).find(|index| lower[*index] == *query_char)?;
+        let position = if next == start {
+            next
+        } else {
+            (next..lower.len())
+                .find(|index| {
+                    lower[*index] == *query_char
+                        && is_word_start(index.checked_sub(1).map(|i| chars[i]), chars[*index])
+                        && rest_matches(&query[query_index + 1..], &lower[index + 1..])
+                })
+                .unwrap_or(next)
+        };
+        positions.push(position);
+    }
+    Some(positions)
+}
+
+fn score_positions(chars: &[char], positions: &[usize]) -> i32 {
+    let mut score = 0;
+    for (index, position) in positions.iter().enumerate() {
+        score += 1;
+        if is_word_start(position.checked_sub(1).map(|i| chars[i]), chars[*position]) {
+            score += 8;
+        }
+        match index.checked_sub(1).map(|previous| positions[previous]) {
+            Some(previous) if previous + 1 == *position => score += 5,
+            Some(previous) => score -= (*position - previous - 1).min(5) as i32,
+            None => {}
+        }
+    }
+    // 同样匹配时较短的文本更靠前
+    score - (chars.len() / 8) as i32
+}
+
+fn rest_matches(query: &[char], text: &[char]) -> bool {
+    let mut text = text.iter();
+    query.iter().all(|c| text.any(|t| t == c))
+}
+
+/**
+ * 在定义中模糊搜索标签，按分数从高到低返回最多 limit 个（定义序号，匹配结果），
+ * 调用和 hook 节点不是定义，不参与搜索
+ */
+pub fn search_symbols(
+    definitions: &[CodeNode],
+    query: &str,
+    limit: usize,
+) -> Vec<(usize, FuzzyMatch)> {
+    let mut matches = definitions
+        .iter()
+        .enumerate()
+        .filter(|(_, node)| {
+            node.level() > 0
+                && !matches!(node.block_type(), CodeBlockType::CALL | CodeBlockType::HOOK)
+        })
+        .filter_map(|(index, node)| Some((index, fuzzy_match(query, node.label.trim())?)))
+        .collect::<Vec<(usize, FuzzyMatch)>>();
+    matches.sort_by(|(a_index, a), (b_index, b)| b.score.cmp(&a.score).then(a_index.cmp(b_index)));
+    matches.truncate(limit);
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requires_every_query_char_in_order() {
+        assert!(fuzzy_match("abc", "a_b_c").is_some());
+        assert!(fuzzy_match("cba", "a_b_c").is_none());
+        assert!(fuzzy_match("", "abc").is_none());
+    }
+
+    #[test]
+    fn ignores_case_and_whitespace_in_the_query() {
+        let found = fuzzy_match("Fuzzy M", "fn fuzzy_match").unwrap();
+        assert_eq!(found.positions, vec![3, 4, 5, 6, 7, 9]);
+    }
+
+    #[test]
+    fn prefers_word_starts() {
+        assert_eq!(
+            fuzzy_match("fm", "fuzzy_match").unwrap().positions,
+            vec![0, 6]
+        );
+        assert_eq!(
+            fuzzy_match("gn", "getNodeName").unwrap().positions,
+            vec![0, 3]
+        );
+        // 第一个字符取得分最高的位置
+        assert_eq!(
+            fuzzy_match("fuzzy", "fn fuzzy_match").unwrap().positions,
+            vec![3, 4, 5, 6, 7]
+        );
+    }
+
+    #[test]
+    fn consecutive_matches_score_higher() {
+        let consecutive = fuzzy_match("node", "node_index").unwrap();
+        let scattered = fuzzy_match("node", "no_other_device").unwrap();
+        assert!(consecutive.score > scattered.score);
+    }
+
+    #[test]
+    fn search_skips_calls_and_sorts_by_score() {
+        let definitions = vec![
+            CodeNode::new("a.rs", "a.rs", "", 0, CodeBlockType::NORMAL, 0),
+            CodeNode::new("call", "parse", "", 1, CodeBlockType::CALL, 2),
+            CodeNode::new("b", "fn prepare_source", "", 2, CodeBlockType::FUNCTION, 1),
+            CodeNode::new("c", "fn parse", "", 3, CodeBlockType::FUNCTION, 1),
+        ];
+        let found = search_symbols(&definitions, "pars", 10)
+            .into_iter()
+            .map(|(index, _)| index)
+            .collect::<Vec<usize>>();
+        assert_eq!(found, vec![3, 2]);
+        assert_eq!(search_symbols(&definitions, "pars", 1).len(), 1);
+    }
+}
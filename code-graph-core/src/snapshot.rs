@@ -0,0 +1,319 @@
+use std::{
+    collections::{HashMap, HashSet},
+    ffi::OsStr,
+    fs,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    compare::{is_renamed, outermost},
+    patch::{add_child, group_node},
+    read_source, recursion_dir, valid_file_extention, CodeGraphError, CodeNode, CodeNodeIndex,
+    EdgeKind, Graph, GraphBuilder, Result, Tree, TreeType,
+};
+
+/**
+ * 某一时刻项目的符号图，节点 ID 使用相对路径，使得不同时间的快照可以对应
+ */
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub name: String,
+    // 创建时间（unix 时间戳）
+    pub created: i64,
+    pub graph: Graph,
+}
+
+impl Snapshot {
+    /**
+     * 扫描项目生成快照
+     */
+    pub fn capture(name: &str, root: &Path) -> Result<Self> {
+        let created = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs() as i64)
+            .unwrap_or(0);
+        Ok(Self {
+            name: name.to_owned(),
+            created,
+            graph: project_graph(root)?,
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let file = fs::File::create(path)?;
+        serde_json::to_writer(file, self).map_err(|err| CodeGraphError::Io(err.into()))
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = read_source(path)?;
+        serde_json::from_str(&text)
+            .map_err(|err| CodeGraphError::Parse(format!("快照格式错误: {}", err)))
+    }
+}
+
+/**
+ * 扫描项目中的定义和调用关系，文件路径使用相对 root 的路径，
+ * 文件根节点不保存代码内容，减小快照体积
+ */
+pub fn project_graph(root: &Path) -> Result<Graph> {
+    let mut pathes: Vec<PathBuf> = vec![];
+    recursion_dir(root, &mut pathes, Tree::new("", "", TreeType::Directory))?;
+    pathes.sort();
+    let mut sources = vec![];
+    for path in pathes {
+        let ext = path.extension().and_then(OsStr::to_str).unwrap_or("");
+        if !valid_file_extention(ext) {
+            continue;
+        }
+        // 无法读取的文件直接跳过
+        let Ok(code) = read_source(&path) else {
+            continue;
+        };
+        let relative_path = path.strip_prefix(root).unwrap_or(&path);
+        sources.push((relative_path.to_str().unwrap_or("").to_owned(), code));
+    }
+    sources_graph(&sources)
+}
+
+/**
+ * 由内存中的（相对路径，代码）生成符号图，文件根节点不保存代码内容，历史版本的图也由此生成
+ */
+pub(crate) fn sources_graph(sources: &[(String, String)]) -> Result<Graph> {
+    let mut builder = GraphBuilder::new();
+    for (path, code) in sources {
+        builder = builder.add_source(path, code)?;
+    }
+    let mut graph = builder.resolve_calls().build();
+    for (_, node) in graph.nodes_mut() {
+        if node.level() == 0 {
+            node.block.clear();
+        }
+    }
+    Ok(graph)
+}
+
+/**
+ * 两个时间点之间符号图的差异
+ */
+#[derive(Debug, Clone, Default)]
+pub struct SnapshotDiff {
+    pub added: Vec<CodeNode>,
+    pub removed: Vec<CodeNode>,
+    pub renamed: Vec<(CodeNode, CodeNode)>,
+    // 调用边用（调用方，被调用方）表示
+    pub added_calls: Vec<(CodeNode, CodeNode)>,
+    pub removed_calls: Vec<(CodeNode, CodeNode)>,
+}
+
+/**
+ * 图中所有调用边两端节点的 ID
+ */
+fn call_edges(graph: &Graph) -> HashSet<(&str, &str)> {
+    graph
+        .edges()
+        .filter(|edge| edge.kind() == EdgeKind::Calls)
+        .filter_map(|edge| Some((graph.node(edge.from())?.id(), graph.node(edge.to())?.id())))
+        .collect()
+}
+
+/**
+ * 对比旧快照和当前的符号图
+ */
+pub fn diff_graphs(old: &Graph, new: &Graph) -> SnapshotDiff {
+    let id_map = |graph: &Graph| {
+        graph
+            .nodes()
+            .filter(|(_, node)| node.level() > 0)
+            .map(|(index, node)| (node.id().to_owned(), index))
+            .collect::<HashMap<String, CodeNodeIndex>>()
+    };
+    let (old_ids, new_ids) = (id_map(old), id_map(new));
+    let missing = |graph: &Graph, ids: &HashMap<String, CodeNodeIndex>| {
+        graph
+            .nodes()
+            .filter(|(_, node)| node.level() > 0 && !ids.contains_key(node.id()))
+            .map(|(index, _)| index)
+            .collect::<Vec<CodeNodeIndex>>()
+    };
+    let removed = outermost(old, &missing(old, &new_ids));
+    let mut added = outermost(new, &missing(new, &old_ids));
+
+    let mut diff = SnapshotDiff::default();
+    for old_index in removed {
+        let Some(old_node) = old.node(old_index) else {
+            continue;
+        };
+        let renamed_to = added.iter().position(|new_index| {
+            new.node(*new_index)
+                .is_some_and(|new_node| is_renamed(old_node, new_node))
+        });
+        match renamed_to {
+            Some(position) => {
+                let new_index = added.remove(position);
+                let new_node = new.node(new_index).cloned().unwrap_or_default();
+                diff.renamed.push((old_node.clone(), new_node));
+            }
+            None => diff.removed.push(old_node.clone()),
+        }
+    }
+    diff.added = added
+        .into_iter()
+        .filter_map(|index| new.node(index).cloned())
+        .collect();
+
+    let (old_calls, new_calls) = (call_edges(old), call_edges(new));
+    let edge_nodes =
+        |graph: &Graph, ids: &HashMap<String, CodeNodeIndex>, (from, to): (&str, &str)| {
+            let node = |id: &str| ids.get(id).and_then(|index| graph.node(*index)).cloned();
+            Some((node(from)?, node(to)?))
+        };
+    let mut added_calls = new_calls
+        .difference(&old_calls)
+        .copied()
+        .collect::<Vec<_>>();
+    let mut removed_calls = old_calls
+        .difference(&new_calls)
+        .copied()
+        .collect::<Vec<_>>();
+    added_calls.sort();
+    removed_calls.sort();
+    diff.added_calls = added_calls
+        .into_iter()
+        .filter_map(|edge| edge_nodes(new, &new_ids, edge))
+        .collect();
+    diff.removed_calls = removed_calls
+        .into_iter()
+        .filter_map(|edge| edge_nodes(old, &old_ids, edge))
+        .collect();
+    diff
+}
+
+/**
+ * 生成快照差异的图：根节点下按差异类型分组，节点路径为 root 下的完整路径
+ */
+pub fn build_snapshot_graph(diff: &SnapshotDiff, snapshot_name: &str, root: &Path) -> Graph {
+    let with_full_path = |node: &CodeNode| {
+        let mut node = node.clone();
+        node.file_path = root.join(&node.file_path).to_str().unwrap_or("").to_owned();
+        node
+    };
+    let call_node = |(from, to): &(CodeNode, CodeNode)| {
+        let mut node = with_full_path(from);
+        node.label = format!("{} → {}", from.label.trim(), to.label.trim());
+        node
+    };
+    let mut graph = Graph::new();
+    let title = format!("快照 {} → 当前", snapshot_name);
+    let root_index = graph.add_node(group_node(snapshot_name, &title));
+    let groups = [
+        (
+            "added",
+            "新增",
+            diff.added.iter().map(with_full_path).collect::<Vec<_>>(),
+        ),
+        (
+            "removed",
+            "删除",
+            diff.removed.iter().map(with_full_path).collect(),
+        ),
+        (
+            "renamed",
+            "重命名",
+            diff.renamed
+                .iter()
+                .map(|(old, new)| {
+                    let mut node = with_full_path(new);
+                    node.label = format!("{} → {}", old.label.trim(), new.label.trim());
+                    node
+                })
+                .collect(),
+        ),
+        (
+            "added-calls",
+            "新增调用",
+            diff.added_calls.iter().map(call_node).collect(),
+        ),
+        (
+            "removed-calls",
+            "删除调用",
+            diff.removed_calls.iter().map(call_node).collect(),
+        ),
+    ];
+    for (id, title, nodes) in groups {
+        if nodes.is_empty() {
+            continue;
+        }
+        let group_index = add_child(
+            &mut graph,
+            root_index,
+            group_node(id, &format!("{} ({})", title, nodes.len())),
+            1,
+        );
+        for node in nodes {
+            add_child(&mut graph, group_index, node, 2);
+        }
+    }
+    graph
+}
+
+#[cfg(all(test, feature = "lang-rust"))]
+mod tests {
+    use super::*;
+
+    fn graph(code: &str) -> Graph {
+        sources_graph(&[("a.rs".to_owned(), code.to_owned())]).unwrap()
+    }
+
+    fn labels(nodes: &[CodeNode]) -> Vec<&str> {
+        nodes.iter().map(|node| node.label.trim()).collect()
+    }
+
+    fn call_labels(calls: &[(CodeNode, CodeNode)]) -> Vec<(&str, &str)> {
+        calls
+            .iter()
+            .map(|(from, to)| (from.label.trim(), to.label.trim()))
+            .collect()
+    }
+
+    #[test]
+    fn diff_graphs_finds_added_removed_and_renamed_definitions() {
+        let old = graph(
+            "fn keep() { helper(); }\nfn helper() {}\nfn gone() {}\n\
+             fn old_name(x: u32) -> u32 { x * 2 + 1 }\n",
+        );
+        let new = graph(
+            "fn keep() {}\nfn helper() {}\nfn fresh() { helper(); }\n\
+             fn new_name(x: u32) -> u32 { x * 2 + 1 }\n",
+        );
+        let diff = diff_graphs(&old, &new);
+        assert_eq!(labels(&diff.removed), vec!["fn gone"]);
+        assert_eq!(labels(&diff.added), vec!["fn fresh"]);
+        assert_eq!(diff.renamed.len(), 1);
+        assert_eq!(
+            (
+                diff.renamed[0].0.label.trim(),
+                diff.renamed[0].1.label.trim()
+            ),
+            ("fn old_name", "fn new_name")
+        );
+        assert_eq!(
+            call_labels(&diff.added_calls),
+            vec![("fn fresh", "fn helper")]
+        );
+        assert_eq!(
+            call_labels(&diff.removed_calls),
+            vec![("fn keep", "fn helper")]
+        );
+    }
+
+    #[test]
+    fn diff_graphs_of_same_code_is_empty() {
+        let code = "struct A;\nimpl A {\n    fn run(&self) { helper(); }\n}\nfn helper() {}\n";
+        let diff = diff_graphs(&graph(code), &graph(code));
+        assert!(diff.added.is_empty() && diff.removed.is_empty() && diff.renamed.is_empty());
+        assert!(diff.added_calls.is_empty() && diff.removed_calls.is_empty());
+    }
+}
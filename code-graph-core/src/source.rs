@@ -0,0 +1,240 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use crate::{symbol_cache::content_hash, valid_file_extention, CodeGraphError, Result};
+
+/**
+ * 可能受支持的文件扩展名，实际是否支持取决于启用的语言
+ */
+const KNOWN_EXTENSIONS: [&str; 9] = ["rs", "java", "c", "h", "js", "jsx", "py", "ts", "tsx"];
+
+/**
+ * 临时目录下用于存放项目副本的目录，name 中的特殊字符会被替换，
+ * 目录名后加上 name 的哈希，替换后相同的不同 name 不会共用一个目录
+ */
+pub fn cache_dir(name: &str) -> PathBuf {
+    let readable = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect::<String>();
+    std::env::temp_dir().join("code-graph").join(format!(
+        "{}-{:016x}",
+        readable,
+        content_hash(name)
+    ))
+}
+
+/**
+ * 清空目录，不存在时创建
+ */
+fn reset_dir(dir: &Path) -> Result<()> {
+    if dir.exists() {
+        fs::remove_dir_all(dir)?;
+    }
+    fs::create_dir_all(dir)?;
+    Ok(())
+}
+
+/**
+ * 执行命令，失败时返回带标准错误输出的错误
+ */
+fn run_command(command: &mut Command) -> Result<()> {
+    let output = command.output()?;
+    if !output.status.success() {
+        return Err(CodeGraphError::Command(format!(
+            "{}: {}",
+            command.get_program().to_string_lossy(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(())
+}
+
+/**
+ * 用单引号包裹，作为远程 shell 的参数
+ */
+fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "'\\''"))
+}
+
+/**
+ * 解析 user@host:/path 形式的远程地址，以 - 开头的主机会被 ssh 当作选项，不允许使用
+ */
+pub fn parse_remote(location: &str) -> Option<(&str, &str)> {
+    let (host, dir) = location.split_once(':')?;
+    (!host.is_empty() && !host.starts_with('-') && !dir.is_empty()).then_some((host, dir))
+}
+
+/**
+ * 通过 ssh 以只读方式把远程目录中受支持的源文件复制到 dest，
+ * 远程只执行 find 和 tar，所有文件通过一个连接传输
+ */
+pub fn fetch_remote_dir(location: &str, dest: &Path) -> Result<()> {
+    let (host, dir) = parse_remote(location).ok_or_else(|| {
+        CodeGraphError::Command(format!("远程地址格式应为 user@host:/path: {}", location))
+    })?;
+    let names = KNOWN_EXTENSIONS
+        .iter()
+        .filter(|ext| valid_file_extention(ext))
+        .map(|ext| format!("-name '*.{}'", ext))
+        .collect::<Vec<String>>()
+        .join(" -o ");
+    let script = format!(
+        "cd -- {} && find . -type f \\( {} \\) -print0 | tar -cf - --null -T -",
+        shell_quote(dir),
+        names
+    );
+    reset_dir(dest)?;
+    let mut ssh = Command::new("ssh")
+        .args(["-o", "BatchMode=yes", "--", host, &script])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let stdout = ssh
+        .stdout
+        .take()
+        .ok_or_else(|| CodeGraphError::Command("无法读取 ssh 输出".to_owned()))?;
+    let tar = run_command(
+        Command::new("tar")
+            .arg("-xf")
+            .arg("-")
+            .arg("-C")
+            .arg(dest)
+            .stdin(stdout),
+    );
+    let ssh = ssh.wait_with_output()?;
+    if !ssh.status.success() {
+        return Err(CodeGraphError::Command(format!(
+            "ssh {}: {}",
+            host,
+            String::from_utf8_lossy(&ssh.stderr).trim()
+        )));
+    }
+    tar
+}
+
+/**
+ * 支持打开的压缩包扩展名，.crate 是 gzip 压缩的 tar 包，
+ * 只压缩了单个文件的 .gz 和 .bz2 不是目录，不支持
+ */
+pub const ARCHIVE_EXTENSIONS: [&str; 6] = ["zip", "tar", "tar.gz", "tgz", "tar.bz2", "crate"];
+
+/**
+ * 将压缩包解压到 dest，zip 使用 unzip，其余格式使用 tar
+ */
+pub fn extract_archive(archive: &Path, dest: &Path) -> Result<()> {
+    let name = archive
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    if !ARCHIVE_EXTENSIONS
+        .iter()
+        .any(|ext| name.ends_with(&format!(".{}", ext)))
+    {
+        return Err(CodeGraphError::UnsupportedArchive(name));
+    }
+    let is_zip = name.ends_with(".zip");
+    reset_dir(dest)?;
+    if is_zip {
+        run_command(
+            Command::new("unzip")
+                .arg("-q")
+                .arg("-o")
+                .arg(archive)
+                .arg("-d")
+                .arg(dest),
+        )
+    } else {
+        // tar 会根据内容自动识别压缩格式
+        run_command(
+            Command::new("tar")
+                .arg("-xf")
+                .arg(archive)
+                .arg("-C")
+                .arg(dest),
+        )
+    }
+}
+
+/**
+ * 把项目目录在 revision（提交、分支或标签）时的内容导出到 dest，不修改工作区和索引，
+ * 项目是仓库中的子目录时只导出该子目录
+ */
+pub fn export_revision(project: &Path, revision: &str, dest: &Path) -> Result<()> {
+    run_command(
+        Command::new("git")
+            .arg("-C")
+            .arg(project)
+            .args(["rev-parse", "--verify", "--quiet"])
+            .arg(format!("{}^{{commit}}", revision)),
+    )
+    .map_err(|_| CodeGraphError::Command(format!("找不到版本 {}", revision)))?;
+    reset_dir(dest)?;
+    let archive = dest.join(".revision.tar");
+    run_command(
+        Command::new("git")
+            .arg("-C")
+            .arg(project)
+            .args(["archive", "--format=tar", "-o"])
+            .arg(&archive)
+            .arg(revision),
+    )?;
+    let result = run_command(
+        Command::new("tar")
+            .arg("-xf")
+            .arg(&archive)
+            .arg("-C")
+            .arg(dest),
+    );
+    fs::remove_file(&archive)?;
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_remote_splits_host_and_dir() {
+        assert_eq!(
+            parse_remote("me@example.com:/srv/project"),
+            Some(("me@example.com", "/srv/project"))
+        );
+        assert_eq!(
+            parse_remote("host:relative/dir"),
+            Some(("host", "relative/dir"))
+        );
+    }
+
+    #[test]
+    fn parse_remote_rejects_incomplete_and_option_like_hosts() {
+        for location in ["/local/path", "host:", ":/srv", "-oProxyCommand=evil:/srv"] {
+            assert_eq!(parse_remote(location), None, "{}", location);
+        }
+    }
+
+    #[test]
+    fn extract_archive_rejects_unknown_formats() {
+        let dest = std::env::temp_dir()
+            .join("code-graph-tests")
+            .join("never-created");
+        for name in ["a.rar", "a.rs.gz", "a.bz2"] {
+            assert!(matches!(
+                extract_archive(Path::new(name), &dest),
+                Err(CodeGraphError::UnsupportedArchive(archive)) if archive == name
+            ));
+        }
+        assert!(!dest.exists());
+    }
+
+    #[test]
+    fn cache_dir_keeps_distinct_names_apart() {
+        // 替换特殊字符后相同的名字仍然对应不同的目录
+        assert_ne!(cache_dir("a/b"), cache_dir("a_b"));
+        assert_eq!(cache_dir("a/b"), cache_dir("a/b"));
+    }
+}
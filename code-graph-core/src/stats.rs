@@ -0,0 +1,143 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+};
+
+use crate::{find_symbol_query, CodeBlockType, CodeNode};
+
+/**
+ * 最大的文件和函数各列出这么多个
+ */
+pub const TOP_COUNT: usize = 10;
+
+/**
+ * 一种语言的文件统计
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LanguageStats {
+    pub language: &'static str,
+    pub files: usize,
+    pub lines: usize,
+    // 函数、类型等定义数，不包括调用
+    pub definitions: usize,
+}
+
+/**
+ * 扫描项目后的统计数据
+ */
+#[derive(Debug, Clone, Default)]
+pub struct ProjectStats {
+    pub files: usize,
+    pub lines: usize,
+    // 按行数从多到少排列
+    pub languages: Vec<LanguageStats>,
+    // 每种代码类型的定义数，按数量从多到少排列
+    pub block_types: Vec<(CodeBlockType, usize)>,
+    // （文件路径，行数），按行数从多到少排列
+    pub largest_files: Vec<(String, usize)>,
+    // （函数，行数），按行数从多到少排列
+    pub largest_functions: Vec<(CodeNode, usize)>,
+    // 函数、方法和组件的数量
+    pub functions: usize,
+    pub average_complexity: f64,
+    pub max_complexity: usize,
+}
+
+fn language_name(file_path: &str) -> Option<&'static str> {
+    let extension = Path::new(file_path).extension()?.to_str()?;
+    find_symbol_query(extension).map(|query| query.get_lang_name())
+}
+
+/**
+ * 统计项目：files 为扫描的文件和它们的行数，definitions 为扫描得到的所有定义，
+ * 同一个定义只计一次，复杂度只统计函数、方法和组件
+ */
+pub fn project_stats(files: &[(String, usize)], definitions: &[CodeNode]) -> ProjectStats {
+    let mut languages: HashMap<&'static str, LanguageStats> = HashMap::new();
+    for (file_path, lines) in files {
+        let Some(language) = language_name(file_path) else {
+            continue;
+        };
+        let stats = languages.entry(language).or_insert(LanguageStats {
+            language,
+            files: 0,
+            lines: 0,
+            definitions: 0,
+        });
+        stats.files += 1;
+        stats.lines += lines;
+    }
+    // 按第一次出现的顺序，数量相同时保持这个顺序
+    let mut block_types: Vec<(CodeBlockType, usize)> = vec![];
+    let mut functions = vec![];
+    let mut seen = HashSet::new();
+    for node in definitions
+        .iter()
+        .filter(|node| node.level > 0 && node.block_type != CodeBlockType::CALL)
+    {
+        if !seen.insert(&node.id) {
+            continue;
+        }
+        match block_types
+            .iter_mut()
+            .find(|(block_type, _)| *block_type == node.block_type)
+        {
+            Some((_, count)) => *count += 1,
+            None => block_types.push((node.block_type.clone(), 1)),
+        }
+        if let Some(stats) =
+            language_name(&node.file_path).and_then(|language| languages.get_mut(language))
+        {
+            stats.definitions += 1;
+        }
+        if matches!(
+            node.block_type,
+            CodeBlockType::FUNCTION | CodeBlockType::METHOD | CodeBlockType::COMPONENT
+        ) {
+            let (start, end) = node.line_range();
+            functions.push((node, end - start + 1));
+        }
+    }
+    let mut languages = languages.into_values().collect::<Vec<LanguageStats>>();
+    languages.sort_by(|a, b| (b.lines, a.language).cmp(&(a.lines, b.language)));
+    block_types.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+    let mut largest_files = files
+        .iter()
+        .filter(|(file_path, _)| language_name(file_path).is_some())
+        .cloned()
+        .collect::<Vec<(String, usize)>>();
+    largest_files.sort_by(|a, b| (b.1, &a.0).cmp(&(a.1, &b.0)));
+    largest_files.truncate(TOP_COUNT);
+    let total_complexity = functions
+        .iter()
+        .map(|(node, _)| node.complexity)
+        .sum::<usize>();
+    let average_complexity = match functions.len() {
+        0 => 0.0,
+        count => total_complexity as f64 / count as f64,
+    };
+    let max_complexity = functions
+        .iter()
+        .map(|(node, _)| node.complexity)
+        .max()
+        .unwrap_or(0);
+    let function_count = functions.len();
+    functions.sort_by(|a, b| {
+        (b.1, &a.0.file_path, a.0.file_location).cmp(&(a.1, &b.0.file_path, b.0.file_location))
+    });
+    functions.truncate(TOP_COUNT);
+    ProjectStats {
+        files: languages.iter().map(|stats| stats.files).sum(),
+        lines: languages.iter().map(|stats| stats.lines).sum(),
+        languages,
+        block_types,
+        largest_files,
+        largest_functions: functions
+            .into_iter()
+            .map(|(node, lines)| (node.clone(), lines))
+            .collect(),
+        functions: function_count,
+        average_complexity,
+        max_complexity,
+    }
+}
@@ -0,0 +1,443 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    fetch_calls, fetch_definitions, get_symbol_query, read_source,
+    todo::{find_todos, TodoComment, TODO_MARKERS},
+    CodeGraphError, CodeNode, Result,
+};
+
+// 提取规则变化后增加版本号，旧版本的缓存不再使用
+const CACHE_VERSION: u32 = 3;
+
+/**
+ * 一个文件中提取到的调用、定义和待办注释
+ */
+#[derive(Debug, Clone, Default)]
+pub struct FileSymbols {
+    pub calls: Vec<CodeNode>,
+    pub definitions: Vec<CodeNode>,
+    pub todos: Vec<TodoComment>,
+    pub lines: usize,
+}
+
+impl FileSymbols {
+    /**
+     * 解析文件，不支持的语言或语法解析失败时调用和定义为空
+     */
+    pub fn extract(path: &str, code: &str) -> Self {
+        let extension = Path::new(path)
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .unwrap_or("");
+        Self {
+            calls: get_symbol_query(extension)
+                .and_then(|symbol_query| fetch_calls(path, code, symbol_query))
+                .unwrap_or_default(),
+            definitions: get_symbol_query(extension)
+                .and_then(|symbol_query| fetch_definitions(path, code, symbol_query))
+                .unwrap_or_default(),
+            todos: find_todos(path, code),
+            lines: code.lines().count(),
+        }
+    }
+}
+
+/**
+ * 缓存的节点，代码内容不重复保存，只记录它在文件内容中的字节范围，
+ * 找不到范围时（代码内容不是文件中的原文）保留代码内容
+ */
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedNode {
+    node: CodeNode,
+    range: Option<(usize, usize)>,
+}
+
+impl CachedNode {
+    fn new(mut node: CodeNode, code: &str, line_starts: &[usize]) -> Self {
+        // 代码内容从所在行开始，同样的原文出现在多处时取哪一处结果都相同
+        let line_start = line_starts
+            .get(node.file_location.saturating_sub(1))
+            .copied()
+            .unwrap_or(0);
+        let range = code[line_start..]
+            .find(node.block.as_str())
+            .map(|offset| (line_start + offset, line_start + offset + node.block.len()));
+        if range.is_some() {
+            node.block = String::new();
+        }
+        Self { node, range }
+    }
+
+    /**
+     * 从文件内容中取回代码内容，范围超出文件内容时返回 None
+     */
+    fn restore(&self, code: &str) -> Option<CodeNode> {
+        let mut node = self.node.clone();
+        if let Some((start, end)) = self.range {
+            node.block = code.get(start..end)?.to_owned();
+        }
+        Some(node)
+    }
+}
+
+/**
+ * 缓存的一个文件，记录文件的修改时间、大小和内容的哈希，文件内容只保存一份，
+ * 修改时间和大小都没有变化时不需要再读取文件
+ */
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedFile {
+    path: String,
+    // 修改时间（unix 纳秒）
+    modified: u64,
+    size: u64,
+    hash: u64,
+    code: String,
+    calls: Vec<CachedNode>,
+    definitions: Vec<CachedNode>,
+    // （标记，内容，行号）
+    todos: Vec<(String, String, usize)>,
+    lines: usize,
+}
+
+impl CachedFile {
+    fn new(path: &str, (modified, size): (u64, u64), code: &str, symbols: &FileSymbols) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(code.match_indices('\n').map(|(index, _)| index + 1))
+            .collect::<Vec<usize>>();
+        let cache_nodes = |nodes: &[CodeNode]| {
+            nodes
+                .iter()
+                .map(|node| CachedNode::new(node.clone(), code, &line_starts))
+                .collect()
+        };
+        Self {
+            path: path.to_owned(),
+            modified,
+            size,
+            hash: content_hash(code),
+            code: code.to_owned(),
+            calls: cache_nodes(&symbols.calls),
+            definitions: cache_nodes(&symbols.definitions),
+            todos: symbols
+                .todos
+                .iter()
+                .map(|todo| (todo.marker.to_owned(), todo.text.clone(), todo.line))
+                .collect(),
+            lines: symbols.lines,
+        }
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /**
+     * 用保存的文件内容还原提取结果，缓存损坏时返回 None
+     */
+    fn symbols(&self) -> Option<FileSymbols> {
+        let restore = |nodes: &[CachedNode]| {
+            nodes
+                .iter()
+                .map(|node| node.restore(&self.code))
+                .collect::<Option<Vec<CodeNode>>>()
+        };
+        Some(FileSymbols {
+            calls: restore(&self.calls)?,
+            definitions: restore(&self.definitions)?,
+            todos: self
+                .todos
+                .iter()
+                .filter_map(|(marker, text, line)| {
+                    Some(TodoComment {
+                        marker: TODO_MARKERS
+                            .iter()
+                            .find(|known| **known == marker.as_str())
+                            .copied()?,
+                        text: text.clone(),
+                        file_path: self.path.clone(),
+                        line: *line,
+                    })
+                })
+                .collect(),
+            lines: self.lines,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct CacheData {
+    version: String,
+    // 项目目录，哈希相同的不同项目不使用对方的缓存
+    project: String,
+    files: Vec<CachedFile>,
+}
+
+/**
+ * 项目的符号缓存，保存在用户缓存目录下（例如 ~/.cache/code-graph/symbols），
+ * 按项目路径的哈希区分，重新打开项目时没有修改的文件不需要再解析。
+ * 不放在项目目录中：打开的目录可能是只读的、远程目录的副本、解压的压缩包或导出的历史版本，
+ * 每次都会被清空，写在项目中还会留下未跟踪的文件
+ */
+#[derive(Debug, Clone, Default)]
+pub struct SymbolCache {
+    project: String,
+    file: PathBuf,
+    files: HashMap<String, CachedFile>,
+}
+
+impl SymbolCache {
+    /**
+     * 读取项目的缓存，没有缓存、格式不对或者由其他版本生成时为空
+     */
+    pub fn load(project: &Path) -> Self {
+        let project = project
+            .canonicalize()
+            .unwrap_or_else(|_| project.to_path_buf())
+            .to_string_lossy()
+            .into_owned();
+        let file = cache_root().join(format!("{:016x}.json", content_hash(&project)));
+        let files = fs::read(&file)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<CacheData>(&bytes).ok())
+            .filter(|data| data.version == cache_version() && data.project == project)
+            .map(|data| {
+                data.files
+                    .into_iter()
+                    .map(|file| (file.path.clone(), file))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self {
+            project,
+            file,
+            files,
+        }
+    }
+
+    /**
+     * 返回文件的提取结果：修改时间和大小都没有变化时直接使用缓存，不读取文件；
+     * 否则读取文件，内容的哈希相同时仍然使用缓存，不同时重新解析。无法读取的文件返回 None
+     */
+    pub fn get_or_extract(&self, path: &Path) -> Option<(CachedFile, FileSymbols)> {
+        let name = path.to_str()?;
+        let stamp = file_stamp(path)?;
+        let cached = self.files.get(name);
+        if let Some((cached, symbols)) = cached
+            .filter(|cached| (cached.modified, cached.size) == stamp)
+            .and_then(|cached| Some((cached, cached.symbols()?)))
+        {
+            return Some((cached.clone(), symbols));
+        }
+        let code = read_source(path).ok()?;
+        if let Some((cached, symbols)) = cached
+            .filter(|cached| cached.hash == content_hash(&code))
+            .and_then(|cached| Some((cached, cached.symbols()?)))
+        {
+            let cached = CachedFile {
+                modified: stamp.0,
+                size: stamp.1,
+                ..cached.clone()
+            };
+            return Some((cached, symbols));
+        }
+        let symbols = FileSymbols::extract(name, &code);
+        Some((CachedFile::new(name, stamp, &code, &symbols), symbols))
+    }
+
+    /**
+     * 用这次扫描的文件替换缓存，已经删除的文件不再保留，返回缓存是否有变化
+     */
+    pub fn replace(&mut self, files: Vec<CachedFile>) -> bool {
+        let changed = files.len() != self.files.len()
+            || files.iter().any(|file| {
+                !self.files.get(&file.path).is_some_and(|cached| {
+                    (cached.modified, cached.size) == (file.modified, file.size)
+                })
+            });
+        self.files = files
+            .into_iter()
+            .map(|file| (file.path.clone(), file))
+            .collect();
+        changed
+    }
+
+    /**
+     * 写入缓存文件
+     */
+    pub fn save(&self) -> Result<()> {
+        if let Some(dir) = self.file.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut files = self.files.values().cloned().collect::<Vec<CachedFile>>();
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        let data = CacheData {
+            version: cache_version(),
+            project: self.project.clone(),
+            files,
+        };
+        let bytes = serde_json::to_vec(&data).map_err(|err| CodeGraphError::Io(err.into()))?;
+        // 先写入临时文件再替换，写到一半退出时不会留下损坏的缓存
+        let temp = self.file.with_extension("json.tmp");
+        fs::write(&temp, bytes)?;
+        fs::rename(temp, &self.file)?;
+        Ok(())
+    }
+}
+
+/**
+ * 存放所有项目缓存的目录，没有用户缓存目录时使用临时目录
+ */
+fn cache_root() -> PathBuf {
+    dirs::cache_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("code-graph")
+        .join("symbols")
+}
+
+fn cache_version() -> String {
+    format!("{}-{}", env!("CARGO_PKG_VERSION"), CACHE_VERSION)
+}
+
+/**
+ * 文件的修改时间（unix 纳秒）和大小
+ */
+fn file_stamp(path: &Path) -> Option<(u64, u64)> {
+    let metadata = fs::metadata(path).ok()?;
+    let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+    Some((modified.as_nanos() as u64, metadata.len()))
+}
+
+/**
+ * FNV-1a 哈希，结果不随 Rust 版本变化，可以保存到磁盘
+ */
+pub(crate) fn content_hash(code: &str) -> u64 {
+    code.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(all(test, feature = "lang-rust"))]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use super::*;
+
+    fn temp_file(name: &str, code: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join("code-graph-tests").join(format!(
+            "{}-{}",
+            name,
+            std::process::id()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("a.rs");
+        fs::write(&path, code).unwrap();
+        path
+    }
+
+    fn set_modified(path: &Path, time: SystemTime) {
+        fs::File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(time)
+            .unwrap();
+    }
+
+    fn labels(symbols: &FileSymbols) -> Vec<&str> {
+        symbols
+            .definitions
+            .iter()
+            .map(|node| node.label.trim())
+            .collect()
+    }
+
+    #[test]
+    fn cached_symbols_restore_blocks_from_ranges() {
+        let code = "fn a() { b(); }\n// TODO 拆分\nfn b() {}\n";
+        let path = temp_file("restore", code);
+        let (cached, symbols) = SymbolCache::default().get_or_extract(&path).unwrap();
+        assert_eq!(labels(&symbols), vec!["fn a", "fn b"]);
+        let restored = cached.symbols().unwrap();
+        let blocks = |symbols: &FileSymbols| {
+            symbols
+                .definitions
+                .iter()
+                .chain(&symbols.calls)
+                .map(|node| node.block.clone())
+                .collect::<Vec<String>>()
+        };
+        assert_eq!(blocks(&restored), blocks(&symbols));
+        assert_eq!(restored.todos, symbols.todos);
+        // 找到范围的节点不再保存代码内容
+        assert!(cached
+            .definitions
+            .iter()
+            .all(|node| node.node.block.is_empty()));
+        let json = serde_json::to_string(&cached).unwrap();
+        let loaded = serde_json::from_str::<CachedFile>(&json).unwrap();
+        assert_eq!(blocks(&loaded.symbols().unwrap()), blocks(&symbols));
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn unchanged_stamp_uses_cache_without_reading() {
+        let path = temp_file("stamp", "fn old() {}\n");
+        let time = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+        set_modified(&path, time);
+        let mut cache = SymbolCache::default();
+        let (cached, _) = cache.get_or_extract(&path).unwrap();
+        assert!(cache.replace(vec![cached]));
+
+        // 大小和修改时间不变时不读取文件，仍然是旧的结果
+        fs::write(&path, "fn new() {}\n").unwrap();
+        set_modified(&path, time);
+        let (cached, symbols) = cache.get_or_extract(&path).unwrap();
+        assert_eq!(labels(&symbols), vec!["fn old"]);
+        assert!(!cache.replace(vec![cached]));
+
+        // 修改时间变化后读取文件，内容不同时重新解析
+        set_modified(&path, time + Duration::from_secs(1));
+        let (cached, symbols) = cache.get_or_extract(&path).unwrap();
+        assert_eq!(labels(&symbols), vec!["fn new"]);
+        assert!(cache.replace(vec![cached]));
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn same_content_with_new_stamp_reuses_cache() {
+        let path = temp_file("hash", "fn same() {}\n");
+        let time = SystemTime::UNIX_EPOCH + Duration::from_secs(2_000_000);
+        set_modified(&path, time);
+        let mut cache = SymbolCache::default();
+        let (cached, _) = cache.get_or_extract(&path).unwrap();
+        cache.replace(vec![cached.clone()]);
+
+        set_modified(&path, time + Duration::from_secs(60));
+        let (updated, symbols) = cache.get_or_extract(&path).unwrap();
+        assert_eq!(labels(&symbols), vec!["fn same"]);
+        assert_eq!(updated.hash, cached.hash);
+        assert_ne!(updated.modified, cached.modified);
+        // 只有修改时间变了，缓存需要更新
+        assert!(cache.replace(vec![updated]));
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn deleted_files_leave_the_cache() {
+        let path = temp_file("deleted", "fn a() {}\n");
+        let mut cache = SymbolCache::default();
+        let (cached, _) = cache.get_or_extract(&path).unwrap();
+        cache.replace(vec![cached]);
+        assert!(cache.replace(vec![]));
+        assert!(cache.files.is_empty());
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+        assert!(cache.get_or_extract(&path).is_none());
+    }
+}
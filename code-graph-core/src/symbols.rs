@@ -0,0 +1,299 @@
+use std::path::Path;
+
+use crate::{
+    find_symbol_query,
+    label::{simplify_generics, GenericsDisplay},
+    CodeBlockType, CodeNode,
+};
+
+/**
+ * 调用的限定部分
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Qualifier {
+    // 直接调用，例如 foo()
+    None,
+    // 通过 self、this、Self 调用，所属类型为调用方所在的类型
+    SelfType,
+    // 路径调用，例如 Foo::new()、b::other()，为最后一段之前的名字
+    Path(String),
+    // 方法调用，例如 x.foo()，为接收者的最后一个名字，接收者是表达式时为空
+    Receiver(String),
+}
+
+/**
+ * 调用的目标：被调用的名字和限定部分
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallTarget {
+    pub name: String,
+    pub qualifier: Qualifier,
+}
+
+/**
+ * 符号表中区分的语言，TypeScript 与 JavaScript 可以互相调用，视为同一种
+ */
+pub fn language_of(file_path: &str, language: Option<&str>) -> String {
+    let extension = Path::new(file_path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or("");
+    let language = find_symbol_query(extension)
+        .map(|query| query.get_lang_name())
+        .or(language)
+        .unwrap_or(extension);
+    match language {
+        "TypeScript" => "JavaScript".to_owned(),
+        _ => language.to_owned(),
+    }
+}
+
+fn is_word(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+/**
+ * 文字开头的名字，例如 Foo<T> 中的 Foo
+ */
+fn leading_name(text: &str) -> Option<&str> {
+    let end = text.find(|c: char| !is_word(c)).unwrap_or(text.len());
+    (end > 0).then_some(&text[..end])
+}
+
+/**
+ * 类型表达式中的类型名：去掉引用、mut、dyn、impl、生命周期和路径，例如 &'a mut fmt::Display 为 Display
+ */
+fn type_name(text: &str) -> Option<&str> {
+    let mut text = text.trim_start();
+    loop {
+        let rest = text
+            .strip_prefix('&')
+            .or_else(|| text.strip_prefix('*'))
+            .or_else(|| text.strip_prefix("mut "))
+            .or_else(|| text.strip_prefix("dyn "))
+            .or_else(|| text.strip_prefix("impl "))
+            .or_else(|| {
+                text.strip_prefix('\'')
+                    .map(|rest| rest.trim_start_matches(is_word))
+            });
+        match rest {
+            Some(rest) => text = rest.trim_start(),
+            None => break,
+        }
+    }
+    let path_end = text
+        .find(|c: char| !is_word(c) && c != ':')
+        .unwrap_or(text.len());
+    let last = text[..path_end].trim_end_matches(':').rsplit("::").next()?;
+    leading_name(last)
+}
+
+/**
+ * 定义登记到符号表中的名字：函数、方法为函数名，类型为类型名，
+ * JavaScript 的 const a, b 为每个变量名，impl、derive、宏调用和 hook 调用不声明名字
+ */
+pub fn definition_names(node: &CodeNode) -> Vec<String> {
+    if matches!(
+        node.block_type,
+        CodeBlockType::IMPL | CodeBlockType::CALL | CodeBlockType::NORMAL | CodeBlockType::HOOK
+    ) {
+        return vec![];
+    }
+    let label = simplify_generics(&node.label, GenericsDisplay::Strip);
+    let words = label.split_whitespace().collect::<Vec<&str>>();
+    if words.contains(&"impl") {
+        return vec![];
+    }
+    let keywords = [
+        "class",
+        "interface",
+        "struct",
+        "trait",
+        "enum",
+        "type",
+        "macro_rules!",
+    ];
+    if let Some(position) = words.iter().position(|word| keywords.contains(word)) {
+        return words
+            .get(position + 1)
+            .and_then(|word| leading_name(word))
+            .map(|name| vec![name.to_owned()])
+            .unwrap_or_default();
+    }
+    if let Some(position) = words
+        .iter()
+        .position(|word| matches!(*word, "const" | "let" | "var"))
+    {
+        return words[position + 1..]
+            .iter()
+            .filter_map(|word| leading_name(word))
+            .map(str::to_owned)
+            .collect();
+    }
+    words
+        .last()
+        .and_then(|word| leading_name(word.trim_start_matches(['*', '&'])))
+        .map(|name| vec![name.to_owned()])
+        .unwrap_or_default()
+}
+
+/**
+ * impl、类、结构体和 trait 所属的类型名，其中的方法以 类型::方法 的形式登记，
+ * impl Trait for Type 属于 Type
+ */
+pub fn owner_type(node: &CodeNode) -> Option<String> {
+    if !matches!(
+        node.block_type,
+        CodeBlockType::CLASS | CodeBlockType::STRUCT
+    ) {
+        return None;
+    }
+    let label = simplify_generics(&node.label, GenericsDisplay::Strip);
+    match label.split_once("impl ") {
+        Some((_, rest)) => {
+            let type_text = rest
+                .split_once(" for ")
+                .map_or(rest, |(_, type_text)| type_text);
+            type_name(type_text).map(str::to_owned)
+        }
+        None => definition_names(node).into_iter().next(),
+    }
+}
+
+/**
+ * 稳定 ID 中使用的名字，不包含可见性、async 和泛型等修饰：定义为声明的名字，
+ * impl 为所属类型，impl Trait for Type 为 Trait for Type，不声明名字的节点为整理空白后的标签
+ */
+pub(crate) fn stable_name(node: &CodeNode) -> String {
+    let label = simplify_generics(&node.label, GenericsDisplay::Strip);
+    if let Some((_, rest)) = label.split_once("impl ") {
+        if let Some((trait_text, type_text)) = rest.split_once(" for ") {
+            if let (Some(trait_name), Some(type_name)) =
+                (type_name(trait_text), type_name(type_text))
+            {
+                return format!("{} for {}", trait_name, type_name);
+            }
+        }
+    }
+    if let Some(owner) = owner_type(node) {
+        return owner;
+    }
+    let names = definition_names(node);
+    if !names.is_empty() {
+        return names.join(",");
+    }
+    node.label
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+/**
+ * 解析调用的目标，obj.method() 的调用节点标签只有方法名，接收者从调用的代码中取
+ */
+pub fn call_target(call: &CodeNode) -> CallTarget {
+    let label = simplify_generics(&call.label, GenericsDisplay::Strip);
+    if let Some((path, name)) = label.rsplit_once("::") {
+        let last = path.rsplit("::").next().unwrap_or(path);
+        let qualifier = match last {
+            "Self" => Qualifier::SelfType,
+            // self::f、crate::f、super::f 是模块中的函数
+            "self" | "crate" | "super" | "" => Qualifier::None,
+            _ => Qualifier::Path(last.to_owned()),
+        };
+        return CallTarget {
+            name: name.to_owned(),
+            qualifier,
+        };
+    }
+    let block = call.block.as_str();
+    let receiver = block
+        .match_indices(label.as_str())
+        .find_map(|(position, _)| {
+            let before = &block[..position];
+            let before = before
+                .strip_suffix('.')
+                .or_else(|| before.strip_suffix("->"))?;
+            let after = &block[position + label.len()..];
+            (!after.starts_with(is_word)).then_some(before)
+        });
+    let qualifier = match receiver {
+        Some(receiver) => {
+            let start = receiver
+                .rfind(|c: char| !is_word(c))
+                .map_or(0, |position| position + 1);
+            match &receiver[start..] {
+                "self" | "this" | "cls" => Qualifier::SelfType,
+                name => Qualifier::Receiver(name.to_owned()),
+            }
+        }
+        None => Qualifier::None,
+    };
+    CallTarget {
+        name: label,
+        qualifier,
+    }
+}
+
+/**
+ * 从代码中的声明推断变量的类型，找不到时返回 None：
+ * x: Foo（Rust、TypeScript、Python 的类型标注和结构体字段）、
+ * x = Foo::new()、x = Foo(...)、x = new Foo(...)，以及 Java 的 Foo x
+ */
+pub fn declared_type<'a>(code: &'a str, variable: &str) -> Option<&'a str> {
+    if variable.is_empty() {
+        return None;
+    }
+    let is_type = |name: &&str| name.starts_with(char::is_uppercase);
+    code.match_indices(variable).find_map(|(position, _)| {
+        let before = &code[..position];
+        let after = &code[position + variable.len()..];
+        if before.ends_with(is_word) || after.starts_with(is_word) {
+            return None;
+        }
+        let after = after.trim_start();
+        if let Some(annotation) = after
+            .strip_prefix(':')
+            .filter(|rest| !rest.starts_with(':'))
+        {
+            return type_name(annotation);
+        }
+        if let Some(value) = after
+            .strip_prefix('=')
+            .filter(|rest| !rest.starts_with(['=', '>']))
+        {
+            let value = value.trim_start();
+            let value = value.strip_prefix("new ").unwrap_or(value);
+            let path_end = value
+                .find(|c: char| !is_word(c) && c != ':')
+                .unwrap_or(value.len());
+            let path = &value[..path_end];
+            // Foo::new() 取 Foo，Foo(...) 和 Foo { .. } 只认大写开头的类型名
+            return match path.rsplit_once("::") {
+                Some((path, _)) => path.rsplit("::").next().and_then(leading_name),
+                None => leading_name(path).filter(is_type),
+            };
+        }
+        before
+            .trim_end()
+            .rsplit(|c: char| !is_word(c))
+            .next()
+            .filter(is_type)
+    })
+}
+
+/**
+ * 文件是否对应模块名，例如 b.rs、b/mod.rs、b.py、b/__init__.py 对应 b
+ */
+pub fn is_module_file(file_path: &str, module: &str) -> bool {
+    let path = Path::new(file_path);
+    let stem = path.file_stem().and_then(|stem| stem.to_str());
+    match stem {
+        Some("mod" | "__init__" | "index") => path
+            .parent()
+            .and_then(|dir| dir.file_name())
+            .is_some_and(|dir| dir == module),
+        Some(stem) => stem == module,
+        None => false,
+    }
+}
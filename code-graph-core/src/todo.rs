@@ -0,0 +1,164 @@
+/**
+ * 识别的待办标记
+ */
+pub const TODO_MARKERS: [&str; 3] = ["TODO", "FIXME", "HACK"];
+
+/**
+ * 注释中的一条待办
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TodoComment {
+    // TODO、FIXME 或 HACK
+    pub marker: &'static str,
+    // 从标记开始的注释内容，例如 TODO(alice): 支持泛型
+    pub text: String,
+    pub file_path: String,
+    // 所在行，从 1 开始
+    pub line: usize,
+}
+
+/**
+ * 查找文件注释中的待办标记，标记需要是完整的单词并且全部大写，
+ * python 文件识别 # 注释，其他文件识别 // 和 /* */ 注释，字符串中的内容不算
+ */
+pub fn find_todos(file_path: &str, code: &str) -> Vec<TodoComment> {
+    let python = file_path.ends_with(".py");
+    // 单引号在 python 和 js 中是字符串，在其他语言中是字符字面量或 Rust 的生命周期
+    let single_quote = python
+        || [".js", ".jsx", ".ts", ".tsx"]
+            .iter()
+            .any(|ext| file_path.ends_with(ext));
+    let mut todos = vec![];
+    let mut in_block = false;
+    for (number, line) in code.lines().enumerate() {
+        let comment = if in_block {
+            match line.find("*/") {
+                Some(end) => {
+                    in_block = false;
+                    &line[..end]
+                }
+                None => line,
+            }
+        } else {
+            match comment_start(line, python, single_quote) {
+                Some(start) if line[start..].starts_with("/*") => {
+                    let comment = &line[start + 2..];
+                    match comment.find("*/") {
+                        Some(end) => &comment[..end],
+                        None => {
+                            in_block = true;
+                            comment
+                        }
+                    }
+                }
+                Some(start) => &line[start..],
+                None => continue,
+            }
+        };
+        if let Some((marker, position)) = find_marker(comment) {
+            todos.push(TodoComment {
+                marker,
+                text: comment[position..].trim().to_owned(),
+                file_path: file_path.to_owned(),
+                line: number + 1,
+            });
+        }
+    }
+    todos
+}
+
+/**
+ * 行中第一个不在字符串中的注释开始位置
+ */
+fn comment_start(line: &str, python: bool, single_quote: bool) -> Option<usize> {
+    let bytes = line.as_bytes();
+    let mut quote = None;
+    let mut i = 0;
+    while i < bytes.len() {
+        let c = bytes[i];
+        match quote {
+            Some(_) if c == b'\\' => i += 1,
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if matches!(c, b'"' | b'`') || (single_quote && c == b'\'') => quote = Some(c),
+            // 跳过 '"' 这样的字符字面量
+            None if c == b'\'' && bytes.get(i + 2) == Some(&b'\'') => i += 2,
+            None if python && c == b'#' => return Some(i),
+            None if !python && (bytes[i..].starts_with(b"//") || bytes[i..].starts_with(b"/*")) => {
+                return Some(i)
+            }
+            None => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/**
+ * 注释中第一个完整单词形式的标记和它的位置
+ */
+fn find_marker(comment: &str) -> Option<(&'static str, usize)> {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    TODO_MARKERS
+        .iter()
+        .filter_map(|marker| {
+            comment
+                .match_indices(marker)
+                .find(|(position, _)| {
+                    !comment[..*position].ends_with(is_word)
+                        && !comment[position + marker.len()..].starts_with(is_word)
+                })
+                .map(|(position, _)| (*marker, position))
+        })
+        .min_by_key(|(_, position)| *position)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn found(file_path: &str, code: &str) -> Vec<(&'static str, String, usize)> {
+        find_todos(file_path, code)
+            .into_iter()
+            .map(|todo| (todo.marker, todo.text, todo.line))
+            .collect()
+    }
+
+    #[test]
+    fn finds_markers_in_line_and_block_comments() {
+        let code = "fn a() {} // TODO(alice): 支持泛型\n\
+                    /* 说明\n   FIXME 边界情况\n*/\n\
+                    /* HACK: 临时处理 */ fn b() {}\n";
+        assert_eq!(
+            found("a.rs", code),
+            vec![
+                ("TODO", "TODO(alice): 支持泛型".to_owned(), 1),
+                ("FIXME", "FIXME 边界情况".to_owned(), 3),
+                ("HACK", "HACK: 临时处理".to_owned(), 5),
+            ]
+        );
+    }
+
+    #[test]
+    fn ignores_strings_other_words_and_lowercase() {
+        let code = "let s = \"// TODO 不是注释\";\n\
+                    let c = '\"'; // TODOS 和 todo 都不算\n\
+                    // XTODO、TODO_LIST\n";
+        assert!(found("a.rs", code).is_empty());
+    }
+
+    #[test]
+    fn python_and_javascript_comments() {
+        assert_eq!(
+            found(
+                "a.py",
+                "x = '# TODO 字符串'  # FIXME 真的注释\n// TODO 不是注释\n"
+            ),
+            vec![("FIXME", "FIXME 真的注释".to_owned(), 1)]
+        );
+        assert_eq!(
+            found("a.js", "const s = '// TODO'; // HACK 绕过\n"),
+            vec![("HACK", "HACK 绕过".to_owned(), 1)]
+        );
+    }
+}
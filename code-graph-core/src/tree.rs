@@ -0,0 +1,272 @@
+use std::path::PathBuf;
+use std::{fs::read_dir, path::Path};
+
+use crate::{CodeGraphError, Result};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TreeType {
+    File,
+    Directory,
+}
+
+#[derive(Clone, Default, Debug)]
+pub struct Tree {
+    pub label: String,
+    full_path: String,
+    children: Vec<Tree>,
+    tree_type: Option<TreeType>,
+    clicked: bool,
+    // 超过扫描深度，未继续扫描的目录
+    truncated: bool,
+    // 目录的下一层是否已经读取
+    loaded: bool,
+    // 按需读取时还可以进入的子目录层数，None 表示不限制
+    remaining_depth: Option<usize>,
+}
+
+impl Tree {
+    pub fn new(name: &str, full_path: &str, tree_type: TreeType) -> Self {
+        Self {
+            label: name.to_owned(),
+            full_path: full_path.to_owned(),
+            children: vec![],
+            tree_type: Some(tree_type),
+            clicked: false,
+            truncated: false,
+            loaded: false,
+            remaining_depth: None,
+        }
+    }
+
+    /**
+     * 只读取第一层的目录树，子目录在展开时通过 load_children 读取，
+     * max_depth 与 recursion_dir_with_depth 相同，超过深度的目录标记为 truncated
+     */
+    pub fn lazy_dir(name: &str, dir: &Path, max_depth: Option<usize>) -> Result<Self> {
+        let full_path = dir
+            .to_str()
+            .ok_or_else(|| CodeGraphError::Encoding(dir.display().to_string()))?;
+        let mut tree = Tree::new(name, full_path, TreeType::Directory);
+        tree.remaining_depth = max_depth;
+        tree.load_children()?;
+        Ok(tree)
+    }
+
+    pub fn full_path(&self) -> &str {
+        &self.full_path
+    }
+
+    pub fn tree_type(&self) -> TreeType {
+        self.tree_type.clone().unwrap_or(TreeType::File)
+    }
+
+    pub fn truncated(&self) -> bool {
+        self.truncated
+    }
+
+    pub fn loaded(&self) -> bool {
+        self.loaded
+    }
+
+    /**
+     * 读取目录的下一层，已经读取过、不是目录或被截断时不做任何事，
+     * 读取失败时也不再重试，目录显示为空，无法读取或名字不是 UTF-8 的条目被跳过
+     */
+    pub fn load_children(&mut self) -> Result<()> {
+        if self.loaded || self.truncated || self.tree_type() != TreeType::Directory {
+            return Ok(());
+        }
+        self.loaded = true;
+        // 无法读取的条目（例如损坏的挂载点）跳过，不影响其他条目
+        for entry in read_dir(&self.full_path)?.flatten() {
+            let path_buf = entry.path();
+            let is_dir = path_buf.is_dir();
+            let tree_type = if is_dir {
+                TreeType::Directory
+            } else {
+                TreeType::File
+            };
+            // 名字不是 UTF-8 的文件无法显示和解析，跳过，不影响其他文件
+            let (Some(name), Some(full_path)) = (
+                path_buf.file_name().and_then(|name| name.to_str()),
+                path_buf.to_str(),
+            ) else {
+                continue;
+            };
+            let mut tree = Tree::new(name, full_path, tree_type);
+            if is_dir {
+                match self.remaining_depth {
+                    Some(0) => tree.truncated = true,
+                    depth => tree.remaining_depth = depth.map(|depth| depth - 1),
+                }
+            }
+            self.children.push(tree);
+        }
+        Ok(())
+    }
+
+    pub fn children(&self) -> &[Tree] {
+        &self.children
+    }
+
+    pub fn children_mut(&mut self) -> &mut Vec<Tree> {
+        &mut self.children
+    }
+
+    pub fn clicked(&self) -> bool {
+        if self.clicked {
+            return true;
+        } else if !self.children.is_empty() {
+            for child in &self.children {
+                if child.clicked() {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+}
+
+/**
+ * 递归扫描目录，生成文件树并收集所有文件路径
+ */
+pub fn recursion_dir(root_path: &Path, pathes: &mut Vec<PathBuf>, root_tree: Tree) -> Result<Tree> {
+    recursion_dir_with_depth(root_path, pathes, root_tree, None)
+}
+
+/**
+ * 递归扫描目录，最多进入 max_depth 层子目录，更深的目录标记为 truncated，
+ * max_depth 为 None 时不限制。只有 root_path 无法读取时返回错误，
+ * 无法读取的子目录（例如没有权限）显示为空目录，无法读取或名字不是 UTF-8 的条目被跳过
+ */
+pub fn recursion_dir_with_depth(
+    root_path: &Path,
+    pathes: &mut Vec<PathBuf>,
+    mut root_tree: Tree,
+    max_depth: Option<usize>,
+) -> Result<Tree> {
+    if root_path.is_dir() {
+        root_tree.loaded = true;
+        for entry in read_dir(root_path)?.flatten() {
+            let path_buf = entry.path();
+            let is_dir = path_buf.is_dir();
+            let tree_type = if is_dir {
+                TreeType::Directory
+            } else {
+                TreeType::File
+            };
+            // 名字不是 UTF-8 的文件无法显示和解析，跳过，不影响其他文件
+            let (Some(name), Some(full_path)) = (
+                path_buf.file_name().and_then(|name| name.to_str()),
+                path_buf.to_str(),
+            ) else {
+                continue;
+            };
+            let mut tree = Tree::new(name, full_path, tree_type);
+            if path_buf.is_dir() {
+                if max_depth == Some(0) {
+                    tree.truncated = true;
+                } else {
+                    let depth = max_depth.map(|depth| depth - 1);
+                    tree = recursion_dir_with_depth(path_buf.as_path(), pathes, tree, depth)
+                        .unwrap_or_else(|_| {
+                            let mut tree = Tree::new(name, full_path, TreeType::Directory);
+                            tree.loaded = true;
+                            tree
+                        });
+                }
+            } else if path_buf.is_file() {
+                pathes.push(path_buf);
+            }
+            root_tree.children.push(tree);
+        }
+    }
+    Ok(root_tree)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    /**
+     * 临时目录：a.rs、src/b.rs、src/deep/c.rs
+     */
+    fn sample_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join("code-graph-tests").join(format!(
+            "{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("src").join("deep")).unwrap();
+        fs::write(dir.join("a.rs"), "fn a() {}").unwrap();
+        fs::write(dir.join("src").join("b.rs"), "fn b() {}").unwrap();
+        fs::write(dir.join("src").join("deep").join("c.rs"), "fn c() {}").unwrap();
+        dir
+    }
+
+    fn scan(dir: &Path, max_depth: Option<usize>) -> (Tree, Vec<PathBuf>) {
+        let mut pathes = vec![];
+        let root = Tree::new("root", dir.to_str().unwrap(), TreeType::Directory);
+        let tree = recursion_dir_with_depth(dir, &mut pathes, root, max_depth).unwrap();
+        pathes.sort();
+        (tree, pathes)
+    }
+
+    fn child<'a>(tree: &'a Tree, name: &str) -> &'a Tree {
+        tree.children()
+            .iter()
+            .find(|child| child.label == name)
+            .unwrap_or_else(|| panic!("没有 {}", name))
+    }
+
+    #[test]
+    fn unlimited_depth_collects_every_file() {
+        let dir = sample_dir("unlimited");
+        let (tree, pathes) = scan(&dir, None);
+        assert_eq!(
+            pathes,
+            vec![
+                dir.join("a.rs"),
+                dir.join("src").join("b.rs"),
+                dir.join("src").join("deep").join("c.rs"),
+            ]
+        );
+        let deep = child(child(&tree, "src"), "deep");
+        assert!(!deep.truncated() && deep.loaded());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn max_depth_truncates_deeper_directories() {
+        let dir = sample_dir("depth");
+        let (tree, pathes) = scan(&dir, Some(1));
+        assert_eq!(pathes, vec![dir.join("a.rs"), dir.join("src").join("b.rs")]);
+        let deep = child(child(&tree, "src"), "deep");
+        assert!(deep.truncated() && deep.children().is_empty());
+
+        let (tree, pathes) = scan(&dir, Some(0));
+        assert_eq!(pathes, vec![dir.join("a.rs")]);
+        assert!(child(&tree, "src").truncated());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn non_utf8_names_are_skipped() {
+        use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+
+        let dir = sample_dir("encoding");
+        let name = OsStr::from_bytes(b"bad\xff.rs");
+        // 有的文件系统不允许这样的文件名
+        if fs::write(dir.join(name), "fn bad() {}").is_err() {
+            return;
+        }
+        let (tree, pathes) = scan(&dir, None);
+        assert_eq!(pathes.len(), 3);
+        assert_eq!(tree.children().len(), 2);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
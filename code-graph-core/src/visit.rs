@@ -0,0 +1,165 @@
+use tree_sitter::{Node, Tree};
+
+use crate::{
+    cyclomatic_complexity, doc_comment, lang::SymbolQuery, parse_code, CodeBlockType, CodeNode,
+    CodeNodeIndex, Graph, Result,
+};
+
+/**
+ * 遍历语法树时的上下文
+ */
+pub struct VisitContext<'a> {
+    path: &'a str,
+    code: &'a str,
+    // 包含当前语法节点的最内层定义
+    parent: CodeNodeIndex,
+    level: usize,
+    graph: &'a mut Graph,
+}
+
+impl<'a> VisitContext<'a> {
+    pub(crate) fn new(
+        path: &'a str,
+        code: &'a str,
+        parent: CodeNodeIndex,
+        level: usize,
+        graph: &'a mut Graph,
+    ) -> Self {
+        Self {
+            path,
+            code,
+            parent,
+            level,
+            graph,
+        }
+    }
+
+    pub fn path(&self) -> &str {
+        self.path
+    }
+
+    pub fn code(&self) -> &str {
+        self.code
+    }
+
+    /**
+     * 语法节点对应的源代码
+     */
+    pub fn text(&self, node: &Node) -> &str {
+        &self.code[node.byte_range()]
+    }
+
+    /**
+     * 包含当前语法节点的最内层定义，语法节点本身是定义时即为该定义
+     */
+    pub fn parent(&self) -> CodeNodeIndex {
+        self.parent
+    }
+
+    pub fn graph(&self) -> &Graph {
+        self.graph
+    }
+
+    /**
+     * 在当前定义下添加自定义节点，不会改变后续语法节点的 parent
+     */
+    pub fn add_node(&mut self, mut code_node: CodeNode) -> CodeNodeIndex {
+        code_node.file_path = self.path.to_string();
+        code_node.level = self.level;
+        let index = self.graph.add_node(code_node);
+        self.graph.add_edge(self.parent, index);
+        index
+    }
+
+    /**
+     * 根据语法节点创建自定义节点并添加到当前定义下
+     */
+    pub fn add_fact(
+        &mut self,
+        node: &Node,
+        label: &str,
+        block_type: CodeBlockType,
+    ) -> CodeNodeIndex {
+        let code_node = CodeNode::new(
+            "",
+            label,
+            self.text(node),
+            node.start_position().row + 1,
+            block_type,
+            0,
+        );
+        self.add_node(code_node)
+    }
+}
+
+/**
+ * 解析代码并生成大纲，同时对每个语法节点调用 visitor，
+ * visitor 可以通过 VisitContext 向图中添加自定义节点，返回文件根节点
+ *
+ * ```ignore
+ * walk_definitions("api.rs", code, &RustQuery, &mut graph, |node, ctx| {
+ *     if node.kind() == "attribute_item" && ctx.text(node).contains("route") {
+ *         let label = ctx.text(node).to_owned();
+ *         ctx.add_fact(node, &label, CodeBlockType::NORMAL);
+ *     }
+ * })?;
+ * ```
+ */
+pub fn walk_definitions(
+    path: &str,
+    code: &str,
+    symbol_query: &dyn SymbolQuery,
+    graph: &mut Graph,
+    visitor: impl FnMut(&Node, &mut VisitContext),
+) -> Result<CodeNodeIndex> {
+    let tree = parse_code(path, code, symbol_query)?;
+    Ok(walk_tree(&tree, path, code, symbol_query, graph, visitor))
+}
+
+/**
+ * 由已经解析好的语法树生成大纲，返回文件根节点
+ */
+pub(crate) fn walk_tree(
+    tree: &Tree,
+    path: &str,
+    code: &str,
+    symbol_query: &dyn SymbolQuery,
+    graph: &mut Graph,
+    mut visitor: impl FnMut(&Node, &mut VisitContext),
+) -> CodeNodeIndex {
+    let mut root_code_node = CodeNode::new(path, path, code, 0, CodeBlockType::NORMAL, 0);
+    root_code_node.file_path = path.to_string();
+    root_code_node.language = Some(symbol_query.get_lang_name().to_owned());
+    let root_index = graph.add_node(root_code_node);
+    let mut context = VisitContext::new(path, code, root_index, 1, graph);
+    walk_node(tree.root_node(), symbol_query, &mut context, &mut visitor);
+    graph.assign_stable_ids(root_index);
+    root_index
+}
+
+pub(crate) fn walk_node(
+    node: Node,
+    symbol_query: &dyn SymbolQuery,
+    context: &mut VisitContext,
+    visitor: &mut dyn FnMut(&Node, &mut VisitContext),
+) {
+    let (parent, level) = (context.parent, context.level);
+    if let Some(mut code_node) = symbol_query.get_definition(context.code, &node) {
+        code_node.complexity = cyclomatic_complexity(&node);
+        code_node.doc = doc_comment(context.code, &node);
+        let modifiers = symbol_query.get_modifiers(context.code, &node);
+        code_node.visibility = modifiers.visibility;
+        code_node.is_async = modifiers.is_async;
+        code_node.is_test = modifiers.is_test;
+        code_node.is_unsafe = modifiers.is_unsafe;
+        code_node.language = Some(symbol_query.get_lang_name().to_owned());
+        context.parent = context.add_node(code_node);
+        context.level += 1;
+    }
+    visitor(&node, context);
+    for child in node.children(&mut node.walk()) {
+        walk_node(child, symbol_query, context, visitor);
+    }
+    context.parent = parent;
+    context.level = level;
+}
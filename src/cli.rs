@@ -0,0 +1,296 @@
+use std::{
+    ffi::OsStr,
+    fs,
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use code_graph::{
+    ctags::{graph_tags, write_tags},
+    export_each_file, exporters, read_source, recursion_dir,
+    snapshot::project_graph,
+    valid_file_extention, Exporter, GraphBuilder, Result, Tree, TreeType,
+};
+
+const USAGE: &str = "用法:
+  code-graph watch <目录> [--export json|dot|mermaid|svg|md] [--out <文件>] [--interval <毫秒>]
+  code-graph export <目录> [--export json|dot|mermaid|svg|md] [--out-dir <目录>]
+  code-graph tags <目录> [--out <文件>]";
+
+/**
+ * watch 命令的参数
+ */
+struct WatchArgs {
+    dir: PathBuf,
+    exporter: Box<dyn Exporter>,
+    out: PathBuf,
+    interval: Duration,
+}
+
+/**
+ * export 命令的参数
+ */
+struct ExportArgs {
+    dir: PathBuf,
+    exporter: Box<dyn Exporter>,
+    out_dir: PathBuf,
+}
+
+/**
+ * tags 命令的参数
+ */
+struct TagsArgs {
+    dir: PathBuf,
+    out: PathBuf,
+}
+
+/**
+ * 处理命令行参数，没有子命令时返回 None，由调用方启动界面
+ */
+pub fn run(args: &[String]) -> Option<i32> {
+    match args.first().map(String::as_str) {
+        Some("watch") => Some(match parse_watch_args(&args[1..]) {
+            Ok(watch_args) => watch(watch_args),
+            Err(message) => {
+                eprintln!("{}\n{}", message, USAGE);
+                2
+            }
+        }),
+        Some("export") => Some(match parse_export_args(&args[1..]) {
+            Ok(export_args) => export_files(export_args),
+            Err(message) => {
+                eprintln!("{}\n{}", message, USAGE);
+                2
+            }
+        }),
+        Some("tags") => Some(match parse_tags_args(&args[1..]) {
+            Ok(tags_args) => write_tags_file(tags_args),
+            Err(message) => {
+                eprintln!("{}\n{}", message, USAGE);
+                2
+            }
+        }),
+        Some("-h") | Some("--help") => {
+            println!("{}", USAGE);
+            Some(0)
+        }
+        _ => None,
+    }
+}
+
+fn find_exporter(format: &str) -> Option<Box<dyn Exporter>> {
+    exporters().into_iter().find(|exporter| {
+        exporter.extension() == format || exporter.name().eq_ignore_ascii_case(format)
+    })
+}
+
+fn parse_watch_args(args: &[String]) -> std::result::Result<WatchArgs, String> {
+    let mut dir = None;
+    let mut format = "json".to_owned();
+    let mut out = None;
+    let mut interval = Duration::from_millis(1000);
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let mut value = || {
+            iter.next()
+                .cloned()
+                .ok_or_else(|| format!("缺少参数值: {}", arg))
+        };
+        match arg.as_str() {
+            "--export" => format = value()?,
+            "--out" => out = Some(PathBuf::from(value()?)),
+            "--interval" => {
+                let millis = value()?
+                    .parse::<u64>()
+                    .map_err(|_| "--interval 需要是整数".to_owned())?;
+                interval = Duration::from_millis(millis.max(100));
+            }
+            _ if dir.is_none() && !arg.starts_with("--") => dir = Some(PathBuf::from(arg)),
+            _ => return Err(format!("未知参数: {}", arg)),
+        }
+    }
+    let dir = dir.ok_or_else(|| "缺少要监听的目录".to_owned())?;
+    let exporter = find_exporter(&format).ok_or_else(|| format!("不支持的导出格式: {}", format))?;
+    let out = out.unwrap_or_else(|| PathBuf::from(format!("graph.{}", exporter.extension())));
+    Ok(WatchArgs {
+        dir,
+        exporter,
+        out,
+        interval,
+    })
+}
+
+fn parse_export_args(args: &[String]) -> std::result::Result<ExportArgs, String> {
+    let mut dir = None;
+    let mut format = "svg".to_owned();
+    let mut out_dir = PathBuf::from("graphs");
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let mut value = || {
+            iter.next()
+                .cloned()
+                .ok_or_else(|| format!("缺少参数值: {}", arg))
+        };
+        match arg.as_str() {
+            "--export" => format = value()?,
+            "--out-dir" => out_dir = PathBuf::from(value()?),
+            _ if dir.is_none() && !arg.starts_with("--") => dir = Some(PathBuf::from(arg)),
+            _ => return Err(format!("未知参数: {}", arg)),
+        }
+    }
+    let dir = dir.ok_or_else(|| "缺少要导出的目录".to_owned())?;
+    let exporter = find_exporter(&format).ok_or_else(|| format!("不支持的导出格式: {}", format))?;
+    Ok(ExportArgs {
+        dir,
+        exporter,
+        out_dir,
+    })
+}
+
+fn parse_tags_args(args: &[String]) -> std::result::Result<TagsArgs, String> {
+    let mut dir = None;
+    let mut out = None;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let mut value = || {
+            iter.next()
+                .cloned()
+                .ok_or_else(|| format!("缺少参数值: {}", arg))
+        };
+        match arg.as_str() {
+            "--out" => out = Some(PathBuf::from(value()?)),
+            _ if dir.is_none() && !arg.starts_with("--") => dir = Some(PathBuf::from(arg)),
+            _ => return Err(format!("未知参数: {}", arg)),
+        }
+    }
+    let dir = dir.ok_or_else(|| "缺少要扫描的目录".to_owned())?;
+    // 默认写入项目根目录，tags 中的相对路径才能对应到文件
+    let out = out.unwrap_or_else(|| dir.join("tags"));
+    Ok(TagsArgs { dir, out })
+}
+
+/**
+ * 扫描项目，生成 universal-ctags 格式的 tags 文件
+ */
+fn write_tags_file(tags_args: TagsArgs) -> i32 {
+    let result = project_graph(&tags_args.dir).and_then(|graph| {
+        let tags = graph_tags(&graph);
+        let mut file = fs::File::create(&tags_args.out)?;
+        write_tags(&tags, &mut file)?;
+        Ok(tags.len())
+    });
+    match result {
+        Ok(count) => {
+            println!("已写入 {} 个符号到 {}", count, tags_args.out.display());
+            0
+        }
+        Err(err) => {
+            eprintln!("生成 tags 失败: {}", err);
+            1
+        }
+    }
+}
+
+/**
+ * 为项目中的每个文件导出一张图
+ */
+fn export_files(export_args: ExportArgs) -> i32 {
+    let result = snapshot(&export_args.dir).and_then(|files| {
+        let pathes = files.into_iter().map(|(path, _)| path).collect::<Vec<_>>();
+        export_each_file(
+            &export_args.dir,
+            &pathes,
+            &export_args.out_dir,
+            export_args.exporter.as_ref(),
+        )
+    });
+    match result {
+        Ok(count) => {
+            println!(
+                "已导出 {} 个文件到 {}",
+                count,
+                export_args.out_dir.display()
+            );
+            0
+        }
+        Err(err) => {
+            eprintln!("导出失败: {}", err);
+            1
+        }
+    }
+}
+
+/**
+ * 项目中受支持的文件及其修改时间，用于判断是否需要重新扫描
+ */
+fn snapshot(dir: &Path) -> Result<Vec<(PathBuf, Option<SystemTime>)>> {
+    let mut pathes = vec![];
+    recursion_dir(dir, &mut pathes, Tree::new("", "", TreeType::Directory))?;
+    let mut files = pathes
+        .into_iter()
+        .filter(|path| {
+            let ext = path.extension().and_then(OsStr::to_str).unwrap_or("");
+            valid_file_extention(ext)
+        })
+        .map(|path| {
+            let modified = fs::metadata(&path).and_then(|meta| meta.modified()).ok();
+            (path, modified)
+        })
+        .collect::<Vec<_>>();
+    files.sort();
+    Ok(files)
+}
+
+/**
+ * 扫描项目并导出，先写入临时文件再替换，避免读取方读到写了一半的文件
+ */
+fn export(watch_args: &WatchArgs, files: &[(PathBuf, Option<SystemTime>)]) -> Result<usize> {
+    let mut builder = GraphBuilder::new();
+    for (path, _) in files {
+        // 无法读取的文件直接跳过
+        let Ok(code) = read_source(path) else {
+            continue;
+        };
+        builder = builder.add_source(path.to_str().unwrap_or(""), &code)?;
+    }
+    let graph = builder.resolve_calls().build();
+    let mut tmp_path = watch_args.out.clone().into_os_string();
+    tmp_path.push(".tmp");
+    let mut file = fs::File::create(&tmp_path)?;
+    watch_args.exporter.export(&graph, &mut file)?;
+    fs::rename(&tmp_path, &watch_args.out)?;
+    Ok(graph.node_count())
+}
+
+/**
+ * 持续监听目录，文件变化后重新扫描并导出
+ */
+fn watch(watch_args: WatchArgs) -> i32 {
+    let mut last_snapshot = None;
+    loop {
+        match snapshot(&watch_args.dir) {
+            Ok(files) => {
+                if last_snapshot.as_ref() != Some(&files) {
+                    match export(&watch_args, &files) {
+                        Ok(node_count) => println!(
+                            "已导出 {} 个文件、{} 个节点到 {}",
+                            files.len(),
+                            node_count,
+                            watch_args.out.display()
+                        ),
+                        Err(err) => eprintln!("导出失败: {}", err),
+                    }
+                    last_snapshot = Some(files);
+                }
+            }
+            Err(err) => {
+                eprintln!("扫描目录失败: {}", err);
+                if last_snapshot.is_none() {
+                    return 1;
+                }
+            }
+        }
+        thread::sleep(watch_args.interval);
+    }
+}
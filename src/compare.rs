@@ -0,0 +1,158 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::mpsc,
+    thread,
+};
+
+use code_graph::{
+    compare::{build_compare_graph, compare_dirs},
+    snapshot::{build_snapshot_graph, diff_graphs, project_graph, Snapshot},
+    source::{cache_dir, export_revision},
+};
+use rfd::FileDialog;
+
+use crate::MyApp;
+
+impl MyApp {
+    /**
+     * 依次选择两个目录，在后台对比其中的定义
+     */
+    pub(crate) fn compare_dirs(&mut self) {
+        let Some(left_root) = FileDialog::new().set_title("选择目录 A").pick_folder() else {
+            return;
+        };
+        let Some(right_root) = FileDialog::new().set_title("选择目录 B").pick_folder() else {
+            return;
+        };
+        let (tx, rx) = mpsc::channel();
+        self.compare_rx = Some(rx);
+        thread::spawn(move || {
+            let dir_name = |path: &Path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .unwrap_or("")
+                    .to_owned()
+            };
+            let result = compare_dirs(&left_root, &right_root).map(|comparison| {
+                build_compare_graph(&comparison, &dir_name(&left_root), &dir_name(&right_root))
+            });
+            let _ = tx.send(result);
+        });
+    }
+    /**
+     * 在后台导出当前项目在 diff_revision 时的内容，对比其中的定义和工作区中的定义
+     */
+    pub(crate) fn compare_revision(&mut self) {
+        let revision = self.diff_revision.trim().to_owned();
+        if self.root_path.is_empty() || revision.is_empty() {
+            return;
+        }
+        let root_path = PathBuf::from(&self.root_path);
+        let project_name = root_path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or("project")
+            .to_owned();
+        let (tx, rx) = mpsc::channel();
+        self.compare_rx = Some(rx);
+        thread::spawn(move || {
+            // 不同位置的同名项目不共用导出目录
+            let base = cache_dir(&format!("{}-{}", root_path.display(), revision));
+            let result = export_revision(&root_path, &revision, &base)
+                .and_then(|_| compare_dirs(&base, &root_path))
+                .map(|comparison| build_compare_graph(&comparison, &revision, &project_name));
+            let _ = tx.send(result);
+        });
+    }
+    /**
+     * 在后台扫描当前项目，保存为快照文件，快照名称取文件名
+     */
+    pub(crate) fn save_snapshot(&mut self) {
+        if self.root_path.is_empty() {
+            return;
+        }
+        let Some(snapshot_path) = FileDialog::new()
+            .add_filter("snapshot", &["json"])
+            .set_file_name("snapshot.json")
+            .save_file()
+        else {
+            return;
+        };
+        let name = snapshot_path
+            .file_stem()
+            .and_then(|name| name.to_str())
+            .unwrap_or("snapshot")
+            .to_owned();
+        let root_path = PathBuf::from(&self.root_path);
+        let (tx, rx) = mpsc::channel();
+        self.snapshot_rx = Some(rx);
+        thread::spawn(move || {
+            let result = Snapshot::capture(&name, &root_path)
+                .and_then(|snapshot| snapshot.save(&snapshot_path));
+            let _ = tx.send(result);
+        });
+    }
+    /**
+     * 选择快照文件，在后台对比快照和当前项目
+     */
+    pub(crate) fn compare_snapshot(&mut self) {
+        if self.root_path.is_empty() {
+            return;
+        }
+        let Some(snapshot_path) = FileDialog::new()
+            .add_filter("snapshot", &["json"])
+            .pick_file()
+        else {
+            return;
+        };
+        let root_path = PathBuf::from(&self.root_path);
+        let (tx, rx) = mpsc::channel();
+        self.compare_rx = Some(rx);
+        thread::spawn(move || {
+            let result = Snapshot::load(&snapshot_path).and_then(|snapshot| {
+                let current = project_graph(&root_path)?;
+                let diff = diff_graphs(&snapshot.graph, &current);
+                Ok(build_snapshot_graph(&diff, &snapshot.name, &root_path))
+            });
+            let _ = tx.send(result);
+        });
+    }
+    /**
+     * 依次选择旧快照和新快照，在后台生成两者的差异图，打开了项目时节点路径为项目下的完整路径
+     */
+    pub(crate) fn diff_snapshots(&mut self) {
+        let pick = |title: &str| {
+            FileDialog::new()
+                .set_title(title)
+                .add_filter("snapshot", &["json"])
+                .pick_file()
+        };
+        let Some(old_path) = pick("选择旧快照") else {
+            return;
+        };
+        let Some(new_path) = pick("选择新快照") else {
+            return;
+        };
+        let root_path = PathBuf::from(&self.root_path);
+        let (tx, rx) = mpsc::channel();
+        self.compare_rx = Some(rx);
+        thread::spawn(move || {
+            let result = Snapshot::load(&old_path).and_then(|old| {
+                let new = Snapshot::load(&new_path)?;
+                let diff = old.graph.diff(&new.graph);
+                let mut graph = old.graph.diff_view(&new.graph, &diff);
+                if !root_path.as_os_str().is_empty() {
+                    for (_, node) in graph.nodes_mut() {
+                        node.file_path = root_path
+                            .join(&node.file_path)
+                            .to_str()
+                            .unwrap_or("")
+                            .to_owned();
+                    }
+                }
+                Ok(graph)
+            });
+            let _ = tx.send(result);
+        });
+    }
+}
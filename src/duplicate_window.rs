@@ -0,0 +1,80 @@
+use std::path::{Path, PathBuf};
+
+use code_graph::duplicate::build_duplicate_graph;
+
+use crate::MyApp;
+
+impl MyApp {
+    pub(crate) fn duplicate_window(&mut self, ctx: &egui::Context) {
+        let mut show_duplicates = self.show_duplicates;
+        egui::Window::new("重复代码")
+            .open(&mut show_duplicates)
+            .default_size([360.0, 420.0])
+            .show(ctx, |ui| {
+                if let Some(rx) = &self.duplicate_rx {
+                    if let Ok(duplicates) = rx.try_recv() {
+                        self.duplicates = duplicates;
+                        self.duplicate_rx = None;
+                    } else {
+                        ui.spinner();
+                        return;
+                    }
+                }
+                if self.duplicates.is_empty() {
+                    ui.label("没有找到重复的函数");
+                    return;
+                }
+                ui.horizontal(|ui| {
+                    ui.label(format!("{} 组重复的函数", self.duplicates.len()));
+                    if ui
+                        .button("在图中显示")
+                        .on_hover_text("每组重复代码一个分组，重复的函数之间用紫色虚线连接")
+                        .clicked()
+                    {
+                        let root_path = PathBuf::from(&self.root_path);
+                        let project_name = root_path
+                            .file_name()
+                            .and_then(|name| name.to_str())
+                            .unwrap_or("project")
+                            .to_owned();
+                        self.pending_graph = Some(build_duplicate_graph(
+                            &project_name,
+                            &root_path,
+                            &self.duplicates,
+                        ));
+                    }
+                });
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for (number, group) in self.duplicates.iter().enumerate() {
+                        ui.separator();
+                        ui.label(format!(
+                            "重复 {}：{} 处，每处 {} 个词",
+                            number + 1,
+                            group.nodes.len(),
+                            group.tokens
+                        ));
+                        for node in &group.nodes {
+                            let location = Path::new(&node.file_path)
+                                .strip_prefix(&self.root_path)
+                                .unwrap_or(Path::new(&node.file_path))
+                                .display()
+                                .to_string();
+                            if ui
+                                .link(format!(
+                                    "{}  {}:{}",
+                                    node.label.trim(),
+                                    location,
+                                    node.file_location
+                                ))
+                                .clicked()
+                            {
+                                self.pending_location =
+                                    Some((node.file_path.clone(), node.file_location));
+                            }
+                        }
+                    }
+                });
+            });
+        self.show_duplicates = show_duplicates;
+    }
+}
@@ -0,0 +1,280 @@
+use std::{fs, path::PathBuf, sync::mpsc, thread};
+
+use arboard::Clipboard;
+use code_graph::{
+    ctags::{graph_tags, load_tags, write_tags},
+    export::{CsvExporter, MarkdownExporter},
+    export_each_file, exporter_for,
+    graph_image::{capture_graph, capture_viewport, copy_image},
+    snapshot::project_graph,
+    CodeGraphError, CodeNode, Exporter, Graph,
+};
+use eframe::egui::Ui;
+use image::RgbaImage;
+use rfd::{FileDialog, MessageDialog};
+
+use crate::{GraphTab, MyApp};
+
+impl MyApp {
+    /**
+     * 选择输出目录，在后台为项目中的每个文件分别导出一张图
+     */
+    pub(crate) fn export_all_files(&mut self, format: &str) {
+        let Some(out_dir) = FileDialog::new().set_title("选择输出目录").pick_folder() else {
+            return;
+        };
+        let root_path = PathBuf::from(&self.root_path);
+        let pathes = self.pathes.clone();
+        let format = format.to_owned();
+        let (tx, rx) = mpsc::channel();
+        self.batch_export_rx = Some(rx);
+        thread::spawn(move || {
+            let result = exporter_for(&format)
+                .ok_or_else(|| CodeGraphError::UnsupportedLanguage(format.clone()))
+                .and_then(|exporter| {
+                    export_each_file(&root_path, &pathes, &out_dir, exporter.as_ref())
+                });
+            let _ = tx.send(result);
+        });
+    }
+    /**
+     * 选择保存位置，将当前的图导出为指定格式
+     */
+    pub(crate) fn export_graph(&self, exporter: &dyn Exporter) {
+        // SVG 保持画布中的布局和配色
+        let canvas_svg = self.graph_view.svg_exporter(&self.graph);
+        // Markdown 大纲使用选择的链接方式，路径相对项目根目录
+        let outline = MarkdownExporter {
+            links: self.markdown_links,
+            root: PathBuf::from(&self.root_path),
+        };
+        let exporter = if exporter.extension() == canvas_svg.extension() {
+            &canvas_svg
+        } else if exporter.extension() == outline.extension() {
+            &outline
+        } else {
+            exporter
+        };
+        let Some(export_path) = FileDialog::new()
+            .add_filter(exporter.name(), &[exporter.extension()])
+            .set_file_name(format!("code-graph.{}", exporter.extension()))
+            .save_file()
+        else {
+            return;
+        };
+        let result = fs::File::create(&export_path)
+            .map_err(CodeGraphError::from)
+            .and_then(|mut file| exporter.export(&self.graph, &mut file));
+        if let Err(err) = result {
+            MessageDialog::new()
+                .set_title("导出失败")
+                .set_description(err.to_string())
+                .show();
+        }
+    }
+    /**
+     * 选择输出目录，将节点和连线分别导出为 nodes.csv 和 edges.csv
+     */
+    pub(crate) fn export_csv(&self) {
+        let Some(out_dir) = FileDialog::new().set_title("选择输出目录").pick_folder() else {
+            return;
+        };
+        if let Err(err) = CsvExporter::export_dir(&self.graph, &out_dir) {
+            MessageDialog::new()
+                .set_title("导出失败")
+                .set_description(err.to_string())
+                .show();
+        }
+    }
+    /**
+     * 保存当前的图，包含节点位置、可见性和视角，之后可以原样重新打开
+     */
+    pub(crate) fn save_graph_file(&self) {
+        let Some(path) = FileDialog::new()
+            .add_filter("graph", &["json"])
+            .set_file_name("code-graph.json")
+            .save_file()
+        else {
+            return;
+        };
+        if let Err(err) = self.graph.save_json(&path) {
+            MessageDialog::new()
+                .set_title("保存失败")
+                .set_description(err.to_string())
+                .show();
+        }
+    }
+    /**
+     * 打开保存的图，保持保存时的布局，不重新布局
+     */
+    pub(crate) fn open_graph_file(&mut self) {
+        let Some(path) = FileDialog::new().add_filter("graph", &["json"]).pick_file() else {
+            return;
+        };
+        match Graph::load_json(&path) {
+            Ok(graph) => {
+                self.switch_tab(GraphTab::Code);
+                self.history_shown = None;
+                self.stash_annotations();
+                self.graph = graph;
+                self.cycles_key = None;
+                self.refresh_overlay();
+                self.current_node = CodeNode::default();
+                self.call_stats = None;
+                self.graph_view.impact.clear();
+                self.call_sites_of = None;
+            }
+            Err(err) => {
+                MessageDialog::new()
+                    .set_title("打开失败")
+                    .set_description(err.to_string())
+                    .show();
+            }
+        }
+    }
+    /**
+     * 导入 ctags 生成的 tags 文件，之后打开没有语法解析的文件时使用其中的符号
+     */
+    pub(crate) fn import_tags(&mut self) {
+        let Some(path) = FileDialog::new().set_file_name("tags").pick_file() else {
+            return;
+        };
+        match load_tags(&path) {
+            Ok(tags) if tags.is_empty() => {
+                MessageDialog::new()
+                    .set_title("导入失败")
+                    .set_description("没有找到符号，需要 ctags 格式的 tags 文件")
+                    .show();
+            }
+            Ok(tags) => self.tags = tags,
+            Err(err) => {
+                MessageDialog::new()
+                    .set_title("导入失败")
+                    .set_description(err.to_string())
+                    .show();
+            }
+        }
+    }
+    /**
+     * 在后台扫描当前项目，生成 universal-ctags 格式的 tags 文件，
+     * 其中的路径相对项目根目录
+     */
+    pub(crate) fn generate_tags(&mut self) {
+        if self.root_path.is_empty() {
+            return;
+        }
+        let root_path = PathBuf::from(&self.root_path);
+        let Some(tags_path) = FileDialog::new()
+            .set_directory(&root_path)
+            .set_file_name("tags")
+            .save_file()
+        else {
+            return;
+        };
+        let (tx, rx) = mpsc::channel();
+        self.tags_rx = Some(rx);
+        thread::spawn(move || {
+            let result = project_graph(&root_path).and_then(|graph| {
+                let tags = graph_tags(&graph);
+                let mut file = fs::File::create(&tags_path)?;
+                write_tags(&tags, &mut file)?;
+                Ok(tags.len())
+            });
+            let _ = tx.send(result);
+        });
+    }
+    /**
+     * 选择保存位置，离屏绘制整张图后在后台光栅化并保存为 PNG
+     */
+    pub(crate) fn export_png(&mut self, ui: &Ui) {
+        let Some(export_path) = FileDialog::new()
+            .add_filter("PNG", &["png"])
+            .set_file_name("code-graph.png")
+            .save_file()
+        else {
+            return;
+        };
+        let frame = capture_graph(
+            &mut self.graph_view,
+            &self.graph,
+            self.png_scale,
+            ui.visuals().clone(),
+            self.fonts.clone(),
+        );
+        let (tx, rx) = mpsc::channel();
+        self.png_rx = Some(rx);
+        thread::spawn(move || {
+            let result = frame.and_then(|frame| frame.save_png(&export_path));
+            let _ = tx.send(result);
+        });
+    }
+    /**
+     * 按窗口中看到的画面离屏绘制，在后台光栅化后复制到剪贴板
+     */
+    pub(crate) fn copy_viewport_image(&mut self, ui: &Ui) {
+        let frame = capture_viewport(
+            &mut self.graph_view,
+            &self.graph,
+            ui.ctx().pixels_per_point(),
+            ui.visuals().clone(),
+            self.fonts.clone(),
+        );
+        let frame = match frame {
+            Ok(frame) => frame,
+            Err(err) => {
+                MessageDialog::new()
+                    .set_title("复制失败")
+                    .set_description(err.to_string())
+                    .show();
+                return;
+            }
+        };
+        let (tx, rx) = mpsc::channel();
+        self.copy_image_rx = Some(rx);
+        thread::spawn(move || {
+            let _ = tx.send(frame.rasterize());
+        });
+    }
+    /**
+     * 把光栅化后的图片放到剪贴板，剪贴板只在第一次复制时打开
+     */
+    pub(crate) fn set_clipboard_image(&mut self, image: &RgbaImage) {
+        let clipboard = match self.clipboard.take().map_or_else(Clipboard::new, Ok) {
+            Ok(clipboard) => self.clipboard.insert(clipboard),
+            Err(err) => {
+                MessageDialog::new()
+                    .set_title("复制失败")
+                    .set_description(err.to_string())
+                    .show();
+                return;
+            }
+        };
+        if let Err(err) = copy_image(clipboard, image) {
+            MessageDialog::new()
+                .set_title("复制失败")
+                .set_description(err.to_string())
+                .show();
+        }
+    }
+    /**
+     * 将聚焦节点及其可见的后代导出后复制到剪贴板
+     */
+    pub(crate) fn copy_subtree(&mut self, ui: &Ui, exporter: &dyn Exporter) {
+        let Some(focus) = self.graph.get_focus_idx() else {
+            return;
+        };
+        let subtree = self.graph.subtree(focus, |node| node.visiable()).to_graph();
+        let mut output = vec![];
+        match exporter.export(&subtree, &mut output) {
+            Ok(()) => ui
+                .ctx()
+                .copy_text(String::from_utf8_lossy(&output).into_owned()),
+            Err(err) => {
+                MessageDialog::new()
+                    .set_title("复制失败")
+                    .set_description(err.to_string())
+                    .show();
+            }
+        }
+    }
+}
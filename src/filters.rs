@@ -0,0 +1,132 @@
+use std::collections::{HashMap, HashSet};
+
+use code_graph::{
+    symbols::{call_target, definition_names, Qualifier},
+    CodeNode, CodeNodeIndex, EdgeKind,
+};
+use regex::{Regex, RegexBuilder};
+
+use crate::MyApp;
+
+impl MyApp {
+    /**
+     * 隐藏没有被调用、也没有调用项目中其他定义的节点
+     */
+    pub(crate) fn apply_hide_orphans(&mut self) {
+        if !self.hide_orphans {
+            return;
+        }
+        // 图中已有调用边的两端直接视为有调用关系
+        let mut connected = HashSet::new();
+        for edge in self.graph.edges() {
+            if edge.kind() == EdgeKind::Calls {
+                connected.insert(edge.from());
+                connected.insert(edge.to());
+            }
+        }
+        // 项目中的调用只遍历一次：记录被调用的定义和能解析的调用所在的行
+        let mut callees = HashSet::new();
+        let mut caller_lines: HashMap<&str, Vec<usize>> = HashMap::new();
+        let mut call_names: HashMap<String, Vec<&CodeNode>> = HashMap::new();
+        for call in self.call_nodes.iter() {
+            if self.definitions.is_empty() {
+                let target = call_target(call);
+                if !matches!(target.qualifier, Qualifier::Path(_)) {
+                    call_names.entry(target.name).or_default().push(call);
+                }
+            } else if let Some(definition) = self.definitions.resolve(call) {
+                callees.insert(definition.id());
+                caller_lines
+                    .entry(call.file_path.as_str())
+                    .or_default()
+                    .push(call.file_location);
+            }
+        }
+        for lines in caller_lines.values_mut() {
+            lines.sort_unstable();
+        }
+        let connected = |index: CodeNodeIndex| {
+            if connected.contains(&index) {
+                return true;
+            }
+            let Some(node) = self.graph.node(index) else {
+                return false;
+            };
+            let (start, end) = node.line_range();
+            let inside = |call: &CodeNode| {
+                call.file_path == node.file_path
+                    && start <= call.file_location
+                    && call.file_location <= end
+            };
+            if self.definitions.is_empty() {
+                return definition_names(node).iter().any(|name| {
+                    call_names
+                        .get(name)
+                        .is_some_and(|calls| calls.iter().any(|call| !inside(call)))
+                });
+            }
+            let calls_inside = caller_lines
+                .get(node.file_path.as_str())
+                .is_some_and(|lines| {
+                    let first = lines.partition_point(|line| *line < start);
+                    lines.get(first).is_some_and(|line| *line <= end)
+                });
+            calls_inside || callees.contains(node.id())
+        };
+        let orphans = self.graph.orphans_by(connected);
+        for index in orphans {
+            if let Some(node) = self.graph.node_mut(index) {
+                node.set_visiable(false);
+            }
+        }
+    }
+    /**
+     * 只显示 unsafe 的定义时隐藏其他节点
+     */
+    pub(crate) fn apply_unsafe_filter(&mut self) {
+        if self.only_unsafe {
+            self.graph.hide_unmatched(|node| node.is_unsafe);
+        }
+    }
+    /**
+     * 只显示公开接口时隐藏非公开的定义
+     */
+    pub(crate) fn apply_public_filter(&mut self) {
+        if self.only_public {
+            self.graph.hide_non_public();
+        }
+    }
+    /**
+     * 隐藏标签与筛选条件不匹配的节点，正则表达式无效时不筛选
+     */
+    pub(crate) fn apply_name_filter(&mut self) {
+        let Ok(Some(filter)) = name_filter(&self.name_filter, self.name_filter_regex) else {
+            return;
+        };
+        self.graph.hide_unmatched(|node| {
+            let label = node.label.trim();
+            filter.is_match(label) || label.split(' ').any(|word| filter.is_match(word))
+        });
+    }
+}
+
+/**
+ * 节点筛选条件，为空时返回 None，不使用正则表达式时按子串忽略大小写匹配
+ */
+pub(crate) fn name_filter(
+    pattern: &str,
+    regex: bool,
+) -> std::result::Result<Option<Regex>, regex::Error> {
+    let pattern = pattern.trim();
+    if pattern.is_empty() {
+        return Ok(None);
+    }
+    if regex {
+        Regex::new(pattern).map(Some)
+    } else {
+        RegexBuilder::new(&regex::escape(pattern))
+            .case_insensitive(true)
+            .build()
+            .map(Some)
+    }
+}
@@ -0,0 +1,115 @@
+use eframe::egui::{self, Ui};
+
+use crate::MyApp;
+
+impl MyApp {
+    /**
+     * 画布上方的查找栏：输入时高亮标签包含该文字的节点（忽略大小写）并聚焦到第一个，
+     * F3 / Enter 跳到下一个，Shift+F3 / Shift+Enter 跳到上一个，Esc 关闭
+     */
+    pub(crate) fn find_bar(&mut self, ui: &mut Ui) {
+        if !self.show_find {
+            return;
+        }
+        // 先匹配带 Shift 的快捷键，否则会被当作 F3
+        let (previous, next) = ui.input_mut(|input| {
+            (
+                input.consume_key(egui::Modifiers::SHIFT, egui::Key::F3),
+                input.consume_key(egui::Modifiers::NONE, egui::Key::F3),
+            )
+        });
+        let mut step = if previous {
+            Some(-1)
+        } else {
+            next.then_some(1)
+        };
+        let mut query_changed = false;
+        let mut close = false;
+        ui.horizontal(|ui| {
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut self.find_query)
+                    .hint_text("在图中查找")
+                    .desired_width(200.0),
+            );
+            if std::mem::take(&mut self.find_focus_requested) {
+                response.request_focus();
+            }
+            query_changed = response.changed();
+            if response.lost_focus() {
+                let (enter, shift, escape) = ui.input(|input| {
+                    (
+                        input.key_pressed(egui::Key::Enter),
+                        input.modifiers.shift,
+                        input.key_pressed(egui::Key::Escape),
+                    )
+                });
+                if enter {
+                    step = Some(if shift { -1 } else { 1 });
+                    // 继续输入或连续按 Enter
+                    response.request_focus();
+                }
+                close |= escape;
+            }
+            let has_matches = !self.find_matches.is_empty();
+            if ui
+                .add_enabled(has_matches, egui::Button::new("⏶").small())
+                .on_hover_text("上一个 (Shift+F3)")
+                .clicked()
+            {
+                step = Some(-1);
+            }
+            if ui
+                .add_enabled(has_matches, egui::Button::new("⏷").small())
+                .on_hover_text("下一个 (F3)")
+                .clicked()
+            {
+                step = Some(1);
+            }
+            if has_matches {
+                ui.label(format!(
+                    "{}/{}",
+                    self.find_current + 1,
+                    self.find_matches.len()
+                ));
+            } else if !self.find_query.trim().is_empty() {
+                ui.weak("没有匹配的节点");
+            }
+            close |= ui.small_button("✖").on_hover_text("关闭 (Esc)").clicked();
+        });
+        if close {
+            self.show_find = false;
+            self.find_matches.clear();
+            self.graph_view.search_matches.clear();
+            return;
+        }
+        // 图可能在查找时发生变化，每一帧重新匹配
+        let query = self.find_query.trim().to_lowercase();
+        self.find_matches = if query.is_empty() {
+            vec![]
+        } else {
+            self.graph
+                .nodes()
+                .filter(|(_, node)| node.label.to_lowercase().contains(&query))
+                .map(|(index, _)| index)
+                .collect()
+        };
+        self.graph_view.search_matches = self.find_matches.iter().copied().collect();
+        if self.find_matches.is_empty() {
+            self.find_current = 0;
+            return;
+        }
+        let count = self.find_matches.len() as isize;
+        if query_changed {
+            self.find_current = 0;
+        } else if let Some(step) = step {
+            self.find_current = (self.find_current as isize + step).rem_euclid(count) as usize;
+        } else {
+            self.find_current = self.find_current.min(self.find_matches.len() - 1);
+        }
+        if query_changed || step.is_some() {
+            let index = self.find_matches[self.find_current];
+            self.reveal_node(ui, index);
+            self.graph_view.center_on(ui, &mut self.graph, index);
+        }
+    }
+}
@@ -0,0 +1,281 @@
+use std::{borrow::Cow, collections::HashMap, io, path::Path};
+
+use arboard::Clipboard;
+use code_graph_core::{Camera, Graph};
+use egui::{
+    epaint::{ClippedPrimitive, Primitive, Vertex},
+    Color32, ColorImage, Context, FontDefinitions, ImageData, Pos2, Rect, TextureId, Vec2,
+    ViewportId, Visuals,
+};
+use image::{ImageFormat, Rgba, RgbaImage};
+
+use crate::GraphView;
+
+// 导出图片的最大边长（像素）
+const MAX_IMAGE_SIDE: f32 = 16384.0;
+// 图片四周留出的空白
+const MARGIN: f32 = 16.0;
+
+/**
+ * 离屏绘制得到的一帧：三角形网格和用到的纹理，可以在后台线程中光栅化
+ */
+pub struct GraphFrame {
+    primitives: Vec<ClippedPrimitive>,
+    textures: HashMap<TextureId, ColorImage>,
+    pixels_per_point: f32,
+    width: u32,
+    height: u32,
+    background: Color32,
+}
+
+/**
+ * 在离屏的 egui 上下文中按整张图的范围绘制一帧，不受窗口大小的限制，
+ * scale 为每个点对应的像素数，fonts 和 visuals 应与界面使用的一致
+ */
+pub fn capture_graph(
+    view: &mut GraphView,
+    graph: &Graph,
+    scale: f32,
+    visuals: Visuals,
+    fonts: FontDefinitions,
+) -> io::Result<GraphFrame> {
+    let bounds = view.content_bounds(graph);
+    if !bounds.is_positive() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "图中没有可见的节点",
+        ));
+    }
+    let size = bounds.size() + Vec2::splat(MARGIN * 2.0);
+    let pixels = size * scale;
+    if pixels.max_elem() > MAX_IMAGE_SIDE {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "图片尺寸 {:.0}x{:.0} 超过 {} 像素，请减小缩放比例",
+                pixels.x, pixels.y, MAX_IMAGE_SIDE
+            ),
+        ));
+    }
+    // 视角对准整张图，缩放由 pixels_per_point 决定
+    let mut graph = graph.clone();
+    *graph.camera_mut() = Camera {
+        offset: Vec2::splat(MARGIN) - bounds.min.to_vec2(),
+        zoom: 1.0,
+    };
+    Ok(render(view, &mut graph, size, scale, visuals, fonts))
+}
+
+/**
+ * 按当前的视角绘制画布中可见的部分，与窗口中看到的画面一致（不包含网格和缩略图）
+ */
+pub fn capture_viewport(
+    view: &mut GraphView,
+    graph: &Graph,
+    scale: f32,
+    visuals: Visuals,
+    fonts: FontDefinitions,
+) -> io::Result<GraphFrame> {
+    let size = view.viewport_size();
+    if !(size.x > 0.0 && size.y > 0.0) || graph.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "画布中没有可以复制的内容",
+        ));
+    }
+    let mut graph = graph.clone();
+    Ok(render(view, &mut graph, size, scale, visuals, fonts))
+}
+
+/**
+ * 在离屏的 egui 上下文中绘制大小为 size（点）的一帧并三角化
+ */
+fn render(
+    view: &mut GraphView,
+    graph: &mut Graph,
+    size: Vec2,
+    scale: f32,
+    visuals: Visuals,
+    fonts: FontDefinitions,
+) -> GraphFrame {
+    let pixels = size * scale;
+    let ctx = Context::default();
+    ctx.set_fonts(fonts);
+    let background = visuals.panel_fill;
+    ctx.set_visuals(visuals);
+    let mut raw_input = egui::RawInput {
+        screen_rect: Some(Rect::from_min_size(Pos2::ZERO, size)),
+        ..Default::default()
+    };
+    raw_input
+        .viewports
+        .entry(ViewportId::ROOT)
+        .or_default()
+        .native_pixels_per_point = Some(scale);
+    let output = ctx.run(raw_input, |ctx| {
+        egui::CentralPanel::default()
+            .frame(egui::Frame::none().fill(background))
+            .show(ctx, |ui| view.offscreen_ui(ui, graph));
+    });
+
+    let mut textures = HashMap::new();
+    for (id, delta) in output.textures_delta.set {
+        let image = match delta.image {
+            ImageData::Color(image) => (*image).clone(),
+            ImageData::Font(font) => ColorImage {
+                size: font.size,
+                pixels: font.srgba_pixels(None).collect(),
+            },
+        };
+        match (delta.pos, textures.get_mut(&id)) {
+            // 局部更新已有的纹理
+            (Some([x, y]), Some(texture)) => {
+                let texture: &mut ColorImage = texture;
+                for row in 0..image.height() {
+                    for column in 0..image.width() {
+                        texture[(x + column, y + row)] = image[(column, row)];
+                    }
+                }
+            }
+            _ => {
+                textures.insert(id, image);
+            }
+        }
+    }
+    let pixels_per_point = output.pixels_per_point;
+    GraphFrame {
+        primitives: ctx.tessellate(output.shapes, pixels_per_point),
+        textures,
+        pixels_per_point,
+        width: pixels.x.ceil() as u32,
+        height: pixels.y.ceil() as u32,
+        background,
+    }
+}
+
+impl GraphFrame {
+    /**
+     * 用软件光栅化把网格画到图片上，颜色为 egui 使用的预乘 alpha
+     */
+    pub fn rasterize(&self) -> RgbaImage {
+        let (width, height) = (self.width as usize, self.height as usize);
+        let mut pixels = vec![self.background; width * height];
+        for ClippedPrimitive {
+            clip_rect,
+            primitive,
+        } in &self.primitives
+        {
+            let Primitive::Mesh(mesh) = primitive else {
+                continue;
+            };
+            let clip = (*clip_rect * self.pixels_per_point).intersect(Rect::from_min_size(
+                Pos2::ZERO,
+                Vec2::new(width as f32, height as f32),
+            ));
+            if !clip.is_positive() {
+                continue;
+            }
+            let texture = self.textures.get(&mesh.texture_id);
+            for triangle in mesh.indices.chunks_exact(3) {
+                let vertices = [
+                    &mesh.vertices[triangle[0] as usize],
+                    &mesh.vertices[triangle[1] as usize],
+                    &mesh.vertices[triangle[2] as usize],
+                ];
+                self.fill_triangle(&mut pixels, width, clip, vertices, texture);
+            }
+        }
+        let mut image = RgbaImage::new(self.width, self.height);
+        for (pixel, color) in image.pixels_mut().zip(pixels) {
+            let [r, g, b, a] = color.to_srgba_unmultiplied();
+            *pixel = Rgba([r, g, b, a]);
+        }
+        image
+    }
+
+    /**
+     * 按像素中心是否落在三角形内填充，颜色和纹理坐标按重心坐标插值
+     */
+    fn fill_triangle(
+        &self,
+        pixels: &mut [Color32],
+        width: usize,
+        clip: Rect,
+        vertices: [&Vertex; 3],
+        texture: Option<&ColorImage>,
+    ) {
+        let points = vertices.map(|vertex| vertex.pos * self.pixels_per_point);
+        let edge = |a: Pos2, b: Pos2, p: Pos2| (b - a).x * (p - a).y - (b - a).y * (p - a).x;
+        let area = edge(points[0], points[1], points[2]);
+        if area.abs() < f32::EPSILON {
+            return;
+        }
+        let bounds = Rect::from_points(&points).intersect(clip);
+        if !bounds.is_positive() {
+            return;
+        }
+        for y in bounds.top().floor() as usize..bounds.bottom().ceil() as usize {
+            for x in bounds.left().floor() as usize..bounds.right().ceil() as usize {
+                let p = Pos2::new(x as f32 + 0.5, y as f32 + 0.5);
+                let weights = [
+                    edge(points[1], points[2], p) / area,
+                    edge(points[2], points[0], p) / area,
+                    edge(points[0], points[1], p) / area,
+                ];
+                if weights.iter().any(|weight| *weight < 0.0) {
+                    continue;
+                }
+                let mut color = [0.0; 4];
+                let mut uv = Vec2::ZERO;
+                for (vertex, weight) in vertices.iter().zip(weights) {
+                    for (channel, value) in color.iter_mut().zip(vertex.color.to_array()) {
+                        *channel += value as f32 * weight;
+                    }
+                    uv += vertex.uv.to_vec2() * weight;
+                }
+                if let Some(texture) = texture {
+                    let column =
+                        ((uv.x * texture.width() as f32) as usize).min(texture.width() - 1);
+                    let row = ((uv.y * texture.height() as f32) as usize).min(texture.height() - 1);
+                    let texel = texture[(column, row)].to_array();
+                    for (channel, value) in color.iter_mut().zip(texel) {
+                        *channel *= value as f32 / 255.0;
+                    }
+                }
+                // 预乘 alpha 的 over 混合
+                let target = &mut pixels[y * width + x];
+                let inverse = 1.0 - color[3] / 255.0;
+                let blended = color
+                    .iter()
+                    .zip(target.to_array())
+                    .map(|(source, target)| (source + target as f32 * inverse).round() as u8)
+                    .collect::<Vec<u8>>();
+                *target = Color32::from_rgba_premultiplied(
+                    blended[0], blended[1], blended[2], blended[3],
+                );
+            }
+        }
+    }
+
+    /**
+     * 光栅化后保存为 PNG 文件
+     */
+    pub fn save_png(&self, path: &Path) -> io::Result<()> {
+        self.rasterize()
+            .save_with_format(path, ImageFormat::Png)
+            .map_err(io::Error::other)
+    }
+}
+
+/**
+ * 把图片放到系统剪贴板，部分平台上剪贴板的内容由本进程提供，clipboard 需要一直保留
+ */
+pub fn copy_image(clipboard: &mut Clipboard, image: &RgbaImage) -> io::Result<()> {
+    clipboard
+        .set_image(arboard::ImageData {
+            width: image.width() as usize,
+            height: image.height() as usize,
+            bytes: Cow::Borrowed(image.as_raw()),
+        })
+        .map_err(io::Error::other)
+}
@@ -0,0 +1,220 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::mpsc,
+    thread,
+};
+
+use code_graph::{
+    call_graph::build_call_graph,
+    fields::{build_field_graph, field_users, is_rust_struct, struct_fields},
+    hierarchy::build_hierarchy_graph,
+    imports::{build_import_graph, dependency_cycles},
+    patch::build_patch_graph,
+    CodeNode, Graph,
+};
+use eframe::egui::Ui;
+use rfd::{FileDialog, MessageDialog};
+
+use crate::{GraphTab, MyApp};
+
+impl MyApp {
+    /**
+     * 选择补丁文件，在后台生成只包含改动符号的图
+     */
+    pub(crate) fn load_patch(&mut self) {
+        let Some(patch_path) = FileDialog::new()
+            .add_filter("patch", &["patch", "diff"])
+            .pick_file()
+        else {
+            return;
+        };
+        let patch = match fs::read_to_string(&patch_path) {
+            Ok(patch) => patch,
+            Err(err) => {
+                MessageDialog::new()
+                    .set_title("打开失败")
+                    .set_description(err.to_string())
+                    .show();
+                return;
+            }
+        };
+        let patch_name = patch_path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or("patch")
+            .to_owned();
+        let root_path = PathBuf::from(&self.root_path);
+        let pathes = self.pathes.clone();
+        let call_nodes = self.call_nodes.clone();
+        let (tx, rx) = mpsc::channel();
+        self.patch_rx = Some(rx);
+        thread::spawn(move || {
+            let graph = build_patch_graph(&root_path, &patch_name, &patch, &pathes, &call_nodes);
+            let _ = tx.send(graph);
+        });
+    }
+    /**
+     * 在后台根据扫描得到的定义和调用生成整个项目的调用图
+     */
+    pub(crate) fn load_call_graph(&mut self) {
+        if self.definitions.is_empty() {
+            return;
+        }
+        let root_path = PathBuf::from(&self.root_path);
+        let project_name = root_path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or("project")
+            .to_owned();
+        let definitions = self.definitions.clone();
+        let call_nodes = self.call_nodes.clone();
+        let (tx, rx) = mpsc::channel();
+        self.call_graph_rx = Some(rx);
+        thread::spawn(move || {
+            let graph = build_call_graph(&project_name, &root_path, &definitions, &call_nodes);
+            let _ = tx.send(graph);
+        });
+    }
+    /**
+     * 在后台根据扫描得到的定义生成类型的继承和实现层级
+     */
+    pub(crate) fn load_hierarchy(&mut self) {
+        if self.definitions.is_empty() {
+            return;
+        }
+        let project_name = Path::new(&self.root_path)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or("project")
+            .to_owned();
+        let definitions = self.definitions.clone();
+        let (tx, rx) = mpsc::channel();
+        self.hierarchy_rx = Some(rx);
+        thread::spawn(move || {
+            let _ = tx.send(build_hierarchy_graph(&project_name, &definitions));
+        });
+    }
+    /**
+     * 在后台查找读写当前结构体字段的函数，生成字段与函数之间的使用图
+     */
+    pub(crate) fn load_field_usage(&mut self) {
+        if self.definitions.is_empty() || !is_rust_struct(&self.current_node) {
+            return;
+        }
+        let definitions = self.definitions.clone();
+        let structure = self.current_node.clone();
+        let (tx, rx) = mpsc::channel();
+        self.field_usage_rx = Some(rx);
+        thread::spawn(move || {
+            let graph = struct_fields(&structure).map(|fields| {
+                let users = field_users(&definitions, &structure, &fields);
+                build_field_graph(&structure, &fields, &users)
+            });
+            let _ = tx.send(graph);
+        });
+    }
+    /**
+     * 在后台解析项目中每个文件的导入语句，生成文件之间的依赖图
+     */
+    pub(crate) fn load_dependency_graph(&mut self) {
+        if self.root_path.is_empty() {
+            return;
+        }
+        let root_path = PathBuf::from(&self.root_path);
+        let project_name = root_path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or("project")
+            .to_owned();
+        let pathes = self.pathes.clone();
+        let (tx, rx) = mpsc::channel();
+        self.dependency_rx = Some(rx);
+        thread::spawn(move || {
+            let graph = build_import_graph(&project_name, &root_path, &pathes);
+            let _ = tx.send(graph);
+        });
+    }
+    /**
+     * 在依赖图标签页中显示生成的依赖图，代码图保留在另一个标签页中
+     */
+    pub(crate) fn show_dependency_graph(&mut self, ui: &mut Ui, graph: Graph) {
+        self.switch_tab(GraphTab::Code);
+        let code_graph = std::mem::replace(&mut self.graph, Graph::new());
+        self.show_graph(ui, graph);
+        self.hidden_graph = Some(code_graph);
+        self.graph_tab = GraphTab::Dependencies;
+    }
+    /**
+     * 交换两个标签页的图，切换到依赖图时标出循环依赖
+     */
+    pub(crate) fn switch_tab(&mut self, tab: GraphTab) {
+        if tab == self.graph_tab {
+            return;
+        }
+        let Some(graph) = self.hidden_graph.take() else {
+            return;
+        };
+        self.hidden_graph = Some(std::mem::replace(&mut self.graph, graph));
+        self.graph_tab = tab;
+        self.cycles_key = None;
+        self.current_node = CodeNode::default();
+        self.call_stats = None;
+        self.graph_view.impact.clear();
+        self.call_sites_of = None;
+    }
+    /**
+     * 查找当前图中的循环并交给画布标出，结果保存在 cycles 中，
+     * 只在打开、重新解析、筛选或展开调用等操作改变图之后重新计算
+     */
+    pub(crate) fn mark_cycles(&mut self) {
+        let key = (
+            self.show_cycles || self.graph_tab == GraphTab::Dependencies,
+            self.graph.node_count(),
+            self.graph.edges().count(),
+        );
+        if self.cycles_key == Some(key) {
+            return;
+        }
+        self.cycles_key = Some(key);
+        self.graph_view.cycle_groups.clear();
+        self.cycles = if key.0 {
+            dependency_cycles(&self.graph)
+        } else {
+            vec![]
+        };
+        for (group, cycle) in self.cycles.iter().enumerate() {
+            for index in cycle {
+                self.graph_view.cycle_groups.insert(*index, group);
+            }
+        }
+    }
+    /**
+     * 有依赖图时在画布上方显示标签页，关闭依赖图后回到代码图
+     */
+    pub(crate) fn graph_tabs(&mut self, ui: &mut Ui) {
+        if self.hidden_graph.is_none() && self.graph_tab == GraphTab::Code {
+            return;
+        }
+        let mut selected = self.graph_tab;
+        let mut closed = false;
+        ui.horizontal(|ui| {
+            ui.selectable_value(&mut selected, GraphTab::Code, "代码图");
+            ui.selectable_value(&mut selected, GraphTab::Dependencies, "依赖图");
+            if ui.small_button("✖").on_hover_text("关闭依赖图").clicked() {
+                closed = true;
+            }
+            if !self.cycles.is_empty() {
+                ui.colored_label(
+                    ui.visuals().error_fg_color,
+                    format!("{} 处循环依赖", self.cycles.len()),
+                );
+            }
+        });
+        self.switch_tab(selected);
+        if closed {
+            self.switch_tab(GraphTab::Code);
+            self.hidden_graph = None;
+        }
+    }
+}
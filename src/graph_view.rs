@@ -0,0 +1,1833 @@
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap, HashSet, VecDeque},
+    hash::{Hash, Hasher},
+    path::Path,
+};
+
+use code_graph_core::{
+    export::SvgExporter,
+    find_clusters,
+    git::{format_date, format_elapsed},
+    history::RevisionChange,
+    route_edges, separate_overlaps, simplify_generics, ClusterBy, CodeBlockType, CodeNode,
+    CodeNodeIndex, DiffStatus, EdgeKind, EdgeRoute, EdgeRouting, GenericsDisplay, Graph,
+    LayoutEngine, LinearLayout, Visibility,
+};
+use egui::{
+    emath, epaint::RectShape, Color32, EventFilter, Key, Pos2, Rect, Stroke, Ui, Vec2, WidgetInfo,
+    WidgetType,
+};
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+
+use crate::theme::GraphTheme;
+
+/**
+ * 节点着色模式
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorMode {
+    // 按代码类型着色
+    BlockType,
+    // 按 git 变更热度着色
+    Churn,
+    // 按导入的性能数据中的耗时着色
+    Profile,
+    // 按导入的测试覆盖率着色
+    Coverage,
+    // 按函数的圈复杂度从绿到红着色
+    Complexity,
+    // 按最近修改时间着色，越近越红
+    Age,
+    // 按热点分数（圈复杂度 × 提交数）着色
+    Hotspot,
+}
+
+/**
+ * 连线样式
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum EdgeStyle {
+    // 两端之间的直线
+    Straight,
+    // 正交折线
+    #[default]
+    Elbow,
+    // 三次贝塞尔曲线，沿折线两端的方向出入节点
+    Bezier,
+}
+
+impl EdgeStyle {
+    pub const ALL: [EdgeStyle; 3] = [EdgeStyle::Straight, EdgeStyle::Elbow, EdgeStyle::Bezier];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            EdgeStyle::Straight => "直线",
+            EdgeStyle::Elbow => "折线",
+            EdgeStyle::Bezier => "曲线",
+        }
+    }
+}
+
+lazy_static! {
+    // 高对比度配色不可编辑
+    static ref HIGH_CONTRAST_THEME: GraphTheme = GraphTheme::high_contrast();
+}
+
+// 画布缩放范围
+const MIN_ZOOM: f32 = 0.25;
+const MAX_ZOOM: f32 = 4.0;
+// 节点之间至少保留的间距，小于内置布局的间距，不会打乱布局
+const NODE_SPACING: f32 = 4.0;
+// 便签的颜色和文字换行宽度
+const ANNOTATION_FILL: Color32 = Color32::from_rgb(255, 244, 168);
+const ANNOTATION_STROKE: Color32 = Color32::from_rgb(200, 176, 64);
+const ANNOTATION_WIDTH: f32 = 160.0;
+// 专注模式下与焦点节点无关的节点和连线的不透明度
+const DIMMED_OPACITY: f32 = 0.15;
+// 圈复杂度达到该值时显示为最红
+const MAX_COMPLEXITY: usize = 20;
+// 查找到的节点外框的颜色
+const SEARCH_HIGHLIGHT: Color32 = Color32::from_rgb(255, 176, 0);
+// 循环依赖中的节点和连线的颜色
+const CYCLE_HIGHLIGHT: Color32 = Color32::from_rgb(220, 50, 47);
+// 重复代码之间连线的颜色
+const DUPLICATE_COLOR: Color32 = Color32::from_rgb(150, 90, 200);
+// 影响分析中节点外框的颜色
+const IMPACT_HIGHLIGHT: Color32 = Color32::from_rgb(230, 120, 30);
+// unsafe 角标和虚线外框的颜色
+const UNSAFE_COLOR: Color32 = Color32::from_rgb(180, 20, 90);
+// 差异图和历史版本过渡中新增、删除和修改的节点和连线的颜色
+const ADDED_COLOR: Color32 = Color32::from_rgb(40, 160, 70);
+const REMOVED_COLOR: Color32 = Color32::from_rgb(220, 50, 47);
+const CHANGED_COLOR: Color32 = Color32::from_rgb(220, 170, 0);
+// 切换历史版本时过渡动画的时长（秒）
+const TRANSITION_SECONDS: f64 = 0.8;
+
+/**
+ * 切换历史版本时的过渡：新出现的节点淡入，消失的节点在原来的位置淡出
+ */
+#[derive(Debug, Clone)]
+pub struct Transition {
+    change: RevisionChange,
+    // 开始时间，为 egui 的输入时间
+    started: f64,
+}
+
+impl Transition {
+    pub fn new(change: RevisionChange, started: f64) -> Self {
+        Self { change, started }
+    }
+}
+
+/**
+ * 图组件上发生的交互
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphEvent {
+    NodeClicked(CodeNodeIndex),
+    NodeDoubleClicked(CodeNodeIndex),
+    NodeHovered(CodeNodeIndex),
+    // 节点菜单中选择在图中显示调用方
+    FindCallers(CodeNodeIndex),
+    // 点击函数节点的展开调用按钮
+    ExpandCallees(CodeNodeIndex),
+    // 节点菜单中添加或移除书签
+    ToggleBookmark(CodeNodeIndex),
+    // 点击定义右上角的引用次数
+    ShowReferences(CodeNodeIndex),
+    // 节点菜单中选择影响分析
+    AnalyseImpact(CodeNodeIndex),
+    BackgroundClicked,
+    None,
+}
+
+/**
+ * 图的绘制组件，负责渲染和交互，数据保存在 Graph 中
+ */
+pub struct GraphView {
+    pub color_mode: ColorMode,
+    pub layout_engine: Box<dyn LayoutEngine>,
+    pub generics_display: GenericsDisplay,
+    // 使用高对比度配色
+    pub high_contrast: bool,
+    pub edge_style: EdgeStyle,
+    // 按代码类型着色的配色，高对比度模式下不使用
+    pub theme: GraphTheme,
+    // 多个文件的图中按文件或目录分组显示
+    pub cluster_by: ClusterBy,
+    // 专注模式：只突出显示离焦点节点不超过指定步数的节点，None 表示关闭
+    pub focus_hops: Option<usize>,
+    // 加入书签的节点 ID，节点上显示星标
+    pub bookmarks: HashSet<String>,
+    // 包含待办注释的定义 ID -> 待办数量，节点上显示待办角标
+    pub todo_counts: HashMap<String, usize>,
+    // 查找匹配的节点，外框高亮显示
+    pub search_matches: HashSet<CodeNodeIndex>,
+    // 在节点底部用细条显示代码行数，最长的定义为满格
+    pub size_bars: bool,
+    // 循环依赖中的节点和所在循环的序号，同一循环中的节点和连线用红色标出
+    pub cycle_groups: HashMap<CodeNodeIndex, usize>,
+    // 影响分析中的节点和到被分析节点的层数，被分析的节点为 0
+    pub impact: HashMap<CodeNodeIndex, usize>,
+    // 给 unsafe 的定义加上虚线外框
+    pub unsafe_outline: bool,
+    // 正在进行的历史版本过渡动画
+    pub transition: Option<Transition>,
+    // 缓存的连线走线，节点位置（相对第一个可见节点）和大小不变时复用
+    routes: Vec<EdgeRoute>,
+    routes_key: u64,
+    routes_anchor: Pos2,
+    // 缓存的节点文字大小，按标签记录，泛型的显示方式变化时清空
+    text_sizes: HashMap<String, Vec2>,
+    text_sizes_display: GenericsDisplay,
+    // 正在编辑的便签序号
+    editing_annotation: Option<usize>,
+    // 正在离屏绘制导出的图片
+    offscreen: bool,
+    // 上一帧画布的大小，复制当前画面时使用
+    viewport_size: Vec2,
+}
+
+impl Default for GraphView {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GraphView {
+    pub fn new() -> Self {
+        Self {
+            color_mode: ColorMode::BlockType,
+            layout_engine: Box::new(LinearLayout::default()),
+            generics_display: GenericsDisplay::Full,
+            high_contrast: false,
+            edge_style: EdgeStyle::default(),
+            theme: GraphTheme::default(),
+            cluster_by: ClusterBy::default(),
+            focus_hops: None,
+            bookmarks: HashSet::new(),
+            todo_counts: HashMap::new(),
+            search_matches: HashSet::new(),
+            size_bars: false,
+            cycle_groups: HashMap::new(),
+            impact: HashMap::new(),
+            unsafe_outline: false,
+            transition: None,
+            routes: vec![],
+            routes_key: 0,
+            routes_anchor: Pos2::ZERO,
+            text_sizes: HashMap::new(),
+            text_sizes_display: GenericsDisplay::Full,
+            editing_annotation: None,
+            offscreen: false,
+            viewport_size: Vec2::ZERO,
+        }
+    }
+
+    pub fn with_layout_engine(mut self, layout_engine: impl LayoutEngine + 'static) -> Self {
+        self.layout_engine = Box::new(layout_engine);
+        self
+    }
+
+    /**
+     * 测量节点大小后对节点进行布局
+     */
+    pub fn layout(&mut self, ui: &Ui, graph: &mut Graph) {
+        self.measure_nodes(ui, graph);
+        self.layout_engine.layout(graph, ui.available_size());
+    }
+
+    /**
+     * 节点的相对位置、大小或可见性变化时重新计算走线，
+     * 整体平移时复用缓存，返回缓存的走线需要平移的距离
+     */
+    fn update_routes(&mut self, graph: &Graph, routing: EdgeRouting) -> Vec2 {
+        let anchor = graph
+            .nodes()
+            .find(|(_, node)| node.visiable())
+            .map_or(Pos2::ZERO, |(_, node)| node.position());
+        let mut hasher = DefaultHasher::new();
+        routing.hash(&mut hasher);
+        graph.edges().count().hash(&mut hasher);
+        for (_, node) in graph.nodes() {
+            node.visiable().hash(&mut hasher);
+            let relative = node.position() - anchor;
+            for value in [relative.x, relative.y, node.size().x, node.size().y] {
+                value.to_bits().hash(&mut hasher);
+            }
+        }
+        let key = hasher.finish();
+        if key != self.routes_key {
+            self.routes = route_edges(graph, routing);
+            self.routes_key = key;
+            self.routes_anchor = anchor;
+        }
+        anchor - self.routes_anchor
+    }
+
+    /**
+     * 图的结构变化后重新布局，保持根节点的位置不变
+     */
+    pub fn relayout(&mut self, ui: &Ui, graph: &mut Graph) {
+        self.measure_nodes(ui, graph);
+        self.layout_keeping_root(graph, ui.available_size());
+    }
+
+    /**
+     * 根据文字测量每个节点的大小，只有第一次出现的标签需要排版文字
+     */
+    fn measure_nodes(&mut self, ui: &Ui, graph: &mut Graph) {
+        if self.text_sizes_display != self.generics_display {
+            self.text_sizes.clear();
+            self.text_sizes_display = self.generics_display;
+        }
+        for (_, node) in graph.nodes_mut() {
+            let text_size = match self.text_sizes.get(&node.label) {
+                Some(size) => *size,
+                None => {
+                    let size = ui
+                        .painter()
+                        .layout_no_wrap(
+                            simplify_generics(&node.label, self.generics_display),
+                            egui::FontId::default(),
+                            egui::Color32::WHITE,
+                        )
+                        .size();
+                    self.text_sizes.insert(node.label.clone(), size);
+                    size
+                }
+            };
+            node.set_size(text_size + Vec2::new(16.0, 8.0));
+        }
+    }
+
+    /**
+     * 展开或折叠节点的所有后代，第一个子节点可见时折叠，没有子节点时不变
+     */
+    fn toggle_children(&self, graph: &mut Graph, index: CodeNodeIndex, viewport: Vec2) {
+        let Some(first_child) = graph.children_of(index).next() else {
+            return;
+        };
+        let expanded = graph
+            .node(first_child)
+            .is_some_and(|sub_node| sub_node.visiable());
+        let descendants = graph.descendants(index).collect::<Vec<CodeNodeIndex>>();
+        for index in descendants {
+            if let Some(sub_node) = graph.node_mut(index) {
+                sub_node.set_visiable(!expanded);
+            }
+        }
+        self.layout_keeping_root(graph, viewport);
+    }
+
+    fn layout_keeping_root(&self, graph: &mut Graph, viewport: Vec2) {
+        let root_position = graph.node(CodeNodeIndex(0)).map(|root| root.position());
+        self.layout_engine.layout(graph, viewport);
+        // 自定义布局没有考虑节点宽度时也不会重叠
+        separate_overlaps(graph, None, NODE_SPACING);
+        if let (Some(old), Some(root)) = (root_position, graph.node(CodeNodeIndex(0))) {
+            let offset = old - root.position();
+            graph.translate(offset);
+        }
+    }
+
+    /**
+     * 聚焦节点，并平移视角使节点位于画布中央
+     */
+    pub fn center_on(&self, ui: &Ui, graph: &mut Graph, index: CodeNodeIndex) {
+        graph.set_focus_idx(Some(index));
+        look_at_node(graph, index, ui.available_size());
+    }
+
+    /**
+     * 绘制图并返回本帧的交互事件，双击优先于单击，单击优先于悬停
+     */
+    pub fn ui(&mut self, ui: &mut Ui, graph: &mut Graph) -> GraphEvent {
+        let (response, painter) =
+            ui.allocate_painter(ui.available_size(), egui::Sense::click_and_drag());
+        if !self.offscreen {
+            self.viewport_size = response.rect.size();
+        }
+
+        let focus_stroke_color;
+        let stroke_color;
+        let text_color;
+        let grid_color;
+        let edge_color;
+        let dark_mode = ui.ctx().style().visuals.dark_mode;
+
+        if self.high_contrast {
+            text_color = egui::Color32::BLACK;
+            if dark_mode {
+                stroke_color = egui::Color32::WHITE;
+                focus_stroke_color = egui::Color32::from_rgb(255, 64, 255);
+                grid_color = Color32::from_gray(40);
+                edge_color = egui::Color32::WHITE;
+                painter.rect_filled(response.rect, 0.0, Color32::BLACK);
+            } else {
+                stroke_color = egui::Color32::BLACK;
+                focus_stroke_color = egui::Color32::from_rgb(200, 0, 140);
+                grid_color = Color32::from_gray(200);
+                edge_color = egui::Color32::BLACK;
+                painter.rect_filled(response.rect, 0.0, Color32::WHITE);
+            }
+        } else if dark_mode {
+            stroke_color = egui::Color32::LIGHT_GRAY;
+            text_color = egui::Color32::WHITE;
+            focus_stroke_color = egui::Color32::LIGHT_BLUE;
+            grid_color = Color32::from_gray(50);
+            edge_color = egui::Color32::GRAY;
+        } else {
+            focus_stroke_color = egui::Color32::BLUE;
+            stroke_color = egui::Color32::DARK_GRAY;
+            text_color = egui::Color32::DARK_GRAY;
+            grid_color = Color32::from_gray(220);
+            edge_color = egui::Color32::GRAY;
+        }
+
+        // 滚轮、双指捏合或 Ctrl+滚轮缩放，以指针位置为基准，保持指针下的内容不动
+        let (scroll_delta, zoom_delta) =
+            ui.input(|input| (input.smooth_scroll_delta, input.zoom_delta()));
+        let zoom_delta = zoom_delta * (scroll_delta.y / 200.0).exp();
+        if response.contains_pointer() && zoom_delta != 1.0 {
+            let camera = graph.camera_mut();
+            let zoom = (camera.zoom * zoom_delta).clamp(MIN_ZOOM, MAX_ZOOM);
+            let anchor = response.hover_pos().unwrap_or(response.rect.center()) - response.rect.min;
+            camera.zoom_around(anchor.to_pos2(), zoom);
+        }
+        let camera = graph.camera();
+        let zoom = camera.zoom;
+        let to_screen = emath::RectTransform::from_to(
+            Rect::from_min_size((-camera.offset).to_pos2(), response.rect.size() / zoom),
+            response.rect,
+        );
+
+        // 获取可用区域
+        let rect = ui.max_rect();
+
+        // 定义网格参数，网格随视角平移和缩放
+        let cell_size = (10.0 * zoom).max(5.0); // 网格单元格大小
+        let half_width = 0.25; // 线条宽度的一半
+        let grid_origin = to_screen.transform_pos(Pos2::ZERO);
+        // 所有网格线合并为一个网格（Mesh）一次提交
+        let mut grid = egui::Mesh::default();
+
+        // 垂直线
+        let mut x = rect.left() + (grid_origin.x - rect.left()).rem_euclid(cell_size);
+        while x <= rect.right() {
+            let line = Rect::from_x_y_ranges(x - half_width..=x + half_width, rect.y_range());
+            grid.add_colored_rect(line, grid_color);
+            x += cell_size;
+        }
+
+        // 水平线
+        let mut y = rect.top() + (grid_origin.y - rect.top()).rem_euclid(cell_size);
+        while y <= rect.bottom() {
+            let line = Rect::from_x_y_ranges(rect.x_range(), y - half_width..=y + half_width);
+            grid.add_colored_rect(line, grid_color);
+            y += cell_size;
+        }
+        // 离屏绘制导出图片时不画网格
+        if !self.offscreen {
+            painter.add(grid);
+        }
+
+        self.measure_nodes(ui, graph);
+        let fill_color_list = self.fill_colors(graph, dark_mode);
+        // 项目中有多种语言时在节点上显示语言
+        let multi_language = {
+            let mut languages = graph
+                .nodes()
+                .filter_map(|(_, node)| node.language.as_deref());
+            let first_language = languages.next();
+            first_language.is_some_and(|first| languages.any(|language| language != first))
+        };
+        let max_lines = graph
+            .nodes()
+            .filter(|(_, node)| node.visiable() && is_definition(node))
+            .map(|(_, node)| node.line_count)
+            .max()
+            .unwrap_or(0);
+        let mut focus_node = graph.get_focus_idx();
+        // 专注模式下淡化的节点
+        let dimmed = match (self.focus_hops, focus_node) {
+            (Some(hops), Some(center)) => {
+                let mut dimmed = vec![true; graph.node_count()];
+                for index in graph.neighborhood(center, hops).indices() {
+                    dimmed[index.0] = false;
+                }
+                dimmed
+            }
+            _ => vec![false; graph.node_count()],
+        };
+        // 过渡动画的进度，从 0.0 到 1.0，结束后清除
+        let progress = self.transition.as_ref().map_or(1.0, |transition| {
+            ((ui.input(|input| input.time) - transition.started) / TRANSITION_SECONDS)
+                .clamp(0.0, 1.0) as f32
+        });
+        if progress >= 1.0 {
+            self.transition = None;
+        } else {
+            ui.ctx().request_repaint();
+        }
+        let appearing = match &self.transition {
+            Some(transition) => graph
+                .nodes()
+                .map(|(_, node)| transition.change.appeared.contains(node.id()))
+                .collect(),
+            None => vec![false; graph.node_count()],
+        };
+        let mut event = GraphEvent::None;
+        // 通过键盘获得焦点但不在画布中的节点，绘制完成后平移到中央
+        let mut scroll_to = None;
+        // 正在拖动的节点，拖动后推开与它重叠的节点
+        let mut dragged = None;
+        // 拖动背景、中键拖动或按住空格拖动时平移视角
+        let space_down = ui.input(|input| input.key_down(Key::Space));
+        let mut pan = if response.dragged() {
+            response.drag_delta()
+        } else {
+            Vec2::ZERO
+        };
+
+        // 引用次数角标和覆盖率外框会超出节点，判断是否在画布中时留出余量
+        let viewport = response.rect.expand(8.0);
+
+        // 绘制分组的背景，点击分组右上角的名称折叠或展开
+        let clusters = find_clusters(graph, self.cluster_by);
+        let (cluster_fill, cluster_stroke) = if dark_mode {
+            (Color32::from_white_alpha(8), Color32::from_gray(90))
+        } else {
+            (Color32::from_black_alpha(10), Color32::from_gray(180))
+        };
+        let mut toggled_cluster = None;
+        for cluster in &clusters {
+            let Some(bounds) = cluster
+                .members
+                .iter()
+                .filter_map(|index| graph.node(*index))
+                .filter(|node| node.visiable())
+                .map(|node| {
+                    Rect::from_min_size(
+                        to_screen.transform_pos(node.position()),
+                        node.size() * zoom,
+                    )
+                })
+                .reduce(|bounds, rect| bounds.union(rect))
+            else {
+                continue;
+            };
+            let bounds = bounds.expand(6.0 * zoom);
+            let collapsed = graph.is_cluster_collapsed(&cluster.key);
+            let title = if collapsed {
+                format!(
+                    "{}（已折叠 {} 个节点）",
+                    cluster.label,
+                    cluster.members.len()
+                )
+            } else {
+                cluster.label.clone()
+            };
+            let galley =
+                painter.layout_no_wrap(title, egui::FontId::proportional(10.0), Color32::WHITE);
+            let header_size = Vec2::new(galley.size().x + 8.0, 14.0);
+            let header_rect = Rect::from_min_size(
+                Pos2::new(bounds.right() - header_size.x, bounds.top() - 7.0),
+                header_size,
+            );
+            if !viewport.intersects(bounds.union(header_rect)) {
+                continue;
+            }
+            painter.rect(bounds, 6.0, cluster_fill, Stroke::new(1.0, cluster_stroke));
+            painter.rect_filled(header_rect, 7.0, Color32::from_gray(100));
+            painter.galley(
+                header_rect.center() - galley.size() / 2.0,
+                galley,
+                Color32::WHITE,
+            );
+            let header_id = response.id.with(("cluster", &cluster.key));
+            let header_response = ui.interact(header_rect, header_id, egui::Sense::click());
+            header_response.widget_info(|| {
+                let action = if collapsed { "展开" } else { "折叠" };
+                WidgetInfo::labeled(
+                    WidgetType::Button,
+                    true,
+                    format!("{}分组 {}", action, cluster.label),
+                )
+            });
+            if header_response.clicked() {
+                toggled_cluster = Some(cluster);
+            }
+            header_response.on_hover_text(&cluster.key);
+        }
+        if let Some(cluster) = toggled_cluster {
+            if graph.is_cluster_collapsed(&cluster.key) {
+                graph.expand_cluster(&cluster.key);
+            } else {
+                graph.collapse_cluster(cluster);
+            }
+            self.layout_keeping_root(graph, response.rect.size());
+        }
+
+        // 绘制节点，图形收集后一次提交给画布
+        let mut node_shapes = vec![];
+        let mut navigate = None;
+        let mut toggled = None;
+        for (index, node) in graph.nodes_mut() {
+            let node_pos = to_screen.transform_pos(node.position());
+            let rect = egui::Rect::from_min_size(node_pos, node.size() * zoom);
+            let point_id = response.id.with(node.id());
+            // 画布外的节点不绘制也不响应交互，获得键盘焦点的节点除外
+            let on_screen =
+                viewport.intersects(rect) || ui.memory(|memory| memory.has_focus(point_id));
+            if node.visiable() && on_screen {
+                let fade = |color: Color32| {
+                    let color = if dimmed[index.0] {
+                        color.gamma_multiply(DIMMED_OPACITY)
+                    } else {
+                        color
+                    };
+                    if appearing[index.0] {
+                        color.gamma_multiply(progress)
+                    } else {
+                        color
+                    }
+                };
+                node_shapes.push(egui::Shape::Rect(RectShape::new(
+                    rect,
+                    5.0,
+                    fade(fill_color_list[index.0]),
+                    Stroke::new(1.0, fade(stroke_color)),
+                )));
+
+                let galley = painter.layout_no_wrap(
+                    simplify_generics(&node.label, self.generics_display),
+                    egui::FontId::proportional(egui::FontId::default().size * zoom),
+                    fade(text_color),
+                );
+                node_shapes.push(egui::Shape::galley(
+                    node_pos + Vec2::new(8.0, 4.0) * zoom,
+                    galley,
+                    text_color,
+                ));
+
+                if self.size_bars && max_lines > 0 && is_definition(node) {
+                    let ratio = node.line_count as f32 / max_lines as f32;
+                    let track = Rect::from_min_max(
+                        Pos2::new(rect.left() + 4.0 * zoom, rect.bottom() - 3.5 * zoom),
+                        Pos2::new(rect.right() - 4.0 * zoom, rect.bottom() - 1.5 * zoom),
+                    );
+                    let bar = Rect::from_min_size(
+                        track.min,
+                        Vec2::new(track.width() * ratio, track.height()),
+                    );
+                    node_shapes.push(egui::Shape::rect_filled(
+                        bar,
+                        1.0,
+                        fade(heat_color(Color32::from_rgb(90, 170, 90), ratio, dark_mode)),
+                    ));
+                }
+
+                // 上边缘左侧显示书签、待办、可见性、异步、测试和语言角标
+                let mut badge_x = rect.left() + 6.0;
+                let bookmark_badge = self
+                    .bookmarks
+                    .contains(node.id())
+                    .then_some(("★", Color32::from_rgb(218, 165, 32)));
+                let todo_badge = self
+                    .todo_counts
+                    .contains_key(node.id())
+                    .then_some(("TODO", Color32::from_rgb(200, 120, 0)));
+                for (text, color) in bookmark_badge
+                    .into_iter()
+                    .chain(todo_badge)
+                    .chain(node_badges(node, multi_language))
+                {
+                    let galley = painter.layout_no_wrap(
+                        text.to_owned(),
+                        egui::FontId::proportional(8.0),
+                        fade(Color32::WHITE),
+                    );
+                    let badge_rect = Rect::from_min_size(
+                        Pos2::new(badge_x, rect.top() - 5.0),
+                        Vec2::new(galley.size().x + 6.0, 10.0),
+                    );
+                    node_shapes.push(egui::Shape::rect_filled(badge_rect, 5.0, fade(color)));
+                    node_shapes.push(egui::Shape::galley(
+                        badge_rect.center() - galley.size() / 2.0,
+                        galley,
+                        Color32::WHITE,
+                    ));
+                    badge_x = badge_rect.right() + 3.0;
+                }
+
+                // 右上角显示引用次数，没有引用的定义使用灰色，点击查看引用列表
+                let mut reference_badge = None;
+                if let Some(references) = node.references {
+                    let badge_color = if references == 0 {
+                        Color32::GRAY
+                    } else {
+                        ui.visuals().selection.bg_fill
+                    };
+                    let galley = painter.layout_no_wrap(
+                        format!("{} 处引用", references),
+                        egui::FontId::proportional(9.0),
+                        fade(Color32::WHITE),
+                    );
+                    let badge_size = Vec2::new(galley.size().x + 8.0, 14.0);
+                    // 右端与节点右边缘之外对齐，避免盖住上边缘左侧的角标
+                    let badge_rect = Rect::from_min_size(
+                        Pos2::new(rect.right() + 6.0 - badge_size.x, rect.top() - 7.0),
+                        badge_size,
+                    );
+                    node_shapes.push(egui::Shape::rect_filled(badge_rect, 7.0, fade(badge_color)));
+                    node_shapes.push(egui::Shape::galley(
+                        badge_rect.center() - galley.size() / 2.0,
+                        galley,
+                        Color32::WHITE,
+                    ));
+                    reference_badge = Some((badge_rect, references));
+                }
+
+                let node_response = ui.interact(rect, point_id, egui::Sense::click_and_drag());
+                node_response.widget_info(|| {
+                    WidgetInfo::labeled(WidgetType::Button, true, accessible_label(node))
+                });
+                if node_response.dragged_by(egui::PointerButton::Middle)
+                    || (node_response.dragged() && space_down)
+                {
+                    pan += node_response.drag_delta();
+                } else if node_response.dragged() {
+                    // 更新节点位置
+                    node.set_position(node.position() + node_response.drag_delta() / zoom);
+                    dragged = Some(index);
+                }
+                if node_response.gained_focus() && !response.rect.contains_rect(rect) {
+                    scroll_to = Some(index);
+                }
+                // 键盘操作：Enter 在编辑器中打开，空格选中节点并展开或折叠子节点，
+                // 方向键或 hjkl 沿连线移动到相邻的节点
+                let open_pressed =
+                    node_response.has_focus() && ui.input(|input| input.key_pressed(Key::Enter));
+                if node_response.has_focus() {
+                    // 方向键由画布处理，不让 egui 按位置切换焦点
+                    ui.memory_mut(|memory| {
+                        memory.set_focus_lock_filter(
+                            point_id,
+                            EventFilter {
+                                horizontal_arrows: true,
+                                vertical_arrows: true,
+                                ..Default::default()
+                            },
+                        )
+                    });
+                    if let Some(direction) = ui.input(navigation_direction) {
+                        navigate = Some((index, direction));
+                    }
+                    if ui.input(|input| input.key_pressed(Key::Space)) {
+                        toggled = Some(index);
+                    }
+                }
+                if node_response.clicked() {
+                    // 点击后可以直接用键盘继续操作
+                    node_response.request_focus();
+                }
+                if node_response.double_clicked() || open_pressed {
+                    focus_node = Some(index);
+                    event = GraphEvent::NodeDoubleClicked(index);
+                } else if node_response.clicked() {
+                    focus_node = Some(index);
+                    event = GraphEvent::NodeClicked(index);
+                } else if node_response.hovered() && event == GraphEvent::None {
+                    event = GraphEvent::NodeHovered(index);
+                }
+                // 悬停或选中的函数节点右下角显示展开调用的按钮
+                if is_function(node)
+                    && (focus_node == Some(index) || ui.rect_contains_pointer(rect.expand(8.0)))
+                {
+                    let button_rect =
+                        Rect::from_center_size(rect.right_bottom(), Vec2::splat(14.0));
+                    let button_response = ui
+                        .interact(button_rect, point_id.with("callees"), egui::Sense::click())
+                        .on_hover_text("展开调用的函数");
+                    button_response.widget_info(|| {
+                        WidgetInfo::labeled(
+                            WidgetType::Button,
+                            true,
+                            format!("展开调用 {}", accessible_label(node)),
+                        )
+                    });
+                    let button_color = if button_response.hovered() {
+                        focus_stroke_color
+                    } else {
+                        stroke_color
+                    };
+                    node_shapes.push(egui::Shape::circle_filled(
+                        button_rect.center(),
+                        7.0,
+                        fade(button_color),
+                    ));
+                    let galley = painter.layout_no_wrap(
+                        "→".to_owned(),
+                        egui::FontId::proportional(10.0),
+                        fade(Color32::WHITE),
+                    );
+                    node_shapes.push(egui::Shape::galley(
+                        button_rect.center() - galley.size() / 2.0,
+                        galley,
+                        Color32::WHITE,
+                    ));
+                    if button_response.clicked() {
+                        focus_node = Some(index);
+                        event = GraphEvent::ExpandCallees(index);
+                    }
+                }
+                // 引用次数的点击在节点之后注册，优先于节点本身
+                if let Some((badge_rect, references)) = reference_badge {
+                    let badge_response = ui
+                        .interact(
+                            badge_rect,
+                            point_id.with("references"),
+                            egui::Sense::click(),
+                        )
+                        .on_hover_text("查看引用")
+                        .on_hover_cursor(egui::CursorIcon::PointingHand);
+                    badge_response.widget_info(|| {
+                        WidgetInfo::labeled(
+                            WidgetType::Button,
+                            true,
+                            format!("{} 的 {} 处引用", accessible_label(node), references),
+                        )
+                    });
+                    if badge_response.clicked() {
+                        focus_node = Some(index);
+                        event = GraphEvent::ShowReferences(index);
+                    }
+                }
+                // 完全没有被测试覆盖的函数加上红色外框
+                if self.color_mode == ColorMode::Coverage && node.coverage() == Some(0.0) {
+                    node_shapes.push(egui::Shape::rect_stroke(
+                        rect.expand(3.0),
+                        7.0,
+                        Stroke::new(2.0, Color32::RED),
+                    ));
+                }
+                if self.cycle_groups.contains_key(&index) {
+                    node_shapes.push(egui::Shape::rect_stroke(
+                        rect,
+                        5.0,
+                        Stroke::new(2.0, fade(CYCLE_HIGHLIGHT)),
+                    ));
+                }
+                if self.unsafe_outline && node.is_unsafe {
+                    let outline = rect.expand(2.5);
+                    node_shapes.extend(egui::Shape::dashed_line(
+                        &[
+                            outline.left_top(),
+                            outline.right_top(),
+                            outline.right_bottom(),
+                            outline.left_bottom(),
+                            outline.left_top(),
+                        ],
+                        Stroke::new(2.0, fade(UNSAFE_COLOR)),
+                        6.0,
+                        3.0,
+                    ));
+                }
+                // 差异图中删除的节点用虚线外框，新增和修改的用实线外框
+                match node.diff {
+                    Some(DiffStatus::Removed) => {
+                        let outline = rect.expand(2.0);
+                        node_shapes.extend(egui::Shape::dashed_line(
+                            &[
+                                outline.left_top(),
+                                outline.right_top(),
+                                outline.right_bottom(),
+                                outline.left_bottom(),
+                                outline.left_top(),
+                            ],
+                            Stroke::new(2.5, fade(REMOVED_COLOR)),
+                            6.0,
+                            3.0,
+                        ));
+                    }
+                    Some(status) => node_shapes.push(egui::Shape::rect_stroke(
+                        rect.expand(2.0),
+                        6.0,
+                        Stroke::new(2.5, fade(diff_color(status))),
+                    )),
+                    None => {}
+                }
+                if appearing[index.0] {
+                    node_shapes.push(egui::Shape::rect_stroke(
+                        rect.expand(2.0),
+                        6.0,
+                        Stroke::new(2.0, ADDED_COLOR.gamma_multiply(1.0 - progress)),
+                    ));
+                }
+                // 影响分析的起点用粗框，受影响的定义用细框
+                if let Some(depth) = self.impact.get(&index) {
+                    node_shapes.push(egui::Shape::rect_stroke(
+                        rect.expand(2.0),
+                        6.0,
+                        Stroke::new(if *depth == 0 { 3.0 } else { 2.0 }, fade(IMPACT_HIGHLIGHT)),
+                    ));
+                }
+                // 查找匹配的节点外面加一圈半透明的光晕
+                if self.search_matches.contains(&index) {
+                    node_shapes.push(egui::Shape::rect_stroke(
+                        rect.expand(4.0),
+                        8.0,
+                        Stroke::new(5.0, SEARCH_HIGHLIGHT.gamma_multiply(0.35)),
+                    ));
+                    node_shapes.push(egui::Shape::rect_stroke(
+                        rect.expand(1.5),
+                        6.0,
+                        Stroke::new(1.5, SEARCH_HIGHLIGHT),
+                    ));
+                }
+                if let Some(f_node) = focus_node {
+                    if f_node == index {
+                        node_shapes.push(egui::Shape::rect_stroke(
+                            rect,
+                            5.0,
+                            Stroke::new(2.5, focus_stroke_color),
+                        ));
+                    }
+                }
+                // 右键或长按打开节点菜单
+                node_response.context_menu(|ui| {
+                    if ui.button("选中").clicked() {
+                        focus_node = Some(index);
+                        event = GraphEvent::NodeClicked(index);
+                        ui.close_menu();
+                    }
+                    if ui.button("在编辑器中打开").clicked() {
+                        focus_node = Some(index);
+                        event = GraphEvent::NodeDoubleClicked(index);
+                        ui.close_menu();
+                    }
+                    if ui.button("复制名称").clicked() {
+                        ui.ctx().copy_text(node.label.trim().to_owned());
+                        ui.close_menu();
+                    }
+                    if node.level() > 0
+                        && node.block_type() != &CodeBlockType::CALL
+                        && ui.button("查找调用方").clicked()
+                    {
+                        focus_node = Some(index);
+                        event = GraphEvent::FindCallers(index);
+                        ui.close_menu();
+                    }
+                    let bookmark_action = if self.bookmarks.contains(node.id()) {
+                        "移除书签"
+                    } else {
+                        "添加书签"
+                    };
+                    if ui.button(bookmark_action).clicked() {
+                        focus_node = Some(index);
+                        event = GraphEvent::ToggleBookmark(index);
+                        ui.close_menu();
+                    }
+                    if is_function(node) && ui.button("展开调用").clicked() {
+                        focus_node = Some(index);
+                        event = GraphEvent::ExpandCallees(index);
+                        ui.close_menu();
+                    }
+                    if is_definition(node) && ui.button("影响分析").clicked() {
+                        focus_node = Some(index);
+                        event = GraphEvent::AnalyseImpact(index);
+                        ui.close_menu();
+                    }
+                });
+                let mut hover_text = node.doc.clone().into_iter().collect::<Vec<String>>();
+                if let Some(count) = self.todo_counts.get(node.id()) {
+                    hover_text.push(format!("{} 条待办注释", count));
+                }
+                if matches!(self.color_mode, ColorMode::Churn | ColorMode::Age) {
+                    if let Some(churn) = node.churn().filter(|churn| churn.last_modified > 0) {
+                        hover_text.push(format!(
+                            "提交次数: {}\n最近修改: {} {}（{}）",
+                            churn.commits,
+                            churn.last_author,
+                            format_date(churn.last_modified),
+                            format_elapsed(churn.last_modified)
+                        ));
+                    }
+                }
+                if self.color_mode == ColorMode::Coverage {
+                    match node.coverage() {
+                        Some(0.0) => hover_text.push("行覆盖率: 0%（未测试）".to_owned()),
+                        Some(coverage) => {
+                            hover_text.push(format!("行覆盖率: {:.1}%", coverage * 100.0))
+                        }
+                        None => {}
+                    }
+                }
+                if self.size_bars && is_definition(node) {
+                    hover_text.push(format!(
+                        "{} 行，{}",
+                        node.line_count,
+                        format_byte_size(node.byte_size)
+                    ));
+                }
+                if self.color_mode == ColorMode::Complexity && is_function(node) {
+                    hover_text.push(format!("圈复杂度: {}", node.complexity));
+                }
+                if self.color_mode == ColorMode::Hotspot {
+                    if let (Some(score), Some(churn)) = (node.hotspot_score(), node.churn()) {
+                        hover_text.push(format!(
+                            "热点分数: {}（圈复杂度 {} × 提交 {}）",
+                            score, node.complexity, churn.commits
+                        ));
+                    }
+                }
+                if self.color_mode == ColorMode::Profile {
+                    if let Some(sample) = node.profile() {
+                        hover_text.push(format!(
+                            "耗时占比: {:.1}%\n采样数: {}（自身 {}）",
+                            sample.ratio * 100.0,
+                            sample.total_samples,
+                            sample.self_samples
+                        ));
+                    }
+                }
+                if let Some(group) = self.cycle_groups.get(&index) {
+                    let members = self.cycle_groups.values().filter(|g| *g == group).count();
+                    hover_text.push(format!("循环依赖：与另外 {} 个节点互相依赖", members - 1));
+                }
+                match node.diff {
+                    Some(DiffStatus::Added) => hover_text.push("差异：新增".to_owned()),
+                    Some(DiffStatus::Removed) => hover_text.push("差异：删除".to_owned()),
+                    Some(DiffStatus::Changed) => hover_text.push("差异：修改".to_owned()),
+                    None => {}
+                }
+                match self.impact.get(&index) {
+                    Some(0) => hover_text.push("影响分析的起点".to_owned()),
+                    Some(depth) => hover_text.push(format!("影响分析：第 {} 层调用方", depth)),
+                    None => {}
+                }
+                if !hover_text.is_empty() {
+                    node_response.on_hover_text(hover_text.join("\n\n"));
+                }
+            }
+        }
+        // 上一个版本中有、这个版本中没有的节点在原来的位置淡出
+        if let Some(transition) = &self.transition {
+            let opacity = 1.0 - progress;
+            for node in &transition.change.vanished {
+                let rect = Rect::from_min_size(
+                    to_screen.transform_pos(node.position()),
+                    node.size() * zoom,
+                );
+                if !viewport.intersects(rect) {
+                    continue;
+                }
+                node_shapes.push(egui::Shape::rect_filled(
+                    rect,
+                    5.0,
+                    Color32::GRAY.gamma_multiply(0.5 * opacity),
+                ));
+                node_shapes.extend(egui::Shape::dashed_line(
+                    &[
+                        rect.left_top(),
+                        rect.right_top(),
+                        rect.right_bottom(),
+                        rect.left_bottom(),
+                        rect.left_top(),
+                    ],
+                    Stroke::new(2.0, REMOVED_COLOR.gamma_multiply(opacity)),
+                    6.0,
+                    3.0,
+                ));
+                let galley = painter.layout_no_wrap(
+                    simplify_generics(&node.label, self.generics_display),
+                    egui::FontId::proportional(egui::FontId::default().size * zoom),
+                    text_color.gamma_multiply(opacity),
+                );
+                node_shapes.push(egui::Shape::galley(
+                    rect.min + Vec2::new(8.0, 4.0) * zoom,
+                    galley,
+                    text_color,
+                ));
+            }
+        }
+        painter.extend(node_shapes);
+        if let Some(next) =
+            navigate.and_then(|(index, direction)| adjacent_node(graph, index, direction))
+        {
+            focus_node = Some(next);
+            event = GraphEvent::NodeClicked(next);
+            if let Some(node) = graph.node(next) {
+                let next_id = response.id.with(node.id());
+                ui.memory_mut(|memory| memory.request_focus(next_id));
+            }
+        }
+        if let Some(index) = toggled {
+            self.toggle_children(graph, index, response.rect.size());
+        }
+        graph.camera_mut().offset += pan / zoom;
+        if let Some(index) = dragged {
+            separate_overlaps(graph, Some(index), NODE_SPACING);
+        }
+        graph.set_focus_idx(focus_node);
+        if let Some(index) = scroll_to {
+            look_at_node(graph, index, response.rect.size());
+        }
+        if response.clicked() && event == GraphEvent::None {
+            event = GraphEvent::BackgroundClicked;
+        }
+
+        // 绘制边
+        let routing = self.layout_engine.edge_routing();
+        let offset = self.update_routes(graph, routing);
+        // 调用关系的每处调用行号，多处调用时在连线中间显示次数
+        let call_lines = graph
+            .edges()
+            .filter(|edge| edge.kind() == EdgeKind::Calls && edge.call_lines().len() > 1)
+            .map(|edge| ((edge.from().0, edge.to().0), edge.call_lines()))
+            .collect::<HashMap<(usize, usize), &[usize]>>();
+        // 差异图中有变化的连线
+        let edge_diffs = graph
+            .edges()
+            .filter_map(|edge| Some(((edge.from(), edge.to(), edge.kind()), edge.diff()?)))
+            .collect::<HashMap<(CodeNodeIndex, CodeNodeIndex, EdgeKind), DiffStatus>>();
+        // 连线图形收集后一次提交给画布
+        let mut edge_shapes = vec![];
+        let mut label_shapes = vec![];
+        for route in &self.routes {
+            let in_cycle = route.kind == EdgeKind::Calls
+                && self
+                    .cycle_groups
+                    .get(&route.from)
+                    .is_some_and(|group| self.cycle_groups.get(&route.to) == Some(group));
+            let color = match route.kind {
+                EdgeKind::Contains => edge_color,
+                EdgeKind::Calls if in_cycle => CYCLE_HIGHLIGHT,
+                EdgeKind::Calls => focus_stroke_color,
+                EdgeKind::Duplicate => DUPLICATE_COLOR,
+            };
+            let edge_diff = edge_diffs.get(&(route.from, route.to, route.kind));
+            let color = edge_diff.map_or(color, |status| diff_color(*status));
+            // 任意一端被淡化的连线也淡化
+            let color = if dimmed[route.from.0] || dimmed[route.to.0] {
+                color.gamma_multiply(DIMMED_OPACITY)
+            } else {
+                color
+            };
+            let points = route
+                .points
+                .iter()
+                .map(|point| to_screen.transform_pos(*point + offset))
+                .collect::<Vec<Pos2>>();
+            let (Some(start), Some(end)) = (points.first().copied(), points.last().copied()) else {
+                continue;
+            };
+            // 曲线不会超出控制点围成的范围，整体在画布外的连线不绘制
+            let reach = ((end - start).length() / 2.0).max(20.0 * zoom);
+            if !viewport.intersects(Rect::from_points(&points).expand(reach)) {
+                continue;
+            }
+            let stroke = Stroke::new(
+                if in_cycle || edge_diff.is_some() {
+                    2.0
+                } else {
+                    1.0
+                },
+                color,
+            );
+            // 终点处的方向：直线为整条线的方向，折线和曲线为最后一段的方向
+            let before_end = match self.edge_style {
+                EdgeStyle::Straight => start,
+                EdgeStyle::Elbow | EdgeStyle::Bezier => points
+                    .get(points.len().saturating_sub(2))
+                    .copied()
+                    .unwrap_or(start),
+            };
+            edge_shapes.extend(edge_end(route.kind, end, end - before_end, color, zoom));
+            let midpoint = match self.edge_style {
+                EdgeStyle::Straight => {
+                    edge_shapes.push(egui::Shape::line_segment([start, end], stroke));
+                    start.lerp(end, 0.5)
+                }
+                EdgeStyle::Elbow => {
+                    let midpoint = polyline_midpoint(&points);
+                    edge_shapes.push(egui::Shape::line(points, stroke));
+                    midpoint
+                }
+                EdgeStyle::Bezier => {
+                    // 控制点沿折线第一段和最后一段的方向伸出
+                    let direction = |from: Pos2, to: Pos2| (to - from).normalized() * reach;
+                    let first = points.get(1).copied().unwrap_or(end);
+                    let last = points
+                        .get(points.len().saturating_sub(2))
+                        .copied()
+                        .unwrap_or(start);
+                    let shape = egui::epaint::CubicBezierShape::from_points_stroke(
+                        [
+                            start,
+                            start + direction(start, first),
+                            end + direction(end, last),
+                            end,
+                        ],
+                        false,
+                        Color32::TRANSPARENT,
+                        stroke,
+                    );
+                    let midpoint = shape.sample(0.5);
+                    edge_shapes.push(shape.into());
+                    midpoint
+                }
+            };
+            if let Some(lines) = call_lines.get(&(route.from.0, route.to.0)) {
+                let galley = painter.layout_no_wrap(
+                    lines.len().to_string(),
+                    egui::FontId::proportional(10.0),
+                    Color32::WHITE,
+                );
+                let label_size = Vec2::new(galley.size().x.max(galley.size().y) + 6.0, 14.0);
+                let label_rect = Rect::from_center_size(midpoint, label_size);
+                label_shapes.push(egui::Shape::rect_filled(label_rect, 7.0, color));
+                label_shapes.push(egui::Shape::galley(
+                    label_rect.center() - galley.size() / 2.0,
+                    galley,
+                    Color32::WHITE,
+                ));
+                // 悬停时列出每处调用的位置
+                let file_name = graph
+                    .node(route.from)
+                    .and_then(|node| Path::new(&node.file_path).file_name())
+                    .and_then(|name| name.to_str())
+                    .unwrap_or("");
+                let mut hover_text = format!("{} 处调用", lines.len());
+                for line in lines.iter() {
+                    hover_text.push_str(&format!("\n{}:{}", file_name, line));
+                }
+                let label_id = response.id.with(("call-count", route.from.0, route.to.0));
+                ui.interact(label_rect, label_id, egui::Sense::hover())
+                    .on_hover_text(hover_text);
+            }
+        }
+        // 调用次数画在所有连线上面
+        painter.extend(edge_shapes);
+        painter.extend(label_shapes);
+        // 绘制伸缩
+        let mut toggle_shapes = vec![];
+        let mut level_queue = graph.roots().collect::<VecDeque<CodeNodeIndex>>();
+        while let Some(node_index) = level_queue.pop_front() {
+            let sub_nodes = graph
+                .children_of(node_index)
+                .collect::<Vec<CodeNodeIndex>>();
+            level_queue.extend(sub_nodes.iter().copied());
+            let Some(node) = graph.node(node_index) else {
+                continue;
+            };
+            if !sub_nodes.is_empty() && node.visiable() {
+                // 伸缩按钮放在包含关系的边从父节点分出的位置
+                let tree_point = match routing {
+                    EdgeRouting::Branch => {
+                        to_screen.transform_pos(node.position())
+                            + Vec2::new(-10.0, node.size().y / 2.0) * zoom
+                    }
+                    EdgeRouting::TopDown => {
+                        to_screen.transform_pos(node.position())
+                            + Vec2::new(node.size().x / 2.0, node.size().y + 8.0) * zoom
+                    }
+                    EdgeRouting::LeftRight | EdgeRouting::Straight => {
+                        to_screen.transform_pos(node.position())
+                            + Vec2::new(node.size().x + 8.0, node.size().y / 2.0) * zoom
+                    }
+                };
+                let point_id = response.id.with(format!("edge-{}", node.id()));
+                if !viewport.contains(tree_point) && !ui.memory(|memory| memory.has_focus(point_id))
+                {
+                    continue;
+                }
+                toggle_shapes.push(egui::Shape::circle_filled(tree_point, 5.0, stroke_color));
+
+                let node_response = ui.interact(
+                    egui::Rect::from_center_size(tree_point, Vec2::new(10.0, 10.0)),
+                    point_id,
+                    egui::Sense::click(),
+                );
+                let expanded = graph
+                    .node(sub_nodes[0])
+                    .is_some_and(|sub_node| sub_node.visiable());
+                node_response.widget_info(|| {
+                    let action = if expanded { "折叠" } else { "展开" };
+                    WidgetInfo::labeled(
+                        WidgetType::Button,
+                        true,
+                        format!("{} {}", action, accessible_label(node)),
+                    )
+                });
+                if !expanded {
+                    toggle_shapes.push(egui::Shape::circle_stroke(
+                        tree_point,
+                        7.0,
+                        Stroke::new(2.0, focus_stroke_color),
+                    ));
+                }
+                if node_response.clicked() {
+                    self.toggle_children(graph, node_index, response.rect.size());
+                }
+            }
+        }
+        painter.extend(toggle_shapes);
+        // 双击画布空白处添加便签
+        if response.double_clicked() {
+            if let Some(pointer) = response.interact_pointer_pos() {
+                let position = to_screen.inverse().transform_pos(pointer);
+                self.editing_annotation = Some(graph.add_annotation(position, ""));
+            }
+        }
+        self.draw_annotations(ui, graph, &painter, &response, &to_screen, viewport);
+        if !self.offscreen {
+            self.draw_minimap(ui, graph, &response, &fill_color_list);
+        }
+        event
+    }
+
+    /**
+     * 绘制便签，拖动移动，双击或右键菜单编辑，编辑结束时内容为空的便签会被删除
+     */
+    fn draw_annotations(
+        &mut self,
+        ui: &mut Ui,
+        graph: &mut Graph,
+        painter: &egui::Painter,
+        response: &egui::Response,
+        to_screen: &emath::RectTransform,
+        viewport: Rect,
+    ) {
+        let zoom = graph.camera().zoom;
+        let font = egui::FontId::proportional(12.0 * zoom);
+        let padding = Vec2::splat(6.0 * zoom);
+        let wrap_width = ANNOTATION_WIDTH * zoom;
+        let stroke = Stroke::new(1.0, ANNOTATION_STROKE);
+        if self
+            .editing_annotation
+            .is_some_and(|index| index >= graph.annotations().len())
+        {
+            self.editing_annotation = None;
+        }
+        let mut removed = None;
+        for index in 0..graph.annotations().len() {
+            let Some(annotation) = graph.annotation_mut(index) else {
+                continue;
+            };
+            let min = to_screen.transform_pos(annotation.position);
+            let id = response.id.with(("annotation", index));
+            let galley = painter.layout(
+                annotation.text.clone(),
+                font.clone(),
+                Color32::BLACK,
+                wrap_width,
+            );
+            if self.editing_annotation == Some(index) {
+                let height = galley.size().y.max(font.size);
+                let rect = Rect::from_min_size(min, Vec2::new(wrap_width, height) + padding * 2.0);
+                painter.rect(rect, 2.0, ANNOTATION_FILL, stroke);
+                let edit = ui.put(
+                    rect.shrink2(padding),
+                    egui::TextEdit::multiline(&mut annotation.text)
+                        .id(id)
+                        .font(font.clone())
+                        .text_color(Color32::BLACK)
+                        .frame(false)
+                        .desired_width(wrap_width)
+                        .desired_rows(1)
+                        .hint_text("输入便签内容"),
+                );
+                if edit.lost_focus() {
+                    self.editing_annotation = None;
+                    if annotation.text.trim().is_empty() {
+                        removed = Some(index);
+                    }
+                } else if !edit.has_focus() {
+                    // 刚开始编辑，让输入框获得焦点
+                    edit.request_focus();
+                }
+                continue;
+            }
+            let rect = Rect::from_min_size(min, galley.size() + padding * 2.0);
+            if !viewport.intersects(rect) {
+                continue;
+            }
+            painter.rect(rect, 2.0, ANNOTATION_FILL, stroke);
+            painter.galley(rect.min + padding, galley, Color32::BLACK);
+            let note_response = ui.interact(rect, id, egui::Sense::click_and_drag());
+            note_response.widget_info(|| {
+                WidgetInfo::labeled(WidgetType::Label, true, format!("便签 {}", annotation.text))
+            });
+            if note_response.dragged() {
+                annotation.position += note_response.drag_delta() / zoom;
+            }
+            if note_response.double_clicked() {
+                self.editing_annotation = Some(index);
+            }
+            note_response.context_menu(|ui| {
+                if ui.button("编辑").clicked() {
+                    self.editing_annotation = Some(index);
+                    ui.close_menu();
+                }
+                if ui.button("删除").clicked() {
+                    removed = Some(index);
+                    ui.close_menu();
+                }
+            });
+        }
+        if let Some(removed) = removed {
+            graph.remove_annotation(removed);
+            self.editing_annotation = self
+                .editing_annotation
+                .filter(|index| *index != removed)
+                .map(|index| if index > removed { index - 1 } else { index });
+        }
+    }
+
+    /**
+     * 在离屏的界面中绘制导出用的画面，不画网格和缩略图，便签不处于编辑状态
+     */
+    pub(crate) fn offscreen_ui(&mut self, ui: &mut Ui, graph: &mut Graph) {
+        let editing_annotation = self.editing_annotation.take();
+        self.offscreen = true;
+        self.ui(ui, graph);
+        self.offscreen = false;
+        self.editing_annotation = editing_annotation;
+    }
+
+    pub(crate) fn viewport_size(&self) -> Vec2 {
+        self.viewport_size
+    }
+
+    /**
+     * 图中所有可见内容（节点、连线和便签）的范围，便签的大小按行数估算
+     */
+    pub(crate) fn content_bounds(&self, graph: &Graph) -> Rect {
+        let mut bounds = graph
+            .nodes()
+            .filter(|(_, node)| node.visiable())
+            .map(|(_, node)| Rect::from_min_size(node.position(), node.size()))
+            .fold(Rect::NOTHING, |bounds, rect| bounds.union(rect));
+        for route in route_edges(graph, self.layout_engine.edge_routing()) {
+            for point in route.points {
+                bounds.extend_with(point);
+            }
+        }
+        for annotation in graph.annotations() {
+            let lines = annotation.text.lines().count().max(1) as f32;
+            let size = Vec2::new(ANNOTATION_WIDTH, lines * 15.0) + Vec2::splat(12.0);
+            bounds = bounds.union(Rect::from_min_size(annotation.position, size));
+        }
+        bounds
+    }
+
+    /**
+     * 所有节点按当前着色模式的填充颜色，按节点序号排列
+     */
+    pub fn fill_colors(&self, graph: &Graph, dark_mode: bool) -> Vec<Color32> {
+        let block_type_map = if self.high_contrast {
+            HIGH_CONTRAST_THEME.colors(dark_mode)
+        } else {
+            self.theme.colors(dark_mode)
+        };
+        graph
+            .nodes()
+            .map(|(_, node)| self.node_fill_color(node, block_type_map, dark_mode))
+            .collect()
+    }
+
+    /**
+     * 导出当前画布的 SVG：保持节点位置和连线走线，使用浅色模式下的节点颜色
+     */
+    pub fn svg_exporter(&self, graph: &Graph) -> SvgExporter {
+        SvgExporter {
+            keep_layout: Some(self.layout_engine.edge_routing()),
+            fills: self
+                .fill_colors(graph, false)
+                .iter()
+                .map(|color| format!("#{:02x}{:02x}{:02x}", color.r(), color.g(), color.b()))
+                .collect(),
+        }
+    }
+
+    /**
+     * 计算节点的填充颜色
+     */
+    fn node_fill_color(
+        &self,
+        node: &CodeNode,
+        block_type_map: &HashMap<CodeBlockType, Color32>,
+        dark_mode: bool,
+    ) -> Color32 {
+        let type_color = block_type_map
+            .get(node.block_type())
+            .copied()
+            .unwrap_or(egui::Color32::DARK_GRAY);
+        match self.color_mode {
+            ColorMode::BlockType => type_color,
+            ColorMode::Churn => {
+                let cold = block_type_map
+                    .get(&CodeBlockType::NORMAL)
+                    .copied()
+                    .unwrap_or(egui::Color32::DARK_GRAY);
+                match node.churn() {
+                    Some(churn) => heat_color(cold, churn.heat, dark_mode),
+                    None => cold,
+                }
+            }
+            ColorMode::Age => {
+                let cold = block_type_map
+                    .get(&CodeBlockType::NORMAL)
+                    .copied()
+                    .unwrap_or(egui::Color32::DARK_GRAY);
+                match node.churn() {
+                    Some(churn) => heat_color(cold, churn.recency, dark_mode),
+                    None => cold,
+                }
+            }
+            ColorMode::Profile => {
+                let cold = block_type_map
+                    .get(&CodeBlockType::NORMAL)
+                    .copied()
+                    .unwrap_or(egui::Color32::DARK_GRAY);
+                match node.profile() {
+                    Some(sample) => heat_color(cold, sample.heat, dark_mode),
+                    None => cold,
+                }
+            }
+            ColorMode::Coverage => match node.coverage() {
+                // 覆盖率越低越红
+                Some(coverage) => {
+                    let covered = if dark_mode {
+                        Color32::from_rgb(30, 120, 60)
+                    } else {
+                        Color32::from_rgb(150, 220, 150)
+                    };
+                    heat_color(covered, 1.0 - coverage, dark_mode)
+                }
+                None => block_type_map
+                    .get(&CodeBlockType::NORMAL)
+                    .copied()
+                    .unwrap_or(egui::Color32::DARK_GRAY),
+            },
+            // 只有函数有圈复杂度，复杂度为 1 时为绿色
+            ColorMode::Complexity if is_function(node) => {
+                let simple = if dark_mode {
+                    Color32::from_rgb(30, 120, 60)
+                } else {
+                    Color32::from_rgb(150, 220, 150)
+                };
+                let heat = node.complexity.saturating_sub(1) as f32 / (MAX_COMPLEXITY - 1) as f32;
+                heat_color(simple, heat, dark_mode)
+            }
+            ColorMode::Complexity => block_type_map
+                .get(&CodeBlockType::NORMAL)
+                .copied()
+                .unwrap_or(egui::Color32::DARK_GRAY),
+            ColorMode::Hotspot => {
+                let cold = block_type_map
+                    .get(&CodeBlockType::NORMAL)
+                    .copied()
+                    .unwrap_or(egui::Color32::DARK_GRAY);
+                match node.churn().filter(|_| is_function(node)) {
+                    Some(churn) => heat_color(cold, churn.hotspot, dark_mode),
+                    None => cold,
+                }
+            }
+        }
+    }
+
+    fn draw_minimap(
+        &self,
+        ui: &mut Ui,
+        graph: &Graph,
+        response: &egui::Response,
+        fill_color_list: &[Color32],
+    ) {
+        let minimap_size = Vec2::new(200.0, 150.0); // 缩略图大小
+        let minimap_margin = 10.0; // 缩略图与画布边缘的间距
+
+        // 计算缩略图位置(右下角)
+        let minimap_pos = Pos2::new(
+            response.rect.right() - minimap_size.x - minimap_margin,
+            response.rect.bottom() - minimap_size.y - minimap_margin,
+        );
+
+        let minimap_rect = Rect::from_min_size(minimap_pos, minimap_size);
+
+        // 绘制缩略图背景
+        ui.painter()
+            .rect_filled(minimap_rect, 0.0, ui.visuals().extreme_bg_color);
+
+        // 计算缩放比例
+        let scale_x = minimap_size.x / response.rect.width();
+        let scale_y = minimap_size.y / response.rect.height();
+        let scale = scale_x.min(scale_y);
+
+        for (index, node) in graph.nodes() {
+            if node.visiable() {
+                // 检查节点是否在可视区域内
+
+                let mut minimap_node_pos = minimap_pos + (node.position().to_vec2() * scale);
+                let mut node_size = node.size() * scale;
+                if minimap_node_pos.x < minimap_rect.min.x {
+                    node_size.x -= minimap_rect.min.x - minimap_node_pos.x;
+                    minimap_node_pos.x = minimap_rect.min.x;
+                }
+                if minimap_node_pos.y < minimap_rect.min.y {
+                    node_size.y -= minimap_rect.min.y - minimap_node_pos.y;
+                    minimap_node_pos.y = minimap_rect.min.y;
+                }
+                if minimap_node_pos.x + node_size.x > minimap_rect.max.x {
+                    node_size.x = minimap_rect.max.x - minimap_node_pos.x;
+                }
+                if minimap_node_pos.y + node_size.y > minimap_rect.max.y {
+                    node_size.y = minimap_rect.max.y - minimap_node_pos.y;
+                }
+                let node_rect = Rect::from_min_size(minimap_node_pos, node_size);
+
+                ui.painter()
+                    .rect_filled(node_rect, 0.0, fill_color_list[index.0]);
+            }
+        }
+        // 绘制缩略图边框
+        ui.painter().rect_stroke(
+            minimap_rect,
+            0.0,
+            Stroke::new(1.0, ui.visuals().text_color()),
+        );
+    }
+}
+
+/**
+ * 可以展开调用的函数和方法节点
+ */
+fn diff_color(status: DiffStatus) -> Color32 {
+    match status {
+        DiffStatus::Added => ADDED_COLOR,
+        DiffStatus::Removed => REMOVED_COLOR,
+        DiffStatus::Changed => CHANGED_COLOR,
+    }
+}
+
+fn is_function(node: &CodeNode) -> bool {
+    matches!(
+        node.block_type(),
+        CodeBlockType::FUNCTION | CodeBlockType::METHOD | CodeBlockType::COMPONENT
+    )
+}
+
+/**
+ * 文件根节点和调用以外的定义
+ */
+fn is_definition(node: &CodeNode) -> bool {
+    node.level() > 0 && !matches!(node.block_type(), CodeBlockType::CALL | CodeBlockType::HOOK)
+}
+
+/**
+ * 字节数的显示文字，例如 3.2 KB
+ */
+fn format_byte_size(bytes: usize) -> String {
+    if bytes < 1024 {
+        format!("{} B", bytes)
+    } else if bytes < 1024 * 1024 {
+        format!("{:.1} KB", bytes as f32 / 1024.0)
+    } else {
+        format!("{:.1} MB", bytes as f32 / (1024.0 * 1024.0))
+    }
+}
+
+/**
+ * 提供给屏幕阅读器的节点描述
+ */
+fn accessible_label(node: &CodeNode) -> String {
+    let mut label = format!(
+        "{:?} {}，第 {} 行",
+        node.block_type(),
+        node.label.trim(),
+        node.file_location
+    );
+    if let Some(references) = node.references {
+        label.push_str(&format!("，{} 处引用", references));
+    }
+    match node.visibility {
+        Some(Visibility::Public) => label.push_str("，公开"),
+        Some(Visibility::Private) => label.push_str("，私有"),
+        None => {}
+    }
+    if node.is_async {
+        label.push_str("，异步");
+    }
+    if node.is_test {
+        label.push_str("，测试");
+    }
+    if node.is_unsafe {
+        label.push_str("，unsafe");
+    }
+    label
+}
+
+/**
+ * 连线终点的方向标记：调用关系为箭头，包含关系为圆点，重复关系和没有方向时不画
+ */
+fn edge_end(
+    kind: EdgeKind,
+    tip: Pos2,
+    direction: Vec2,
+    color: Color32,
+    zoom: f32,
+) -> Option<egui::Shape> {
+    match kind {
+        EdgeKind::Calls => {
+            let direction = direction.normalized();
+            if direction == Vec2::ZERO {
+                return None;
+            }
+            let back = tip - direction * 8.0 * zoom;
+            let side = direction.rot90() * 4.0 * zoom;
+            Some(egui::Shape::convex_polygon(
+                vec![tip, back + side, back - side],
+                color,
+                Stroke::NONE,
+            ))
+        }
+        EdgeKind::Contains => Some(egui::Shape::circle_filled(tip, 2.5 * zoom, color)),
+        EdgeKind::Duplicate => None,
+    }
+}
+
+/**
+ * 热度颜色，从 cold 逐渐过渡到红色
+ */
+pub fn heat_color(cold: Color32, heat: f32, dark_mode: bool) -> Color32 {
+    let hot = if dark_mode {
+        Color32::from_rgb(200, 40, 30)
+    } else {
+        Color32::from_rgb(255, 110, 90)
+    };
+    let heat = heat.clamp(0.0, 1.0);
+    let lerp = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * heat) as u8;
+    Color32::from_rgb(
+        lerp(cold.r(), hot.r()),
+        lerp(cold.g(), hot.g()),
+        lerp(cold.b(), hot.b()),
+    )
+}
+
+/**
+ * 节点上显示的角标文字和颜色
+ */
+fn node_badges(node: &CodeNode, multi_language: bool) -> Vec<(&str, Color32)> {
+    let mut badges = vec![];
+    match node.visibility {
+        Some(Visibility::Public) => badges.push(("pub", Color32::from_rgb(46, 139, 87))),
+        Some(Visibility::Private) => badges.push(("priv", Color32::from_rgb(120, 120, 120))),
+        None => {}
+    }
+    if node.is_async {
+        badges.push(("async", Color32::from_rgb(128, 90, 213)));
+    }
+    if node.is_test {
+        badges.push(("test", Color32::from_rgb(210, 110, 0)));
+    }
+    if node.is_unsafe {
+        badges.push(("unsafe", UNSAFE_COLOR));
+    }
+    if let Some(language) = node.language.as_deref().filter(|_| multi_language) {
+        let short_name = match language {
+            "Rust" => "rs",
+            "Java" => "java",
+            "C" => "c",
+            "JavaScript" => "js",
+            "Python" => "py",
+            "TypeScript" => "ts",
+            language => language,
+        };
+        badges.push((short_name, Color32::from_rgb(60, 90, 130)));
+    }
+    badges
+}
+
+/**
+ * 折线按长度计算的中点
+ */
+fn polyline_midpoint(points: &[Pos2]) -> Pos2 {
+    let length = points
+        .windows(2)
+        .map(|segment| segment[0].distance(segment[1]))
+        .sum::<f32>();
+    let mut remaining = length / 2.0;
+    for segment in points.windows(2) {
+        let segment_length = segment[0].distance(segment[1]);
+        if remaining <= segment_length && segment_length > 0.0 {
+            return segment[0].lerp(segment[1], remaining / segment_length);
+        }
+        remaining -= segment_length;
+    }
+    points.last().copied().unwrap_or(Pos2::ZERO)
+}
+
+/**
+ * 本帧按下的方向键或 hjkl 对应的方向（画布坐标，y 轴向下）
+ */
+fn navigation_direction(input: &egui::InputState) -> Option<Vec2> {
+    if input.modifiers.any() {
+        return None;
+    }
+    [
+        (Key::ArrowLeft, Key::H, Vec2::LEFT),
+        (Key::ArrowDown, Key::J, Vec2::DOWN),
+        (Key::ArrowUp, Key::K, Vec2::UP),
+        (Key::ArrowRight, Key::L, Vec2::RIGHT),
+    ]
+    .into_iter()
+    .find(|(arrow, letter, _)| input.key_pressed(*arrow) || input.key_pressed(*letter))
+    .map(|(_, _, direction)| direction)
+}
+
+/**
+ * 沿包含或调用关系与节点相连、位于指定方向上的可见节点中最近的一个，
+ * 偏离方向的距离按两倍计算，优先选择正对该方向的节点
+ */
+fn adjacent_node(graph: &Graph, index: CodeNodeIndex, direction: Vec2) -> Option<CodeNodeIndex> {
+    let center = |node: &CodeNode| node.position() + node.size() / 2.0;
+    let from = center(graph.node(index)?);
+    let cost = |offset: Vec2| {
+        let along = offset.dot(direction);
+        along + (offset - direction * along).length() * 2.0
+    };
+    graph
+        .edges()
+        .filter_map(|edge| {
+            if edge.from() == index {
+                Some(edge.to())
+            } else if edge.to() == index {
+                Some(edge.from())
+            } else {
+                None
+            }
+        })
+        .filter(|neighbor| *neighbor != index)
+        .filter_map(|neighbor| {
+            let node = graph.node(neighbor).filter(|node| node.visiable())?;
+            Some((neighbor, center(node) - from))
+        })
+        .filter(|(_, offset)| offset.dot(direction) > 0.0)
+        .min_by(|(_, a), (_, b)| cost(*a).total_cmp(&cost(*b)))
+        .map(|(neighbor, _)| neighbor)
+}
+
+/**
+ * 平移视角使节点位于画布中央
+ */
+fn look_at_node(graph: &mut Graph, index: CodeNodeIndex, canvas_size: Vec2) {
+    if let Some(node) = graph.node(index) {
+        let center = node.position() + node.size() / 2.0;
+        graph
+            .camera_mut()
+            .look_at(center, (canvas_size / 2.0).to_pos2());
+    }
+}
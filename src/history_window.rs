@@ -0,0 +1,204 @@
+use std::{path::PathBuf, sync::mpsc, thread};
+
+use code_graph::{
+    git::format_date,
+    history::{recent_revisions, revision_change, revision_graph},
+    Graph, Transition,
+};
+use eframe::egui::{self, Ui};
+use rfd::MessageDialog;
+
+use crate::MyApp;
+
+// 历史窗口中列出的最近提交数，以及自动播放时每个提交停留的秒数
+const HISTORY_LIMIT: usize = 30;
+const HISTORY_STEP_SECONDS: f64 = 1.5;
+
+impl MyApp {
+    /**
+     * 在后台读取修改过当前项目的最近提交，打开历史窗口
+     */
+    pub(crate) fn load_revisions(&mut self) {
+        if self.root_path.is_empty() {
+            return;
+        }
+        let root_path = PathBuf::from(&self.root_path);
+        let (tx, rx) = mpsc::channel();
+        self.revisions_rx = Some(rx);
+        self.revisions.clear();
+        self.history_graphs.clear();
+        self.history_rx = None;
+        self.history_playing = None;
+        self.show_history = true;
+        thread::spawn(move || {
+            let _ = tx.send(recent_revisions(&root_path, HISTORY_LIMIT));
+        });
+    }
+    /**
+     * 在后台从 git 对象中读取项目在某个提交时的代码，生成符号图
+     */
+    fn load_revision_graph(&mut self, hash: &str) {
+        let root_path = PathBuf::from(&self.root_path);
+        let hash = hash.to_owned();
+        let (tx, rx) = mpsc::channel();
+        self.history_rx = Some(rx);
+        thread::spawn(move || {
+            let graph = revision_graph(&root_path, &hash);
+            let _ = tx.send((hash, graph));
+        });
+    }
+    /**
+     * 显示某个版本的图，从上一个版本切换过来时保留视角，并让出现和消失的节点过渡
+     */
+    pub(crate) fn show_revision_graph(&mut self, ui: &mut Ui, hash: String, mut graph: Graph) {
+        let previous = self
+            .history_shown
+            .is_some()
+            .then(|| std::mem::take(&mut self.graph));
+        if let Some(previous) = &previous {
+            *graph.camera_mut() = previous.camera();
+        }
+        self.show_graph(ui, graph);
+        if let Some(previous) = previous {
+            let change = revision_change(&previous, &self.graph);
+            self.graph_view.transition =
+                Some(Transition::new(change, ui.input(|input| input.time)));
+        }
+        self.history_shown = Some(hash);
+    }
+    pub(crate) fn history_window(&mut self, ctx: &egui::Context) {
+        let mut show_history = self.show_history;
+        egui::Window::new("历史版本")
+            .open(&mut show_history)
+            .default_size([360.0, 160.0])
+            .show(ctx, |ui| {
+                if let Some(rx) = &self.revisions_rx {
+                    match rx.try_recv() {
+                        Ok(result) => {
+                            match result {
+                                Ok(revisions) => {
+                                    self.history_position = revisions.len().saturating_sub(1);
+                                    self.revisions = revisions;
+                                }
+                                Err(err) => {
+                                    MessageDialog::new()
+                                        .set_title("读取提交历史失败")
+                                        .set_description(err.to_string())
+                                        .show();
+                                }
+                            }
+                            self.revisions_rx = None;
+                        }
+                        Err(_) => {
+                            ui.spinner();
+                            return;
+                        }
+                    }
+                }
+                if let Some(rx) = &self.history_rx {
+                    if let Ok((hash, result)) = rx.try_recv() {
+                        match result {
+                            Ok(graph) => {
+                                self.history_graphs.insert(hash, graph);
+                            }
+                            Err(err) => {
+                                self.history_playing = None;
+                                MessageDialog::new()
+                                    .set_title("生成历史版本失败")
+                                    .set_description(err.to_string())
+                                    .show();
+                            }
+                        }
+                        self.history_rx = None;
+                    }
+                }
+                if self.revisions.is_empty() {
+                    ui.label("没有找到修改过该目录的提交");
+                    return;
+                }
+                let last = self.revisions.len() - 1;
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(self.history_position > 0, egui::Button::new("◀"))
+                        .on_hover_text("上一个提交")
+                        .clicked()
+                    {
+                        self.history_position -= 1;
+                    }
+                    ui.add(
+                        egui::Slider::new(&mut self.history_position, 0..=last).show_value(false),
+                    );
+                    if ui
+                        .add_enabled(self.history_position < last, egui::Button::new("▶"))
+                        .on_hover_text("下一个提交")
+                        .clicked()
+                    {
+                        self.history_position += 1;
+                    }
+                    let playing = self.history_playing.is_some();
+                    if ui
+                        .button(if playing { "暂停" } else { "播放" })
+                        .on_hover_text("从选中的提交开始依次切换到更新的提交")
+                        .clicked()
+                    {
+                        self.history_playing = if playing {
+                            None
+                        } else {
+                            if self.history_position == last {
+                                self.history_position = 0;
+                            }
+                            Some(ui.input(|input| input.time))
+                        };
+                    }
+                });
+                let revision = self.revisions[self.history_position].clone();
+                ui.label(format!(
+                    "{}/{}  {}  {}  {}",
+                    self.history_position + 1,
+                    self.revisions.len(),
+                    revision.short_hash,
+                    format_date(revision.time),
+                    revision.author
+                ));
+                ui.weak(&revision.subject);
+
+                // 选中的版本已生成时交给画布显示，还没有生成时在后台生成
+                if self.history_shown.as_ref() != Some(&revision.hash)
+                    && self.pending_history_graph.is_none()
+                {
+                    match self.history_graphs.get(&revision.hash) {
+                        Some(graph) => {
+                            self.pending_history_graph =
+                                Some((revision.hash.clone(), graph.clone()));
+                        }
+                        None if self.history_rx.is_none() => {
+                            self.load_revision_graph(&revision.hash)
+                        }
+                        None => {}
+                    }
+                }
+                if self.history_rx.is_some() {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label("正在生成该版本的图");
+                    });
+                }
+                if let Some(switched) = self.history_playing {
+                    let now = ui.input(|input| input.time);
+                    let ready = self.history_shown.as_ref() == Some(&revision.hash);
+                    if self.history_position == last && ready {
+                        self.history_playing = None;
+                    } else if ready && now - switched >= HISTORY_STEP_SECONDS {
+                        self.history_position += 1;
+                        self.history_playing = Some(now);
+                    }
+                    ui.ctx()
+                        .request_repaint_after(std::time::Duration::from_millis(100));
+                }
+            });
+        if !show_history {
+            self.history_playing = None;
+        }
+        self.show_history = show_history;
+    }
+}
@@ -0,0 +1,74 @@
+use code_graph::ColorMode;
+
+use crate::MyApp;
+
+impl MyApp {
+    pub(crate) fn hotspot_window(&mut self, ctx: &egui::Context) {
+        let mut show_hotspots = self.show_hotspots;
+        egui::Window::new("热点分析")
+            .open(&mut show_hotspots)
+            .default_size([360.0, 420.0])
+            .show(ctx, |ui| {
+                if let Some(rx) = &self.hotspot_rx {
+                    if let Ok(hotspots) = rx.try_recv() {
+                        self.hotspots = hotspots;
+                        self.hotspot_rx = None;
+                    } else {
+                        ui.spinner();
+                        return;
+                    }
+                }
+                if self.hotspots.is_empty() {
+                    ui.label("没有找到热点（需要项目位于 git 仓库中）");
+                    return;
+                }
+                ui.horizontal(|ui| {
+                    ui.label("分数 = 圈复杂度 × 函数涉及的提交数");
+                    if ui
+                        .button("按热点着色")
+                        .on_hover_text("图中的函数按热点分数从冷到热着色")
+                        .clicked()
+                    {
+                        self.graph_view.color_mode = ColorMode::Hotspot;
+                        self.refresh_overlay();
+                    }
+                });
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    egui::Grid::new("hotspot_grid")
+                        .num_columns(6)
+                        .striped(true)
+                        .show(ui, |ui| {
+                            ui.label("排名");
+                            ui.label("分数");
+                            ui.label("复杂度");
+                            ui.label("提交");
+                            ui.label("文件修改");
+                            ui.label("函数");
+                            ui.end_row();
+                            for (rank, hotspot) in self.hotspots.iter().enumerate() {
+                                ui.label(format!("{}", rank + 1));
+                                ui.label(format!("{}", hotspot.score));
+                                ui.label(format!("{}", hotspot.complexity));
+                                ui.label(format!("{}", hotspot.commits));
+                                ui.label(format!("{}", hotspot.changes));
+                                if ui
+                                    .link(hotspot.node.label.trim())
+                                    .on_hover_text(format!(
+                                        "{}:{}",
+                                        hotspot.node.file_path, hotspot.node.file_location
+                                    ))
+                                    .clicked()
+                                {
+                                    self.pending_location = Some((
+                                        hotspot.node.file_path.clone(),
+                                        hotspot.node.file_location,
+                                    ));
+                                }
+                                ui.end_row();
+                            }
+                        });
+                });
+            });
+        self.show_hotspots = show_hotspots;
+    }
+}
@@ -1,25 +1,96 @@
 use std::{
+    collections::HashMap,
     ffi::OsStr,
     fs::{self},
+    io,
     path::{Path, PathBuf},
     process::Command,
-    sync::mpsc::{self, Receiver},
-    thread::{self},
+    sync::{
+        atomic::AtomicBool,
+        mpsc::{Receiver, TryRecvError},
+        Arc,
+    },
+    time::SystemTime,
 };
 
+use arboard::Clipboard;
 use code_graph::{
-    fetch_calls, fetch_symbols, get_symbol_query, recursion_dir, valid_file_extention, CodeNode,
-    Graph, Tree, TreeEvent, TreeType,
+    coverage::Coverage,
+    ctags::{tags_graph, Tag},
+    deps::Dependency,
+    duplicate::DuplicateGroup,
+    git::BlameCache,
+    history::Revision,
+    hotspot::Hotspot,
+    incremental::{update_outline, ParsedFile},
+    matrix::MatrixOrder,
+    profile::Profile,
+    read_source,
+    search::FuzzyMatch,
+    stats::ProjectStats,
+    todo::TodoComment,
+    valid_file_extention, AnnotationNode, CallStats, Camera, ClusterBy, CodeGraphError, CodeNode,
+    CodeNodeIndex, DefinitionIndex, EdgeStyle, GenericsDisplay, Graph, GraphEvent, GraphTheme,
+    GraphView, LayoutKind, LinkStyle, Result, Tree, TreeType, TreeView,
 };
 use eframe::egui::{self};
 use egui::{text::LayoutJob, FontId, Rounding, TextFormat, Ui, Vec2, Widget};
 use font_kit::{family_name::FamilyName, properties::Properties, source::SystemSource};
-use rfd::{FileDialog, MessageDialog};
+use image::RgbaImage;
+use rfd::MessageDialog;
 use serde::{Deserialize, Serialize};
 
+mod cli;
+mod compare;
+mod duplicate_window;
+mod export;
+mod filters;
+mod find_bar;
+mod graph_tabs;
+mod history_window;
+mod hotspot_window;
+mod matrix_window;
+mod navigation;
+mod overlay;
+mod recent_files;
+mod references_window;
+mod remote_window;
+mod right_panel;
+mod side_panel;
+mod stats_window;
+mod symbol_palette;
+mod theme_window;
+
+use side_panel::ScanMessage;
+
+// 节点数量超过该值时自动折叠
+const AUTO_COLLAPSE_NODE_COUNT: usize = 100;
+// 调用位置节点 ID 中的标记
+const CALL_SITE_ID: &str = "/call-site:";
+// 打开符号搜索的快捷键，macOS 上为 Command+P
+const PALETTE_SHORTCUT: egui::KeyboardShortcut =
+    egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::P);
+// 打开查找栏的快捷键，macOS 上为 Command+F
+const FIND_SHORTCUT: egui::KeyboardShortcut =
+    egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::F);
+// 实时更新时检查打开的文件是否修改的间隔秒数
+const LIVE_UPDATE_SECONDS: f64 = 1.0;
+// 打开最近文件列表的快捷键，macOS 上为 Command+E
+const RECENT_FILES_SHORTCUT: egui::KeyboardShortcut =
+    egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::E);
+// 为选中的节点添加或移除书签的快捷键，macOS 上为 Command+D
+const BOOKMARK_SHORTCUT: egui::KeyboardShortcut =
+    egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::D);
+
 fn main() -> eframe::Result {
-    let mut options = eframe::NativeOptions::default();
-    options.persist_window = true;
+    let args = std::env::args().skip(1).collect::<Vec<String>>();
+    if let Some(code) = cli::run(&args) {
+        std::process::exit(code);
+    }
+    let options = eframe::NativeOptions {
+        persist_window: true,
+        ..Default::default()
+    };
     eframe::run_native(
         "Code Graph",
         options,
@@ -59,8 +130,12 @@ fn main() -> eframe::Result {
                 .or_default()
                 .push("system_font".to_owned());
             // cc.egui_ctx.set_debug_on_hover(true);
-            cc.egui_ctx.set_fonts(fonts);
-            let mut my_app = MyApp::default();
+            cc.egui_ctx.set_fonts(fonts.clone());
+            let mut my_app = MyApp {
+                fonts,
+                ..Default::default()
+            };
+            my_app.graph_view.theme = GraphTheme::load();
             if let Some(storage) = cc.storage {
                 if let Some(app_state) = storage.get_string("app_state") {
                     let app_state = serde_json::from_str::<AppState>(&app_state);
@@ -68,6 +143,25 @@ fn main() -> eframe::Result {
                         my_app.project_root_path =
                             Some(Path::new(&app_state.root_path).to_path_buf());
                         my_app.editor = app_state.editor;
+                        my_app.auto_collapse_depth = app_state.auto_collapse_depth;
+                        my_app.scan_depth = app_state.scan_depth;
+                        my_app.graph_view.generics_display = app_state.generics_display;
+                        my_app.graph_view.high_contrast = app_state.high_contrast;
+                        my_app.graph_view.size_bars = app_state.size_bars;
+                        my_app.graph_view.unsafe_outline = app_state.unsafe_outline;
+                        my_app.todo_badges = app_state.todo_badges;
+                        my_app.layout_kind = app_state.layout_kind;
+                        my_app.graph_view.edge_style = app_state.edge_style;
+                        my_app.graph_view.cluster_by = app_state.cluster_by;
+                        my_app.annotations = app_state.annotations;
+                        my_app.graph_view.focus_hops = app_state.focus_hops;
+                        my_app.png_scale = app_state.png_scale;
+                        my_app.markdown_links = app_state.markdown_links;
+                        my_app.bookmarks = app_state.bookmarks;
+                        my_app.recent_files = app_state.recent_files;
+                        my_app.live_update = app_state.live_update;
+                        my_app.sync_bookmarks();
+                        my_app.graph_view.layout_engine = app_state.layout_kind.engine();
                     }
                 }
             }
@@ -82,22 +176,249 @@ enum Editor {
     Zed,
     Idea,
 }
+/**
+ * 画布上方的标签页：代码图为打开的文件或生成的图，依赖图为文件之间的导入关系
+ */
+#[derive(PartialEq, Debug, Clone, Copy)]
+enum GraphTab {
+    Code,
+    Dependencies,
+}
 #[derive(Debug, Serialize, Deserialize)]
 struct AppState {
     editor: Editor,
     root_path: String,
+    #[serde(default = "default_auto_collapse_depth")]
+    auto_collapse_depth: Option<usize>,
+    #[serde(default)]
+    generics_display: GenericsDisplay,
+    #[serde(default)]
+    scan_depth: Option<usize>,
+    #[serde(default)]
+    high_contrast: bool,
+    #[serde(default)]
+    size_bars: bool,
+    #[serde(default)]
+    unsafe_outline: bool,
+    #[serde(default)]
+    todo_badges: bool,
+    #[serde(default)]
+    layout_kind: LayoutKind,
+    #[serde(default)]
+    edge_style: EdgeStyle,
+    #[serde(default)]
+    cluster_by: ClusterBy,
+    #[serde(default)]
+    annotations: HashMap<String, Vec<AnnotationNode>>,
+    #[serde(default)]
+    focus_hops: Option<usize>,
+    #[serde(default = "default_png_scale")]
+    png_scale: f32,
+    #[serde(default)]
+    markdown_links: LinkStyle,
+    #[serde(default)]
+    bookmarks: Vec<Bookmark>,
+    #[serde(default)]
+    recent_files: Vec<String>,
+    #[serde(default = "default_live_update")]
+    live_update: bool,
+}
+
+/**
+ * 节点书签，重新打开文件后按 ID 找回节点，找不到时按行号查找
+ */
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Bookmark {
+    file_path: String,
+    id: String,
+    label: String,
+    line: usize,
+    // 添加书签时的视角，只在单个文件的图中记录
+    camera: Option<Camera>,
+}
+
+fn default_auto_collapse_depth() -> Option<usize> {
+    Some(2)
+}
+
+fn default_png_scale() -> f32 {
+    2.0
+}
+
+fn default_live_update() -> bool {
+    true
 }
 struct MyApp {
     tree: Tree,
+    tree_view: TreeView,
     code: String,
     current_node: CodeNode,
     call_nodes: Vec<CodeNode>,
     filter_call_nodes: Vec<CodeNode>,
+    // 项目中所有的定义
+    definitions: DefinitionIndex,
+    // 每个定义在项目中被引用的次数，扫描完成前为 None
+    reference_counts: Option<HashMap<String, usize>>,
+    // 项目声明的依赖
+    dependencies: Vec<Dependency>,
+    // 项目注释中的待办标记
+    todos: Vec<TodoComment>,
+    // 在包含待办注释的定义上显示角标
+    todo_badges: bool,
+    // 当前节点中的调用
+    callee_nodes: Vec<CodeNode>,
+    // 当前定义的扇入、扇出，需要先扫描项目中的定义
+    call_stats: Option<CallStats>,
+    // 影响分析最多向上查找的调用层数
+    impact_depth: usize,
+    // 是否在图中显示调用位置
+    show_call_sites: bool,
+    // 已经在图中显示调用位置的节点 ID
+    call_sites_of: Option<String>,
+    // 显示的最大层级，None 表示显示全部
+    max_depth: Option<usize>,
+    visibility_changed: bool,
+    // 标签显示方式或布局变化，需要重新测量节点大小并布局
+    labels_changed: bool,
+    // 打开节点很多的文件时，自动折叠超过该层级的节点
+    auto_collapse_depth: Option<usize>,
+    // 隐藏没有调用关系的节点
+    hide_orphans: bool,
+    // 只显示 unsafe 的定义和它们所在的上层节点
+    only_unsafe: bool,
+    // 只显示公开的定义和它们所在的上层节点
+    only_public: bool,
+    // 只显示标签匹配的节点，为空时不筛选
+    name_filter: String,
+    // 按正则表达式匹配，否则按子串匹配（忽略大小写）
+    name_filter_regex: bool,
+    // 扫描项目时最多进入的目录层数，None 表示不限制
+    scan_depth: Option<usize>,
+    layout_kind: LayoutKind,
     project_root_path: Option<PathBuf>,
     root_path: String,
     graph: Graph,
+    graph_view: GraphView,
     editor: Editor,
-    rx: Option<Receiver<(Tree, Vec<CodeNode>)>>,
+    rx: Option<Receiver<ScanMessage>>,
+    scan_progress: (usize, usize, String),
+    // 设置后后台扫描在处理下一个文件前停止
+    scan_cancel: Option<Arc<AtomicBool>>,
+    // 项目中的所有文件
+    pathes: Vec<PathBuf>,
+    hotspots: Vec<Hotspot>,
+    hotspot_rx: Option<Receiver<Vec<Hotspot>>>,
+    show_hotspots: bool,
+    // 变更热度着色用的 git blame 结果，按文件修改时间缓存
+    blame_cache: BlameCache,
+    // 后台线程中对缺少缓存的文件执行 blame
+    blame_rx: Option<Receiver<BlameCache>>,
+    // 项目中几乎相同的函数
+    duplicates: Vec<DuplicateGroup>,
+    duplicate_rx: Option<Receiver<Vec<DuplicateGroup>>>,
+    show_duplicates: bool,
+    // 扫描项目后的统计数据
+    stats: Option<ProjectStats>,
+    show_stats: bool,
+    show_matrix: bool,
+    matrix_order: MatrixOrder,
+    // 点击引用次数后显示的引用列表：定义的标签，以及每处调用和调用所在定义的标签
+    references_of: String,
+    references: Vec<(String, CodeNode)>,
+    show_references: bool,
+    // 导入的性能数据
+    profile: Option<Profile>,
+    // 导入的测试覆盖率
+    coverage: Option<Coverage>,
+    // 远程项目地址，格式为 user@host:/path
+    remote_location: String,
+    show_remote: bool,
+    // 是否显示节点配色设置
+    show_theme: bool,
+    patch_rx: Option<Receiver<Graph>>,
+    call_graph_rx: Option<Receiver<Graph>>,
+    hierarchy_rx: Option<Receiver<Graph>>,
+    field_usage_rx: Option<Receiver<Result<Graph>>>,
+    dependency_rx: Option<Receiver<Graph>>,
+    // 当前显示的标签页，另一个标签页的图保存在 hidden_graph 中
+    graph_tab: GraphTab,
+    hidden_graph: Option<Graph>,
+    compare_rx: Option<Receiver<Result<Graph>>>,
+    // 与当前项目对比的 git 版本，提交、分支或标签
+    diff_revision: String,
+    snapshot_rx: Option<Receiver<Result<()>>>,
+    // 历史浏览：最近的提交（从旧到新）、选中的提交序号和已生成的各版本的图
+    show_history: bool,
+    revisions: Vec<Revision>,
+    revisions_rx: Option<Receiver<Result<Vec<Revision>>>>,
+    history_position: usize,
+    history_graphs: HashMap<String, Graph>,
+    history_rx: Option<Receiver<(String, Result<Graph>)>>,
+    // 图中正在显示的版本
+    history_shown: Option<String>,
+    pending_history_graph: Option<(String, Graph)>,
+    // 自动播放时上一次切换版本的时间
+    history_playing: Option<f64>,
+    // 打开的文件的语法树，文件修改后增量解析，只更新图中变化的节点
+    parsed_file: Option<ParsedFile>,
+    live_update: bool,
+    // 上一次检查打开的文件的时间，以及当时文件的修改时间
+    source_checked: f64,
+    source_modified: Option<SystemTime>,
+    batch_export_rx: Option<Receiver<Result<usize>>>,
+    tags_rx: Option<Receiver<Result<usize>>>,
+    // 导入的 tags 文件中的符号，用于打开没有语法解析的文件
+    tags: Vec<Tag>,
+    png_rx: Option<Receiver<io::Result<()>>>,
+    // 导出 PNG 时每个点对应的像素数
+    png_scale: f32,
+    // Markdown 大纲中定义位置的链接方式
+    markdown_links: LinkStyle,
+    copy_image_rx: Option<Receiver<RgbaImage>>,
+    // 复制图片用的系统剪贴板，部分平台上需要一直保留才能粘贴
+    clipboard: Option<Clipboard>,
+    // 界面使用的字体，离屏绘制导出图片时使用同样的字体
+    fonts: egui::FontDefinitions,
+    // 等待在画布中打开并定位的位置（文件路径，行号）
+    pending_location: Option<(String, usize)>,
+    // 等待在画布中显示调用方的节点
+    pending_callers: Option<CodeNodeIndex>,
+    bookmarks: Vec<Bookmark>,
+    // 等待在画布中打开的书签
+    pending_bookmark: Option<Bookmark>,
+    // 等待在画布中选中并移到中央的节点
+    pending_focus: Option<CodeNodeIndex>,
+    // 等待在画布中显示的图，例如在重复代码报告中生成的图
+    pending_graph: Option<Graph>,
+    // 在图中标出调用或导入关系中的循环，依赖图中总是标出
+    show_cycles: bool,
+    // 当前图中的循环，每个循环为互相可达的节点
+    cycles: Vec<Vec<CodeNodeIndex>>,
+    // 上次查找循环时是否标出以及图的节点数和边数，None 表示图已变化需要重新查找
+    cycles_key: Option<(bool, usize, usize)>,
+    // 从文件列表打开过的文件，最近打开的在前
+    recent_files: Vec<String>,
+    show_recent_files: bool,
+    recent_selected: usize,
+    // 等待在画布中重新打开的文件
+    pending_file: Option<String>,
+    // 画布上方的查找栏
+    show_find: bool,
+    find_query: String,
+    // 标签包含查找文字的节点，按图中的顺序排列
+    find_matches: Vec<CodeNodeIndex>,
+    // 当前聚焦的是第几个匹配
+    find_current: usize,
+    // 打开查找栏后让输入框获得焦点
+    find_focus_requested: bool,
+    // 每个文件的图中添加的便签，按文件路径保存
+    annotations: HashMap<String, Vec<AnnotationNode>>,
+    // 符号搜索
+    show_palette: bool,
+    palette_query: String,
+    // 按分数排列的（定义序号，匹配结果）
+    palette_results: Vec<(usize, FuzzyMatch)>,
+    palette_selected: usize,
     debug: DebugInfo,
 }
 #[derive(Default, Debug)]
@@ -113,42 +434,321 @@ impl Default for MyApp {
             current_node: CodeNode::default(),
             call_nodes: vec![],
             filter_call_nodes: vec![],
+            definitions: DefinitionIndex::default(),
+            reference_counts: None,
+            dependencies: vec![],
+            todos: vec![],
+            todo_badges: false,
+            callee_nodes: vec![],
+            call_stats: None,
+            impact_depth: 3,
+            show_call_sites: false,
+            call_sites_of: None,
+            max_depth: None,
+            visibility_changed: false,
+            labels_changed: false,
+            auto_collapse_depth: default_auto_collapse_depth(),
+            hide_orphans: false,
+            only_unsafe: false,
+            only_public: false,
+            name_filter: String::new(),
+            name_filter_regex: false,
+            scan_depth: None,
+            layout_kind: LayoutKind::default(),
             tree: Tree::new("", "", TreeType::File),
+            tree_view: TreeView::default(),
             project_root_path: None,
             root_path: "".to_owned(),
             graph: Graph::new(),
+            graph_view: GraphView::new(),
             editor: Editor::VSCode,
             rx: None,
+            scan_progress: (0, 0, String::new()),
+            scan_cancel: None,
+            pathes: vec![],
+            hotspots: vec![],
+            hotspot_rx: None,
+            show_hotspots: false,
+            blame_cache: BlameCache::new(),
+            blame_rx: None,
+            duplicates: vec![],
+            duplicate_rx: None,
+            show_duplicates: false,
+            stats: None,
+            show_stats: false,
+            show_matrix: false,
+            matrix_order: MatrixOrder::default(),
+            references_of: String::new(),
+            references: vec![],
+            show_references: false,
+            profile: None,
+            coverage: None,
+            remote_location: String::new(),
+            show_remote: false,
+            show_theme: false,
+            patch_rx: None,
+            call_graph_rx: None,
+            hierarchy_rx: None,
+            field_usage_rx: None,
+            dependency_rx: None,
+            graph_tab: GraphTab::Code,
+            hidden_graph: None,
+            compare_rx: None,
+            diff_revision: String::new(),
+            snapshot_rx: None,
+            show_history: false,
+            revisions: vec![],
+            revisions_rx: None,
+            history_position: 0,
+            history_graphs: HashMap::new(),
+            history_rx: None,
+            history_shown: None,
+            pending_history_graph: None,
+            history_playing: None,
+            parsed_file: None,
+            live_update: true,
+            source_checked: 0.0,
+            source_modified: None,
+            batch_export_rx: None,
+            tags_rx: None,
+            tags: vec![],
+            png_rx: None,
+            png_scale: default_png_scale(),
+            markdown_links: LinkStyle::default(),
+            copy_image_rx: None,
+            clipboard: None,
+            fonts: egui::FontDefinitions::default(),
+            pending_location: None,
+            pending_callers: None,
+            bookmarks: vec![],
+            pending_bookmark: None,
+            pending_focus: None,
+            pending_graph: None,
+            show_cycles: false,
+            cycles: vec![],
+            cycles_key: None,
+            recent_files: vec![],
+            show_recent_files: false,
+            recent_selected: 0,
+            pending_file: None,
+            show_find: false,
+            find_query: String::new(),
+            find_matches: vec![],
+            find_current: 0,
+            find_focus_requested: false,
+            annotations: HashMap::new(),
+            show_palette: false,
+            palette_query: String::new(),
+            palette_results: vec![],
+            palette_selected: 0,
             debug: DebugInfo::default(),
         }
     }
 }
 
 impl MyApp {
-    fn side_panel(&mut self, ui: &mut Ui) {
-        if self.tree.label.is_empty() {
-            ui.label("这里什么也没有");
+    /**
+     * 打开文件并生成图
+     */
+    fn open_file(&mut self, ui: &mut Ui, name: &str) -> bool {
+        self.switch_tab(GraphTab::Code);
+        self.history_shown = None;
+        self.parsed_file = None;
+        let path = Path::new(name);
+        let ext = path
+            .extension()
+            .unwrap_or(OsStr::new(""))
+            .to_str()
+            .unwrap_or("");
+        let result = if valid_file_extention(ext) {
+            read_source(path).and_then(|code| {
+                self.code = code;
+                self.current_node = CodeNode::default();
+                self.call_stats = None;
+                self.graph_view.impact.clear();
+                self.call_sites_of = None;
+                self.stash_annotations();
+                self.graph.clear();
+                // 解析代码，生成图，保留语法树用于文件修改后的增量解析
+                let parsed_file = ParsedFile::parse(name, self.code.clone())?;
+                parsed_file.outline(&mut self.graph);
+                self.cycles_key = None;
+                self.parsed_file = Some(parsed_file);
+                self.source_modified = modified_time(path);
+                Ok(())
+            })
+        } else if let Some(graph) = tags_graph(&self.tags, name) {
+            // 没有语法解析的语言使用导入的 tags 中的符号
+            self.code = graph
+                .node(CodeNodeIndex(0))
+                .map(|root| root.block.clone())
+                .unwrap_or_default();
+            self.current_node = CodeNode::default();
+            self.call_stats = None;
+            self.graph_view.impact.clear();
+            self.call_sites_of = None;
+            self.stash_annotations();
+            self.graph = graph;
+            self.cycles_key = None;
+            Ok(())
         } else {
-            if let TreeEvent::Clicked(name) = self.tree.ui(ui) {
-                let path = Path::new(&name);
-                let ext = path.extension().unwrap_or(OsStr::new("")).to_str().unwrap();
-                if valid_file_extention(ext) {
-                    self.code = fs::read_to_string(path).unwrap();
-                    self.current_node = CodeNode::default();
-                    self.graph.clear();
-                    // 解析代码，生成图
-                    fetch_symbols(&name, &self.code, get_symbol_query(ext), &mut self.graph);
-                    // 布局
-                    self.graph.layout(ui, None);
-                } else {
-                    MessageDialog::new()
-                        .set_title("提示")
-                        .set_description("不受支持的文件类型")
-                        .show();
+            Err(CodeGraphError::UnsupportedLanguage(name.to_owned()))
+        };
+        match result {
+            Ok(()) => {
+                self.restore_annotations();
+                self.refresh_overlay();
+                if let Some(reference_counts) = &self.reference_counts {
+                    self.graph.apply_references(reference_counts);
                 }
+                let auto_collapse_depth = self
+                    .auto_collapse_depth
+                    .filter(|_| self.graph.node_count() > AUTO_COLLAPSE_NODE_COUNT);
+                let depth = self.max_depth.into_iter().chain(auto_collapse_depth).min();
+                self.graph.limit_depth(depth);
+                self.apply_hide_orphans();
+                self.apply_name_filter();
+                self.apply_unsafe_filter();
+                self.apply_public_filter();
+                // 布局
+                self.graph_view.layout(ui, &mut self.graph);
+                true
+            }
+            Err(err) => {
+                MessageDialog::new()
+                    .set_title("提示")
+                    .set_description(err.to_string())
+                    .show();
+                false
             }
         }
     }
+    /**
+     * 记下当前图中的便签，切换文件后再打开时恢复
+     */
+    fn stash_annotations(&mut self) {
+        let Some(file_path) = self
+            .graph
+            .node(CodeNodeIndex(0))
+            .map(|root| root.file_path.clone())
+            .filter(|file_path| !file_path.is_empty())
+        else {
+            return;
+        };
+        if self.graph.annotations().is_empty() {
+            self.annotations.remove(&file_path);
+        } else {
+            self.annotations
+                .insert(file_path, self.graph.annotations().to_vec());
+        }
+    }
+    /**
+     * 恢复之前在同一个文件的图中添加的便签，图中已经有便签（例如导入的 json）时不覆盖
+     */
+    fn restore_annotations(&mut self) {
+        if !self.graph.annotations().is_empty() {
+            return;
+        }
+        let annotations = self
+            .graph
+            .node(CodeNodeIndex(0))
+            .and_then(|root| self.annotations.get(&root.file_path))
+            .cloned();
+        if let Some(annotations) = annotations {
+            self.graph.set_annotations(annotations);
+        }
+    }
+
+    /**
+     * 显示后台生成的图
+     */
+    fn show_graph(&mut self, ui: &mut Ui, graph: Graph) {
+        self.switch_tab(GraphTab::Code);
+        self.history_shown = None;
+        self.stash_annotations();
+        self.graph = graph;
+        self.cycles_key = None;
+        self.restore_annotations();
+        self.refresh_overlay();
+        self.graph.limit_depth(self.max_depth);
+        self.apply_hide_orphans();
+        self.apply_name_filter();
+        self.apply_unsafe_filter();
+        self.apply_public_filter();
+        self.graph_view.layout(ui, &mut self.graph);
+        self.current_node = CodeNode::default();
+        self.call_stats = None;
+        self.graph_view.impact.clear();
+        self.call_sites_of = None;
+    }
+    /**
+     * 实时更新时定期检查打开的文件，修改后增量解析，只更新图中变化的节点，保留布局和展开状态
+     */
+    fn sync_open_file(&mut self, ui: &mut Ui) {
+        if !self.live_update || self.graph_tab != GraphTab::Code || self.history_shown.is_some() {
+            return;
+        }
+        let Some(parsed_file) = &mut self.parsed_file else {
+            return;
+        };
+        // 图已经换成了调用图等其他的图
+        if self.graph.node(CodeNodeIndex(0)).map(|root| root.id()) != Some(parsed_file.path()) {
+            return;
+        }
+        ui.ctx()
+            .request_repaint_after(std::time::Duration::from_secs_f64(LIVE_UPDATE_SECONDS));
+        let now = ui.input(|input| input.time);
+        if now - self.source_checked < LIVE_UPDATE_SECONDS {
+            return;
+        }
+        self.source_checked = now;
+        let path = Path::new(parsed_file.path());
+        let modified = modified_time(path);
+        if modified == self.source_modified {
+            return;
+        }
+        self.source_modified = modified;
+        // 读取失败时可能正在保存，等下一次修改
+        let Ok(code) = read_source(path) else {
+            return;
+        };
+        if !parsed_file.update(code).unwrap_or(false) {
+            return;
+        }
+        let mut outline = Graph::new();
+        parsed_file.outline(&mut outline);
+        let file_path = parsed_file.path().to_owned();
+        self.code = parsed_file.code().to_owned();
+        // 文件中的调用也重新获取，调用列表和调用位置按新的行号显示
+        self.call_nodes.retain(|call| call.file_path != file_path);
+        self.call_nodes.extend(parsed_file.calls());
+        self.graph
+            .retain_nodes(|node| !node.id().contains(CALL_SITE_ID));
+        self.call_sites_of = None;
+        self.graph.expand_all_clusters();
+        if update_outline(&mut self.graph, &outline).is_empty() {
+            return;
+        }
+        self.cycles_key = None;
+        self.refresh_overlay();
+        if let Some(reference_counts) = &self.reference_counts {
+            self.graph.apply_references(reference_counts);
+        }
+        self.apply_hide_orphans();
+        self.apply_name_filter();
+        self.apply_unsafe_filter();
+        self.apply_public_filter();
+        self.graph_view.relayout(ui, &mut self.graph);
+        match self.graph.find_node_by_id(self.current_node.id()) {
+            Some(index) => self.select_node(index),
+            None => {
+                self.current_node = CodeNode::default();
+                self.call_stats = None;
+                self.graph_view.impact.clear();
+            }
+        }
+    }
+
     fn open_editor(&self, file_path: &str, line_number: usize) {
         let command = match self.editor {
             Editor::Zed => "zed",
@@ -171,86 +771,9 @@ impl MyApp {
                 .set_title("打开失败")
                 .set_description(err.to_string())
                 .show();
-            return true;
+            true
         }); // 传递命令行参数
     }
-    fn right_panel(&mut self, ui: &mut Ui) {
-        ui.add_space(10.0);
-        egui::Grid::new("param_grid")
-            .num_columns(2)
-            .spacing([10.0, 10.0])
-            .show(ui, |ui| {
-                ui.label("选择编辑器");
-                ui.horizontal(|ui| {
-                    egui::ComboBox::from_id_source("choose editor")
-                        .selected_text(format!("{:?}", self.editor))
-                        .show_ui(ui, |ui| {
-                            ui.selectable_value(&mut self.editor, Editor::Idea, "Idea");
-                            ui.selectable_value(&mut self.editor, Editor::VSCode, "VSCode");
-                            ui.selectable_value(&mut self.editor, Editor::Zed, "Zed");
-                        });
-                    ui.add_space(4.0);
-                    if self.get_normal_button("打开").ui(ui).clicked() {
-                        self.open_editor(
-                            &self.current_node.file_path,
-                            self.current_node.file_location,
-                        );
-                    }
-                });
-
-                ui.end_row();
-            });
-
-        ui.add_space(10.0);
-        egui::CollapsingHeader::new("调用列表")
-            .default_open(true)
-            .show(ui, |ui| {
-                for node in &self.filter_call_nodes {
-                    let mut job = LayoutJob::default();
-                    job.append(
-                        node.block.replace("\n", " ").replace(" ", "").as_str(),
-                        0.0,
-                        TextFormat {
-                            color: ui.style().visuals.text_color(),
-                            ..Default::default()
-                        },
-                    );
-                    job.append(
-                        format!("\n{}:{}", node.file_path, node.file_location).as_str(),
-                        0.0,
-                        TextFormat {
-                            font_id: FontId::monospace(8.0),
-                            ..Default::default()
-                        },
-                    );
-                    if egui::Button::new(job)
-                        .rounding(Rounding::same(8.0))
-                        .min_size(egui::Vec2::new(ui.available_width(), 0.0))
-                        .ui(ui)
-                        .clicked()
-                    {
-                        self.open_editor(&node.file_path, node.file_location);
-                    }
-                }
-            });
-
-        ui.add_space(10.0);
-        egui::CollapsingHeader::new("代码预览")
-            .default_open(true)
-            .show(ui, |ui| {
-                let language = "rs";
-                let theme = egui_extras::syntax_highlighting::CodeTheme::from_memory(ui.ctx());
-                egui::ScrollArea::vertical().show(ui, |ui| {
-                    egui_extras::syntax_highlighting::code_view_ui(
-                        ui,
-                        &theme,
-                        &self.current_node.block,
-                        language,
-                    );
-                });
-            });
-        ui.add_space(10.0);
-    }
 
     fn draw_debug_info(&self, ctx: &egui::Context) {
         let painter = ctx.debug_painter();
@@ -268,18 +791,36 @@ impl MyApp {
         // 例如，内存使用、对象数量等
     }
 
-    fn get_normal_button(&mut self, text: &str) -> egui::Button {
-        return egui::Button::new(text).rounding(Rounding::same(5.0));
+    fn get_normal_button(&mut self, text: &str) -> egui::Button<'_> {
+        egui::Button::new(text).rounding(Rounding::same(5.0))
     }
 }
 
 impl eframe::App for MyApp {
     fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        self.stash_annotations();
         storage.set_string(
             "app_state",
             serde_json::to_string(&AppState {
                 editor: self.editor.clone(),
                 root_path: self.root_path.clone(),
+                auto_collapse_depth: self.auto_collapse_depth,
+                generics_display: self.graph_view.generics_display,
+                scan_depth: self.scan_depth,
+                high_contrast: self.graph_view.high_contrast,
+                size_bars: self.graph_view.size_bars,
+                unsafe_outline: self.graph_view.unsafe_outline,
+                todo_badges: self.todo_badges,
+                layout_kind: self.layout_kind,
+                edge_style: self.graph_view.edge_style,
+                cluster_by: self.graph_view.cluster_by,
+                annotations: self.annotations.clone(),
+                focus_hops: self.graph_view.focus_hops,
+                png_scale: self.png_scale,
+                markdown_links: self.markdown_links,
+                bookmarks: self.bookmarks.clone(),
+                recent_files: self.recent_files.clone(),
+                live_update: self.live_update,
             })
             .unwrap(),
         );
@@ -291,78 +832,98 @@ impl eframe::App for MyApp {
             self.debug.fps = 1.0 / time;
             self.draw_debug_info(ctx);
         }
-        egui::SidePanel::left("side_panel")
-            .resizable(true)
-            .show_separator_line(false)
-            .show(ctx, |ui| {
-                ui.add_space(10.0);
-                ui.horizontal(|ui| {
-                    ui.label("文件列表");
-                    // let file_icon = egui::include_image!("../assets/folder.png");
-                    let open_file_button = ui.add(self.get_normal_button("选择"));
-                    if open_file_button.clicked() {
-                        // 打开系统目录
-                        if let Some(path) = FileDialog::new().pick_folder() {
-                            self.root_path = path.as_os_str().to_str().unwrap().to_string();
-                            self.project_root_path = Some(path);
-                        }
+        if ctx.input_mut(|input| input.consume_shortcut(&BOOKMARK_SHORTCUT)) {
+            if let Some(index) = self.graph.get_focus_idx() {
+                self.toggle_bookmark(index);
+            }
+        }
+        if ctx.input_mut(|input| input.consume_shortcut(&FIND_SHORTCUT)) {
+            self.show_find = true;
+            self.find_focus_requested = true;
+        }
+        if ctx.input_mut(|input| input.consume_shortcut(&RECENT_FILES_SHORTCUT)) {
+            self.show_recent_files = !self.show_recent_files;
+            self.show_palette = false;
+            self.recent_selected = 0;
+        }
+        if ctx.input_mut(|input| input.consume_shortcut(&PALETTE_SHORTCUT)) {
+            self.show_palette = !self.show_palette;
+            self.show_recent_files = false;
+            self.palette_query.clear();
+            self.palette_results.clear();
+            self.palette_selected = 0;
+        }
+        // 在菜单之前绘制，避免打开搜索的那次点击被当作点击了搜索框外面
+        if self.show_palette {
+            self.symbol_palette(ctx);
+        }
+        if self.show_recent_files {
+            self.recent_files_popup(ctx);
+        }
+        egui::TopBottomPanel::top("menu_bar").show(ctx, |ui| {
+            egui::menu::bar(ui, |ui| {
+                ui.menu_button("文件", |ui| {
+                    if ui.button("打开图…").clicked() {
+                        ui.close_menu();
+                        self.open_graph_file();
+                    }
+                    let has_graph = !self.graph.is_empty();
+                    if ui
+                        .add_enabled(has_graph, egui::Button::new("保存图…"))
+                        .clicked()
+                    {
+                        ui.close_menu();
+                        self.save_graph_file();
+                    }
+                    ui.separator();
+                    if ui.button("导入 tags…").clicked() {
+                        ui.close_menu();
+                        self.import_tags();
+                    }
+                    let can_generate = !self.root_path.is_empty() && self.tags_rx.is_none();
+                    if ui
+                        .add_enabled(can_generate, egui::Button::new("生成 tags…"))
+                        .clicked()
+                    {
+                        ui.close_menu();
+                        self.generate_tags();
                     }
-                    open_file_button.on_hover_text("选择项目目录");
                 });
-                if let Some(dir_path) = &self.project_root_path {
-                    // 清除图里的数据
-                    self.graph.clear();
-                    let new_tree = Tree::new(
-                        dir_path.as_os_str().to_str().unwrap(),
-                        dir_path.as_os_str().to_str().unwrap(),
-                        TreeType::Directory,
-                    );
-                    let dir_path = dir_path.clone();
-                    let (tx, rx) = mpsc::channel();
-                    self.rx = Some(rx);
-                    // 在后台线程中执行耗时任务
-                    thread::spawn(move || {
-                        let mut pathes = vec![];
-                        let result = recursion_dir(&dir_path, &mut pathes, new_tree);
-                        let call_node_list = pathes
-                            .iter()
-                            .map(|path_buffer| {
-                                let ext = path_buffer
-                                    .extension()
-                                    .unwrap_or(OsStr::new(""))
-                                    .to_str()
-                                    .unwrap();
-                                let name = path_buffer.as_os_str().to_str().unwrap();
-                                if valid_file_extention(ext) {
-                                    let code = fs::read_to_string(path_buffer).unwrap_or("".into());
-                                    return fetch_calls(&name, &code, get_symbol_query(ext));
-                                }
-                                return vec![];
-                            })
-                            .flatten()
-                            .collect::<Vec<CodeNode>>();
-                        // 解析获取文件中说有使用了符号的代码
-                        tx.send((result, call_node_list)).unwrap();
-                    });
-                    self.project_root_path = None
-                }
-
-                if let Some(rx) = &self.rx {
-                    if let Ok(result) = rx.try_recv() {
-                        self.tree = result.0;
-                        self.call_nodes = result.1;
-                        self.rx = None;
-                    } else {
-                        ui.spinner();
+                ui.menu_button("导航", |ui| {
+                    let button = egui::Button::new("查找符号…")
+                        .shortcut_text(ui.ctx().format_shortcut(&PALETTE_SHORTCUT));
+                    if ui.add(button).clicked() {
+                        ui.close_menu();
+                        self.show_palette = true;
+                        self.show_recent_files = false;
+                        self.palette_query.clear();
+                        self.palette_results.clear();
+                        self.palette_selected = 0;
+                    }
+                    let button = egui::Button::new("在图中查找…")
+                        .shortcut_text(ui.ctx().format_shortcut(&FIND_SHORTCUT));
+                    if ui.add(button).clicked() {
+                        ui.close_menu();
+                        self.show_find = true;
+                        self.find_focus_requested = true;
+                    }
+                    let button = egui::Button::new("最近打开的文件…")
+                        .shortcut_text(ui.ctx().format_shortcut(&RECENT_FILES_SHORTCUT));
+                    if ui.add(button).clicked() {
+                        ui.close_menu();
+                        self.show_recent_files = true;
+                        self.show_palette = false;
+                        self.recent_selected = 0;
                     }
-                }
-
-                ui.add_space(10.0);
-                egui::ScrollArea::both().show(ui, |ui| {
-                    ui.set_min_height(ui.available_height());
-                    self.side_panel(ui);
                 });
             });
+        });
+        egui::SidePanel::left("side_panel")
+            .resizable(true)
+            .show_separator_line(false)
+            .show(ctx, |ui| {
+                self.side_panel(ui);
+            });
         egui::SidePanel::right("right_panel")
             .min_width(240.0)
             .resizable(true)
@@ -374,23 +935,289 @@ impl eframe::App for MyApp {
                 });
             });
 
+        if self.show_hotspots {
+            self.hotspot_window(ctx);
+        }
+        if self.show_stats {
+            self.stats_window(ctx);
+        }
+        if self.show_matrix {
+            self.matrix_window(ctx);
+        }
+        if self.show_history {
+            self.history_window(ctx);
+        }
+        if self.show_duplicates {
+            self.duplicate_window(ctx);
+        }
+        if self.show_references {
+            self.references_window(ctx);
+        }
+        if self.show_remote {
+            self.remote_window(ctx);
+        }
+        if self.show_theme {
+            self.theme_window(ctx);
+        }
+
         egui::CentralPanel::default().show(ctx, |ui| {
             egui::Frame::canvas(ui.style()).show(ui, |ui| {
-                let response = self.graph.ui(ui);
-                if let Some(focue_node) = self.graph.get_focus_idx() {
-                    self.current_node = self.graph.get_node(focue_node);
-                    self.filter_call_nodes.clear();
-                    for node in &self.call_nodes {
-                        let current_label = &self.current_node.label;
-                        for ele in current_label.split(" ") {
-                            if ele == node.label {
-                                self.filter_call_nodes.push(node.clone());
+                if let Some((file_path, line_number)) = self.pending_location.take() {
+                    self.open_location(ui, &file_path, line_number);
+                }
+                if let Some(index) = self.pending_callers.take() {
+                    self.show_callers(ui, index);
+                }
+                if let Some(bookmark) = self.pending_bookmark.take() {
+                    self.open_bookmark(ui, &bookmark);
+                }
+                if let Some(file_path) = self.pending_file.take() {
+                    self.open_recent_file(ui, &file_path);
+                }
+                if let Some(index) = self.pending_focus.take() {
+                    self.reveal_node(ui, index);
+                    self.graph_view.center_on(ui, &mut self.graph, index);
+                }
+                if let Some(graph) = self.pending_graph.take() {
+                    self.show_graph(ui, graph);
+                }
+                if let Some((hash, graph)) = self.pending_history_graph.take() {
+                    self.show_revision_graph(ui, hash, graph);
+                }
+                if let Some(rx) = &self.blame_rx {
+                    match rx.try_recv() {
+                        Ok(blames) => {
+                            self.blame_cache.merge(blames);
+                            self.blame_rx = None;
+                            self.refresh_overlay();
+                        }
+                        Err(TryRecvError::Empty) => ui
+                            .ctx()
+                            .request_repaint_after(std::time::Duration::from_millis(100)),
+                        Err(TryRecvError::Disconnected) => self.blame_rx = None,
+                    }
+                }
+                if let Some(rx) = &self.patch_rx {
+                    if let Ok(graph) = rx.try_recv() {
+                        self.show_graph(ui, graph);
+                        self.patch_rx = None;
+                    }
+                }
+                if let Some(rx) = &self.call_graph_rx {
+                    if let Ok(graph) = rx.try_recv() {
+                        self.show_graph(ui, graph);
+                        self.call_graph_rx = None;
+                    }
+                }
+                if let Some(rx) = &self.hierarchy_rx {
+                    if let Ok(graph) = rx.try_recv() {
+                        self.show_graph(ui, graph);
+                        self.hierarchy_rx = None;
+                    }
+                }
+                if let Some(rx) = &self.field_usage_rx {
+                    if let Ok(result) = rx.try_recv() {
+                        match result {
+                            Ok(graph) => self.show_graph(ui, graph),
+                            Err(err) => {
+                                MessageDialog::new()
+                                    .set_title("分析字段失败")
+                                    .set_description(err.to_string())
+                                    .show();
                             }
                         }
+                        self.field_usage_rx = None;
                     }
                 }
-                response
+                if let Some(rx) = &self.dependency_rx {
+                    if let Ok(graph) = rx.try_recv() {
+                        self.show_dependency_graph(ui, graph);
+                        self.dependency_rx = None;
+                    }
+                }
+                if let Some(rx) = &self.compare_rx {
+                    if let Ok(result) = rx.try_recv() {
+                        match result {
+                            Ok(graph) => self.show_graph(ui, graph),
+                            Err(err) => {
+                                MessageDialog::new()
+                                    .set_title("对比失败")
+                                    .set_description(err.to_string())
+                                    .show();
+                            }
+                        }
+                        self.compare_rx = None;
+                    }
+                }
+                if let Some(rx) = &self.snapshot_rx {
+                    if let Ok(result) = rx.try_recv() {
+                        if let Err(err) = result {
+                            MessageDialog::new()
+                                .set_title("保存快照失败")
+                                .set_description(err.to_string())
+                                .show();
+                        }
+                        self.snapshot_rx = None;
+                    }
+                }
+                if let Some(rx) = &self.png_rx {
+                    if let Ok(result) = rx.try_recv() {
+                        if let Err(err) = result {
+                            MessageDialog::new()
+                                .set_title("导出失败")
+                                .set_description(err.to_string())
+                                .show();
+                        }
+                        self.png_rx = None;
+                    }
+                }
+                if let Some(rx) = &self.copy_image_rx {
+                    if let Ok(image) = rx.try_recv() {
+                        self.copy_image_rx = None;
+                        self.set_clipboard_image(&image);
+                    }
+                }
+                if let Some(rx) = &self.batch_export_rx {
+                    if let Ok(result) = rx.try_recv() {
+                        let (title, description) = match result {
+                            Ok(count) => ("导出完成", format!("已导出 {} 个文件", count)),
+                            Err(err) => ("导出失败", err.to_string()),
+                        };
+                        MessageDialog::new()
+                            .set_title(title)
+                            .set_description(description)
+                            .show();
+                        self.batch_export_rx = None;
+                    }
+                }
+                if let Some(rx) = &self.tags_rx {
+                    if let Ok(result) = rx.try_recv() {
+                        let (title, description) = match result {
+                            Ok(count) => ("生成完成", format!("已写入 {} 个符号", count)),
+                            Err(err) => ("生成失败", err.to_string()),
+                        };
+                        MessageDialog::new()
+                            .set_title(title)
+                            .set_description(description)
+                            .show();
+                        self.tags_rx = None;
+                    }
+                }
+                if self.visibility_changed {
+                    // 重新计算可见性前展开分组，避免分组记录的隐藏节点与实际不一致
+                    self.graph.expand_all_clusters();
+                    self.graph.limit_depth(self.max_depth);
+                    self.apply_hide_orphans();
+                    self.apply_name_filter();
+                    self.apply_unsafe_filter();
+                    self.apply_public_filter();
+                    self.graph_view.relayout(ui, &mut self.graph);
+                    self.visibility_changed = false;
+                    self.cycles_key = None;
+                    self.labels_changed = false;
+                }
+                if self.labels_changed {
+                    self.graph_view.relayout(ui, &mut self.graph);
+                    self.labels_changed = false;
+                }
+                self.sync_open_file(ui);
+                self.sync_call_sites(ui);
+                self.graph_tabs(ui);
+                self.mark_cycles();
+                self.find_bar(ui);
+                self.breadcrumbs(ui);
+                match self.graph_view.ui(ui, &mut self.graph) {
+                    GraphEvent::NodeClicked(index) => self.select_node(index),
+                    GraphEvent::FindCallers(index) => {
+                        self.select_node(index);
+                        self.show_callers(ui, index);
+                    }
+                    GraphEvent::ToggleBookmark(index) => {
+                        self.select_node(index);
+                        self.toggle_bookmark(index);
+                    }
+                    GraphEvent::ExpandCallees(index) => {
+                        self.select_node(index);
+                        self.expand_callees(ui, index);
+                    }
+                    GraphEvent::AnalyseImpact(index) => {
+                        self.select_node(index);
+                        self.analyse_impact(ui, index);
+                    }
+                    GraphEvent::ShowReferences(index) => {
+                        self.select_node(index);
+                        self.show_references();
+                    }
+                    // 依赖图中双击文件节点在代码图中打开该文件
+                    GraphEvent::NodeDoubleClicked(index)
+                        if self.graph_tab == GraphTab::Dependencies =>
+                    {
+                        if let Some(file_path) = self
+                            .graph
+                            .node(index)
+                            .map(|node| node.file_path.clone())
+                            .filter(|file_path| !file_path.is_empty())
+                        {
+                            self.open_file(ui, &file_path);
+                        }
+                    }
+                    GraphEvent::NodeDoubleClicked(index) => {
+                        self.select_node(index);
+                        self.open_editor(
+                            &self.current_node.file_path,
+                            self.current_node.file_location,
+                        );
+                    }
+                    _ => {}
+                }
             });
         });
     }
 }
+
+/**
+ * 文件的修改时间，无法读取时为 None
+ */
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/**
+ * 调用列表中的按钮，显示调用代码和所在位置
+ */
+fn call_button(ui: &mut Ui, node: &CodeNode) -> egui::Response {
+    let mut job = LayoutJob::default();
+    job.append(
+        node.block.replace("\n", " ").replace(" ", "").as_str(),
+        0.0,
+        TextFormat {
+            color: ui.style().visuals.text_color(),
+            ..Default::default()
+        },
+    );
+    job.append(
+        format!("\n{}:{}", node.file_path, node.file_location).as_str(),
+        0.0,
+        TextFormat {
+            font_id: FontId::monospace(8.0),
+            ..Default::default()
+        },
+    );
+    let response = egui::Button::new(job)
+        .rounding(Rounding::same(8.0))
+        .min_size(egui::Vec2::new(ui.available_width(), 0.0))
+        .ui(ui);
+    response.widget_info(|| {
+        egui::WidgetInfo::labeled(
+            egui::WidgetType::Button,
+            true,
+            format!(
+                "调用 {}，位于 {} 第 {} 行",
+                node.label, node.file_path, node.file_location
+            ),
+        )
+    });
+    response
+}
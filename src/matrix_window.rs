@@ -0,0 +1,103 @@
+use code_graph::{
+    matrix::{CallMatrix, MatrixOrder},
+    CodeNodeIndex,
+};
+
+use crate::MyApp;
+
+impl MyApp {
+    pub(crate) fn matrix_window(&mut self, ctx: &egui::Context) {
+        let mut show_matrix = self.show_matrix;
+        egui::Window::new("调用矩阵")
+            .open(&mut show_matrix)
+            .default_size([480.0, 420.0])
+            .show(ctx, |ui| {
+                // 图中的节点可能随时被筛选或折叠，每次都按当前可见的节点生成
+                let matrix = CallMatrix::new(&self.graph, self.matrix_order);
+                if matrix.is_empty() {
+                    ui.label("图中没有可见的调用关系");
+                    return;
+                }
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_id_source("matrix order")
+                        .selected_text(self.matrix_order.name())
+                        .show_ui(ui, |ui| {
+                            for order in MatrixOrder::ALL {
+                                ui.selectable_value(&mut self.matrix_order, order, order.name());
+                            }
+                        });
+                    ui.label(format!(
+                        "{} 个调用方 × {} 个被调用方，悬停列号查看名称",
+                        matrix.callers.len(),
+                        matrix.callees.len()
+                    ));
+                });
+                let label_of = |index: CodeNodeIndex| {
+                    self.graph
+                        .node(index)
+                        .map_or(String::new(), |node| node.label.trim().to_owned())
+                };
+                let fill = ui.visuals().selection.bg_fill;
+                let mut focus = None;
+                egui::ScrollArea::both().show(ui, |ui| {
+                    egui::Grid::new("call_matrix_grid")
+                        .num_columns(matrix.callees.len() + 1)
+                        .spacing([2.0, 2.0])
+                        .striped(true)
+                        .show(ui, |ui| {
+                            ui.label("调用方 ＼ 被调用方");
+                            for (number, callee) in matrix.callees.iter().enumerate() {
+                                if ui
+                                    .link(format!("{}", number + 1))
+                                    .on_hover_text(label_of(*callee))
+                                    .clicked()
+                                {
+                                    focus = Some(*callee);
+                                }
+                            }
+                            ui.end_row();
+                            for caller in &matrix.callers {
+                                let label = label_of(*caller);
+                                let short = match label.char_indices().nth(30) {
+                                    Some((end, _)) => format!("{}…", &label[..end]),
+                                    None => label.clone(),
+                                };
+                                if ui.link(short).on_hover_text(&label).clicked() {
+                                    focus = Some(*caller);
+                                }
+                                for (number, callee) in matrix.callees.iter().enumerate() {
+                                    let count = matrix.count(*caller, *callee);
+                                    if count == 0 {
+                                        ui.label("");
+                                        continue;
+                                    }
+                                    let strength = count as f32 / matrix.max_count() as f32;
+                                    if ui
+                                        .add(
+                                            egui::Button::new(format!("{}", count))
+                                                .fill(fill.gamma_multiply(0.2 + 0.8 * strength))
+                                                .min_size(egui::vec2(24.0, 0.0)),
+                                        )
+                                        .on_hover_text(format!(
+                                            "{} → {}（列 {}）：{} 次，点击定位调用方",
+                                            label,
+                                            label_of(*callee),
+                                            number + 1,
+                                            count
+                                        ))
+                                        .clicked()
+                                    {
+                                        focus = Some(*caller);
+                                    }
+                                }
+                                ui.end_row();
+                            }
+                        });
+                });
+                if focus.is_some() {
+                    self.pending_focus = focus;
+                }
+            });
+        self.show_matrix = show_matrix;
+    }
+}
@@ -0,0 +1,400 @@
+use std::{collections::HashMap, ffi::OsStr, path::Path};
+
+use code_graph::{
+    fetch_calls, get_symbol_query, read_source, CodeBlockType, CodeNode, CodeNodeIndex,
+    DefinitionIndex,
+};
+use eframe::egui::Ui;
+use rfd::MessageDialog;
+
+use crate::{Bookmark, MyApp, CALL_SITE_ID};
+
+impl MyApp {
+    /**
+     * 打开文件并聚焦到指定行的节点
+     */
+    pub(crate) fn open_location(&mut self, ui: &mut Ui, file_path: &str, line_number: usize) {
+        // 已经打开的文件不需要重新解析
+        let opened = self
+            .graph
+            .node(CodeNodeIndex(0))
+            .is_some_and(|root| root.file_path == file_path);
+        if opened || self.open_file(ui, file_path) {
+            let index = self.graph.find_node_by_location(line_number);
+            self.graph.set_focus_idx(index);
+            if let Some(index) = index {
+                self.reveal_node(ui, index);
+                self.graph_view.center_on(ui, &mut self.graph, index);
+            }
+        }
+    }
+    /**
+     * 选中节点，节点被折叠时展开全部节点
+     */
+    pub(crate) fn reveal_node(&mut self, ui: &mut Ui, index: CodeNodeIndex) {
+        if !self.graph.node(index).is_some_and(|node| node.visiable()) {
+            for (_, node) in self.graph.nodes_mut() {
+                node.set_visiable(true);
+            }
+            self.graph_view.layout(ui, &mut self.graph);
+        }
+        self.select_node(index);
+    }
+    /**
+     * 为节点添加书签，已经添加过时移除
+     */
+    pub(crate) fn toggle_bookmark(&mut self, index: CodeNodeIndex) {
+        let Some(node) = self.graph.node(index) else {
+            return;
+        };
+        if node.id().contains(CALL_SITE_ID) {
+            return;
+        }
+        match self
+            .bookmarks
+            .iter()
+            .position(|bookmark| bookmark.id == node.id())
+        {
+            Some(position) => {
+                self.bookmarks.remove(position);
+            }
+            None => {
+                // 多个文件组成的图中节点位置与单独打开文件时不同，不记录视角
+                let single_file = self
+                    .graph
+                    .node(CodeNodeIndex(0))
+                    .is_some_and(|root| root.file_path == node.file_path);
+                self.bookmarks.push(Bookmark {
+                    file_path: node.file_path.clone(),
+                    id: node.id().to_owned(),
+                    label: node.label.trim().to_owned(),
+                    line: node.file_location,
+                    camera: single_file.then(|| self.graph.camera()),
+                });
+            }
+        }
+        self.sync_bookmarks();
+    }
+    pub(crate) fn sync_bookmarks(&mut self) {
+        self.graph_view.bookmarks = self
+            .bookmarks
+            .iter()
+            .map(|bookmark| bookmark.id.clone())
+            .collect();
+    }
+    /**
+     * 打开书签所在的文件并选中节点，恢复添加书签时的视角
+     */
+    pub(crate) fn open_bookmark(&mut self, ui: &mut Ui, bookmark: &Bookmark) {
+        let opened = self
+            .graph
+            .node(CodeNodeIndex(0))
+            .is_some_and(|root| root.file_path == bookmark.file_path);
+        if !opened && !self.open_file(ui, &bookmark.file_path) {
+            return;
+        }
+        let Some(index) = self
+            .graph
+            .find_node_by_id(&bookmark.id)
+            .or_else(|| self.graph.find_node_by_location(bookmark.line))
+        else {
+            MessageDialog::new()
+                .set_title("提示")
+                .set_description(format!("没有找到书签 {} 对应的定义", bookmark.label))
+                .show();
+            return;
+        };
+        self.reveal_node(ui, index);
+        match bookmark.camera {
+            Some(camera) => {
+                self.graph.set_focus_idx(Some(index));
+                *self.graph.camera_mut() = camera;
+            }
+            None => self.graph_view.center_on(ui, &mut self.graph, index),
+        }
+    }
+    /**
+     * 画布上方的导航路径：从文件到选中节点的各层定义，点击某一层聚焦并居中显示对应的节点
+     */
+    pub(crate) fn breadcrumbs(&mut self, ui: &mut Ui) {
+        if self.graph.is_empty() {
+            return;
+        }
+        let path = self
+            .graph
+            .get_focus_idx()
+            .map(|index| self.graph.ancestor_path(index))
+            .unwrap_or_default();
+        let mut clicked = None;
+        ui.horizontal(|ui| {
+            if path.is_empty() {
+                ui.weak("选中节点后显示所在位置");
+            }
+            for (position, index) in path.iter().enumerate() {
+                let Some(node) = self.graph.node(*index) else {
+                    continue;
+                };
+                if position > 0 {
+                    ui.weak("›");
+                }
+                let current = position + 1 == path.len();
+                if ui
+                    .selectable_label(current, node.label.trim())
+                    .on_hover_text(format!("{}:{}", node.file_path, node.file_location))
+                    .clicked()
+                {
+                    clicked = Some(*index);
+                }
+            }
+        });
+        if let Some(index) = clicked {
+            self.select_node(index);
+            self.graph_view.center_on(ui, &mut self.graph, index);
+        }
+    }
+    /**
+     * 在画布中跳转到调用对应的定义
+     */
+    pub(crate) fn jump_to_definition(&mut self, call: &CodeNode) {
+        match self.definitions.resolve(call) {
+            Some(definition) => {
+                self.pending_location =
+                    Some((definition.file_path.clone(), definition.file_location));
+            }
+            None => {
+                MessageDialog::new()
+                    .set_title("提示")
+                    .set_description(format!("没有找到 {} 的定义", call.label))
+                    .show();
+            }
+        }
+    }
+    /**
+     * 在画布中跳转到调用所在的定义，调用不在任何定义中时跳转到所在的文件
+     */
+    pub(crate) fn jump_to_caller(&mut self, call: &CodeNode) {
+        let location = self
+            .definitions
+            .enclosing(&call.file_path, call.file_location)
+            .map_or(call.file_location, |definition| definition.file_location);
+        self.pending_location = Some((call.file_path.clone(), location));
+    }
+    /**
+     * 把调用了节点的定义加入图中，并从调用方连出调用关系，已经在图中的调用方直接连线，
+     * 对加入的调用方再次查找即可逐层展开反向调用图
+     */
+    pub(crate) fn show_callers(&mut self, ui: &mut Ui, index: CodeNodeIndex) {
+        let Some(target) = self.graph.node(index).cloned() else {
+            return;
+        };
+        if self.definitions.is_empty() {
+            MessageDialog::new()
+                .set_title("提示")
+                .set_description("需要先选择项目目录并等待扫描完成")
+                .show();
+            return;
+        }
+        let callers = self.definitions.callers(&self.call_nodes, &target);
+        if callers.is_empty() {
+            MessageDialog::new()
+                .set_title("提示")
+                .set_description(format!("没有找到调用 {} 的定义", target.label.trim()))
+                .show();
+            return;
+        }
+        for (caller, call_lines) in callers {
+            // 调用方不在图中时加入，保留在所在文件中的层级
+            let from = self.graph.insert_definition(caller, caller.level());
+            self.graph.merge_call_edge(from, index, call_lines);
+        }
+        if let Some(counts) = &self.reference_counts {
+            self.graph.apply_references(counts);
+        }
+        self.graph_view.relayout(ui, &mut self.graph);
+        self.graph_view.center_on(ui, &mut self.graph, index);
+    }
+    /**
+     * 影响分析：把直接或间接调用节点的定义（最多 impact_depth 层）加入图中并连出调用关系，
+     * 用外框标出，用来估计修改函数签名时需要改动的范围
+     */
+    pub(crate) fn analyse_impact(&mut self, ui: &mut Ui, index: CodeNodeIndex) {
+        let Some(target) = self.graph.node(index).cloned() else {
+            return;
+        };
+        if self.definitions.is_empty() {
+            MessageDialog::new()
+                .set_title("提示")
+                .set_description("需要先选择项目目录并等待扫描完成")
+                .show();
+            return;
+        }
+        let impact = self
+            .definitions
+            .impact(&self.call_nodes, &target, self.impact_depth);
+        self.graph_view.impact.clear();
+        self.graph_view.impact.insert(index, 0);
+        let mut indexes = HashMap::from([(target.id().to_owned(), index)]);
+        for (dependent, depth) in &impact.dependents {
+            // 调用方不在图中时加入，保留在所在文件中的层级
+            let dependent_index = self.graph.insert_definition(dependent, dependent.level());
+            self.graph_view.impact.insert(dependent_index, *depth);
+            indexes.insert(dependent.id().to_owned(), dependent_index);
+        }
+        for (caller, callee, call_lines) in impact.calls {
+            if let (Some(from), Some(to)) = (indexes.get(caller.id()), indexes.get(callee)) {
+                self.graph.merge_call_edge(*from, *to, call_lines);
+            }
+        }
+        if let Some(counts) = &self.reference_counts {
+            self.graph.apply_references(counts);
+        }
+        self.graph_view.relayout(ui, &mut self.graph);
+        self.graph_view.center_on(ui, &mut self.graph, index);
+    }
+    /**
+     * 解析函数体中的调用，把能找到定义的被调用函数作为下一层节点加入图中，并连出调用关系，
+     * 扫描项目前只能对应到当前文件中的定义
+     */
+    pub(crate) fn expand_callees(&mut self, ui: &mut Ui, index: CodeNodeIndex) {
+        let Some(function) = self.graph.node(index).cloned() else {
+            return;
+        };
+        let path = Path::new(&function.file_path);
+        let ext = path.extension().and_then(OsStr::to_str).unwrap_or("");
+        let calls = match read_source(path).and_then(|code| {
+            get_symbol_query(ext)
+                .and_then(|symbol_query| fetch_calls(&function.file_path, &code, symbol_query))
+        }) {
+            Ok(calls) => calls,
+            Err(err) => {
+                MessageDialog::new()
+                    .set_title("解析失败")
+                    .set_description(err.to_string())
+                    .show();
+                return;
+            }
+        };
+        let local_definitions;
+        let definitions = if self.definitions.is_empty() {
+            local_definitions =
+                DefinitionIndex::new(self.graph.nodes().map(|(_, node)| node.clone()).collect());
+            &local_definitions
+        } else {
+            &self.definitions
+        };
+        let callees = definitions
+            .callees(&calls, &function)
+            .into_iter()
+            .map(|(callee, call_lines)| (callee.clone(), call_lines))
+            .collect::<Vec<(CodeNode, Vec<usize>)>>();
+        if callees.is_empty() {
+            MessageDialog::new()
+                .set_title("提示")
+                .set_description(format!(
+                    "{} 中没有可以对应到定义的调用",
+                    function.label.trim()
+                ))
+                .show();
+            return;
+        }
+        for (callee, call_lines) in callees {
+            let to = self.graph.insert_definition(&callee, function.level() + 1);
+            self.graph.merge_call_edge(index, to, call_lines);
+        }
+        if let Some(counts) = &self.reference_counts {
+            self.graph.apply_references(counts);
+        }
+        self.graph_view.relayout(ui, &mut self.graph);
+        self.graph_view.center_on(ui, &mut self.graph, index);
+    }
+    /**
+     * 选中节点，刷新调用列表
+     */
+    pub(crate) fn select_node(&mut self, index: CodeNodeIndex) {
+        self.current_node = self.graph.get_node(index);
+        self.filter_call_nodes.clear();
+        for node in &self.call_nodes {
+            // 扫描得到定义后只保留实际对应当前定义的调用，同名的其他定义的调用不算
+            let calls_current = if self.definitions.is_empty() {
+                self.current_node.matches_call(node)
+            } else {
+                self.definitions
+                    .resolve(node)
+                    .is_some_and(|definition| definition.id() == self.current_node.id())
+            };
+            if calls_current {
+                self.filter_call_nodes.push(node.clone());
+            }
+        }
+        self.callee_nodes.clear();
+        if self.current_node.level() > 0 {
+            let (start, end) = self.current_node.line_range();
+            for node in &self.call_nodes {
+                if node.file_path == self.current_node.file_path
+                    && start <= node.file_location
+                    && node.file_location <= end
+                {
+                    self.callee_nodes.push(node.clone());
+                }
+            }
+        }
+        self.call_stats = (!self.definitions.is_empty()
+            && self.current_node.level() > 0
+            && self.current_node.block_type() != &CodeBlockType::CALL)
+            .then(|| {
+                self.definitions
+                    .call_stats(&self.call_nodes, &self.current_node)
+            });
+    }
+    /**
+     * 在图中当前定义的下方以叶子节点显示调用位置
+     */
+    pub(crate) fn sync_call_sites(&mut self, ui: &mut Ui) {
+        // 选中的是调用位置节点时保持不变
+        if self.current_node.id().contains(CALL_SITE_ID) {
+            return;
+        }
+        let target = (self.show_call_sites
+            && self.current_node.level() > 0
+            && self.current_node.block_type() != &CodeBlockType::CALL)
+            .then(|| self.current_node.id().to_owned());
+        if target == self.call_sites_of {
+            return;
+        }
+        self.graph
+            .retain_nodes(|node| !node.id().contains(CALL_SITE_ID));
+        let parent = target.as_ref().and_then(|id| {
+            self.graph
+                .nodes()
+                .find(|(_, node)| node.id() == id)
+                .map(|(index, node)| (index, node.level()))
+        });
+        if let (Some(id), Some((parent, level))) = (&target, parent) {
+            for (index, call) in self.filter_call_nodes.iter().enumerate() {
+                let file_name = Path::new(&call.file_path)
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .unwrap_or("");
+                let label = format!(
+                    "{}:{} {}",
+                    file_name,
+                    call.file_location,
+                    call.block.split_whitespace().collect::<String>()
+                );
+                let mut node = CodeNode::new(
+                    &format!("{}{}{}", id, CALL_SITE_ID, index),
+                    &label,
+                    &call.block,
+                    call.file_location,
+                    CodeBlockType::CALL,
+                    level + 1,
+                );
+                node.file_path = call.file_path.clone();
+                let index = self.graph.add_node(node);
+                self.graph.add_edge(parent, index);
+            }
+        }
+        self.call_sites_of = target;
+        self.graph_view.relayout(ui, &mut self.graph);
+    }
+}
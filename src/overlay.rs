@@ -0,0 +1,130 @@
+use std::{sync::mpsc, thread};
+
+use code_graph::{
+    coverage::parse_coverage, git::BlameCache, profile::parse_collapsed, read_source, ColorMode,
+};
+use rfd::{FileDialog, MessageDialog};
+
+use crate::MyApp;
+
+impl MyApp {
+    /**
+     * 按待办注释所在的最内层定义统计角标，关闭角标时清空
+     */
+    pub(crate) fn refresh_todo_badges(&mut self) {
+        self.graph_view.todo_counts.clear();
+        if !self.todo_badges {
+            return;
+        }
+        for todo in &self.todos {
+            if let Some(definition) = self.definitions.enclosing(&todo.file_path, todo.line) {
+                *self
+                    .graph_view
+                    .todo_counts
+                    .entry(definition.id().to_owned())
+                    .or_insert(0) += 1;
+            }
+        }
+    }
+    /**
+     * 根据着色模式计算图中节点需要的额外数据
+     */
+    pub(crate) fn refresh_overlay(&mut self) {
+        match self.graph_view.color_mode {
+            ColorMode::Churn | ColorMode::Age | ColorMode::Hotspot => self.apply_churn(),
+            ColorMode::Profile => {
+                if let Some(profile) = &self.profile {
+                    self.graph.apply_profile(profile);
+                }
+            }
+            ColorMode::Coverage => {
+                if let Some(coverage) = &self.coverage {
+                    self.graph.apply_coverage(coverage);
+                }
+            }
+            ColorMode::BlockType | ColorMode::Complexity => {}
+        }
+    }
+    /**
+     * 用缓存的 git blame 结果着色，缺少或已修改的文件在后台线程中重新 blame，
+     * 完成后在 update 中合并到缓存并再次着色
+     */
+    fn apply_churn(&mut self) {
+        self.graph.apply_churn(&self.blame_cache);
+        if self.blame_rx.is_some() {
+            return;
+        }
+        let outdated = self
+            .blame_cache
+            .outdated(self.graph.nodes().map(|(_, node)| node.file_path.clone()));
+        if outdated.is_empty() {
+            return;
+        }
+        let (tx, rx) = mpsc::channel();
+        self.blame_rx = Some(rx);
+        thread::spawn(move || {
+            let _ = tx.send(BlameCache::blame(&outdated));
+        });
+    }
+    /**
+     * 导入 lcov 或 cobertura 格式的覆盖率报告，并切换到覆盖率着色
+     */
+    pub(crate) fn import_coverage(&mut self) {
+        let Some(path) = FileDialog::new()
+            .add_filter("覆盖率报告", &["info", "lcov", "xml"])
+            .pick_file()
+        else {
+            return;
+        };
+        let coverage = match read_source(&path) {
+            Ok(text) => parse_coverage(&text),
+            Err(err) => {
+                MessageDialog::new()
+                    .set_title("导入失败")
+                    .set_description(err.to_string())
+                    .show();
+                return;
+            }
+        };
+        if coverage.is_empty() {
+            MessageDialog::new()
+                .set_title("导入失败")
+                .set_description("没有找到覆盖率数据，需要 lcov 或 cobertura 格式")
+                .show();
+            return;
+        }
+        self.coverage = Some(coverage);
+        self.graph_view.color_mode = ColorMode::Coverage;
+        self.refresh_overlay();
+    }
+    /**
+     * 导入 collapsed stacks 格式的性能数据，并切换到耗时着色
+     */
+    pub(crate) fn import_profile(&mut self) {
+        let Some(path) = FileDialog::new().pick_file() else {
+            return;
+        };
+        let profile = match read_source(&path) {
+            Ok(text) => parse_collapsed(&text),
+            Err(err) => {
+                MessageDialog::new()
+                    .set_title("导入失败")
+                    .set_description(err.to_string())
+                    .show();
+                return;
+            }
+        };
+        if profile.is_empty() {
+            MessageDialog::new()
+                .set_title("导入失败")
+                .set_description(
+                    "没有找到采样数据，需要 collapsed stacks 格式（每行为 a;b;c 采样数）",
+                )
+                .show();
+            return;
+        }
+        self.profile = Some(profile);
+        self.graph_view.color_mode = ColorMode::Profile;
+        self.refresh_overlay();
+    }
+}
@@ -0,0 +1,111 @@
+use std::path::Path;
+
+use eframe::egui::{self, text::LayoutJob, TextFormat, Ui};
+
+use crate::MyApp;
+
+// 最近打开的文件最多保留的数量
+const RECENT_FILE_COUNT: usize = 10;
+
+impl MyApp {
+    /**
+     * 最近打开的文件列表，方向键选择，Enter 或点击重新打开，Esc 关闭
+     */
+    pub(crate) fn recent_files_popup(&mut self, ctx: &egui::Context) {
+        // 按键都消耗掉，避免同时操作画布中获得焦点的节点
+        let (up, down, enter, escape) = ctx.input_mut(|input| {
+            (
+                input.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp),
+                input.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown),
+                input.consume_key(egui::Modifiers::NONE, egui::Key::Enter),
+                input.consume_key(egui::Modifiers::NONE, egui::Key::Escape),
+            )
+        });
+        let file_count = self.recent_files.len();
+        if down && file_count > 0 {
+            self.recent_selected = (self.recent_selected + 1) % file_count;
+        }
+        if up && file_count > 0 {
+            self.recent_selected = (self.recent_selected + file_count - 1) % file_count;
+        }
+        let mut chosen = None;
+        let area = egui::Area::new(egui::Id::new("recent files"))
+            .order(egui::Order::Foreground)
+            .anchor(egui::Align2::CENTER_TOP, [0.0, 60.0])
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.set_width(480.0);
+                    ui.strong("最近打开的文件");
+                    if self.recent_files.is_empty() {
+                        ui.weak("在文件列表中打开的文件会显示在这里");
+                        return;
+                    }
+                    for (position, file_path) in self.recent_files.iter().enumerate() {
+                        let selected = position == self.recent_selected;
+                        let job = recent_file_job(ui, file_path, &self.root_path);
+                        let item = ui.add(
+                            egui::Button::new(job)
+                                .selected(selected)
+                                .frame(selected)
+                                .min_size(egui::Vec2::new(ui.available_width(), 0.0)),
+                        );
+                        if item.clicked() || (selected && enter) {
+                            chosen = Some(file_path.clone());
+                        }
+                    }
+                });
+            });
+        if let Some(file_path) = chosen {
+            self.pending_file = Some(file_path);
+            self.show_recent_files = false;
+        }
+        if escape || area.response.clicked_elsewhere() {
+            self.show_recent_files = false;
+        }
+    }
+    /**
+     * 打开文件并记录到最近打开的文件中，打开失败时从列表中移除
+     */
+    pub(crate) fn open_recent_file(&mut self, ui: &mut Ui, file_path: &str) {
+        self.recent_files.retain(|recent| recent != file_path);
+        if self.open_file(ui, file_path) {
+            self.recent_files.insert(0, file_path.to_owned());
+            self.recent_files.truncate(RECENT_FILE_COUNT);
+        }
+    }
+}
+
+/**
+ * 最近打开的文件的文字：文件名，后面是相对项目目录的路径
+ */
+fn recent_file_job(ui: &Ui, file_path: &str, root_path: &str) -> LayoutJob {
+    let visuals = ui.visuals();
+    let path = Path::new(file_path);
+    let file_name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(file_path);
+    let relative = path
+        .strip_prefix(root_path)
+        .ok()
+        .filter(|_| !root_path.is_empty())
+        .unwrap_or(path);
+    let mut job = LayoutJob::default();
+    job.append(
+        file_name,
+        0.0,
+        TextFormat {
+            color: visuals.strong_text_color(),
+            ..Default::default()
+        },
+    );
+    job.append(
+        &format!("  {}", relative.display()),
+        0.0,
+        TextFormat {
+            color: visuals.weak_text_color(),
+            ..Default::default()
+        },
+    );
+    job
+}
@@ -0,0 +1,77 @@
+use std::path::Path;
+
+use crate::{call_button, MyApp};
+
+impl MyApp {
+    /**
+     * 列出选中定义的所有引用，与引用次数使用同样的对应规则，按所在的定义分组
+     */
+    pub(crate) fn show_references(&mut self) {
+        self.references_of = self.current_node.label.trim().to_owned();
+        self.references = self
+            .filter_call_nodes
+            .iter()
+            .map(|call| {
+                let caller = self
+                    .definitions
+                    .enclosing(&call.file_path, call.file_location)
+                    .map_or_else(
+                        || {
+                            Path::new(&call.file_path)
+                                .file_name()
+                                .and_then(|name| name.to_str())
+                                .unwrap_or("")
+                                .to_owned()
+                        },
+                        |definition| definition.label.trim().to_owned(),
+                    );
+                (caller, call.clone())
+            })
+            .collect();
+        self.references.sort_by(|(a, a_call), (b, b_call)| {
+            (&a_call.file_path, a, a_call.file_location).cmp(&(
+                &b_call.file_path,
+                b,
+                b_call.file_location,
+            ))
+        });
+        self.show_references = true;
+    }
+    pub(crate) fn references_window(&mut self, ctx: &egui::Context) {
+        let mut show_references = self.show_references;
+        egui::Window::new(format!("引用：{}", self.references_of))
+            .id(egui::Id::new("references_window"))
+            .open(&mut show_references)
+            .default_size([360.0, 360.0])
+            .show(ctx, |ui| {
+                if self.references.is_empty() {
+                    ui.label("项目中没有找到引用");
+                    return;
+                }
+                ui.label(format!("{} 处引用", self.references.len()));
+                let mut jump_call = None;
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    let mut last_caller = None;
+                    for (caller, call) in &self.references {
+                        if last_caller != Some((caller, &call.file_path)) {
+                            ui.separator();
+                            ui.strong(caller);
+                            last_caller = Some((caller, &call.file_path));
+                        }
+                        let response = call_button(ui, call)
+                            .on_hover_text("跳转到调用所在的定义，右键在编辑器中打开");
+                        if response.clicked() {
+                            jump_call = Some(call.clone());
+                        }
+                        if response.secondary_clicked() {
+                            self.open_editor(&call.file_path, call.file_location);
+                        }
+                    }
+                });
+                if let Some(call) = jump_call {
+                    self.jump_to_caller(&call);
+                }
+            });
+        self.show_references = show_references;
+    }
+}
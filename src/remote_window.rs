@@ -0,0 +1,34 @@
+use code_graph::source::fetch_remote_dir;
+
+use crate::MyApp;
+
+impl MyApp {
+    /**
+     * 输入远程地址，通过 ssh 获取源文件后扫描
+     */
+    pub(crate) fn remote_window(&mut self, ctx: &egui::Context) {
+        let mut show_remote = self.show_remote;
+        let mut open = false;
+        egui::Window::new("打开远程项目")
+            .open(&mut show_remote)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label("通过 ssh 只读获取远程目录中的源文件");
+                ui.horizontal(|ui| {
+                    ui.text_edit_singleline(&mut self.remote_location)
+                        .on_hover_text("例如 user@host:/home/user/project");
+                    let enabled = !self.remote_location.is_empty() && self.rx.is_none();
+                    open = ui.add_enabled(enabled, egui::Button::new("打开")).clicked();
+                });
+            });
+        self.show_remote = show_remote && !open;
+        if !open {
+            return;
+        }
+        let location = self.remote_location.trim().to_owned();
+        let fetch_location = location.clone();
+        self.scan_copy(&location, move |dir_path| {
+            fetch_remote_dir(&fetch_location, dir_path)
+        });
+    }
+}
@@ -0,0 +1,944 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::mpsc,
+    thread,
+};
+
+use code_graph::{
+    duplicate::find_duplicates, exporters, fields::is_rust_struct, hotspot::analyse_hotspots,
+    ClusterBy, ColorMode, EdgeStyle, GenericsDisplay, LayoutKind, LinkStyle,
+};
+use eframe::egui::{self, Ui, Widget};
+
+use crate::{
+    call_button, filters::name_filter, Editor, GraphTab, MyApp, AUTO_COLLAPSE_NODE_COUNT,
+    BOOKMARK_SHORTCUT,
+};
+
+impl MyApp {
+    pub(crate) fn right_panel(&mut self, ui: &mut Ui) {
+        ui.add_space(10.0);
+        egui::Grid::new("param_grid")
+            .num_columns(2)
+            .spacing([10.0, 10.0])
+            .show(ui, |ui| {
+                self.appearance_rows(ui);
+                self.analysis_rows(ui);
+                self.view_rows(ui);
+                self.export_rows(ui);
+            });
+
+        ui.add_space(10.0);
+        self.doc_section(ui);
+
+        ui.add_space(10.0);
+        self.bookmark_section(ui);
+
+        ui.add_space(10.0);
+        self.cycle_section(ui);
+
+        ui.add_space(10.0);
+        self.todo_section(ui);
+
+        ui.add_space(10.0);
+        self.caller_section(ui);
+
+        ui.add_space(10.0);
+        self.callee_section(ui);
+
+        ui.add_space(10.0);
+        self.call_stats_section(ui);
+
+        ui.add_space(10.0);
+        self.code_preview_section(ui);
+        ui.add_space(10.0);
+    }
+    /**
+     * 右侧面板表格中的外观和编辑器设置：编辑器、着色、布局、连线和分组等
+     */
+    fn appearance_rows(&mut self, ui: &mut Ui) {
+        ui.label("选择编辑器");
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_source("choose editor")
+                .selected_text(format!("{:?}", self.editor))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.editor, Editor::Idea, "Idea");
+                    ui.selectable_value(&mut self.editor, Editor::VSCode, "VSCode");
+                    ui.selectable_value(&mut self.editor, Editor::Zed, "Zed");
+                });
+            ui.add_space(4.0);
+            if self.get_normal_button("打开").ui(ui).clicked() {
+                self.open_editor(
+                    &self.current_node.file_path,
+                    self.current_node.file_location,
+                );
+            }
+        });
+
+        ui.end_row();
+
+        ui.label("着色模式");
+        let mut color_mode = self.graph_view.color_mode;
+        egui::ComboBox::from_id_source("color mode")
+            .selected_text(match color_mode {
+                ColorMode::BlockType => "代码类型",
+                ColorMode::Churn => "变更热度",
+                ColorMode::Profile => "运行耗时",
+                ColorMode::Coverage => "测试覆盖率",
+                ColorMode::Complexity => "圈复杂度",
+                ColorMode::Age => "修改时间",
+                ColorMode::Hotspot => "热点",
+            })
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut color_mode, ColorMode::BlockType, "代码类型");
+                ui.selectable_value(&mut color_mode, ColorMode::Churn, "变更热度");
+                ui.selectable_value(&mut color_mode, ColorMode::Age, "修改时间");
+                ui.selectable_value(&mut color_mode, ColorMode::Complexity, "圈复杂度");
+                ui.selectable_value(&mut color_mode, ColorMode::Hotspot, "热点");
+                if self.profile.is_some() {
+                    ui.selectable_value(&mut color_mode, ColorMode::Profile, "运行耗时");
+                }
+                if self.coverage.is_some() {
+                    ui.selectable_value(&mut color_mode, ColorMode::Coverage, "测试覆盖率");
+                }
+            });
+        if color_mode != self.graph_view.color_mode {
+            self.graph_view.color_mode = color_mode;
+            self.refresh_overlay();
+        }
+        ui.end_row();
+
+        ui.label("高对比度");
+        ui.checkbox(&mut self.graph_view.high_contrast, "启用");
+        ui.end_row();
+
+        ui.label("待办注释");
+        if ui
+            .checkbox(&mut self.todo_badges, "显示角标")
+            .on_hover_text("在包含 TODO、FIXME、HACK 注释的定义上显示角标")
+            .changed()
+        {
+            self.refresh_todo_badges();
+        }
+        ui.end_row();
+
+        ui.label("代码行数");
+        ui.checkbox(&mut self.graph_view.size_bars, "显示")
+            .on_hover_text("在节点底部用细条显示定义的行数，最长的定义为满格");
+        ui.end_row();
+
+        ui.label("节点配色");
+        if self.get_normal_button("编辑").ui(ui).clicked() {
+            self.show_theme = true;
+        }
+        ui.end_row();
+
+        ui.label("布局");
+        let mut layout_kind = self.layout_kind;
+        egui::ComboBox::from_id_source("layout kind")
+            .selected_text(layout_kind.name())
+            .show_ui(ui, |ui| {
+                for kind in LayoutKind::ALL {
+                    ui.selectable_value(&mut layout_kind, kind, kind.name());
+                }
+            });
+        if layout_kind != self.layout_kind {
+            self.layout_kind = layout_kind;
+            self.graph_view.layout_engine = layout_kind.engine();
+            self.labels_changed = true;
+        }
+        ui.end_row();
+
+        ui.label("连线样式");
+        egui::ComboBox::from_id_source("edge style")
+            .selected_text(self.graph_view.edge_style.name())
+            .show_ui(ui, |ui| {
+                for style in EdgeStyle::ALL {
+                    ui.selectable_value(&mut self.graph_view.edge_style, style, style.name());
+                }
+            });
+        ui.end_row();
+
+        ui.label("分组");
+        let mut cluster_by = self.graph_view.cluster_by;
+        egui::ComboBox::from_id_source("cluster by")
+            .selected_text(cluster_by.name())
+            .show_ui(ui, |ui| {
+                for by in ClusterBy::ALL {
+                    ui.selectable_value(&mut cluster_by, by, by.name());
+                }
+            });
+        if cluster_by != self.graph_view.cluster_by {
+            self.graph_view.cluster_by = cluster_by;
+            self.graph.expand_all_clusters();
+            self.labels_changed = true;
+        }
+        ui.end_row();
+
+        ui.label("泛型显示");
+        let mut generics_display = self.graph_view.generics_display;
+        egui::ComboBox::from_id_source("generics display")
+            .selected_text(match generics_display {
+                GenericsDisplay::Full => "完整",
+                GenericsDisplay::Abbreviate => "缩写",
+                GenericsDisplay::Strip => "隐藏",
+            })
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut generics_display, GenericsDisplay::Full, "完整");
+                ui.selectable_value(&mut generics_display, GenericsDisplay::Abbreviate, "缩写");
+                ui.selectable_value(&mut generics_display, GenericsDisplay::Strip, "隐藏");
+            });
+        if generics_display != self.graph_view.generics_display {
+            self.graph_view.generics_display = generics_display;
+            self.labels_changed = true;
+        }
+        ui.end_row();
+    }
+    /**
+     * 右侧面板表格中的分析工具：统计、矩阵、热点、各种图、对比、历史和快照
+     */
+    fn analysis_rows(&mut self, ui: &mut Ui) {
+        ui.label("项目统计");
+        if ui
+            .add_enabled(self.stats.is_some(), self.get_normal_button("查看"))
+            .on_hover_text("各语言的文件数和行数、各类定义的数量、最大的文件和函数以及平均圈复杂度")
+            .clicked()
+        {
+            self.show_stats = true;
+        }
+        ui.end_row();
+
+        ui.label("调用矩阵");
+        if ui
+            .add_enabled(!self.graph.is_empty(), self.get_normal_button("打开"))
+            .on_hover_text("用行为调用方、列为被调用方的表格显示图中的调用，适合连线密集的图")
+            .clicked()
+        {
+            self.show_matrix = true;
+        }
+        ui.end_row();
+
+        ui.label("热点分析");
+        if self.get_normal_button("生成报告").ui(ui).clicked() {
+            let root_path = PathBuf::from(&self.root_path);
+            let pathes = self.pathes.clone();
+            let (tx, rx) = mpsc::channel();
+            self.hotspot_rx = Some(rx);
+            self.show_hotspots = true;
+            thread::spawn(move || {
+                let _ = tx.send(analyse_hotspots(&root_path, &pathes));
+            });
+        }
+        ui.end_row();
+
+        ui.label("重复代码");
+        if ui
+            .add_enabled(!self.definitions.is_empty(), self.get_normal_button("查找"))
+            .on_hover_text("查找项目中除了变量名和字面量以外完全相同的函数")
+            .clicked()
+        {
+            let definitions = self.definitions.clone();
+            let (tx, rx) = mpsc::channel();
+            self.duplicate_rx = Some(rx);
+            self.show_duplicates = true;
+            thread::spawn(move || {
+                let _ = tx.send(find_duplicates(definitions.definitions()));
+            });
+        }
+        ui.end_row();
+
+        ui.label("性能数据");
+        if self
+            .get_normal_button("导入")
+            .ui(ui)
+            .on_hover_text("导入 perf/flamegraph 的 collapsed stacks 文件")
+            .clicked()
+        {
+            self.import_profile();
+        }
+        ui.end_row();
+
+        ui.label("覆盖率");
+        if self
+            .get_normal_button("导入")
+            .ui(ui)
+            .on_hover_text("导入 lcov 或 cobertura 覆盖率报告")
+            .clicked()
+        {
+            self.import_coverage();
+        }
+        ui.end_row();
+
+        ui.label("调用图");
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(
+                    !self.definitions.is_empty(),
+                    self.get_normal_button("项目调用图"),
+                )
+                .on_hover_text("把项目中所有的定义和它们之间的调用关系画在一张图中")
+                .clicked()
+            {
+                self.load_call_graph();
+            }
+            if self.call_graph_rx.is_some() {
+                ui.spinner();
+            }
+        });
+        ui.end_row();
+
+        ui.label("类型层级");
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(
+                    !self.definitions.is_empty(),
+                    self.get_normal_button("继承关系"),
+                )
+                .on_hover_text("Java 的 extends / implements 和 Rust 的 trait 实现，父类型在上")
+                .clicked()
+            {
+                self.load_hierarchy();
+            }
+            if self.hierarchy_rx.is_some() {
+                ui.spinner();
+            }
+        });
+        ui.end_row();
+
+        ui.label("字段使用");
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(
+                    !self.definitions.is_empty() && is_rust_struct(&self.current_node),
+                    self.get_normal_button("读写字段的函数"),
+                )
+                .on_hover_text(
+                    "选中 Rust 结构体后，列出读写每个字段的函数，只被少数函数使用的字段可以考虑拆分出去",
+                )
+                .clicked()
+            {
+                self.load_field_usage();
+            }
+            if self.field_usage_rx.is_some() {
+                ui.spinner();
+            }
+        });
+        ui.end_row();
+
+        ui.label("依赖图");
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(
+                    !self.root_path.is_empty(),
+                    self.get_normal_button("文件依赖"),
+                )
+                .on_hover_text("按 use、import 和 #include 语句生成文件之间的依赖图")
+                .clicked()
+            {
+                self.load_dependency_graph();
+            }
+            if self.dependency_rx.is_some() {
+                ui.spinner();
+            }
+        });
+        ui.end_row();
+
+        ui.label("补丁分析");
+        ui.horizontal(|ui| {
+            if self.get_normal_button("加载补丁").ui(ui).clicked() {
+                self.load_patch();
+            }
+            if self.patch_rx.is_some() {
+                ui.spinner();
+            }
+        });
+        ui.end_row();
+
+        ui.label("目录对比");
+        ui.horizontal(|ui| {
+            if self.get_normal_button("选择目录").ui(ui).clicked() {
+                self.compare_dirs();
+            }
+            if self.compare_rx.is_some() {
+                ui.spinner();
+            }
+        });
+        ui.end_row();
+
+        ui.label("Git 对比");
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.diff_revision)
+                    .hint_text("main、HEAD~3")
+                    .desired_width(80.0),
+            );
+            if ui
+                .add_enabled(
+                    !self.root_path.is_empty() && !self.diff_revision.trim().is_empty(),
+                    self.get_normal_button("对比"),
+                )
+                .on_hover_text("对比该版本和工作区中的定义，列出新增、删除和修改的符号")
+                .clicked()
+            {
+                self.compare_revision();
+            }
+        });
+        ui.end_row();
+
+        ui.label("历史");
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(!self.root_path.is_empty(), self.get_normal_button("浏览"))
+                .on_hover_text("在最近的提交之间切换，按每个提交中的代码重新生成图，观察模块的演变")
+                .clicked()
+            {
+                self.load_revisions();
+            }
+            if self.revisions_rx.is_some() {
+                ui.spinner();
+            }
+        });
+        ui.end_row();
+
+        ui.label("快照");
+        ui.horizontal(|ui| {
+            let has_project = !self.root_path.is_empty();
+            if ui
+                .add_enabled(has_project, self.get_normal_button("保存"))
+                .on_hover_text("保存当前项目的符号图")
+                .clicked()
+            {
+                self.save_snapshot();
+            }
+            if ui
+                .add_enabled(has_project, self.get_normal_button("对比"))
+                .on_hover_text("对比快照和当前项目的符号与调用关系")
+                .clicked()
+            {
+                self.compare_snapshot();
+            }
+            if ui
+                .add(self.get_normal_button("差异图"))
+                .on_hover_text("选择旧快照和新快照，在一张图中用绿色、红色和黄色标出新增、删除和修改的节点与连线")
+                .clicked()
+            {
+                self.diff_snapshots();
+            }
+            if self.snapshot_rx.is_some() {
+                ui.spinner();
+            }
+        });
+        ui.end_row();
+    }
+    /**
+     * 右侧面板表格中的图中显示的内容：层级、折叠、扫描深度和各种筛选
+     */
+    fn view_rows(&mut self, ui: &mut Ui) {
+        ui.label("显示层级");
+        let max_level = self.graph.max_level().max(1);
+        let mut depth = self.max_depth.unwrap_or(max_level).min(max_level);
+        if ui
+            .add(egui::Slider::new(&mut depth, 1..=max_level))
+            .changed()
+        {
+            self.max_depth = (depth < max_level).then_some(depth);
+            self.visibility_changed = true;
+        }
+        ui.end_row();
+
+        ui.label("自动折叠");
+        ui.horizontal(|ui| {
+            let mut enabled = self.auto_collapse_depth.is_some();
+            ui.checkbox(&mut enabled, "").on_hover_text(format!(
+                "打开超过 {} 个节点的文件时折叠深层节点",
+                AUTO_COLLAPSE_NODE_COUNT
+            ));
+            let mut depth = self.auto_collapse_depth.unwrap_or(2);
+            ui.add_enabled(
+                enabled,
+                egui::DragValue::new(&mut depth)
+                    .range(1..=10)
+                    .prefix("超过 ")
+                    .suffix(" 层"),
+            );
+            self.auto_collapse_depth = enabled.then_some(depth);
+        });
+        ui.end_row();
+
+        ui.label("实时更新");
+        ui.checkbox(&mut self.live_update, "文件修改后更新图")
+            .on_hover_text("在编辑器中保存打开的文件后，只重新解析修改的部分并更新变化的节点");
+        ui.end_row();
+
+        ui.label("扫描深度");
+        ui.horizontal(|ui| {
+            let mut enabled = self.scan_depth.is_some();
+            ui.checkbox(&mut enabled, "")
+                .on_hover_text("限制扫描的目录层数，重新打开项目后生效");
+            let mut depth = self.scan_depth.unwrap_or(8);
+            ui.add_enabled(
+                enabled,
+                egui::DragValue::new(&mut depth)
+                    .range(1..=64)
+                    .prefix("最多 ")
+                    .suffix(" 层"),
+            );
+            self.scan_depth = enabled.then_some(depth);
+        });
+        ui.end_row();
+
+        ui.label("孤立节点");
+        if ui.checkbox(&mut self.hide_orphans, "隐藏").changed() {
+            self.visibility_changed = true;
+        }
+        ui.end_row();
+
+        ui.label("循环");
+        ui.checkbox(&mut self.show_cycles, "标出")
+            .on_hover_text("标出调用或导入关系中互相依赖的节点，并在「循环依赖」中列出");
+        ui.end_row();
+
+        ui.label("筛选节点");
+        ui.horizontal(|ui| {
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut self.name_filter)
+                    .hint_text(if self.name_filter_regex {
+                        "正则表达式"
+                    } else {
+                        "名称"
+                    })
+                    .desired_width(120.0),
+            );
+            if response.changed() {
+                self.visibility_changed = true;
+            }
+            if ui
+                .checkbox(&mut self.name_filter_regex, ".*")
+                .on_hover_text("使用正则表达式")
+                .changed()
+            {
+                self.visibility_changed = true;
+            }
+            if let Err(err) = name_filter(&self.name_filter, self.name_filter_regex) {
+                ui.colored_label(ui.visuals().error_fg_color, "⚠")
+                    .on_hover_text(err.to_string());
+            }
+        });
+        ui.end_row();
+
+        ui.label("公开接口");
+        if ui
+            .checkbox(&mut self.only_public, "只显示")
+            .on_hover_text(
+                "隐藏非公开的定义（Rust 中没有 pub、Java 中不是 public、JavaScript 中没有导出），只显示模块或 crate 对外的接口",
+            )
+            .changed()
+        {
+            self.visibility_changed = true;
+        }
+        ui.end_row();
+
+        ui.label("unsafe");
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.graph_view.unsafe_outline, "标出")
+                .on_hover_text("给 unsafe 函数、unsafe impl 和包含 unsafe 块的函数加上虚线外框");
+            if ui
+                .checkbox(&mut self.only_unsafe, "只显示")
+                .on_hover_text("只显示 unsafe 的定义和它们所在的上层节点")
+                .changed()
+            {
+                self.visibility_changed = true;
+            }
+        });
+        ui.end_row();
+
+        ui.label("调用位置");
+        ui.checkbox(&mut self.show_call_sites, "显示在图中");
+        ui.end_row();
+
+        ui.label("专注模式");
+        ui.horizontal(|ui| {
+            let mut enabled = self.graph_view.focus_hops.is_some();
+            ui.checkbox(&mut enabled, "")
+                .on_hover_text("选中节点后淡化与它距离较远的节点");
+            let mut hops = self.graph_view.focus_hops.unwrap_or(2);
+            ui.add_enabled(
+                enabled,
+                egui::DragValue::new(&mut hops)
+                    .range(1..=10)
+                    .prefix("相距 ")
+                    .suffix(" 步以内"),
+            );
+            self.graph_view.focus_hops = enabled.then_some(hops);
+        });
+        ui.end_row();
+
+        ui.label("影响分析");
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::DragValue::new(&mut self.impact_depth)
+                    .range(1..=20)
+                    .prefix("向上 ")
+                    .suffix(" 层"),
+            )
+            .on_hover_text("右键节点选择「影响分析」时最多查找的调用层数");
+            if !self.graph_view.impact.is_empty() {
+                ui.label(format!("{} 个定义受影响", self.graph_view.impact.len() - 1));
+                if ui.small_button("清除").clicked() {
+                    self.graph_view.impact.clear();
+                }
+            }
+        });
+        ui.end_row();
+    }
+    /**
+     * 右侧面板表格中的导出和复制
+     */
+    fn export_rows(&mut self, ui: &mut Ui) {
+        ui.label("导出图");
+        ui.horizontal(|ui| {
+            let has_graph = !self.graph.is_empty();
+            for exporter in exporters() {
+                let button = self.get_normal_button(exporter.name());
+                if ui.add_enabled(has_graph, button).clicked() {
+                    self.export_graph(exporter.as_ref());
+                }
+            }
+            let button = self.get_normal_button("CSV");
+            if ui
+                .add_enabled(has_graph, button)
+                .on_hover_text("导出 nodes.csv 和 edges.csv")
+                .clicked()
+            {
+                self.export_csv();
+            }
+            let exporting_png = self.png_rx.is_some();
+            let button = self.get_normal_button("PNG");
+            if ui
+                .add_enabled(has_graph && !exporting_png, button)
+                .on_hover_text("将整张图导出为图片，不受窗口大小限制")
+                .clicked()
+            {
+                self.export_png(ui);
+            }
+            ui.add(
+                egui::DragValue::new(&mut self.png_scale)
+                    .range(0.5..=8.0)
+                    .speed(0.1)
+                    .suffix("x"),
+            )
+            .on_hover_text("PNG 的缩放比例");
+            let copying_image = self.copy_image_rx.is_some();
+            let button = self.get_normal_button("复制图片");
+            if ui
+                .add_enabled(has_graph && !copying_image, button)
+                .on_hover_text("将窗口中看到的画面复制到剪贴板")
+                .clicked()
+            {
+                self.copy_viewport_image(ui);
+            }
+            if exporting_png || copying_image {
+                ui.spinner();
+            }
+        });
+        ui.end_row();
+
+        ui.label("大纲链接");
+        egui::ComboBox::from_id_source("markdown links")
+            .selected_text(self.markdown_links.name())
+            .show_ui(ui, |ui| {
+                for links in LinkStyle::ALL {
+                    ui.selectable_value(&mut self.markdown_links, links, links.name());
+                }
+            })
+            .response
+            .on_hover_text("导出 Markdown 大纲时定义位置的链接方式");
+        ui.end_row();
+
+        ui.label("批量导出");
+        ui.horizontal(|ui| {
+            let has_files = !self.pathes.is_empty();
+            for exporter in exporters() {
+                let button = self.get_normal_button(exporter.name());
+                if ui
+                    .add_enabled(has_files, button)
+                    .on_hover_text("为项目中的每个文件分别导出一张图")
+                    .clicked()
+                {
+                    self.export_all_files(exporter.extension());
+                }
+            }
+            if self.batch_export_rx.is_some() {
+                ui.spinner();
+            }
+        });
+        ui.end_row();
+
+        ui.label("复制子树");
+        ui.horizontal(|ui| {
+            let has_focus = self.graph.get_focus_idx().is_some();
+            // JSON 不适合粘贴到文档中，只提供图表格式
+            for exporter in exporters()
+                .into_iter()
+                .filter(|exporter| exporter.extension() != "json")
+            {
+                let button = self.get_normal_button(exporter.name());
+                if ui.add_enabled(has_focus, button).clicked() {
+                    self.copy_subtree(ui, exporter.as_ref());
+                }
+            }
+        });
+        ui.end_row();
+    }
+    /**
+     * 聚焦节点的文档注释
+     */
+    fn doc_section(&mut self, ui: &mut Ui) {
+        egui::CollapsingHeader::new("文档")
+            .default_open(true)
+            .show(ui, |ui| match &self.current_node.doc {
+                Some(doc) => {
+                    ui.label(doc);
+                }
+                None => {
+                    ui.weak("没有文档注释");
+                }
+            });
+    }
+    /**
+     * 书签列表
+     */
+    fn bookmark_section(&mut self, ui: &mut Ui) {
+        egui::CollapsingHeader::new("书签")
+            .default_open(true)
+            .show(ui, |ui| {
+                if let Some(index) = self.graph.get_focus_idx() {
+                    let bookmarked = self
+                        .bookmarks
+                        .iter()
+                        .any(|bookmark| bookmark.id == self.current_node.id());
+                    let text = if bookmarked {
+                        "★ 移除选中的节点"
+                    } else {
+                        "☆ 添加选中的节点"
+                    };
+                    if ui
+                        .button(text)
+                        .on_hover_text(ui.ctx().format_shortcut(&BOOKMARK_SHORTCUT))
+                        .clicked()
+                    {
+                        self.toggle_bookmark(index);
+                    }
+                }
+                if self.bookmarks.is_empty() {
+                    ui.weak("右键节点或按快捷键添加书签");
+                }
+                let mut removed = None;
+                for (position, bookmark) in self.bookmarks.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        if ui.small_button("✖").on_hover_text("移除书签").clicked() {
+                            removed = Some(position);
+                        }
+                        let file_name = Path::new(&bookmark.file_path)
+                            .file_name()
+                            .and_then(|name| name.to_str())
+                            .unwrap_or(&bookmark.file_path);
+                        if ui
+                            .link(&bookmark.label)
+                            .on_hover_text(format!("{}:{}", file_name, bookmark.line))
+                            .clicked()
+                        {
+                            self.pending_bookmark = Some(bookmark.clone());
+                        }
+                    });
+                }
+                if let Some(position) = removed {
+                    self.bookmarks.remove(position);
+                    self.sync_bookmarks();
+                }
+            });
+    }
+    /**
+     * 依赖图中的循环
+     */
+    fn cycle_section(&mut self, ui: &mut Ui) {
+        egui::CollapsingHeader::new("循环依赖")
+            .default_open(true)
+            .show(ui, |ui| {
+                if !self.show_cycles && self.graph_tab != GraphTab::Dependencies {
+                    ui.weak("勾选「循环」后列出图中的循环");
+                } else if self.cycles.is_empty() {
+                    ui.weak("图中没有循环");
+                }
+                for (position, cycle) in self.cycles.iter().enumerate() {
+                    ui.label(format!("循环 {}（{} 个节点）", position + 1, cycle.len()));
+                    ui.horizontal_wrapped(|ui| {
+                        for index in cycle {
+                            let Some(node) = self.graph.node(*index) else {
+                                continue;
+                            };
+                            if ui
+                                .link(node.label.trim())
+                                .on_hover_text(format!("{}:{}", node.file_path, node.file_location))
+                                .clicked()
+                            {
+                                self.pending_focus = Some(*index);
+                            }
+                        }
+                    });
+                }
+            });
+    }
+    /**
+     * 项目中的待办注释
+     */
+    fn todo_section(&mut self, ui: &mut Ui) {
+        egui::CollapsingHeader::new(format!("待办注释 ({})", self.todos.len()))
+            .id_source("todo_comments")
+            .default_open(false)
+            .show(ui, |ui| {
+                if self.todos.is_empty() {
+                    ui.label("打开项目目录后列出注释中的 TODO、FIXME、HACK");
+                    return;
+                }
+                egui::ScrollArea::vertical()
+                    .id_source("todo_scroll")
+                    .max_height(240.0)
+                    .show(ui, |ui| {
+                        for todo in &self.todos {
+                            let location = Path::new(&todo.file_path)
+                                .strip_prefix(&self.root_path)
+                                .unwrap_or(Path::new(&todo.file_path))
+                                .display()
+                                .to_string();
+                            ui.horizontal(|ui| {
+                                let color = match todo.marker {
+                                    "FIXME" => egui::Color32::from_rgb(220, 50, 47),
+                                    "HACK" => egui::Color32::from_rgb(128, 90, 213),
+                                    _ => egui::Color32::from_rgb(200, 120, 0),
+                                };
+                                ui.colored_label(color, todo.marker);
+                                if ui
+                                    .button(format!("{}:{}", location, todo.line))
+                                    .on_hover_text(&todo.text)
+                                    .clicked()
+                                {
+                                    self.pending_location =
+                                        Some((todo.file_path.clone(), todo.line));
+                                }
+                            });
+                        }
+                    });
+            });
+    }
+    /**
+     * 调用当前定义的位置
+     */
+    fn caller_section(&mut self, ui: &mut Ui) {
+        egui::CollapsingHeader::new("调用列表")
+            .default_open(true)
+            .show(ui, |ui| {
+                if !self.filter_call_nodes.is_empty() {
+                    if let Some(index) = self.graph.get_focus_idx() {
+                        if ui
+                            .button("在图中显示调用方")
+                            .on_hover_text("把调用当前定义的函数加入图中，并连出调用关系")
+                            .clicked()
+                        {
+                            self.pending_callers = Some(index);
+                        }
+                    }
+                }
+                let mut jump_call = None;
+                for node in &self.filter_call_nodes {
+                    let response = call_button(ui, node)
+                        .on_hover_text("跳转到调用所在的定义，右键在编辑器中打开");
+                    if response.clicked() {
+                        jump_call = Some(node.clone());
+                    }
+                    if response.secondary_clicked() {
+                        self.open_editor(&node.file_path, node.file_location);
+                    }
+                }
+                if let Some(call) = jump_call {
+                    self.jump_to_caller(&call);
+                }
+            });
+    }
+    /**
+     * 当前定义中调用的符号
+     */
+    fn callee_section(&mut self, ui: &mut Ui) {
+        egui::CollapsingHeader::new("调用的符号")
+            .default_open(true)
+            .show(ui, |ui| {
+                let mut jump_call = None;
+                for node in &self.callee_nodes {
+                    let response = call_button(ui, node).on_hover_text("跳转到定义");
+                    if response.clicked() {
+                        jump_call = Some(node.clone());
+                    }
+                }
+                if let Some(call) = jump_call {
+                    self.jump_to_definition(&call);
+                }
+            });
+    }
+    /**
+     * 当前定义的调用统计
+     */
+    fn call_stats_section(&mut self, ui: &mut Ui) {
+        egui::CollapsingHeader::new("调用统计")
+            .default_open(true)
+            .show(ui, |ui| {
+                let Some(stats) = self.call_stats else {
+                    ui.label("选中定义后显示，需要先打开项目目录");
+                    return;
+                };
+                egui::Grid::new("call_stats_grid")
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        ui.label("扇入")
+                            .on_hover_text("直接调用当前定义的不同函数数量");
+                        ui.label(format!("{}", stats.fan_in));
+                        ui.end_row();
+                        ui.label("扇出")
+                            .on_hover_text("当前定义直接调用的不同函数数量");
+                        ui.label(format!("{}", stats.fan_out));
+                        ui.end_row();
+                        ui.label("上游").on_hover_text(
+                            "直接或间接调用当前定义的函数数量，以及最多经过几层调用",
+                        );
+                        ui.label(format!("{} 个，{} 层", stats.upstream, stats.caller_depth));
+                        ui.end_row();
+                        ui.label("下游").on_hover_text(
+                            "当前定义直接或间接调用的函数数量，以及最多经过几层调用",
+                        );
+                        ui.label(format!(
+                            "{} 个，{} 层",
+                            stats.downstream, stats.callee_depth
+                        ));
+                        ui.end_row();
+                    });
+            });
+    }
+    /**
+     * 当前定义的代码
+     */
+    fn code_preview_section(&mut self, ui: &mut Ui) {
+        egui::CollapsingHeader::new("代码预览")
+            .default_open(true)
+            .show(ui, |ui| {
+                let language = "rs";
+                let theme = egui_extras::syntax_highlighting::CodeTheme::from_memory(ui.ctx());
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    egui_extras::syntax_highlighting::code_view_ui(
+                        ui,
+                        &theme,
+                        &self.current_node.block,
+                        language,
+                    );
+                });
+            });
+    }
+}
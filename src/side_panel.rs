@@ -0,0 +1,347 @@
+use std::{
+    collections::HashMap,
+    ffi::OsStr,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc::{self, Sender},
+        Arc,
+    },
+    thread,
+};
+
+use code_graph::{
+    deps::{find_dependencies, Dependency},
+    imports::resolve_imports,
+    recursion_dir_with_depth,
+    source::{cache_dir, extract_archive, ARCHIVE_EXTENSIONS},
+    stats::{project_stats, ProjectStats},
+    symbol_cache::{CachedFile, FileSymbols, SymbolCache},
+    todo::TodoComment,
+    valid_file_extention, CodeNode, DefinitionIndex, Result, Tree, TreeEvent, TreeType,
+};
+use eframe::egui::{self, Ui};
+use rayon::prelude::*;
+use rfd::{FileDialog, MessageDialog};
+
+use crate::MyApp;
+
+/**
+ * 后台扫描项目的结果
+ */
+pub(crate) struct ScanResult {
+    tree: Tree,
+    call_nodes: Vec<CodeNode>,
+    definitions: DefinitionIndex,
+    reference_counts: HashMap<String, usize>,
+    dependencies: Vec<Dependency>,
+    pathes: Vec<PathBuf>,
+    todos: Vec<TodoComment>,
+    stats: ProjectStats,
+}
+/**
+ * 后台扫描项目时发给界面的消息
+ */
+pub(crate) enum ScanMessage {
+    // 已经解析的文件数、需要解析的文件总数和最近解析完的文件，总数为 0 时还在读取目录
+    Progress(usize, usize, String),
+    Finished(Box<Result<ScanResult>>),
+    Canceled,
+}
+
+impl MyApp {
+    /**
+     * 左侧面板：选择项目、扫描进度、文件列表和依赖
+     */
+    pub(crate) fn side_panel(&mut self, ui: &mut Ui) {
+        ui.add_space(10.0);
+        ui.horizontal(|ui| {
+            ui.label("文件列表");
+            // let file_icon = egui::include_image!("../assets/folder.png");
+            let open_file_button = ui.add(self.get_normal_button("选择"));
+            if open_file_button.clicked() {
+                // 打开系统目录
+                if let Some(path) = FileDialog::new().pick_folder() {
+                    self.root_path = path.as_os_str().to_str().unwrap().to_string();
+                    self.project_root_path = Some(path);
+                }
+            }
+            open_file_button.on_hover_text("选择项目目录");
+            let remote_button = ui.add(self.get_normal_button("远程"));
+            if remote_button.clicked() {
+                self.show_remote = true;
+            }
+            remote_button.on_hover_text("通过 SSH 打开远程项目");
+            let archive_button = ui.add(self.get_normal_button("压缩包"));
+            if archive_button.clicked() {
+                if let Some(archive) = FileDialog::new()
+                    .add_filter("压缩包", &ARCHIVE_EXTENSIONS)
+                    .pick_file()
+                {
+                    let root_label = archive.to_str().unwrap_or("").to_owned();
+                    self.scan_copy(&root_label, move |dir_path| {
+                        extract_archive(&archive, dir_path)
+                    });
+                }
+            }
+            archive_button.on_hover_text("打开 .zip、.tar.gz 或 .crate 压缩包");
+        });
+        if let Some(dir_path) = self.project_root_path.take() {
+            let root_label = dir_path.to_str().unwrap_or("").to_owned();
+            // 文件列表只读取第一层，不需要等扫描完成
+            if let Ok(tree) = Tree::lazy_dir(&root_label, &dir_path, self.scan_depth) {
+                self.tree = tree;
+            }
+            self.start_scan(dir_path, root_label, |_| Ok(()));
+        }
+
+        let mut finished = None;
+        if let Some(rx) = &self.rx {
+            while let Ok(message) = rx.try_recv() {
+                match message {
+                    ScanMessage::Progress(done, total, file_path) => {
+                        self.scan_progress = (done, total, file_path);
+                    }
+                    message => {
+                        finished = Some(message);
+                        break;
+                    }
+                }
+            }
+        }
+        if let Some(message) = finished {
+            self.rx = None;
+            self.scan_cancel = None;
+            if let ScanMessage::Finished(result) = message {
+                match *result {
+                    Ok(result) => {
+                        // 扫描前已经显示了这个目录时保留已经展开读取的子目录
+                        if self.tree.full_path() != result.tree.full_path() {
+                            self.tree = result.tree;
+                        }
+                        self.call_nodes = result.call_nodes;
+                        self.definitions = result.definitions;
+                        self.graph.apply_references(&result.reference_counts);
+                        self.reference_counts = Some(result.reference_counts);
+                        self.pathes = result.pathes;
+                        self.dependencies = result.dependencies;
+                        self.todos = result.todos;
+                        self.stats = Some(result.stats);
+                        self.refresh_todo_badges();
+                    }
+                    Err(err) => {
+                        MessageDialog::new()
+                            .set_title("提示")
+                            .set_description(format!("扫描项目失败: {}", err))
+                            .show();
+                    }
+                }
+            }
+        }
+        if self.rx.is_some() {
+            self.scan_progress_bar(ui);
+        }
+
+        ui.add_space(10.0);
+        egui::ScrollArea::both().show(ui, |ui| {
+            ui.set_min_height(ui.available_height());
+            self.file_list(ui);
+        });
+    }
+    /**
+     * 文件列表，目录在展开时才读取，以及项目的依赖
+     */
+    fn file_list(&mut self, ui: &mut Ui) {
+        if self.tree.label.is_empty() {
+            ui.label("这里什么也没有");
+        } else {
+            if let TreeEvent::Clicked(name) = self.tree_view.ui(ui, &mut self.tree) {
+                self.open_recent_file(ui, &name);
+            }
+        }
+        if !self.dependencies.is_empty() {
+            ui.add_space(10.0);
+            egui::CollapsingHeader::new("依赖")
+                .default_open(false)
+                .show(ui, |ui| {
+                    let mut open_source = None;
+                    for dependency in &self.dependencies {
+                        let text = format!("{} {}", dependency.name, dependency.version);
+                        let response =
+                            ui.add_enabled(dependency.source.is_some(), egui::Link::new(text));
+                        if response.clicked() {
+                            open_source = dependency.source.clone();
+                        }
+                        match &dependency.source {
+                            Some(source) => response.on_hover_text(source.display().to_string()),
+                            None => response.on_disabled_hover_text("本地缓存中没有找到源码"),
+                        };
+                    }
+                    // 打开依赖的源码作为新的项目
+                    if let Some(source) = open_source {
+                        self.root_path = source.to_str().unwrap_or("").to_owned();
+                        self.project_root_path = Some(source);
+                    }
+                });
+        }
+    }
+    /**
+     * 在后台把项目复制到缓存目录后扫描，用于远程项目和压缩包
+     */
+    pub(crate) fn scan_copy(
+        &mut self,
+        root_label: &str,
+        prepare: impl FnOnce(&Path) -> Result<()> + Send + 'static,
+    ) {
+        let dir_path = cache_dir(root_label);
+        self.root_path = dir_path.to_str().unwrap_or("").to_owned();
+        self.start_scan(dir_path, root_label.to_owned(), prepare);
+    }
+    /**
+     * 在后台线程中扫描项目，prepare 在扫描前执行，例如复制远程项目，正在进行的扫描会被取消
+     */
+    fn start_scan(
+        &mut self,
+        dir_path: PathBuf,
+        root_label: String,
+        prepare: impl FnOnce(&Path) -> Result<()> + Send + 'static,
+    ) {
+        // 清除图里的数据
+        self.graph.clear();
+        self.cycles_key = None;
+        self.cancel_scan();
+        let scan_depth = self.scan_depth;
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.scan_cancel = Some(cancel.clone());
+        self.scan_progress = (0, 0, String::new());
+        let (tx, rx) = mpsc::channel();
+        self.rx = Some(rx);
+        thread::spawn(move || {
+            let message = match prepare(&dir_path)
+                .and_then(|()| scan_project(&dir_path, &root_label, scan_depth, &tx, &cancel))
+            {
+                Ok(Some(result)) => ScanMessage::Finished(Box::new(Ok(result))),
+                Ok(None) => ScanMessage::Canceled,
+                Err(err) => ScanMessage::Finished(Box::new(Err(err))),
+            };
+            let _ = tx.send(message);
+        });
+    }
+    /**
+     * 后台扫描的进度：已解析的文件数和最近解析完的文件，以及取消按钮
+     */
+    fn scan_progress_bar(&mut self, ui: &mut Ui) {
+        let (done, total, file_path) = self.scan_progress.clone();
+        let cancel_button = self.get_normal_button("取消");
+        let mut canceled = false;
+        ui.horizontal(|ui| {
+            ui.spinner();
+            if total == 0 {
+                ui.label("正在读取目录");
+            } else {
+                ui.add(
+                    egui::ProgressBar::new(done as f32 / total as f32)
+                        .desired_width(160.0)
+                        .text(format!("{} / {}", done, total)),
+                );
+            }
+            canceled = ui.add(cancel_button).clicked();
+        });
+        if canceled {
+            self.cancel_scan();
+        }
+        if let Some(file_name) = Path::new(&file_path).file_name().and_then(OsStr::to_str) {
+            ui.label(egui::RichText::new(file_name).small().weak())
+                .on_hover_text(&file_path);
+        }
+    }
+    fn cancel_scan(&mut self) {
+        if let Some(cancel) = self.scan_cancel.take() {
+            cancel.store(true, Ordering::Relaxed);
+        }
+        self.rx = None;
+    }
+}
+
+/**
+ * 扫描项目目录，收集文件树、所有调用和定义，每解析完一个文件通过 progress 发送进度，
+ * cancel 被设置后不再解析剩下的文件，返回 None
+ */
+fn scan_project(
+    dir_path: &Path,
+    root_label: &str,
+    scan_depth: Option<usize>,
+    progress: &Sender<ScanMessage>,
+    cancel: &AtomicBool,
+) -> Result<Option<ScanResult>> {
+    let tree = Tree::lazy_dir(root_label, dir_path, scan_depth)?;
+    let mut pathes = vec![];
+    let _ = progress.send(ScanMessage::Progress(0, 0, String::new()));
+    // 解析需要项目中所有的文件，文件列表中的子目录仍然在展开时才读取
+    recursion_dir_with_depth(
+        dir_path,
+        &mut pathes,
+        Tree::new("", "", TreeType::Directory),
+        scan_depth,
+    )?;
+    // 文件之间互不依赖，并行解析，结果仍按文件的顺序排列，没有修改的文件使用上次扫描的缓存
+    let mut cache = SymbolCache::load(dir_path);
+    let source_pathes = pathes
+        .iter()
+        .filter(|path_buffer| {
+            let ext = path_buffer
+                .extension()
+                .unwrap_or(OsStr::new(""))
+                .to_str()
+                .unwrap_or("");
+            valid_file_extention(ext)
+        })
+        .collect::<Vec<&PathBuf>>();
+    let done = AtomicUsize::new(0);
+    let (cached_files, file_symbols): (Vec<CachedFile>, Vec<FileSymbols>) = source_pathes
+        .par_iter()
+        .filter_map(|path_buffer| {
+            if cancel.load(Ordering::Relaxed) {
+                return None;
+            }
+            // 无法读取的文件直接跳过
+            let cached_file = cache.get_or_extract(path_buffer);
+            let done = done.fetch_add(1, Ordering::Relaxed) + 1;
+            let file_path = path_buffer.to_str().unwrap_or("").to_owned();
+            let _ = progress.send(ScanMessage::Progress(done, source_pathes.len(), file_path));
+            cached_file
+        })
+        .unzip();
+    if cancel.load(Ordering::Relaxed) {
+        return Ok(None);
+    }
+    let mut call_node_list = vec![];
+    let mut definitions = vec![];
+    let mut todos = vec![];
+    // 每个文件的行数
+    let mut files = vec![];
+    for (cached_file, symbols) in cached_files.iter().zip(file_symbols) {
+        call_node_list.extend(symbols.calls);
+        definitions.extend(symbols.definitions);
+        todos.extend(symbols.todos);
+        files.push((cached_file.path().to_owned(), symbols.lines));
+    }
+    // 缓存写入失败不影响扫描结果
+    if cache.replace(cached_files) {
+        let _ = cache.save();
+    }
+    let definitions =
+        DefinitionIndex::new(definitions).with_imports(resolve_imports(dir_path, &pathes));
+    let reference_counts = definitions.reference_counts(&call_node_list);
+    let stats = project_stats(&files, definitions.definitions());
+    Ok(Some(ScanResult {
+        tree,
+        call_nodes: call_node_list,
+        definitions,
+        reference_counts,
+        dependencies: find_dependencies(dir_path),
+        pathes,
+        todos,
+        stats,
+    }))
+}
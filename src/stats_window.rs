@@ -0,0 +1,130 @@
+use std::path::Path;
+
+use code_graph::theme::block_type_name;
+use eframe::egui::{self, Ui};
+
+use crate::MyApp;
+
+impl MyApp {
+    pub(crate) fn stats_window(&mut self, ctx: &egui::Context) {
+        let mut show_stats = self.show_stats;
+        egui::Window::new("项目统计")
+            .open(&mut show_stats)
+            .default_size([420.0, 520.0])
+            .show(ctx, |ui| {
+                let Some(stats) = &self.stats else {
+                    ui.label("扫描项目后才能查看统计");
+                    return;
+                };
+                ui.label(format!(
+                    "{} 个文件，{} 行，{} 个函数，平均圈复杂度 {:.1}，最高 {}",
+                    stats.files,
+                    stats.lines,
+                    stats.functions,
+                    stats.average_complexity,
+                    stats.max_complexity
+                ));
+                // 比例条的长度相对于同一个表中最大的值
+                let bar = |ui: &mut Ui, value: usize, max: usize| {
+                    ui.add(
+                        egui::ProgressBar::new(value as f32 / max.max(1) as f32)
+                            .desired_width(120.0)
+                            .text(format!("{}", value)),
+                    );
+                };
+                let mut location = None;
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    ui.separator();
+                    ui.strong("语言");
+                    let max_lines = stats.languages.first().map_or(0, |stats| stats.lines);
+                    egui::Grid::new("stats_language_grid")
+                        .num_columns(4)
+                        .striped(true)
+                        .show(ui, |ui| {
+                            ui.label("语言");
+                            ui.label("行数");
+                            ui.label("文件");
+                            ui.label("定义");
+                            ui.end_row();
+                            for language in &stats.languages {
+                                ui.label(language.language);
+                                bar(ui, language.lines, max_lines);
+                                ui.label(format!("{}", language.files));
+                                ui.label(format!("{}", language.definitions));
+                                ui.end_row();
+                            }
+                        });
+
+                    ui.separator();
+                    ui.strong("定义");
+                    let max_count = stats.block_types.first().map_or(0, |(_, count)| *count);
+                    egui::Grid::new("stats_block_type_grid")
+                        .num_columns(2)
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for (block_type, count) in &stats.block_types {
+                                ui.label(block_type_name(block_type));
+                                bar(ui, *count, max_count);
+                                ui.end_row();
+                            }
+                        });
+
+                    ui.separator();
+                    ui.strong("最大的文件");
+                    let max_lines = stats.largest_files.first().map_or(0, |(_, lines)| *lines);
+                    egui::Grid::new("stats_file_grid")
+                        .num_columns(2)
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for (file_path, lines) in &stats.largest_files {
+                                bar(ui, *lines, max_lines);
+                                let name = Path::new(file_path)
+                                    .strip_prefix(&self.root_path)
+                                    .unwrap_or(Path::new(file_path))
+                                    .display()
+                                    .to_string();
+                                if ui.link(name).clicked() {
+                                    location = Some((file_path.clone(), 1));
+                                }
+                                ui.end_row();
+                            }
+                        });
+
+                    ui.separator();
+                    ui.strong("最大的函数");
+                    let max_lines = stats
+                        .largest_functions
+                        .first()
+                        .map_or(0, |(_, lines)| *lines);
+                    egui::Grid::new("stats_function_grid")
+                        .num_columns(3)
+                        .striped(true)
+                        .show(ui, |ui| {
+                            ui.label("行数");
+                            ui.label("复杂度");
+                            ui.label("函数");
+                            ui.end_row();
+                            for (node, lines) in &stats.largest_functions {
+                                bar(ui, *lines, max_lines);
+                                ui.label(format!("{}", node.complexity));
+                                if ui
+                                    .link(node.label.trim())
+                                    .on_hover_text(format!(
+                                        "{}:{}",
+                                        node.file_path, node.file_location
+                                    ))
+                                    .clicked()
+                                {
+                                    location = Some((node.file_path.clone(), node.file_location));
+                                }
+                                ui.end_row();
+                            }
+                        });
+                });
+                if location.is_some() {
+                    self.pending_location = location;
+                }
+            });
+        self.show_stats = show_stats;
+    }
+}
@@ -0,0 +1,141 @@
+use std::path::Path;
+
+use code_graph::{search::search_symbols, CodeNode};
+use eframe::egui::{self, text::LayoutJob, TextFormat, Ui};
+
+use crate::MyApp;
+
+// 符号搜索最多显示的结果数
+const PALETTE_RESULT_COUNT: usize = 50;
+
+impl MyApp {
+    /**
+     * 在项目的所有定义中模糊搜索，选中后打开所在文件并聚焦到该节点，
+     * 上下方向键切换结果，Enter 打开，Esc 或点击其他位置关闭
+     */
+    pub(crate) fn symbol_palette(&mut self, ctx: &egui::Context) {
+        let (up, down, enter, escape) = ctx.input_mut(|input| {
+            (
+                input.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp),
+                input.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown),
+                input.key_pressed(egui::Key::Enter),
+                input.key_pressed(egui::Key::Escape),
+            )
+        });
+        let result_count = self.palette_results.len();
+        if down && result_count > 0 {
+            self.palette_selected = (self.palette_selected + 1) % result_count;
+        }
+        if up && result_count > 0 {
+            self.palette_selected = (self.palette_selected + result_count - 1) % result_count;
+        }
+        let mut chosen = None;
+        let area = egui::Area::new(egui::Id::new("symbol palette"))
+            .order(egui::Order::Foreground)
+            .anchor(egui::Align2::CENTER_TOP, [0.0, 60.0])
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.set_width(480.0);
+                    let response = egui::TextEdit::singleline(&mut self.palette_query)
+                        .hint_text("搜索项目中的符号")
+                        .desired_width(f32::INFINITY)
+                        .show(ui)
+                        .response;
+                    response.request_focus();
+                    if response.changed() {
+                        self.palette_results = search_symbols(
+                            self.definitions.definitions(),
+                            &self.palette_query,
+                            PALETTE_RESULT_COUNT,
+                        );
+                        self.palette_selected = 0;
+                    }
+                    if self.definitions.is_empty() {
+                        ui.weak("选择项目目录，扫描完成后可以搜索");
+                        return;
+                    }
+                    if self.palette_results.is_empty() {
+                        if !self.palette_query.trim().is_empty() {
+                            ui.weak("没有匹配的符号");
+                        }
+                        return;
+                    }
+                    egui::ScrollArea::vertical()
+                        .max_height(360.0)
+                        .show(ui, |ui| {
+                            for (position, (index, matched)) in
+                                self.palette_results.iter().enumerate()
+                            {
+                                let node = &self.definitions.definitions()[*index];
+                                let selected = position == self.palette_selected;
+                                let job = palette_item_job(ui, node, &matched.positions);
+                                let item = ui.add(
+                                    egui::Button::new(job)
+                                        .selected(selected)
+                                        .frame(selected)
+                                        .min_size(egui::Vec2::new(ui.available_width(), 0.0)),
+                                );
+                                if selected && (up || down) {
+                                    item.scroll_to_me(None);
+                                }
+                                if item.clicked() || (selected && enter) {
+                                    chosen = Some(*index);
+                                }
+                            }
+                        });
+                });
+            });
+        if let Some(index) = chosen {
+            let node = &self.definitions.definitions()[index];
+            self.pending_location = Some((node.file_path.clone(), node.file_location));
+            self.show_palette = false;
+        }
+        if escape || area.response.clicked_elsewhere() {
+            self.show_palette = false;
+        }
+    }
+}
+
+/**
+ * 符号搜索结果的文字：匹配到的字符加粗高亮，后面是所在文件和行号
+ */
+fn palette_item_job(ui: &Ui, node: &CodeNode, positions: &[usize]) -> LayoutJob {
+    let visuals = ui.visuals();
+    let mut job = LayoutJob::default();
+    // 匹配和未匹配的连续字符分段添加
+    let label = node.label.trim().chars().collect::<Vec<char>>();
+    let mut start = 0;
+    while start < label.len() {
+        let highlighted = positions.contains(&start);
+        let end = (start..label.len())
+            .find(|index| positions.contains(index) != highlighted)
+            .unwrap_or(label.len());
+        let format = if highlighted {
+            TextFormat {
+                color: visuals.strong_text_color(),
+                underline: egui::Stroke::new(1.0, visuals.hyperlink_color),
+                ..Default::default()
+            }
+        } else {
+            TextFormat {
+                color: visuals.text_color(),
+                ..Default::default()
+            }
+        };
+        job.append(&label[start..end].iter().collect::<String>(), 0.0, format);
+        start = end;
+    }
+    let file_name = Path::new(&node.file_path)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(&node.file_path);
+    job.append(
+        &format!("  {}:{}", file_name, node.file_location),
+        0.0,
+        TextFormat {
+            color: visuals.weak_text_color(),
+            ..Default::default()
+        },
+    );
+    job
+}
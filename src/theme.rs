@@ -0,0 +1,216 @@
+use std::{collections::HashMap, fs, io, path::PathBuf};
+
+use code_graph_core::CodeBlockType;
+use egui::Color32;
+use serde::{Deserialize, Serialize};
+
+// 配置文件所在目录与 eframe 保存界面状态的目录相同
+const APP_ID: &str = "Code Graph";
+const THEME_FILE: &str = "theme.json";
+
+/**
+ * 可以在配色中设置颜色的代码类型
+ */
+pub const THEME_BLOCK_TYPES: [CodeBlockType; 10] = [
+    CodeBlockType::NORMAL,
+    CodeBlockType::FUNCTION,
+    CodeBlockType::METHOD,
+    CodeBlockType::STRUCT,
+    CodeBlockType::IMPL,
+    CodeBlockType::CLASS,
+    CodeBlockType::CONST,
+    CodeBlockType::CALL,
+    CodeBlockType::COMPONENT,
+    CodeBlockType::HOOK,
+];
+
+/**
+ * 节点按代码类型着色的配色，深色和浅色模式分别设置，
+ * 颜色在配置文件中保存为 #rrggbb 格式
+ */
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GraphTheme {
+    #[serde(with = "hex_colors")]
+    pub dark: HashMap<CodeBlockType, Color32>,
+    #[serde(with = "hex_colors")]
+    pub light: HashMap<CodeBlockType, Color32>,
+}
+
+impl Default for GraphTheme {
+    fn default() -> Self {
+        Self {
+            dark: HashMap::from([
+                (CodeBlockType::NORMAL, Color32::DARK_GRAY),
+                (CodeBlockType::FUNCTION, Color32::DARK_BLUE),
+                (CodeBlockType::STRUCT, Color32::from_rgb(204, 112, 0)),
+                (CodeBlockType::CONST, Color32::from_rgb(204, 112, 0)),
+                (CodeBlockType::CLASS, Color32::DARK_GREEN),
+                (CodeBlockType::COMPONENT, Color32::from_rgb(110, 50, 150)),
+                (CodeBlockType::HOOK, Color32::from_rgb(0, 110, 110)),
+            ]),
+            light: HashMap::from([
+                (CodeBlockType::NORMAL, Color32::LIGHT_GRAY),
+                (CodeBlockType::FUNCTION, Color32::LIGHT_BLUE),
+                (CodeBlockType::STRUCT, Color32::LIGHT_YELLOW),
+                (CodeBlockType::CONST, Color32::LIGHT_YELLOW),
+                (CodeBlockType::CLASS, Color32::LIGHT_GREEN),
+                (CodeBlockType::COMPONENT, Color32::from_rgb(220, 190, 245)),
+                (CodeBlockType::HOOK, Color32::from_rgb(170, 230, 230)),
+            ]),
+        }
+    }
+}
+
+impl GraphTheme {
+    /**
+     * 高对比度配色，节点使用明亮的颜色和黑色文字
+     */
+    pub fn high_contrast() -> Self {
+        Self {
+            dark: HashMap::from([
+                (CodeBlockType::NORMAL, Color32::from_gray(230)),
+                (CodeBlockType::FUNCTION, Color32::from_rgb(0, 200, 255)),
+                (CodeBlockType::STRUCT, Color32::from_rgb(255, 214, 0)),
+                (CodeBlockType::CONST, Color32::from_rgb(255, 214, 0)),
+                (CodeBlockType::CLASS, Color32::from_rgb(0, 230, 118)),
+                (CodeBlockType::COMPONENT, Color32::from_rgb(230, 140, 255)),
+                (CodeBlockType::HOOK, Color32::from_rgb(0, 230, 220)),
+            ]),
+            light: HashMap::from([
+                (CodeBlockType::NORMAL, Color32::WHITE),
+                (CodeBlockType::FUNCTION, Color32::from_rgb(130, 200, 255)),
+                (CodeBlockType::STRUCT, Color32::from_rgb(255, 200, 0)),
+                (CodeBlockType::CONST, Color32::from_rgb(255, 200, 0)),
+                (CodeBlockType::CLASS, Color32::from_rgb(100, 230, 120)),
+                (CodeBlockType::COMPONENT, Color32::from_rgb(215, 160, 255)),
+                (CodeBlockType::HOOK, Color32::from_rgb(90, 220, 210)),
+            ]),
+        }
+    }
+
+    pub fn colors(&self, dark_mode: bool) -> &HashMap<CodeBlockType, Color32> {
+        if dark_mode {
+            &self.dark
+        } else {
+            &self.light
+        }
+    }
+
+    pub fn colors_mut(&mut self, dark_mode: bool) -> &mut HashMap<CodeBlockType, Color32> {
+        if dark_mode {
+            &mut self.dark
+        } else {
+            &mut self.light
+        }
+    }
+
+    /**
+     * 代码类型的颜色，没有设置的类型使用深灰色
+     */
+    pub fn color(&self, dark_mode: bool, block_type: &CodeBlockType) -> Color32 {
+        self.colors(dark_mode)
+            .get(block_type)
+            .copied()
+            .unwrap_or(Color32::DARK_GRAY)
+    }
+
+    /**
+     * 配置文件的位置，无法确定用户目录时为 None
+     */
+    pub fn config_path() -> Option<PathBuf> {
+        eframe::storage_dir(APP_ID).map(|dir| dir.join(THEME_FILE))
+    }
+
+    /**
+     * 读取配置文件中的配色，文件不存在或格式错误时使用默认配色，
+     * 配置文件中没有的类型也使用默认颜色
+     */
+    pub fn load() -> Self {
+        let Some(mut theme) = Self::config_path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str::<GraphTheme>(&content).ok())
+        else {
+            return Self::default();
+        };
+        let default = Self::default();
+        for (colors, default_colors) in [
+            (&mut theme.dark, default.dark),
+            (&mut theme.light, default.light),
+        ] {
+            for (block_type, color) in default_colors {
+                colors.entry(block_type).or_insert(color);
+            }
+        }
+        theme
+    }
+
+    /**
+     * 保存到配置文件，返回文件路径
+     */
+    pub fn save(&self) -> io::Result<PathBuf> {
+        let path = Self::config_path()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "无法确定配置目录"))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let content = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(&path, content)?;
+        Ok(path)
+    }
+}
+
+/**
+ * 代码类型在配色设置中显示的名称
+ */
+pub fn block_type_name(block_type: &CodeBlockType) -> &'static str {
+    match block_type {
+        CodeBlockType::FUNCTION => "函数",
+        CodeBlockType::METHOD => "方法",
+        CodeBlockType::STRUCT => "结构体",
+        CodeBlockType::IMPL => "实现",
+        CodeBlockType::CLASS => "类",
+        CodeBlockType::CONST => "常量",
+        CodeBlockType::NORMAL => "其他",
+        CodeBlockType::CALL => "调用",
+        CodeBlockType::COMPONENT => "组件",
+        CodeBlockType::HOOK => "Hook",
+    }
+}
+
+mod hex_colors {
+    use std::collections::HashMap;
+
+    use code_graph_core::CodeBlockType;
+    use egui::Color32;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        colors: &HashMap<CodeBlockType, Color32>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        // 按类型排序，保存的文件内容保持稳定
+        let mut colors = colors
+            .iter()
+            .map(|(block_type, color)| {
+                let hex = format!("#{:02x}{:02x}{:02x}", color.r(), color.g(), color.b());
+                (format!("{:?}", block_type), hex)
+            })
+            .collect::<Vec<(String, String)>>();
+        colors.sort();
+        serializer.collect_map(colors)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<HashMap<CodeBlockType, Color32>, D::Error> {
+        HashMap::<CodeBlockType, String>::deserialize(deserializer)?
+            .into_iter()
+            .map(|(block_type, hex)| {
+                Color32::from_hex(&hex)
+                    .map(|color| (block_type, color))
+                    .map_err(|_| D::Error::custom(format!("颜色格式错误: {}", hex)))
+            })
+            .collect()
+    }
+}
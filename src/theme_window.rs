@@ -0,0 +1,65 @@
+use code_graph::{
+    theme::{block_type_name, THEME_BLOCK_TYPES},
+    GraphTheme,
+};
+use rfd::MessageDialog;
+
+use crate::MyApp;
+
+impl MyApp {
+    /**
+     * 编辑每种代码类型在深色和浅色模式下的颜色，修改立即生效，保存后下次启动时使用
+     */
+    pub(crate) fn theme_window(&mut self, ctx: &egui::Context) {
+        let mut show_theme = self.show_theme;
+        egui::Window::new("节点配色")
+            .open(&mut show_theme)
+            .resizable(false)
+            .show(ctx, |ui| {
+                let theme = &mut self.graph_view.theme;
+                egui::Grid::new("theme grid")
+                    .num_columns(3)
+                    .spacing([12.0, 6.0])
+                    .show(ui, |ui| {
+                        ui.label("类型");
+                        ui.label("深色");
+                        ui.label("浅色");
+                        ui.end_row();
+                        for block_type in THEME_BLOCK_TYPES {
+                            ui.label(block_type_name(&block_type));
+                            for dark_mode in [true, false] {
+                                let mut color = theme.color(dark_mode, &block_type);
+                                if ui.color_edit_button_srgba(&mut color).changed() {
+                                    theme
+                                        .colors_mut(dark_mode)
+                                        .insert(block_type.clone(), color);
+                                }
+                            }
+                            ui.end_row();
+                        }
+                    });
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    if ui.button("保存").clicked() {
+                        if let Err(err) = theme.save() {
+                            MessageDialog::new()
+                                .set_title("保存失败")
+                                .set_description(err.to_string())
+                                .show();
+                        }
+                    }
+                    if ui.button("恢复默认").clicked() {
+                        *theme = GraphTheme::default();
+                    }
+                });
+                if let Some(path) = GraphTheme::config_path() {
+                    ui.label(
+                        egui::RichText::new(format!("配置文件: {}", path.display()))
+                            .small()
+                            .weak(),
+                    );
+                }
+            });
+        self.show_theme = show_theme;
+    }
+}
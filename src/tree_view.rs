@@ -0,0 +1,61 @@
+use code_graph_core::{Tree, TreeType};
+use eframe::egui::{CollapsingHeader, Ui};
+
+#[derive(Clone, PartialEq)]
+pub enum TreeEvent {
+    Clicked(String),
+    None,
+}
+
+/**
+ * 文件树的绘制组件
+ */
+#[derive(Default)]
+pub struct TreeView {
+    // 当前选中的文件
+    select_path: String,
+}
+
+impl TreeView {
+    pub fn ui(&mut self, ui: &mut Ui, tree: &mut Tree) -> TreeEvent {
+        let root_name = tree.label.clone();
+        self.ui_impl(ui, tree, 0, root_name.as_str())
+    }
+
+    fn ui_impl(&mut self, ui: &mut Ui, tree: &mut Tree, depth: usize, name: &str) -> TreeEvent {
+        if tree.truncated() {
+            ui.weak(format!("{} (已截断)", name))
+                .on_hover_text("超过扫描深度，目录中的文件未被扫描");
+            TreeEvent::None
+        } else if !tree.children().is_empty() || tree.tree_type() == TreeType::Directory {
+            CollapsingHeader::new(name)
+                .id_source(tree.full_path())
+                .default_open(depth < 1)
+                .show(ui, |ui| self.children_ui(ui, tree, depth))
+                .body_returned
+                .unwrap_or(TreeEvent::None)
+        } else {
+            let full_path = tree.full_path().to_owned();
+            if ui
+                .selectable_value(&mut self.select_path, full_path, name)
+                .clicked()
+            {
+                return TreeEvent::Clicked(tree.full_path().to_string());
+            }
+            TreeEvent::None
+        }
+    }
+
+    fn children_ui(&mut self, ui: &mut Ui, tree: &mut Tree, depth: usize) -> TreeEvent {
+        // 第一次展开目录时才读取其中的文件，无法读取的目录显示为空
+        let _ = tree.load_children();
+        for ele in tree.children_mut() {
+            let name = ele.label.clone();
+            let event = self.ui_impl(ui, ele, depth + 1, &name);
+            if let TreeEvent::Clicked(_) = event {
+                return event;
+            }
+        }
+        TreeEvent::None
+    }
+}